@@ -11,6 +11,10 @@ use rpc_service_client::RpcServiceClient;
 /// GrpcClient implementation of the RpcServiceClient
 pub type AtcClient = GrpcClient<RpcServiceClient<Channel>>;
 
+/// Stream of conformance alerts received from the server
+pub type ConformanceAlertStream =
+    std::pin::Pin<Box<dyn futures::Stream<Item = Result<ConformanceAlert, tonic::Status>> + Send>>;
+
 cfg_if::cfg_if! {
     if #[cfg(feature = "stub_backends")] {
         use svc_atc::grpc::server::{RpcServiceServer, ServerImpl};
@@ -27,6 +31,18 @@ cfg_if::cfg_if! {
 impl crate::service::Client<RpcServiceClient<Channel>> for AtcClient {
     type ReadyRequest = ReadyRequest;
     type ReadyResponse = ReadyResponse;
+    type CheckFlightPlanRequest = CheckFlightPlanRequest;
+    type CheckFlightPlanResponse = CheckFlightPlanResponse;
+    type AircraftPosition = AircraftPosition;
+    type TelemetryResponse = TelemetryResponse;
+    type ConformanceAlertsRequest = ConformanceAlertsRequest;
+    type ConformanceAlertStream = ConformanceAlertStream;
+    type ClearanceRequest = ClearanceRequest;
+    type ClearanceResponse = ClearanceResponse;
+    type EmergencyRequest = EmergencyRequest;
+    type EmergencyResponse = EmergencyResponse;
+    type CheckCapacityRequest = CheckCapacityRequest;
+    type CheckCapacityResponse = CheckCapacityResponse;
 
     async fn is_ready(
         &self,
@@ -36,6 +52,73 @@ impl crate::service::Client<RpcServiceClient<Channel>> for AtcClient {
         grpc_debug!("request: {:?}", request);
         self.get_client().await?.is_ready(request).await
     }
+
+    async fn check_flight_plan(
+        &self,
+        request: Self::CheckFlightPlanRequest,
+    ) -> Result<tonic::Response<Self::CheckFlightPlanResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.check_flight_plan(request).await
+    }
+
+    async fn stream_telemetry<S>(
+        &self,
+        request: S,
+    ) -> Result<tonic::Response<Self::TelemetryResponse>, tonic::Status>
+    where
+        S: futures::Stream<Item = Self::AircraftPosition> + Send + 'static,
+    {
+        grpc_info!("{} client.", self.get_name());
+        self.get_client().await?.stream_telemetry(request).await
+    }
+
+    async fn stream_conformance_alerts(
+        &self,
+        request: Self::ConformanceAlertsRequest,
+    ) -> Result<tonic::Response<Self::ConformanceAlertStream>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        let (metadata, stream, extensions) = self
+            .get_client()
+            .await?
+            .stream_conformance_alerts(request)
+            .await?
+            .into_parts();
+
+        Ok(tonic::Response::from_parts(
+            metadata,
+            Box::pin(stream),
+            extensions,
+        ))
+    }
+
+    async fn request_clearance(
+        &self,
+        request: Self::ClearanceRequest,
+    ) -> Result<tonic::Response<Self::ClearanceResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.request_clearance(request).await
+    }
+
+    async fn declare_emergency(
+        &self,
+        request: Self::EmergencyRequest,
+    ) -> Result<tonic::Response<Self::EmergencyResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.declare_emergency(request).await
+    }
+
+    async fn check_capacity(
+        &self,
+        request: Self::CheckCapacityRequest,
+    ) -> Result<tonic::Response<Self::CheckCapacityResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.check_capacity(request).await
+    }
 }
 
 #[cfg(feature = "stub_client")]
@@ -43,6 +126,18 @@ impl crate::service::Client<RpcServiceClient<Channel>> for AtcClient {
 impl crate::service::Client<RpcServiceClient<Channel>> for AtcClient {
     type ReadyRequest = ReadyRequest;
     type ReadyResponse = ReadyResponse;
+    type CheckFlightPlanRequest = CheckFlightPlanRequest;
+    type CheckFlightPlanResponse = CheckFlightPlanResponse;
+    type AircraftPosition = AircraftPosition;
+    type TelemetryResponse = TelemetryResponse;
+    type ConformanceAlertsRequest = ConformanceAlertsRequest;
+    type ConformanceAlertStream = ConformanceAlertStream;
+    type ClearanceRequest = ClearanceRequest;
+    type ClearanceResponse = ClearanceResponse;
+    type EmergencyRequest = EmergencyRequest;
+    type EmergencyResponse = EmergencyResponse;
+    type CheckCapacityRequest = CheckCapacityRequest;
+    type CheckCapacityResponse = CheckCapacityResponse;
 
     async fn is_ready(
         &self,
@@ -52,6 +147,103 @@ impl crate::service::Client<RpcServiceClient<Channel>> for AtcClient {
         grpc_debug!("(MOCK) request: {:?}", request);
         Ok(tonic::Response::new(ReadyResponse { ready: true }))
     }
+
+    async fn check_flight_plan(
+        &self,
+        request: Self::CheckFlightPlanRequest,
+    ) -> Result<tonic::Response<Self::CheckFlightPlanResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(CheckFlightPlanResponse {
+            approved: true,
+            conflicts: vec![],
+            suggested_time_shifts_seconds: vec![],
+            zone_violations: vec![],
+        }))
+    }
+
+    async fn stream_telemetry<S>(
+        &self,
+        request: S,
+    ) -> Result<tonic::Response<Self::TelemetryResponse>, tonic::Status>
+    where
+        S: futures::Stream<Item = Self::AircraftPosition> + Send + 'static,
+    {
+        use futures::StreamExt;
+
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        let accepted = request.count().await as u64;
+        Ok(tonic::Response::new(TelemetryResponse {
+            accepted,
+            rejected: 0,
+        }))
+    }
+
+    async fn stream_conformance_alerts(
+        &self,
+        request: Self::ConformanceAlertsRequest,
+    ) -> Result<tonic::Response<Self::ConformanceAlertStream>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(Box::pin(futures::stream::empty())))
+    }
+
+    async fn request_clearance(
+        &self,
+        request: Self::ClearanceRequest,
+    ) -> Result<tonic::Response<Self::ClearanceResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(ClearanceResponse {
+            flight_id: request.flight_id,
+            clearance_type: request.clearance_type,
+            status: ClearanceStatus::Granted as i32,
+            reason: ClearanceReason::NoReason as i32,
+            issued: None,
+            expires: None,
+            priority: false,
+        }))
+    }
+
+    async fn declare_emergency(
+        &self,
+        request: Self::EmergencyRequest,
+    ) -> Result<tonic::Response<Self::EmergencyResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(EmergencyResponse {
+            emergency_id: String::new(),
+            aircraft_id: request.aircraft_id,
+            flight_id: None,
+            vertiport_id: String::new(),
+            vertipad_id: String::new(),
+            distance_meters: 0.0,
+            clearance: Some(ClearanceResponse {
+                flight_id: String::new(),
+                clearance_type: ClearanceType::Landing as i32,
+                status: ClearanceStatus::Granted as i32,
+                reason: ClearanceReason::NoReason as i32,
+                issued: None,
+                expires: None,
+                priority: true,
+            }),
+            delayed_flight_ids: vec![],
+            declared: None,
+        }))
+    }
+
+    async fn check_capacity(
+        &self,
+        request: Self::CheckCapacityRequest,
+    ) -> Result<tonic::Response<Self::CheckCapacityResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(CheckCapacityResponse {
+            approved: true,
+            hotspots: vec![],
+            ground_delay_seconds: Some(0),
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -89,4 +281,190 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap().into_inner().ready, true);
     }
+
+    #[tokio::test]
+    async fn test_client_check_flight_plan_request() {
+        let name = "atc";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client: AtcClient = GrpcClient::new_client(&server_host, server_port, name);
+        assert_eq!(client.get_name(), name);
+
+        let request = CheckFlightPlanRequest {
+            aircraft_id: "aircraft_id".to_string(),
+            path: vec![
+                PointZ {
+                    latitude: 52.0,
+                    longitude: 4.0,
+                    altitude_meters: 100.0,
+                },
+                PointZ {
+                    latitude: 52.0,
+                    longitude: 4.02,
+                    altitude_meters: 100.0,
+                },
+            ],
+            origin_timeslot_start: Some(prost_types::Timestamp {
+                seconds: 1_700_000_000,
+                nanos: 0,
+            }),
+            origin_timeslot_end: Some(prost_types::Timestamp {
+                seconds: 1_700_000_120,
+                nanos: 0,
+            }),
+            target_timeslot_start: Some(prost_types::Timestamp {
+                seconds: 1_700_000_720,
+                nanos: 0,
+            }),
+            target_timeslot_end: Some(prost_types::Timestamp {
+                seconds: 1_700_000_840,
+                nanos: 0,
+            }),
+        };
+
+        let result = client.check_flight_plan(request).await;
+        println!("{:?}", result);
+        assert!(result.is_ok());
+        assert!(result.unwrap().into_inner().approved);
+    }
+
+    #[tokio::test]
+    async fn test_client_stream_telemetry_request() {
+        let name = "atc";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client: AtcClient = GrpcClient::new_client(&server_host, server_port, name);
+        assert_eq!(client.get_name(), name);
+
+        let positions = (0..3)
+            .map(|second| AircraftPosition {
+                aircraft_id: "00000000-0000-0000-0000-000000000001".to_string(),
+                position: Some(PointZ {
+                    latitude: 52.0,
+                    longitude: 4.0 + second as f64 * 0.0003,
+                    altitude_meters: 100.0,
+                }),
+                ground_speed_mps: 20.0,
+                vertical_speed_mps: 0.0,
+                heading_degrees: 90.0,
+                timestamp: Some(prost_types::Timestamp {
+                    seconds: 1_700_000_000 + second,
+                    nanos: 0,
+                }),
+            })
+            .collect::<Vec<AircraftPosition>>();
+
+        let result = client
+            .stream_telemetry(futures::stream::iter(positions))
+            .await;
+        println!("{:?}", result);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().into_inner().accepted, 3);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "stub_client")]
+    async fn test_client_stream_conformance_alerts_request() {
+        use futures::StreamExt;
+
+        let name = "atc";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client: AtcClient = GrpcClient::new_client(&server_host, server_port, name);
+        assert_eq!(client.get_name(), name);
+
+        let result = client
+            .stream_conformance_alerts(ConformanceAlertsRequest {
+                aircraft_id: "00000000-0000-0000-0000-000000000001".to_string(),
+            })
+            .await;
+        assert!(result.is_ok());
+
+        let alerts = result.unwrap().into_inner().collect::<Vec<_>>().await;
+        assert!(alerts.is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "stub_client")]
+    async fn test_client_request_clearance_request() {
+        let name = "atc";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client: AtcClient = GrpcClient::new_client(&server_host, server_port, name);
+        assert_eq!(client.get_name(), name);
+
+        let result = client
+            .request_clearance(ClearanceRequest {
+                flight_id: "00000000-0000-0000-0000-000000000001".to_string(),
+                aircraft_id: "00000000-0000-0000-0000-000000000002".to_string(),
+                vertipad_id: "00000000-0000-0000-0000-000000000003".to_string(),
+                clearance_type: ClearanceType::Landing as i32,
+            })
+            .await;
+        println!("{:?}", result);
+        assert!(result.is_ok());
+
+        let response = result.unwrap().into_inner();
+        assert_eq!(response.status, ClearanceStatus::Granted as i32);
+        assert_eq!(response.clearance_type, ClearanceType::Landing as i32);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "stub_client")]
+    async fn test_client_declare_emergency_request() {
+        let name = "atc";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client: AtcClient = GrpcClient::new_client(&server_host, server_port, name);
+        assert_eq!(client.get_name(), name);
+
+        let result = client
+            .declare_emergency(EmergencyRequest {
+                aircraft_id: "00000000-0000-0000-0000-000000000001".to_string(),
+                emergency_type: EmergencyType::LowBattery as i32,
+                position: Some(PointZ {
+                    latitude: 52.0,
+                    longitude: 4.0,
+                    altitude_meters: 100.0,
+                }),
+            })
+            .await;
+        println!("{:?}", result);
+        assert!(result.is_ok());
+
+        let clearance = result.unwrap().into_inner().clearance.unwrap();
+        assert_eq!(clearance.status, ClearanceStatus::Granted as i32);
+        assert!(clearance.priority);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "stub_client")]
+    async fn test_client_check_capacity_request() {
+        let name = "atc";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client: AtcClient = GrpcClient::new_client(&server_host, server_port, name);
+        assert_eq!(client.get_name(), name);
+
+        let result = client
+            .check_capacity(CheckCapacityRequest {
+                aircraft_id: "00000000-0000-0000-0000-000000000001".to_string(),
+                origin_vertiport_id: "00000000-0000-0000-0000-000000000002".to_string(),
+                target_vertiport_id: "00000000-0000-0000-0000-000000000003".to_string(),
+                ..Default::default()
+            })
+            .await;
+        println!("{:?}", result);
+        assert!(result.is_ok());
+
+        let response = result.unwrap().into_inner();
+        assert!(response.approved);
+        assert_eq!(response.ground_delay_seconds, Some(0));
+    }
 }
@@ -15,6 +15,523 @@ pub struct ReadyResponse {
     #[prost(bool, tag = "1")]
     pub ready: bool,
 }
+/// Latitude, longitude, and altitude following the WGS-84 standard
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PointZ {
+    /// Latitude
+    #[prost(double, tag = "1")]
+    pub latitude: f64,
+    /// Longitude
+    #[prost(double, tag = "2")]
+    pub longitude: f64,
+    /// Altitude in meters
+    #[prost(double, tag = "3")]
+    pub altitude_meters: f64,
+}
+/// Candidate flight plan to check for conflicts
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckFlightPlanRequest {
+    /// Aircraft ID
+    #[prost(string, tag = "1")]
+    pub aircraft_id: ::prost::alloc::string::String,
+    /// Path
+    #[prost(message, repeated, tag = "2")]
+    pub path: ::prost::alloc::vec::Vec<PointZ>,
+    /// Origin Time Start
+    #[prost(message, optional, tag = "3")]
+    pub origin_timeslot_start: ::core::option::Option<::prost_types::Timestamp>,
+    /// Origin Time End
+    #[prost(message, optional, tag = "4")]
+    pub origin_timeslot_end: ::core::option::Option<::prost_types::Timestamp>,
+    /// Target Time Start
+    #[prost(message, optional, tag = "5")]
+    pub target_timeslot_start: ::core::option::Option<::prost_types::Timestamp>,
+    /// Target Time End
+    #[prost(message, optional, tag = "6")]
+    pub target_timeslot_end: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// Scheduled flight plan in conflict with a candidate flight plan
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightConflict {
+    /// Scheduled flight plan ID
+    #[prost(string, tag = "1")]
+    pub flight_id: ::prost::alloc::string::String,
+    /// Time of closest approach
+    #[prost(message, optional, tag = "2")]
+    pub time: ::core::option::Option<::prost_types::Timestamp>,
+    /// Horizontal distance at closest approach in meters
+    #[prost(double, tag = "3")]
+    pub horizontal_distance_meters: f64,
+    /// Vertical distance at closest approach in meters
+    #[prost(double, tag = "4")]
+    pub vertical_distance_meters: f64,
+}
+/// Candidate path segment crossing an active restricted zone
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZoneViolation {
+    /// Zone ID
+    #[prost(string, tag = "1")]
+    pub zone_id: ::prost::alloc::string::String,
+    /// Index of the first point of the path segment
+    #[prost(uint32, tag = "2")]
+    pub segment_index: u32,
+    /// Time at which the aircraft starts flying the segment
+    #[prost(message, optional, tag = "3")]
+    pub segment_start: ::core::option::Option<::prost_types::Timestamp>,
+    /// Time at which the aircraft finishes flying the segment
+    #[prost(message, optional, tag = "4")]
+    pub segment_end: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// Result of a candidate flight plan check
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckFlightPlanResponse {
+    /// True if the candidate flight plan has no conflicts and crosses no
+    ///   active restricted zones
+    #[prost(bool, tag = "1")]
+    pub approved: bool,
+    /// Scheduled flight plans in conflict with the candidate
+    #[prost(message, repeated, tag = "2")]
+    pub conflicts: ::prost::alloc::vec::Vec<FlightConflict>,
+    /// Time shifts in seconds that would resolve all conflicts and zone violations
+    #[prost(int64, repeated, tag = "3")]
+    pub suggested_time_shifts_seconds: ::prost::alloc::vec::Vec<i64>,
+    /// Candidate path segments crossing active restricted zones
+    #[prost(message, repeated, tag = "4")]
+    pub zone_violations: ::prost::alloc::vec::Vec<ZoneViolation>,
+}
+/// Position report from an aircraft
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AircraftPosition {
+    /// Aircraft ID
+    #[prost(string, tag = "1")]
+    pub aircraft_id: ::prost::alloc::string::String,
+    /// Position of the aircraft
+    #[prost(message, optional, tag = "2")]
+    pub position: ::core::option::Option<PointZ>,
+    /// Horizontal speed over ground in meters per second
+    #[prost(double, tag = "3")]
+    pub ground_speed_mps: f64,
+    /// Vertical speed in meters per second, positive when climbing
+    #[prost(double, tag = "4")]
+    pub vertical_speed_mps: f64,
+    /// Track over ground in degrees clockwise from true north
+    #[prost(double, tag = "5")]
+    pub heading_degrees: f64,
+    /// Time at which the position was measured
+    #[prost(message, optional, tag = "6")]
+    pub timestamp: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// Result of a telemetry stream
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TelemetryResponse {
+    /// Number of position reports added to the traffic picture
+    #[prost(uint64, tag = "1")]
+    pub accepted: u64,
+    /// Number of position reports rejected
+    #[prost(uint64, tag = "2")]
+    pub rejected: u64,
+}
+/// Conformance alerts subscription
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConformanceAlertsRequest {
+    /// Only stream alerts for this aircraft, all aircraft if empty
+    #[prost(string, tag = "1")]
+    pub aircraft_id: ::prost::alloc::string::String,
+}
+/// Alert raised when an aircraft deviates from its acknowledged flight plan
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConformanceAlert {
+    /// Flight plan ID
+    #[prost(string, tag = "1")]
+    pub flight_id: ::prost::alloc::string::String,
+    /// Aircraft ID
+    #[prost(string, tag = "2")]
+    pub aircraft_id: ::prost::alloc::string::String,
+    /// Type of deviation
+    #[prost(enumeration = "ConformanceAlertType", tag = "3")]
+    pub alert_type: i32,
+    /// Deviation in meters for lateral and altitude alerts, seconds for schedule alerts
+    #[prost(double, tag = "4")]
+    pub deviation: f64,
+    /// Tolerance that was exceeded, in the same unit as the deviation
+    #[prost(double, tag = "5")]
+    pub tolerance: f64,
+    /// Reported position of the aircraft
+    #[prost(message, optional, tag = "6")]
+    pub position: ::core::option::Option<PointZ>,
+    /// Time of the position report that raised the alert
+    #[prost(message, optional, tag = "7")]
+    pub timestamp: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// Takeoff or landing clearance request
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClearanceRequest {
+    /// Flight plan ID
+    #[prost(string, tag = "1")]
+    pub flight_id: ::prost::alloc::string::String,
+    /// Aircraft ID
+    #[prost(string, tag = "2")]
+    pub aircraft_id: ::prost::alloc::string::String,
+    /// Vertipad ID
+    #[prost(string, tag = "3")]
+    pub vertipad_id: ::prost::alloc::string::String,
+    /// Type of clearance requested
+    #[prost(enumeration = "ClearanceType", tag = "4")]
+    pub clearance_type: i32,
+}
+/// Decision on a takeoff or landing clearance request
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClearanceResponse {
+    /// Flight plan ID
+    #[prost(string, tag = "1")]
+    pub flight_id: ::prost::alloc::string::String,
+    /// Type of clearance requested
+    #[prost(enumeration = "ClearanceType", tag = "2")]
+    pub clearance_type: i32,
+    /// Decision on the request
+    #[prost(enumeration = "ClearanceStatus", tag = "3")]
+    pub status: i32,
+    /// Reason the clearance was held or denied
+    #[prost(enumeration = "ClearanceReason", tag = "4")]
+    pub reason: i32,
+    /// Time at which the decision was made
+    #[prost(message, optional, tag = "5")]
+    pub issued: ::core::option::Option<::prost_types::Timestamp>,
+    /// Time at which a granted clearance expires
+    #[prost(message, optional, tag = "6")]
+    pub expires: ::core::option::Option<::prost_types::Timestamp>,
+    /// True if the clearance was issued for an emergency
+    #[prost(bool, tag = "7")]
+    pub priority: bool,
+}
+/// Emergency declared by an aircraft
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EmergencyRequest {
+    /// Aircraft ID
+    #[prost(string, tag = "1")]
+    pub aircraft_id: ::prost::alloc::string::String,
+    /// Type of emergency
+    #[prost(enumeration = "EmergencyType", tag = "2")]
+    pub emergency_type: i32,
+    /// Current position of the aircraft
+    #[prost(message, optional, tag = "3")]
+    pub position: ::core::option::Option<PointZ>,
+}
+/// Vertipad assigned to an aircraft declaring an emergency
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EmergencyResponse {
+    /// Emergency ID
+    #[prost(string, tag = "1")]
+    pub emergency_id: ::prost::alloc::string::String,
+    /// Aircraft ID
+    #[prost(string, tag = "2")]
+    pub aircraft_id: ::prost::alloc::string::String,
+    /// Flight plan the aircraft was flying, if any
+    #[prost(string, optional, tag = "3")]
+    pub flight_id: ::core::option::Option<::prost::alloc::string::String>,
+    /// Vertiport of the assigned vertipad
+    #[prost(string, tag = "4")]
+    pub vertiport_id: ::prost::alloc::string::String,
+    /// Assigned vertipad ID
+    #[prost(string, tag = "5")]
+    pub vertipad_id: ::prost::alloc::string::String,
+    /// Horizontal distance in meters from the aircraft to the vertipad
+    #[prost(double, tag = "6")]
+    pub distance_meters: f64,
+    /// Priority landing clearance on the vertipad
+    #[prost(message, optional, tag = "7")]
+    pub clearance: ::core::option::Option<ClearanceResponse>,
+    /// Flight plans of the other aircraft advised to delay
+    #[prost(string, repeated, tag = "8")]
+    pub delayed_flight_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Time at which the emergency was declared
+    #[prost(message, optional, tag = "9")]
+    pub declared: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// Candidate flight plan to check against sector and vertiport capacities
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckCapacityRequest {
+    /// Aircraft ID
+    #[prost(string, tag = "1")]
+    pub aircraft_id: ::prost::alloc::string::String,
+    /// Origin Vertiport ID
+    #[prost(string, tag = "2")]
+    pub origin_vertiport_id: ::prost::alloc::string::String,
+    /// Target Vertiport ID
+    #[prost(string, tag = "3")]
+    pub target_vertiport_id: ::prost::alloc::string::String,
+    /// Path
+    #[prost(message, repeated, tag = "4")]
+    pub path: ::prost::alloc::vec::Vec<PointZ>,
+    /// Origin Time End
+    #[prost(message, optional, tag = "5")]
+    pub origin_timeslot_end: ::core::option::Option<::prost_types::Timestamp>,
+    /// Target Time Start
+    #[prost(message, optional, tag = "6")]
+    pub target_timeslot_start: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// Period in which the demand for a sector or vertiport exceeds its capacity
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CapacityHotspot {
+    /// Type of the oversubscribed resource
+    #[prost(enumeration = "CapacityResourceType", tag = "1")]
+    pub resource_type: i32,
+    /// Sector or vertiport ID
+    #[prost(string, tag = "2")]
+    pub resource_id: ::prost::alloc::string::String,
+    /// Start of the 15 minute period
+    #[prost(message, optional, tag = "3")]
+    pub period_start: ::core::option::Option<::prost_types::Timestamp>,
+    /// End of the 15 minute period
+    #[prost(message, optional, tag = "4")]
+    pub period_end: ::core::option::Option<::prost_types::Timestamp>,
+    /// Maximum number of operations in the period
+    #[prost(uint32, tag = "5")]
+    pub capacity: u32,
+    /// Number of operations planned in the period, including the candidate
+    #[prost(uint32, tag = "6")]
+    pub demand: u32,
+}
+/// Result of a candidate flight plan capacity check
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckCapacityResponse {
+    /// True if the candidate flight plan fits within every capacity
+    #[prost(bool, tag = "1")]
+    pub approved: bool,
+    /// Periods the candidate flight plan would oversubscribe
+    #[prost(message, repeated, tag = "2")]
+    pub hotspots: ::prost::alloc::vec::Vec<CapacityHotspot>,
+    /// Smallest departure delay in seconds keeping the candidate within
+    ///   capacity, absent if there is none within the maximum ground delay
+    #[prost(int64, optional, tag = "3")]
+    pub ground_delay_seconds: ::core::option::Option<i64>,
+}
+/// Type of deviation from an acknowledged flight plan
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ConformanceAlertType {
+    /// Aircraft is too far from the planned path
+    Lateral = 0,
+    /// Aircraft is too far above or below the planned altitude
+    Altitude = 1,
+    /// Aircraft is projected to arrive outside of the target timeslot
+    Schedule = 2,
+}
+impl ConformanceAlertType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ConformanceAlertType::Lateral => "LATERAL",
+            ConformanceAlertType::Altitude => "ALTITUDE",
+            ConformanceAlertType::Schedule => "SCHEDULE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "LATERAL" => Some(Self::Lateral),
+            "ALTITUDE" => Some(Self::Altitude),
+            "SCHEDULE" => Some(Self::Schedule),
+            _ => None,
+        }
+    }
+}
+/// Type of clearance requested by an aircraft
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ClearanceType {
+    /// Takeoff from the origin vertipad
+    Takeoff = 0,
+    /// Landing on the target vertipad
+    Landing = 1,
+}
+impl ClearanceType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ClearanceType::Takeoff => "TAKEOFF",
+            ClearanceType::Landing => "LANDING",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "TAKEOFF" => Some(Self::Takeoff),
+            "LANDING" => Some(Self::Landing),
+            _ => None,
+        }
+    }
+}
+/// Decision on a clearance request
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ClearanceStatus {
+    /// Aircraft may proceed until the clearance expires
+    Granted = 0,
+    /// Aircraft must wait and request clearance again
+    Hold = 1,
+    /// Request does not match an acknowledged flight plan
+    Denied = 2,
+}
+impl ClearanceStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ClearanceStatus::Granted => "GRANTED",
+            ClearanceStatus::Hold => "HOLD",
+            ClearanceStatus::Denied => "DENIED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "GRANTED" => Some(Self::Granted),
+            "HOLD" => Some(Self::Hold),
+            "DENIED" => Some(Self::Denied),
+            _ => None,
+        }
+    }
+}
+/// Reason a clearance was held or denied
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ClearanceReason {
+    /// Clearance was granted
+    NoReason = 0,
+    /// Flight plan was not acknowledged by the carrier
+    NotAcknowledged = 1,
+    /// Aircraft is not assigned to the flight plan
+    AircraftMismatch = 2,
+    /// Vertipad is not the origin or target vertipad of the flight plan
+    VertipadMismatch = 3,
+    /// Vertipad is occupied by another aircraft
+    PadOccupied = 4,
+    /// Other aircraft are flying near the vertipad
+    TrafficNearby = 5,
+    /// Flight plan is not in a state allowing the clearance
+    InvalidState = 6,
+}
+impl ClearanceReason {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ClearanceReason::NoReason => "NO_REASON",
+            ClearanceReason::NotAcknowledged => "NOT_ACKNOWLEDGED",
+            ClearanceReason::AircraftMismatch => "AIRCRAFT_MISMATCH",
+            ClearanceReason::VertipadMismatch => "VERTIPAD_MISMATCH",
+            ClearanceReason::PadOccupied => "PAD_OCCUPIED",
+            ClearanceReason::TrafficNearby => "TRAFFIC_NEARBY",
+            ClearanceReason::InvalidState => "INVALID_STATE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "NO_REASON" => Some(Self::NoReason),
+            "NOT_ACKNOWLEDGED" => Some(Self::NotAcknowledged),
+            "AIRCRAFT_MISMATCH" => Some(Self::AircraftMismatch),
+            "VERTIPAD_MISMATCH" => Some(Self::VertipadMismatch),
+            "PAD_OCCUPIED" => Some(Self::PadOccupied),
+            "TRAFFIC_NEARBY" => Some(Self::TrafficNearby),
+            "INVALID_STATE" => Some(Self::InvalidState),
+            _ => None,
+        }
+    }
+}
+/// Type of emergency declared by an aircraft
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum EmergencyType {
+    /// Battery charge is too low to reach the target vertiport
+    LowBattery = 0,
+    /// Command and control link with the aircraft was lost
+    LostLink = 1,
+    /// Aircraft has a mechanical or system failure
+    Mechanical = 2,
+    /// A passenger needs medical attention
+    Medical = 3,
+}
+impl EmergencyType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            EmergencyType::LowBattery => "LOW_BATTERY",
+            EmergencyType::LostLink => "LOST_LINK",
+            EmergencyType::Mechanical => "MECHANICAL",
+            EmergencyType::Medical => "MEDICAL",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "LOW_BATTERY" => Some(Self::LowBattery),
+            "LOST_LINK" => Some(Self::LostLink),
+            "MECHANICAL" => Some(Self::Mechanical),
+            "MEDICAL" => Some(Self::Medical),
+            _ => None,
+        }
+    }
+}
+/// Type of airspace resource with a limited capacity
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CapacityResourceType {
+    /// Flights entering a sector
+    Sector = 0,
+    /// Takeoffs and landings at a vertiport
+    Vertiport = 1,
+}
+impl CapacityResourceType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            CapacityResourceType::Sector => "SECTOR",
+            CapacityResourceType::Vertiport => "VERTIPORT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "SECTOR" => Some(Self::Sector),
+            "VERTIPORT" => Some(Self::Vertiport),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 #[cfg(not(tarpaulin_include))]
 pub mod rpc_service_client {
@@ -122,5 +639,161 @@ pub mod rpc_service_client {
             req.extensions_mut().insert(GrpcMethod::new("grpc.RpcService", "isReady"));
             self.inner.unary(req, path, codec).await
         }
+        /// Check a candidate flight plan against scheduled flight plans
+        pub async fn check_flight_plan(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CheckFlightPlanRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CheckFlightPlanResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/checkFlightPlan",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "checkFlightPlan"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Stream aircraft position reports into the traffic picture
+        pub async fn stream_telemetry(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<Message = super::AircraftPosition>,
+        ) -> std::result::Result<
+            tonic::Response<super::TelemetryResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/streamTelemetry",
+            );
+            let mut req = request.into_streaming_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "streamTelemetry"));
+            self.inner.client_streaming(req, path, codec).await
+        }
+        /// Stream conformance alerts as they are raised
+        pub async fn stream_conformance_alerts(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ConformanceAlertsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::ConformanceAlert>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/streamConformanceAlerts",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "streamConformanceAlerts"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        /// Request takeoff or landing clearance at a vertipad
+        pub async fn request_clearance(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ClearanceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ClearanceResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/requestClearance",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "requestClearance"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Declare an emergency and get a priority landing clearance
+        pub async fn declare_emergency(
+            &mut self,
+            request: impl tonic::IntoRequest<super::EmergencyRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::EmergencyResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/declareEmergency",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "declareEmergency"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Check a candidate flight plan against sector and vertiport capacities
+        pub async fn check_capacity(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CheckCapacityRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CheckCapacityResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/checkCapacity",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "checkCapacity"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
@@ -11,6 +11,30 @@ where
     type ReadyRequest;
     /// The type expected for ReadyResponse structs.
     type ReadyResponse;
+    /// The type expected for CheckFlightPlanRequest structs.
+    type CheckFlightPlanRequest;
+    /// The type expected for CheckFlightPlanResponse structs.
+    type CheckFlightPlanResponse;
+    /// The type expected for AircraftPosition structs.
+    type AircraftPosition;
+    /// The type expected for TelemetryResponse structs.
+    type TelemetryResponse;
+    /// The type expected for ConformanceAlertsRequest structs.
+    type ConformanceAlertsRequest;
+    /// The type expected for the stream of ConformanceAlert structs.
+    type ConformanceAlertStream;
+    /// The type expected for ClearanceRequest structs.
+    type ClearanceRequest;
+    /// The type expected for ClearanceResponse structs.
+    type ClearanceResponse;
+    /// The type expected for EmergencyRequest structs.
+    type EmergencyRequest;
+    /// The type expected for EmergencyResponse structs.
+    type EmergencyResponse;
+    /// The type expected for CheckCapacityRequest structs.
+    type CheckCapacityRequest;
+    /// The type expected for CheckCapacityResponse structs.
+    type CheckCapacityResponse;
 
     /// Returns a [`tonic::Response`] containing a [`ReadyResponse`](Self::ReadyResponse)
     /// Takes an [`ReadyRequest`](Self::ReadyRequest).
@@ -38,4 +62,202 @@ where
         &self,
         request: Self::ReadyRequest,
     ) -> Result<tonic::Response<Self::ReadyResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`CheckFlightPlanResponse`](Self::CheckFlightPlanResponse)
+    /// Takes a [`CheckFlightPlanRequest`](Self::CheckFlightPlanRequest).
+    ///
+    /// The candidate flight plan is approved if it does not conflict with
+    ///  any of the scheduled flight plans.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the candidate flight plan is invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unavailable`] if the scheduled flight plans could not be retrieved.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_atc_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = AtcClient::new_client(&host, port, "atc");
+    ///     let response = client
+    ///         .check_flight_plan(atc::CheckFlightPlanRequest::default())
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn check_flight_plan(
+        &self,
+        request: Self::CheckFlightPlanRequest,
+    ) -> Result<tonic::Response<Self::CheckFlightPlanResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`TelemetryResponse`](Self::TelemetryResponse)
+    /// Takes a stream of [`AircraftPosition`](Self::AircraftPosition).
+    ///
+    /// Position reports are added to the traffic picture as they arrive.
+    /// Invalid or out of order reports are counted as rejected.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unknown`] if the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_atc_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = AtcClient::new_client(&host, port, "atc");
+    ///     let positions = futures::stream::iter(vec![atc::AircraftPosition::default()]);
+    ///     let response = client
+    ///         .stream_telemetry(positions)
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn stream_telemetry<S>(
+        &self,
+        request: S,
+    ) -> Result<tonic::Response<Self::TelemetryResponse>, tonic::Status>
+    where
+        S: futures::Stream<Item = Self::AircraftPosition> + Send + 'static;
+
+    /// Returns a [`tonic::Response`] containing a [`ConformanceAlertStream`](Self::ConformanceAlertStream)
+    /// Takes a [`ConformanceAlertsRequest`](Self::ConformanceAlertsRequest).
+    ///
+    /// Alerts are streamed as they are raised, for a single aircraft or for
+    ///  all aircraft if no aircraft ID is given.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unknown`] if the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use futures::StreamExt;
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_atc_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = AtcClient::new_client(&host, port, "atc");
+    ///     let mut alerts = client
+    ///         .stream_conformance_alerts(atc::ConformanceAlertsRequest::default())
+    ///         .await?
+    ///         .into_inner();
+    ///     while let Some(alert) = alerts.next().await {
+    ///         println!("ALERT={:?}", alert?);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn stream_conformance_alerts(
+        &self,
+        request: Self::ConformanceAlertsRequest,
+    ) -> Result<tonic::Response<Self::ConformanceAlertStream>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`ClearanceResponse`](Self::ClearanceResponse)
+    /// Takes a [`ClearanceRequest`](Self::ClearanceRequest).
+    ///
+    /// Clearance is granted if the vertipad is free and no other aircraft
+    ///  are flying nearby, held until they are, or denied if the request does
+    ///  not match an acknowledged flight plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the flight plan ID or clearance type is invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::NotFound`] if the flight plan does not exist.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unavailable`] if the vertiport flight plans could not be retrieved.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_atc_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = AtcClient::new_client(&host, port, "atc");
+    ///     let response = client
+    ///         .request_clearance(atc::ClearanceRequest::default())
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn request_clearance(
+        &self,
+        request: Self::ClearanceRequest,
+    ) -> Result<tonic::Response<Self::ClearanceResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing an [`EmergencyResponse`](Self::EmergencyResponse)
+    /// Takes an [`EmergencyRequest`](Self::EmergencyRequest).
+    ///
+    /// The aircraft is granted a priority landing clearance on the nearest
+    ///  vertipad able to receive it, and the other aircraft scheduled on the
+    ///  vertipad are advised to delay.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the aircraft ID, emergency type or position is invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::NotFound`] if no suitable vertipad is within range.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unavailable`] if the vertipads could not be retrieved.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_atc_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = AtcClient::new_client(&host, port, "atc");
+    ///     let response = client
+    ///         .declare_emergency(atc::EmergencyRequest::default())
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn declare_emergency(
+        &self,
+        request: Self::EmergencyRequest,
+    ) -> Result<tonic::Response<Self::EmergencyResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`CheckCapacityResponse`](Self::CheckCapacityResponse)
+    /// Takes a [`CheckCapacityRequest`](Self::CheckCapacityRequest).
+    ///
+    /// The takeoff, landing and sector entries of the candidate flight plan
+    ///  are counted with those of the scheduled flight plans. The candidate
+    ///  is approved if no sector or vertiport exceeds its capacity, otherwise
+    ///  the oversubscribed periods are returned with the smallest departure
+    ///  delay keeping the candidate within capacity.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if a vertiport ID, the path or the timeslots are invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unavailable`] if the scheduled flight plans could not be retrieved.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_atc_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = AtcClient::new_client(&host, port, "atc");
+    ///     let response = client
+    ///         .check_capacity(atc::CheckCapacityRequest::default())
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn check_capacity(
+        &self,
+        request: Self::CheckCapacityRequest,
+    ) -> Result<tonic::Response<Self::CheckCapacityResponse>, tonic::Status>;
 }
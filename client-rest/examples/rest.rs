@@ -41,6 +41,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let data = AckRequest {
             fp_id: "abcdef12".to_string(),
             status: AckStatus::Confirm,
+            reason: None,
         };
 
         let data_str = serde_json::to_string(&data).unwrap();
@@ -59,6 +60,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("{}: {}", uri, result_str);
     }
 
+    // GET /atc/plans
+    {
+        let uri = format!(
+            "{}/atc/plans?aircraft_id={}&include_cargo=true&limit=10",
+            url, "7b4d8c6a-7e5c-4ad4-8b3a-2f5c1b0a9d1e"
+        );
+        let req = Request::builder()
+            .method(Method::GET)
+            .uri(uri.clone())
+            .body(Body::empty())
+            .unwrap();
+
+        let resp = client.request(req).await;
+        let (success, result_str) = evaluate(resp, StatusCode::OK);
+        ok &= success;
+
+        println!("{}: {}", uri, result_str);
+    }
+
     if ok {
         println!("\u{1F9c1} All endpoints responded!");
     } else {
@@ -11,21 +11,79 @@ pub struct AckRequest {
 
     /// Acknowledgement Status
     pub status: AckStatus,
+
+    /// Reason for rejecting the flight plan, only used with [`AckStatus::Deny`]
+    #[serde(default)]
+    pub reason: Option<DenyReason>,
 }
 
 /// Confirm itinerary Operation Status
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub enum AckStatus {
-    /// Unauthorized request
+    /// Flight plan rejected by the carrier
     Deny,
 
-    /// Unavailable Service
+    /// Flight plan accepted by the carrier
     Confirm,
 }
 
+/// Reason code provided by a carrier when rejecting a flight plan
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum DenyReason {
+    /// No reason provided
+    Unspecified,
+
+    /// Aircraft is not available for the flight
+    AircraftUnavailable,
+
+    /// Insufficient battery or fuel for the planned route
+    InsufficientEnergy,
+
+    /// Aircraft requires maintenance
+    Maintenance,
+
+    /// Weather conditions unsuitable for the flight
+    Weather,
+
+    /// Planned route is not flyable by the aircraft
+    RouteUnflyable,
+}
+
+/// Response to an acknowledgement request
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AckResponse {
+    /// Flight plan ID that was confirmed or rejected
+    pub fp_id: String,
+
+    /// Acknowledgement Status as registered by svc-atc
+    pub status: AckStatus,
+
+    /// Reason code registered with a rejection
+    pub reason: Option<DenyReason>,
+
+    /// True if the flight plan was marked for re-planning
+    pub replan_required: bool,
+
+    /// Time at which the acknowledgement was registered
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Rejection of a flight plan registered by a carrier
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FlightDenial {
+    /// Flight plan ID that was rejected
+    pub fp_id: String,
+
+    /// Reason code provided by the carrier
+    pub reason: DenyReason,
+
+    /// Time at which the rejection was registered
+    pub timestamp: DateTime<Utc>,
+}
+
 /// Latitude, longitude, and altitude
 ///  following the WGS-84 standard
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 pub struct PointZ {
     /// Latitude
     pub latitude: f64,
@@ -43,8 +101,118 @@ pub struct Cargo {
     /// Cargo UUID
     pub id: String,
 
-    // /// Cargo weight in grams
-    // pub weight_g: u32
+    /// Cargo weight in grams, None if unknown
+    #[serde(default)]
+    pub weight_g: Option<u32>,
+
+    /// Outer dimensions of the cargo
+    ///
+    /// Reserved: always None until svc-storage records parcel dimensions
+    #[serde(default)]
+    pub dimensions: Option<CargoDimensions>,
+
+    /// True if the cargo contains dangerous goods
+    ///
+    /// Reserved: always None until svc-storage records dangerous goods
+    #[serde(default)]
+    pub dangerous_goods: Option<bool>
+}
+
+/// Outer dimensions of cargo
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CargoDimensions {
+    /// Length in centimeters
+    pub length_cm: u32,
+
+    /// Width in centimeters
+    pub width_cm: u32,
+
+    /// Height in centimeters
+    pub height_cm: u32
+}
+
+/// Kind of payload carried on a flight
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum FlightType {
+    /// Flight without cargo or passengers
+    #[default]
+    Ferry,
+
+    /// Flight carrying cargo only
+    Cargo,
+
+    /// Flight carrying passengers only
+    Rideshare,
+
+    /// Flight carrying both cargo and passengers
+    Mixed
+}
+
+/// Passenger boarding a rideshare flight
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Passenger {
+    /// Passenger (user) UUID
+    pub id: String,
+
+    /// Itinerary UUID under which the passenger booked the flight
+    pub itinerary_id: String,
+
+    /// Assigned seat, None if not assigned
+    #[serde(default)]
+    pub seat: Option<String>,
+
+    /// Checked baggage weight in grams, None if unknown
+    #[serde(default)]
+    pub baggage_weight_g: Option<u32>
+}
+
+/// Procedure an aircraft follows when its command and control link is lost
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ContingencyAction {
+    /// Continue along the planned path to the target vertipad
+    #[default]
+    Continue,
+
+    /// Fly directly back to the origin vertipad
+    ReturnToOrigin,
+
+    /// Fly directly to an alternate vertipad
+    DivertToAlternate
+}
+
+/// Lost-link contingency procedure of a flight plan
+///
+/// When returning or diverting, the aircraft flies directly to the
+///  destination at its current altitude and then descends onto the vertipad.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Contingency {
+    /// Procedure to follow
+    pub action: ContingencyAction,
+
+    /// Vertiport to land at
+    pub vertiport_id: String,
+
+    /// Vertipad to land at
+    pub vertipad_id: String,
+
+    /// Location of the vertipad to land at
+    pub destination: PointZ
+}
+
+/// Landing site an aircraft can divert to instead of its target vertipad
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Alternate {
+    /// Vertiport ID
+    pub vertiport_id: String,
+
+    /// Vertipad ID
+    pub vertipad_id: String,
+
+    /// Location of the vertipad
+    pub position: PointZ,
+
+    /// Horizontal distance in meters from the target vertipad
+    pub distance_meters: f64
 }
 
 /// Flight Plan Information
@@ -90,5 +258,1230 @@ pub struct FlightPlan {
     pub acquire: Vec<Cargo>,
 
     /// Cargo to deliver
-    pub deliver: Vec<Cargo>
+    pub deliver: Vec<Cargo>,
+
+    /// Kind of payload carried on the flight
+    #[serde(default)]
+    pub flight_type: FlightType,
+
+    /// Boarding list of passengers on a rideshare flight
+    #[serde(default)]
+    pub passengers: Vec<Passenger>,
+
+    /// True if the boarding list could not be fetched, the passengers and
+    ///  flight type then leave out any passengers and the payload weight
+    ///  is unknown
+    #[serde(default)]
+    pub passengers_unavailable: bool,
+
+    /// Maneuvers advised to resolve conflicts with other flight plans
+    #[serde(default)]
+    pub advisories: Vec<Advisory>,
+
+    /// Temporary flight restrictions affecting this flight plan
+    #[serde(default)]
+    pub restrictions: Vec<RestrictionNotice>,
+
+    /// Latest takeoff and landing clearances issued for this flight plan
+    #[serde(default)]
+    pub clearances: Vec<Clearance>,
+
+    /// Holding pattern assigned while the target vertipad is occupied
+    #[serde(default)]
+    pub holding: Option<HoldingPattern>,
+
+    /// Current lifecycle state of the flight plan
+    #[serde(default)]
+    pub state: FlightState,
+
+    /// Latest rejection by the carrier, cleared once the flight plan is
+    ///  acknowledged
+    #[serde(default)]
+    pub denial: Option<FlightDenial>,
+
+    /// Segments of the path the aircraft type cannot fly within the
+    ///  timeslots, empty if the aircraft type has no performance profile
+    #[serde(default)]
+    pub performance_violations: Vec<PerformanceViolation>,
+
+    /// Hazards crossed by the path, and wind or visibility outside of
+    ///  limits at the origin or target vertiport
+    #[serde(default)]
+    pub weather_alerts: Vec<WeatherAlert>,
+
+    /// Total weight in grams of the cargo acquired or delivered and the
+    ///  passenger baggage carried on the flight, None if the weight of
+    ///  any item is unknown
+    #[serde(default)]
+    pub payload_weight_g: Option<u64>,
+
+    /// Procedure to follow if the command and control link is lost
+    #[serde(default)]
+    pub contingency: Option<Contingency>,
+
+    /// Flight time left at the planned arrival in seconds, None if the
+    ///  aircraft type has no performance profile
+    #[serde(default)]
+    pub endurance_reserve_seconds: Option<i64>,
+
+    /// Landing sites reachable from the target vertipad within the
+    ///  remaining endurance, nearest first
+    #[serde(default)]
+    pub alternates: Vec<Alternate>
+}
+
+/// Position report from an aircraft
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AircraftPosition {
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Position of the aircraft
+    pub position: PointZ,
+
+    /// Horizontal speed over ground in meters per second
+    pub ground_speed_mps: f64,
+
+    /// Vertical speed in meters per second, positive when climbing
+    pub vertical_speed_mps: f64,
+
+    /// Track over ground in degrees clockwise from true north
+    pub heading_degrees: f64,
+
+    /// Time at which the position was measured
+    pub timestamp: DateTime<Utc>
+}
+
+/// Latest known state of an aircraft in the traffic picture
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrafficReport {
+    /// Latest position report
+    pub position: AircraftPosition,
+
+    /// Time at which the position report was received
+    pub received: DateTime<Utc>,
+
+    /// True if the aircraft has not reported its position recently
+    pub stale: bool
+}
+
+/// Type of deviation from an acknowledged flight plan
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum ConformanceAlertType {
+    /// Aircraft is too far from the planned path
+    Lateral,
+
+    /// Aircraft is too far above or below the planned altitude
+    Altitude,
+
+    /// Aircraft is projected to arrive outside of the target timeslot
+    Schedule
+}
+
+/// Alert raised when an aircraft deviates from its acknowledged flight plan
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ConformanceAlert {
+    /// Flight plan ID
+    pub fp_id: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Type of deviation
+    pub alert_type: ConformanceAlertType,
+
+    /// Deviation from the flight plan, in meters for lateral and altitude
+    ///  alerts and in seconds for schedule alerts.
+    /// Altitude deviations are positive above the plan, schedule
+    ///  deviations are positive when late.
+    pub deviation: f64,
+
+    /// Tolerance that was exceeded, in the same unit as the deviation
+    pub tolerance: f64,
+
+    /// Reported position of the aircraft
+    pub position: PointZ,
+
+    /// Time of the position report that raised the alert
+    pub timestamp: DateTime<Utc>
+}
+
+/// Predicted loss of separation between two airborne aircraft
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SeparationAlert {
+    /// ID of the first aircraft
+    pub aircraft_id_a: String,
+
+    /// ID of the second aircraft
+    pub aircraft_id_b: String,
+
+    /// Seconds until separation is predicted to be lost, zero if already lost
+    pub time_to_conflict_seconds: f64,
+
+    /// Predicted time of closest point of approach
+    pub cpa_time: DateTime<Utc>,
+
+    /// Predicted horizontal distance at closest point of approach in meters
+    pub cpa_horizontal_meters: f64,
+
+    /// Predicted vertical distance at closest point of approach in meters
+    pub cpa_vertical_meters: f64,
+
+    /// Time at which the alert was first raised
+    pub first_detected: DateTime<Utc>,
+
+    /// Time at which the prediction was last updated
+    pub updated: DateTime<Utc>
+}
+
+/// Type of maneuver advised to resolve a conflict
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum ManeuverType {
+    /// Fly the whole path higher or lower
+    AltitudeChange,
+
+    /// Depart later, or fly slower to arrive later
+    Delay,
+
+    /// Fly a path offset to the left or right of the planned path
+    LateralOffset
+}
+
+/// Maneuver advised to an aircraft to resolve a conflict
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Advisory {
+    /// Flight plan the maneuver applies to
+    pub flight_uuid: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Flight plan in conflict with this flight plan
+    pub conflicting_flight_uuid: String,
+
+    /// Type of maneuver
+    pub maneuver: ManeuverType,
+
+    /// Size of the maneuver, in meters for altitude changes (positive is up)
+    ///  and lateral offsets (positive is right of track), and in seconds for
+    ///  delays
+    pub value: f64,
+
+    /// Flight time added by the maneuver in seconds
+    pub added_flight_time_seconds: f64,
+
+    /// Energy added by the maneuver, expressed as meters of level flight
+    pub added_energy_meters: f64,
+
+    /// Path to fly when following the advisory
+    pub path: Vec<PointZ>,
+
+    /// Time at which the advisory was issued
+    pub issued: DateTime<Utc>
+}
+
+/// Latitude and longitude following the WGS-84 standard
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Point {
+    /// Latitude
+    pub latitude: f64,
+
+    /// Longitude
+    pub longitude: f64
+}
+
+/// Time window during which a zone is active
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TimeWindow {
+    /// Start of the window
+    pub start: DateTime<Utc>,
+
+    /// End of the window
+    pub end: DateTime<Utc>
+}
+
+/// Restricted airspace that flight plans may not cross while it is active
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Zone {
+    /// Zone ID, assigned by the service if empty
+    #[serde(default)]
+    pub id: String,
+
+    /// Human readable name
+    #[serde(default)]
+    pub name: String,
+
+    /// Vertices of the zone boundary
+    pub vertices: Vec<Point>,
+
+    /// Lowest restricted altitude in meters
+    pub floor_meters: f64,
+
+    /// Highest restricted altitude in meters
+    pub ceiling_meters: f64,
+
+    /// Time windows during which the zone is active, always active if empty
+    #[serde(default)]
+    pub windows: Vec<TimeWindow>
+}
+
+/// Flight plan path segment crossing an active zone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ZoneViolation {
+    /// Zone ID
+    pub zone_id: String,
+
+    /// Index of the first point of the path segment
+    pub segment_index: usize,
+
+    /// Time at which the aircraft starts flying the segment
+    pub segment_start: DateTime<Utc>,
+
+    /// Time at which the aircraft finishes flying the segment
+    pub segment_end: DateTime<Utc>
+}
+
+/// Performance limit of the aircraft type exceeded by a flight plan
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum PerformanceViolationType {
+    /// Climb rate above the maximum climb rate
+    ClimbRate,
+
+    /// Descent rate above the maximum descent rate
+    DescentRate,
+
+    /// Ground speed below the cruise speed range
+    BelowCruiseSpeed,
+
+    /// Ground speed above the cruise speed range
+    AboveCruiseSpeed,
+
+    /// Altitude above the service ceiling
+    ServiceCeiling,
+
+    /// Flight duration longer than the endurance
+    Endurance
+}
+
+/// Part of a flight plan that the aircraft cannot fly within its timeslots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PerformanceViolation {
+    /// Performance limit exceeded
+    pub violation_type: PerformanceViolationType,
+
+    /// Index of the first point of the path segment, None if the
+    ///  violation concerns the whole flight
+    pub segment_index: Option<usize>,
+
+    /// Value required by the flight plan, in meters per second, meters
+    ///  or seconds depending on the violation type
+    pub value: f64,
+
+    /// Limit of the aircraft type, in the same unit as the value
+    pub limit: f64
+}
+
+/// Temporary flight restriction (TFR) published through a NOTAM
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Restriction {
+    /// Restriction ID, assigned by the service if empty
+    #[serde(default)]
+    pub id: String,
+
+    /// Reason for the restriction, as published in the NOTAM
+    #[serde(default)]
+    pub reason: String,
+
+    /// Vertices of the restricted area
+    pub vertices: Vec<Point>,
+
+    /// Lowest restricted altitude in meters
+    pub floor_meters: f64,
+
+    /// Highest restricted altitude in meters
+    pub ceiling_meters: f64,
+
+    /// Time at which the restriction becomes active
+    pub start: DateTime<Utc>,
+
+    /// Time at which the restriction expires
+    pub end: DateTime<Utc>,
+
+    /// Cancel affected flight plans instead of flagging them
+    #[serde(default)]
+    pub cancel_affected: bool
+}
+
+/// Action taken on a flight plan affected by a restriction
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum RestrictionAction {
+    /// The flight plan was flagged and should be re-planned
+    Flagged,
+
+    /// The flight plan was cancelled
+    Cancelled
+}
+
+/// Notice to a carrier that a restriction affects one of its flight plans
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RestrictionNotice {
+    /// Restriction ID
+    pub restriction_id: String,
+
+    /// Affected flight plan ID
+    pub flight_uuid: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Action taken on the flight plan
+    pub action: RestrictionAction,
+
+    /// Path segments crossing the restricted area
+    pub violations: Vec<ZoneViolation>,
+
+    /// Time at which the notice was issued
+    pub issued: DateTime<Utc>
+}
+
+/// Weather observed or forecast at a point of the weather grid
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct WeatherSample {
+    /// Location of the grid point
+    pub point: Point,
+
+    /// Mean surface wind speed in meters per second
+    pub wind_speed_mps: f64,
+
+    /// Surface wind gust speed in meters per second, None if not reported
+    #[serde(default)]
+    pub wind_gust_mps: Option<f64>,
+
+    /// Direction the wind blows from in degrees clockwise from true north
+    pub wind_direction_degrees: f64,
+
+    /// Horizontal visibility in meters
+    pub visibility_meters: f64
+}
+
+/// Type of hazardous weather
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum HazardType {
+    /// Convective cell, such as a thunderstorm
+    Convective,
+
+    /// Severe turbulence
+    Turbulence,
+
+    /// Icing conditions
+    Icing,
+
+    /// Any other hazard, described by its name
+    Other
+}
+
+/// Area of hazardous weather that flight plans may not cross while it is
+///  active
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct WeatherHazard {
+    /// Hazard ID, assigned by the service if empty
+    #[serde(default)]
+    pub id: String,
+
+    /// Type of hazard
+    pub hazard_type: HazardType,
+
+    /// Human readable name
+    #[serde(default)]
+    pub name: String,
+
+    /// Vertices of the hazard area
+    pub vertices: Vec<Point>,
+
+    /// Lowest altitude of the hazard in meters
+    pub floor_meters: f64,
+
+    /// Highest altitude of the hazard in meters
+    pub ceiling_meters: f64,
+
+    /// Time at which the hazard becomes active
+    pub start: DateTime<Utc>,
+
+    /// Time at which the hazard expires
+    pub end: DateTime<Utc>
+}
+
+/// Gridded weather and hazard areas from a weather provider
+///
+/// Each report replaces the previous one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct WeatherReport {
+    /// Name of the weather provider
+    #[serde(default)]
+    pub source: String,
+
+    /// Time from which the weather grid is valid
+    pub valid_from: DateTime<Utc>,
+
+    /// Time until which the weather grid is valid
+    pub valid_until: DateTime<Utc>,
+
+    /// Weather at the grid points
+    #[serde(default)]
+    pub samples: Vec<WeatherSample>,
+
+    /// Hazard areas
+    #[serde(default)]
+    pub hazards: Vec<WeatherHazard>
+}
+
+/// Weather condition affecting a flight plan
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum WeatherAlertType {
+    /// Path crosses a hazard area while it is active
+    Hazard,
+
+    /// Wind at the origin or target vertiport exceeds the wind limit
+    Wind,
+
+    /// Visibility at the origin or target vertiport is below the minimum
+    Visibility
+}
+
+/// Notice that weather affects a flight plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct WeatherAlert {
+    /// Affected flight plan ID
+    pub flight_uuid: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Weather condition affecting the flight plan
+    pub alert_type: WeatherAlertType,
+
+    /// Hazard crossed by the path, for hazard alerts
+    #[serde(default)]
+    pub hazard_id: Option<String>,
+
+    /// Path segments crossing the hazard, for hazard alerts
+    #[serde(default)]
+    pub violations: Vec<ZoneViolation>,
+
+    /// Vertiport with wind or visibility outside of limits, for wind and
+    ///  visibility alerts
+    #[serde(default)]
+    pub vertiport_id: Option<String>,
+
+    /// Wind speed in meters per second or visibility in meters, for wind
+    ///  and visibility alerts
+    #[serde(default)]
+    pub value: Option<f64>,
+
+    /// Wind limit or visibility minimum, in the same unit as the value
+    #[serde(default)]
+    pub limit: Option<f64>,
+
+    /// Time at which the alert was issued
+    pub issued: DateTime<Utc>
+}
+
+/// Type of operation on a vertipad
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum PadOperationType {
+    /// Aircraft takes off from the vertipad
+    Departure,
+
+    /// Aircraft lands on the vertipad
+    Arrival
+}
+
+/// Occupancy of a vertipad by a flight plan
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PadOperation {
+    /// Flight plan ID
+    pub flight_uuid: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Vertipad ID
+    pub vertipad_id: String,
+
+    /// Type of operation
+    pub operation: PadOperationType,
+
+    /// Start of the timeslot during which the vertipad is occupied
+    pub slot_start: DateTime<Utc>,
+
+    /// End of the timeslot during which the vertipad is occupied
+    pub slot_end: DateTime<Utc>,
+
+    /// Planned time of the takeoff or landing
+    pub planned_time: DateTime<Utc>,
+
+    /// Recommended time of the takeoff or landing, respecting the minimum
+    ///  spacing between operations at the vertiport
+    pub recommended_time: DateTime<Utc>,
+
+    /// Position in the recommended order of operations, starting at 1
+    pub sequence: u32
+}
+
+/// Two flight plans occupying the same vertipad at the same time
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PadConflict {
+    /// Vertipad ID
+    pub vertipad_id: String,
+
+    /// Flight plan occupying the vertipad first
+    pub flight_uuid_a: String,
+
+    /// Flight plan occupying the vertipad second
+    pub flight_uuid_b: String,
+
+    /// Start of the overlap
+    pub overlap_start: DateTime<Utc>,
+
+    /// End of the overlap
+    pub overlap_end: DateTime<Utc>
+}
+
+/// Vertipad occupancy timeline of a vertiport
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VertiportSchedule {
+    /// Vertiport ID
+    pub vertiport_id: String,
+
+    /// Start of the scheduled window
+    pub window_start: DateTime<Utc>,
+
+    /// End of the scheduled window
+    pub window_end: DateTime<Utc>,
+
+    /// Operations in the recommended order
+    pub operations: Vec<PadOperation>,
+
+    /// Overlapping vertipad occupancies
+    pub conflicts: Vec<PadConflict>
+}
+
+/// Type of vertipad clearance
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ClearanceType {
+    /// Clearance to take off from the origin vertipad
+    Takeoff,
+
+    /// Clearance to land on the target vertipad
+    Landing
+}
+
+/// Decision on a clearance request
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ClearanceStatus {
+    /// Aircraft may take off or land until the clearance expires
+    Granted,
+
+    /// Aircraft must wait and request clearance again
+    Hold,
+
+    /// Aircraft may not take off or land with this flight plan
+    Denied
+}
+
+/// Reason for holding or denying a clearance
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ClearanceReason {
+    /// Flight plan has not been acknowledged by the carrier
+    NotAcknowledged,
+
+    /// Aircraft is not assigned to the flight plan
+    AircraftMismatch,
+
+    /// Vertipad is not the one assigned to the flight plan
+    VertipadMismatch,
+
+    /// Vertipad is occupied by another aircraft
+    PadOccupied,
+
+    /// Other aircraft are flying near the vertipad
+    TrafficNearby,
+
+    /// Flight plan is not in a state allowing the clearance
+    InvalidState
+}
+
+/// Request for clearance to take off from or land on a vertipad
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClearanceRequest {
+    /// Flight plan ID
+    pub fp_id: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Vertipad ID
+    pub vertipad_id: String,
+
+    /// Type of clearance
+    pub clearance_type: ClearanceType
+}
+
+/// Clearance issued for a flight plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Clearance {
+    /// Flight plan ID
+    pub fp_id: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Vertipad ID
+    pub vertipad_id: String,
+
+    /// Type of clearance
+    pub clearance_type: ClearanceType,
+
+    /// Decision on the request
+    pub status: ClearanceStatus,
+
+    /// Reason for holding or denying the clearance
+    pub reason: Option<ClearanceReason>,
+
+    /// Time at which the clearance was issued
+    pub issued: DateTime<Utc>,
+
+    /// Time after which a granted clearance is no longer valid
+    pub expires: Option<DateTime<Utc>>,
+
+    /// Clearance was issued to an aircraft declaring an emergency and takes
+    ///  precedence over the scheduled vertipad operations
+    #[serde(default)]
+    pub priority: bool
+}
+
+/// Request to hold an arriving aircraft until its target vertipad is free
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HoldingRequest {
+    /// Flight plan ID
+    pub fp_id: String
+}
+
+/// Holding pattern assigned to an arriving aircraft
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct HoldingPattern {
+    /// Flight plan ID
+    pub fp_id: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Target vertiport ID
+    pub vertiport_id: String,
+
+    /// Target vertipad ID
+    pub vertipad_id: String,
+
+    /// Center of the holding orbit, at the elevation of the target vertipad
+    pub fix: PointZ,
+
+    /// Radius of the holding orbit in meters
+    pub radius_meters: f64,
+
+    /// Level in the holding stack, starting at 1 for the lowest level
+    pub level: u32,
+
+    /// Altitude of the holding orbit in meters
+    pub altitude_meters: f64,
+
+    /// Closed orbit around the fix, flown clockwise
+    pub orbit: Vec<PointZ>,
+
+    /// Time at which the holding pattern was assigned
+    pub assigned: DateTime<Utc>,
+
+    /// Time at which the aircraft is expected to be released for approach
+    pub expected_approach: DateTime<Utc>
+}
+
+/// Type of emergency declared by an aircraft
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum EmergencyType {
+    /// Battery charge is too low to reach the target vertiport
+    LowBattery,
+
+    /// Command and control link with the aircraft was lost
+    LostLink,
+
+    /// Aircraft has a mechanical or system failure
+    Mechanical,
+
+    /// A passenger needs medical attention
+    Medical
+}
+
+/// Emergency declared by an aircraft
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EmergencyDeclaration {
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Type of emergency
+    pub emergency_type: EmergencyType,
+
+    /// Current position of the aircraft
+    pub position: PointZ
+}
+
+/// Vertipad assigned to an aircraft declaring an emergency
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EmergencyResponse {
+    /// Emergency ID
+    pub emergency_id: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Type of emergency
+    pub emergency_type: EmergencyType,
+
+    /// Flight plan the aircraft was flying, if any
+    #[serde(default)]
+    pub fp_id: Option<String>,
+
+    /// Vertiport of the assigned vertipad
+    pub vertiport_id: String,
+
+    /// Assigned vertipad ID
+    pub vertipad_id: String,
+
+    /// Horizontal distance in meters from the aircraft to the vertipad
+    pub distance_meters: f64,
+
+    /// Priority landing clearance on the vertipad
+    pub clearance: Clearance,
+
+    /// Delays advised to the other aircraft scheduled on the vertipad
+    pub advisories: Vec<Advisory>,
+
+    /// Time at which the emergency was declared
+    pub declared: DateTime<Utc>
+}
+
+/// Type of event written to the emergency audit stream
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum EmergencyEventType {
+    /// Aircraft declared an emergency
+    Declared,
+
+    /// Vertipad was assigned and a priority landing clearance issued
+    PadAssigned,
+
+    /// No vertipad within range could receive the aircraft
+    NoSuitablePad,
+
+    /// Delay was advised to another aircraft scheduled on the vertipad
+    DelayAdvised
+}
+
+/// Entry in the emergency audit stream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct EmergencyEvent {
+    /// Sequence number of the event, starting at 1
+    pub sequence: u64,
+
+    /// Emergency ID
+    pub emergency_id: String,
+
+    /// Aircraft declaring the emergency
+    pub aircraft_id: String,
+
+    /// Type of emergency
+    pub emergency_type: EmergencyType,
+
+    /// Type of event
+    pub event_type: EmergencyEventType,
+
+    /// Flight plan of the aircraft declaring the emergency, or of the
+    ///  delayed aircraft for delay events
+    #[serde(default)]
+    pub flight_uuid: Option<String>,
+
+    /// Assigned vertipad ID
+    #[serde(default)]
+    pub vertipad_id: Option<String>,
+
+    /// Position of the aircraft when the emergency was declared
+    #[serde(default)]
+    pub position: Option<PointZ>,
+
+    /// Delay advised in seconds, for delay events
+    #[serde(default)]
+    pub delay_seconds: Option<f64>,
+
+    /// Time at which the event occurred
+    pub timestamp: DateTime<Utc>
+}
+
+/// Request to assign a lost-link contingency procedure to a flight plan
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ContingencyRequest {
+    /// Procedure to follow
+    pub action: ContingencyAction,
+
+    /// Vertipad to divert to, required when diverting to an alternate
+    #[serde(default)]
+    pub alternate_vertipad_id: Option<String>
+}
+
+/// Alert raised when an airborne aircraft stops reporting its position
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LostLinkAlert {
+    /// Flight plan ID
+    pub fp_id: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Contingency procedure the aircraft is assumed to follow
+    pub contingency: Contingency,
+
+    /// Latest position report received from the aircraft
+    pub last_position: AircraftPosition,
+
+    /// Path the aircraft is assumed to fly from its last reported position
+    pub assumed_path: Vec<PointZ>,
+
+    /// Time at which the aircraft is assumed to reach the destination
+    pub assumed_arrival: DateTime<Utc>,
+
+    /// Time at which the alert was raised
+    pub raised: DateTime<Utc>
+}
+
+/// Vertipad in the vertiport registry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct VertipadStatus {
+    /// Vertipad ID
+    pub vertipad_id: String,
+
+    /// Location of the vertipad
+    pub position: PointZ,
+
+    /// True if an aircraft is parked on the vertipad
+    pub occupied: bool
+}
+
+/// Vertiport in the vertiport registry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Vertiport {
+    /// Vertiport ID
+    pub vertiport_id: String,
+
+    /// Center of the enabled vertipads of the vertiport
+    pub position: PointZ,
+
+    /// Enabled vertipads of the vertiport
+    pub vertipads: Vec<VertipadStatus>
+}
+
+/// Request to divert an airborne aircraft to an alternate landing site
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DivertRequest {
+    /// Vertipad to divert to, the nearest alternate if not given
+    #[serde(default)]
+    pub vertipad_id: Option<String>
+}
+
+/// Amendment of a flight plan diverted to an alternate landing site
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Diversion {
+    /// Flight plan ID
+    pub fp_id: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Vertiport diverted to
+    pub vertiport_id: String,
+
+    /// Vertipad diverted to
+    pub vertipad_id: String,
+
+    /// Amended path from the current position of the aircraft
+    pub path: Vec<PointZ>,
+
+    /// Expected arrival at the vertipad
+    pub arrival: DateTime<Utc>,
+
+    /// Landing clearance reserving the vertipad
+    pub clearance: Clearance
+}
+
+/// Airspace sector with a limit on the flights entering it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Sector {
+    /// Sector ID, assigned by the service if empty
+    #[serde(default)]
+    pub id: String,
+
+    /// Human readable name
+    #[serde(default)]
+    pub name: String,
+
+    /// Vertices of the sector boundary
+    pub vertices: Vec<Point>,
+
+    /// Lowest altitude of the sector in meters
+    pub floor_meters: f64,
+
+    /// Highest altitude of the sector in meters
+    pub ceiling_meters: f64,
+
+    /// Maximum number of flights entering the sector per 15 minutes
+    pub capacity: u32
+}
+
+/// Limit on the takeoffs and landings at a vertiport
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct VertiportCapacity {
+    /// Vertiport ID
+    pub vertiport_id: String,
+
+    /// Maximum number of takeoffs and landings per 15 minutes
+    pub capacity: u32
+}
+
+/// Type of airspace resource with a limited capacity
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum CapacityResourceType {
+    /// Flights entering a sector
+    Sector,
+
+    /// Takeoffs and landings at a vertiport
+    Vertiport
+}
+
+/// Period in which the demand for a sector or vertiport exceeds its capacity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Hotspot {
+    /// Type of the oversubscribed resource
+    pub resource_type: CapacityResourceType,
+
+    /// Sector or vertiport ID
+    pub resource_id: String,
+
+    /// Start of the 15 minute period
+    pub period_start: DateTime<Utc>,
+
+    /// End of the 15 minute period
+    pub period_end: DateTime<Utc>,
+
+    /// Maximum number of operations in the period
+    pub capacity: u32,
+
+    /// Number of operations planned in the period
+    pub demand: u32,
+
+    /// Flight plans operating in the period
+    pub flight_uuids: Vec<String>
+}
+
+/// Departure delay proposed to bring the demand under capacity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct GroundDelay {
+    /// Flight plan ID
+    pub flight_uuid: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Planned takeoff time
+    pub planned_departure: DateTime<Utc>,
+
+    /// Proposed takeoff time
+    pub proposed_departure: DateTime<Utc>,
+
+    /// Delay in seconds
+    pub delay_seconds: i64
+}
+
+/// Demand and capacity balance of the sectors and vertiports
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CapacityReport {
+    /// Start of the reported window
+    pub window_start: DateTime<Utc>,
+
+    /// End of the reported window
+    pub window_end: DateTime<Utc>,
+
+    /// Periods oversubscribed by the planned flights, earliest first
+    pub hotspots: Vec<Hotspot>,
+
+    /// Delays proposed to flights that have not departed yet
+    pub ground_delays: Vec<GroundDelay>,
+
+    /// Periods still oversubscribed with the proposed delays, earliest first
+    pub remaining_hotspots: Vec<Hotspot>
+}
+
+/// Lifecycle state of a flight plan
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum FlightState {
+    /// Flight plan is scheduled and awaiting carrier acknowledgement
+    #[default]
+    Scheduled,
+
+    /// Flight plan was acknowledged by the carrier
+    Acknowledged,
+
+    /// Aircraft was cleared to take off
+    Cleared,
+
+    /// Aircraft is flying the flight plan
+    Airborne,
+
+    /// Aircraft landed at the target vertiport
+    Landed,
+
+    /// Flight plan was cancelled
+    Cancelled,
+
+    /// Aircraft was diverted away from the target vertiport
+    Diverted
+}
+
+/// Change in the lifecycle state of a flight plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FlightTransition {
+    /// State before the transition
+    pub from: FlightState,
+
+    /// State after the transition
+    pub to: FlightState,
+
+    /// Time at which the transition occurred
+    pub timestamp: DateTime<Utc>
+}
+
+/// Lifecycle state and transition log of a flight plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FlightHistory {
+    /// Flight plan ID
+    pub fp_id: String,
+
+    /// Current lifecycle state
+    pub state: FlightState,
+
+    /// Transitions in the order they occurred
+    pub transitions: Vec<FlightTransition>
+}
+
+/// State reported by an aircraft for its flight plan
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FlightStateRequest {
+    /// New lifecycle state
+    pub state: FlightState
+}
+
+/// Kind of change carried by a flight plan update
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum PlanUpdateKind {
+    /// Current version of a flight plan, sent when a subscription starts
+    ///  or when its resume token can no longer be replayed
+    Snapshot,
+
+    /// Flight plan was scheduled for the aircraft
+    New,
+
+    /// Flight plan was changed
+    Amended,
+
+    /// Only the advisories or weather alerts attached to the flight plan
+    ///  changed
+    Advisory,
+
+    /// Flight plan was cancelled
+    Cancelled,
+
+    /// Flight plan is no longer returned to the aircraft, as it was denied,
+    ///  cancelled or finished, or moved out of the flight plan window
+    Removed
+}
+
+/// Flight plan update pushed to an aircraft
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PlanUpdate {
+    /// Sequence number of the update, used as resume token
+    pub sequence: u64,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Flight plan ID
+    pub fp_id: String,
+
+    /// Kind of change
+    pub kind: PlanUpdateKind,
+
+    /// Flight plan after the change
+    pub plan: FlightPlan,
+
+    /// Time at which the update was published
+    pub timestamp: DateTime<Utc>
+}
+
+/// Subscription to the flight plan updates of an aircraft
+#[derive(Debug, Clone, Deserialize, Serialize, IntoParams)]
+pub struct PlanUpdatesQuery {
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Sequence number of the last update received, updates after it are
+    ///  replayed before live updates
+    #[serde(default)]
+    pub resume_token: Option<u64>
+}
+
+/// Window of the demand and capacity balance
+#[derive(Debug, Clone, Deserialize, Serialize, IntoParams)]
+pub struct CapacityQuery {
+    /// Start of the window, defaults to the current time
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+
+    /// End of the window, defaults to the flight plan lookahead after its start
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>
+}
+
+/// Query for the flight plans of an aircraft
+#[derive(Debug, Clone, Deserialize, Serialize, IntoParams)]
+pub struct PlansQuery {
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Earliest departure time of the flight plans, defaults to the
+    ///  configured lookbehind before the current time
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+
+    /// Latest departure time of the flight plans, defaults to the
+    ///  configured lookahead after the current time
+    ///
+    /// The window may not be longer than the configured maximum.
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+
+    /// Include the parcels acquired and delivered by the flight plans,
+    ///  defaults to true
+    ///
+    /// Only the parcel lists are left out, the flight type and payload
+    ///  weight still account for the cargo carried on board.
+    #[serde(default)]
+    pub include_cargo: Option<bool>,
+
+    /// Maximum number of flight plans to return, earliest departures first
+    #[serde(default)]
+    pub limit: Option<u32>
 }
@@ -0,0 +1,114 @@
+//! Compares searching the parcels of each flight plan one at a time with
+//!  the batched search of [`attach_parcels`], over mocked svc-storage.
+//!
+//! Run with `cargo bench -p svc-atc --features stub_backends`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use svc_atc::common::attach_parcels;
+use svc_atc::grpc::client::GrpcClients;
+use svc_atc::rest::api::{Cargo, FlightPlan};
+use svc_atc::Config;
+use svc_storage_client_grpc::prelude::*;
+use tokio::runtime::Runtime;
+
+/// Number of flight plans to attach parcels to
+const PLAN_COUNTS: [usize; 3] = [1, 10, 100];
+
+/// Creates flight plans with one parcel each in mocked svc-storage
+async fn setup(count: usize, grpc_clients: &GrpcClients) -> Vec<FlightPlan> {
+    let mut plans = vec![];
+    for _ in 0..count {
+        let object = grpc_clients
+            .storage
+            .flight_plan
+            .insert(flight_plan::mock::get_data_obj())
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap();
+
+        grpc_clients
+            .storage
+            .flight_plan_parcel
+            .insert(flight_plan_parcel::RowData {
+                flight_plan_id: object.id.clone(),
+                parcel_id: lib_common::uuid::Uuid::new_v4().to_string(),
+                acquire: true,
+                deliver: false,
+            })
+            .await
+            .unwrap();
+
+        plans.push(FlightPlan::try_from(object).unwrap());
+    }
+
+    plans
+}
+
+/// Previous implementation, one search per flight plan
+async fn search_per_plan(plans: &mut [FlightPlan], grpc_clients: &GrpcClients) {
+    for plan in plans.iter_mut() {
+        let filter = AdvancedSearchFilter::search_equals(
+            "flight_plan_id".to_owned(),
+            plan.flight_uuid.clone(),
+        );
+
+        grpc_clients
+            .storage
+            .flight_plan_parcel
+            .search(filter)
+            .await
+            .unwrap()
+            .into_inner()
+            .list
+            .into_iter()
+            .filter(|parcel| parcel.acquire)
+            .for_each(|parcel| {
+                plan.acquire.push(Cargo {
+                    id: parcel.parcel_id,
+                    weight_g: None,
+                    dimensions: None,
+                    dangerous_goods: None,
+                })
+            });
+    }
+}
+
+fn bench_parcels(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let grpc_clients = GrpcClients::default(Config::default());
+    let mut group = c.benchmark_group("attach_parcels");
+
+    // The stubbed svc-storage keeps its records for the whole process and
+    //  can't be reset, so it is filled once for the largest count. Every
+    //  count then searches the same store instead of the records piling up
+    //  from one count to the next.
+    let max_count = PLAN_COUNTS.into_iter().max().unwrap_or_default();
+    let all_plans = runtime.block_on(setup(max_count, &grpc_clients));
+
+    for count in PLAN_COUNTS {
+        let plans = all_plans[..count].to_vec();
+
+        group.bench_with_input(BenchmarkId::new("per_plan", count), &plans, |b, plans| {
+            b.to_async(&runtime).iter(|| async {
+                let mut plans = plans.clone();
+                search_per_plan(&mut plans, &grpc_clients).await;
+                plans
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("batched", count), &plans, |b, plans| {
+            b.to_async(&runtime).iter(|| async {
+                let mut plans = plans.clone();
+                attach_parcels(&mut plans, &grpc_clients).await;
+                plans
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parcels);
+criterion_main!(benches);
@@ -0,0 +1,795 @@
+//! Local cache of the flight plans returned to aircraft
+//!
+//! A background task periodically fetches the upcoming flight plans of all
+//!  aircraft and their cargo and passengers from svc-storage, so that
+//!  aircraft requests and the vertipad operations of vertiports are answered
+//!  without searching svc-storage every time.
+//! The same task checks the fetched flight plans for conflicts and issues
+//!  the resolution advisories attached to the flight plans, and registers
+//!  acknowledged flight plans for conformance monitoring. Flight plans
+//!  acknowledged, rejected or cancelled by their carrier are invalidated,
+//!  and the flight plans of their aircraft are fetched again on the next
+//!  request.
+
+use super::conflict::SeparationMinima;
+use super::deconfliction::{
+    get_scheduled_plans, is_scheduled, CheckError, SCHEDULE_LOOKBEHIND_MINUTES,
+};
+use super::{
+    attach_payload, clearance, conformance, expire_denials, lifecycle, performance, resolution,
+    sequencing,
+};
+use crate::config::Config;
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::FlightPlan;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+use svc_storage_client_grpc::prelude::*;
+use tokio::sync::{Mutex, OnceCell};
+
+/// Flight plans cached by this service
+static PLAN_CACHE: OnceCell<Mutex<PlanCache>> = OnceCell::const_new();
+
+/// Returns the flight plans cached by this service
+pub async fn get_plan_cache() -> &'static Mutex<PlanCache> {
+    PLAN_CACHE
+        .get_or_init(|| async { Mutex::new(PlanCache::default()) })
+        .await
+}
+
+/// Flight plans of an aircraft and the time they were fetched
+#[derive(Debug, Clone)]
+pub struct CachedPlans {
+    /// Flight plans with their cargo and passengers, ordered by departure
+    pub plans: Vec<FlightPlan>,
+
+    /// Time at which the flight plans were fetched from svc-storage
+    pub fetched: DateTime<Utc>,
+}
+
+/// Flight plans with their cargo and passengers, indexed by aircraft and vertiport
+#[derive(Debug, Default)]
+pub struct PlanCache {
+    /// Flight plans by ID
+    plans: HashMap<String, FlightPlan>,
+
+    /// Flight plan IDs by aircraft ID
+    aircraft: HashMap<String, HashSet<String>>,
+
+    /// Flight plan IDs by origin and target vertiport ID
+    vertiports: HashMap<String, HashSet<String>>,
+
+    /// Time of the last refresh of all flight plans
+    synced: Option<DateTime<Utc>>,
+
+    /// Aircraft whose flight plans were fetched since the last refresh
+    ///  of all flight plans, and when
+    refreshed: HashMap<String, DateTime<Utc>>,
+
+    /// Aircraft with invalidated flight plans, and when they were
+    ///  invalidated
+    stale: HashMap<String, DateTime<Utc>>,
+}
+
+impl PlanCache {
+    /// Adds a flight plan to the indexes
+    fn insert(&mut self, plan: FlightPlan) {
+        self.aircraft
+            .entry(plan.aircraft_id.clone())
+            .or_default()
+            .insert(plan.flight_uuid.clone());
+
+        for vertiport_id in [&plan.origin_vertiport_id, &plan.target_vertiport_id] {
+            self.vertiports
+                .entry(vertiport_id.clone())
+                .or_default()
+                .insert(plan.flight_uuid.clone());
+        }
+
+        self.plans.insert(plan.flight_uuid.clone(), plan);
+    }
+
+    /// Removes a flight plan from the indexes
+    fn remove(&mut self, fp_id: &str) {
+        let Some(plan) = self.plans.remove(fp_id) else {
+            return;
+        };
+
+        fn unindex(index: &mut HashMap<String, HashSet<String>>, key: &str, fp_id: &str) {
+            if let Some(ids) = index.get_mut(key) {
+                ids.remove(fp_id);
+                if ids.is_empty() {
+                    index.remove(key);
+                }
+            }
+        }
+
+        unindex(&mut self.aircraft, &plan.aircraft_id, fp_id);
+        unindex(&mut self.vertiports, &plan.origin_vertiport_id, fp_id);
+        unindex(&mut self.vertiports, &plan.target_vertiport_id, fp_id);
+    }
+
+    /// Collects flight plans by ID, ordered by departure
+    fn collect<'a>(&self, ids: impl Iterator<Item = &'a String>) -> Vec<FlightPlan> {
+        let mut plans = ids
+            .filter_map(|fp_id| self.plans.get(fp_id))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        plans.sort_by_key(|plan| plan.origin_timeslot_start);
+        plans
+    }
+
+    /// Number of cached flight plans
+    pub fn len(&self) -> usize {
+        self.plans.len()
+    }
+
+    /// True if no flight plans are cached
+    pub fn is_empty(&self) -> bool {
+        self.plans.is_empty()
+    }
+
+    /// Time of the last refresh of all flight plans
+    pub fn synced(&self) -> Option<DateTime<Utc>> {
+        self.synced
+    }
+
+    /// Replaces all cached flight plans with the flight plans fetched
+    ///  at `fetched`
+    ///
+    /// Aircraft invalidated after `fetched` stay outdated, the fetched
+    ///  flight plans may not include the change.
+    pub fn sync(&mut self, plans: Vec<FlightPlan>, fetched: DateTime<Utc>) {
+        self.plans.clear();
+        self.aircraft.clear();
+        self.vertiports.clear();
+        self.refreshed.clear();
+        self.stale.retain(|_, invalidated| *invalidated > fetched);
+        plans.into_iter().for_each(|plan| self.insert(plan));
+        self.synced = Some(fetched);
+    }
+
+    /// Replaces the cached flight plans of an aircraft with the flight
+    ///  plans fetched at `fetched`
+    pub fn refresh(&mut self, aircraft_id: &str, plans: Vec<FlightPlan>, fetched: DateTime<Utc>) {
+        let ids = self.aircraft.remove(aircraft_id).unwrap_or_default();
+        ids.iter().for_each(|fp_id| self.remove(fp_id));
+        plans
+            .into_iter()
+            .filter(|plan| plan.aircraft_id == aircraft_id)
+            .for_each(|plan| self.insert(plan));
+
+        self.refreshed.insert(aircraft_id.to_string(), fetched);
+        if self
+            .stale
+            .get(aircraft_id)
+            .is_some_and(|invalidated| *invalidated <= fetched)
+        {
+            self.stale.remove(aircraft_id);
+        }
+    }
+
+    /// Marks the flight plans of the aircraft flying a flight plan as
+    ///  outdated from `now`
+    pub fn invalidate(&mut self, fp_id: &str, now: DateTime<Utc>) {
+        if let Some(plan) = self.plans.get(fp_id) {
+            common_debug!("invalidating flight plans of {}.", plan.aircraft_id);
+            self.stale.insert(plan.aircraft_id.clone(), now);
+        }
+    }
+
+    /// Cached flight plans of an aircraft, None if they are outdated or
+    ///  were never fetched
+    pub fn aircraft(&self, aircraft_id: &str) -> Option<CachedPlans> {
+        if self.stale.contains_key(aircraft_id) {
+            return None;
+        }
+
+        let fetched = self.refreshed.get(aircraft_id).copied().or(self.synced)?;
+
+        let plans = self
+            .aircraft
+            .get(aircraft_id)
+            .map(|ids| self.collect(ids.iter()))
+            .unwrap_or_default();
+
+        Some(CachedPlans { plans, fetched })
+    }
+
+    /// Cached flight plans departing from or arriving at a vertiport,
+    ///  None if no cached flight plan uses the vertiport, if the flight
+    ///  plans of an aircraft using it are outdated, or if all flight plans
+    ///  were never fetched
+    ///
+    /// Vertiports without cached flight plans may have flight plans
+    ///  scheduled since the last refresh, and are fetched again.
+    pub fn vertiport(&self, vertiport_id: &str) -> Option<CachedPlans> {
+        let fetched = self.synced?;
+        let plans = self.collect(self.vertiports.get(vertiport_id)?.iter());
+        if plans
+            .iter()
+            .any(|plan| self.stale.contains_key(&plan.aircraft_id))
+        {
+            return None;
+        }
+
+        Some(CachedPlans { plans, fetched })
+    }
+
+    /// All cached flight plans, ordered by departure
+    pub fn plans(&self) -> Vec<FlightPlan> {
+        self.collect(self.plans.keys())
+    }
+}
+
+/// Departure window and maximum age of the cached flight plans
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CachePolicy {
+    /// Time before the current time in which flight plans are returned
+    ///  by default
+    pub lookbehind: Duration,
+
+    /// Time after the current time in which flight plans are returned
+    ///  by default
+    pub lookahead: Duration,
+
+    /// Age after which cached flight plans are fetched again when requested
+    pub max_age: Duration,
+}
+
+impl From<&Config> for CachePolicy {
+    fn from(config: &Config) -> Self {
+        CachePolicy {
+            lookbehind: Duration::try_minutes(config.plans_lookbehind_minutes as i64)
+                .unwrap_or(Duration::zero()),
+            lookahead: Duration::try_minutes(config.plans_lookahead_minutes as i64)
+                .unwrap_or(Duration::zero()),
+            max_age: Duration::try_seconds(config.plan_cache_max_age_seconds as i64)
+                .unwrap_or(Duration::zero()),
+        }
+    }
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        CachePolicy::from(&Config::new())
+    }
+}
+
+impl CachePolicy {
+    /// Departure window of the flight plans returned by default at a time
+    pub fn window(&self, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        (now - self.lookbehind, now + self.lookahead)
+    }
+
+    /// Departure window of the flight plans cached at a time, wide enough
+    ///  to answer requests for the default window until the cached flight
+    ///  plans are too old
+    pub fn cached_window(&self, fetched: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let (start, end) = self.window(fetched);
+        (start - self.max_age, end + self.max_age)
+    }
+
+    /// Departure window of the flight plans fetched at a time, reaching
+    ///  back far enough to include the flights still arriving within the
+    ///  cached window
+    pub fn fetched_window(&self, fetched: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let (start, end) = self.cached_window(fetched);
+        let lookbehind =
+            Duration::try_minutes(SCHEDULE_LOOKBEHIND_MINUTES).unwrap_or(Duration::zero());
+        (start - lookbehind, end)
+    }
+}
+
+/// Gets the flight plans of an aircraft departing within a window and
+///  their cargo and passengers from svc-storage, skipping cancelled, draft
+///  and finished plans
+pub async fn fetch_aircraft_plans(
+    aircraft_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    grpc_clients: &GrpcClients,
+) -> Result<Vec<FlightPlan>, CheckError> {
+    let filter =
+        AdvancedSearchFilter::search_equals("vehicle_id".to_owned(), aircraft_id.to_owned())
+            .and_between(
+                "origin_timeslot_start".to_owned(),
+                start.to_string(),
+                end.to_string(),
+            );
+
+    let mut plans = grpc_clients
+        .storage
+        .flight_plan
+        .search(filter)
+        .await
+        .map_err(|e| {
+            common_error!("svc-storage failure: {e}");
+            CheckError::Unavailable
+        })?
+        .into_inner()
+        .list
+        .into_iter()
+        .filter(|object| object.data.as_ref().is_some_and(is_scheduled))
+        .filter_map(|object| FlightPlan::try_from(object).ok())
+        .collect::<Vec<FlightPlan>>();
+
+    attach_payload(&mut plans, grpc_clients).await;
+    performance::check_flight_plans(&mut plans, grpc_clients).await;
+    plans.sort_by_key(|plan| plan.origin_timeslot_start);
+    Ok(plans)
+}
+
+/// Gets the flight plans of an aircraft departing within a window with
+///  their cargo and passengers, and the time they were fetched
+///
+/// Cached flight plans are returned unless they are outdated, too old or
+///  do not cover the window, otherwise they are fetched from svc-storage.
+pub async fn get_aircraft_plans(
+    aircraft_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    policy: &CachePolicy,
+    grpc_clients: &GrpcClients,
+) -> Result<CachedPlans, CheckError> {
+    let now = Utc::now();
+    let within = |(window_start, window_end): (DateTime<Utc>, DateTime<Utc>)| {
+        window_start <= start && end <= window_end
+    };
+
+    let cached = get_plan_cache()
+        .await
+        .lock()
+        .await
+        .aircraft(aircraft_id)
+        .filter(|cached| now - cached.fetched <= policy.max_age)
+        .filter(|cached| within(policy.cached_window(cached.fetched)));
+
+    if let Some(mut cached) = cached {
+        cached.plans.retain(|plan| {
+            start <= plan.origin_timeslot_start && plan.origin_timeslot_start <= end
+        });
+        return Ok(cached);
+    }
+
+    // Windows outside of the cached window are not cached
+    if !within(policy.cached_window(now)) {
+        common_debug!("flight plan window of {aircraft_id} not cached.");
+        let plans = fetch_aircraft_plans(aircraft_id, start, end, grpc_clients).await?;
+        return Ok(CachedPlans {
+            plans,
+            fetched: now,
+        });
+    }
+
+    common_debug!("flight plans of {aircraft_id} not cached.");
+    let (cache_start, cache_end) = policy.fetched_window(now);
+    let mut plans = fetch_aircraft_plans(aircraft_id, cache_start, cache_end, grpc_clients).await?;
+    get_plan_cache()
+        .await
+        .lock()
+        .await
+        .refresh(aircraft_id, plans.clone(), now);
+
+    plans.retain(|plan| start <= plan.origin_timeslot_start && plan.origin_timeslot_start <= end);
+    Ok(CachedPlans {
+        plans,
+        fetched: now,
+    })
+}
+
+/// If a flight plan occupies a vertipad of a vertiport within a window
+fn uses_vertiport(
+    plan: &FlightPlan,
+    vertiport_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> bool {
+    let within = |time: DateTime<Utc>| start <= time && time <= end;
+    (plan.origin_vertiport_id == vertiport_id && within(plan.origin_timeslot_start))
+        || (plan.target_vertiport_id == vertiport_id && within(plan.target_timeslot_start))
+}
+
+/// Gets the flight plans departing from or arriving at a vertiport with
+///  the vertipad occupied within a window
+///
+/// Cached flight plans are returned unless they are outdated, too old or
+///  do not cover the window, otherwise they are fetched from svc-storage.
+pub async fn get_vertiport_plans(
+    vertiport_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    policy: &CachePolicy,
+    grpc_clients: &GrpcClients,
+) -> Result<Vec<FlightPlan>, CheckError> {
+    let now = Utc::now();
+    let cached = get_plan_cache()
+        .await
+        .lock()
+        .await
+        .vertiport(vertiport_id)
+        .filter(|cached| now - cached.fetched <= policy.max_age)
+        .filter(|cached| {
+            let (window_start, window_end) = policy.cached_window(cached.fetched);
+            window_start <= start && end <= window_end
+        });
+
+    let Some(cached) = cached else {
+        common_debug!("flight plans of vertiport {vertiport_id} not cached.");
+        return sequencing::fetch_vertiport_plans(vertiport_id, start, end, grpc_clients).await;
+    };
+
+    Ok(cached
+        .plans
+        .into_iter()
+        .filter(|plan| uses_vertiport(plan, vertiport_id, start, end))
+        .collect())
+}
+
+/// Marks the cached flight plans of the aircraft flying a flight plan as
+///  outdated
+pub async fn invalidate(fp_id: &str) {
+    get_plan_cache()
+        .await
+        .lock()
+        .await
+        .invalidate(fp_id, Utc::now());
+}
+
+/// Refreshes the cached flight plans of all aircraft and the advisories
+///  issued for conflicts between them, and forgets the lifecycles,
+///  rejections and clearances past their retention period
+pub async fn sync_plan_cache(
+    policy: &CachePolicy,
+    minima: &SeparationMinima,
+    grpc_clients: &GrpcClients,
+) -> Result<usize, CheckError> {
+    let now = Utc::now();
+    let (start, end) = policy.fetched_window(now);
+    let mut plans = get_scheduled_plans(start, end, grpc_clients).await?;
+    let advisories = resolution::advise_scheduled_plans(&plans, minima, now).await;
+    common_debug!("{advisories} advisories issued.");
+
+    attach_payload(&mut plans, grpc_clients).await;
+    performance::check_flight_plans(&mut plans, grpc_clients).await;
+
+    let registered = conformance::register_plans(&plans, now).await;
+    if registered > 0 {
+        common_debug!("monitoring conformance of {registered} more flight plans.");
+    }
+
+    let count = plans.len();
+    get_plan_cache().await.lock().await.sync(plans, now);
+
+    let forgotten = lifecycle::get_lifecycles().await.lock().await.expire(now);
+    if !forgotten.is_empty() {
+        common_debug!(
+            "forgot {} landed or cancelled flight plans.",
+            forgotten.len()
+        );
+    }
+
+    let denials = expire_denials(now).await;
+    if denials > 0 {
+        common_debug!("forgot {denials} flight plan rejections.");
+    }
+
+    let clearances = clearance::get_clearances().await.lock().await.expire(now);
+    if clearances > 0 {
+        common_debug!("forgot {clearances} expired clearances.");
+    }
+
+    Ok(count)
+}
+
+/// Periodically refreshes the cached flight plans of all aircraft
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) infinite loop, tested through sync_plan_cache
+pub async fn cache_loop(config: Config) {
+    let grpc_clients = crate::grpc::client::get_clients().await;
+    let policy = CachePolicy::from(&config);
+    let minima = SeparationMinima::from(&config);
+    let period = std::time::Duration::from_secs(config.plan_cache_interval_seconds.max(1) as u64);
+    let mut interval = tokio::time::interval(period);
+
+    common_info!("flight plan cache started.");
+    loop {
+        interval.tick().await;
+        match sync_plan_cache(&policy, &minima, grpc_clients).await {
+            Ok(count) => common_debug!("cached {count} flight plans."),
+            Err(e) => common_warn!("could not refresh flight plan cache: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{mock_flight_plan_data, mock_minima, mock_plan};
+
+    /// Path of the cached flight plans
+    const PATH: [(f64, f64, f64); 2] = [(-39.0, 145.0, 100.0), (-39.0, 145.02, 100.0)];
+
+    #[test]
+    fn test_plan_cache() {
+        let now = Utc::now();
+        let mut cache = PlanCache::default();
+        let first = mock_plan(&PATH, now);
+        let aircraft_id = first.aircraft_id.clone();
+        assert!(cache.aircraft(&aircraft_id).is_none());
+
+        let mut second = mock_plan(&PATH, now + Duration::try_minutes(20).unwrap());
+        second.aircraft_id = aircraft_id.clone();
+        second.origin_vertiport_id = first.target_vertiport_id.clone();
+        let other = mock_plan(&PATH, now);
+        assert!(cache.vertiport(&other.origin_vertiport_id).is_none());
+
+        cache.sync(vec![second.clone(), first.clone(), other.clone()], now);
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.synced(), Some(now));
+
+        let cached = cache.aircraft(&aircraft_id).unwrap();
+        assert_eq!(cached.fetched, now);
+        let ids = cached
+            .plans
+            .iter()
+            .map(|plan| plan.flight_uuid.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![first.flight_uuid.clone(), second.flight_uuid]);
+
+        // Shared vertiport of both flight plans
+        let shared = cache.vertiport(&first.target_vertiport_id).unwrap();
+        assert_eq!(shared.plans.len(), 2);
+        assert_eq!(shared.fetched, now);
+        assert_eq!(
+            cache
+                .vertiport(&other.origin_vertiport_id)
+                .unwrap()
+                .plans
+                .len(),
+            1
+        );
+
+        // Aircraft without flight plans have an empty cache entry, while
+        //  vertiports without flight plans are fetched again
+        assert!(cache.aircraft("unknown").unwrap().plans.is_empty());
+        assert!(cache.vertiport("unknown").is_none());
+
+        cache.invalidate(&first.flight_uuid, now);
+        assert!(cache.aircraft(&aircraft_id).is_none());
+        assert!(cache.aircraft(&other.aircraft_id).is_some());
+        assert!(cache.vertiport(&first.target_vertiport_id).is_none());
+        assert!(cache.vertiport(&other.origin_vertiport_id).is_some());
+
+        let later = now + Duration::try_seconds(5).unwrap();
+        cache.refresh(&aircraft_id, vec![first.clone()], later);
+        let cached = cache.aircraft(&aircraft_id).unwrap();
+        assert_eq!(cached.fetched, later);
+        assert_eq!(cached.plans.len(), 1);
+        assert_eq!(
+            cache
+                .vertiport(&first.target_vertiport_id)
+                .unwrap()
+                .plans
+                .len(),
+            1
+        );
+        assert_eq!(cache.plans().len(), 2);
+
+        // A refresh of all flight plans applies to all aircraft
+        cache.sync(vec![other.clone()], later);
+        assert!(cache.aircraft(&aircraft_id).unwrap().plans.is_empty());
+        assert!(!cache.is_empty());
+
+        // Invalidations after a fetch started outlive the refresh
+        let fetched = later + Duration::try_seconds(1).unwrap();
+        let invalidated = fetched + Duration::try_seconds(1).unwrap();
+        cache.invalidate(&other.flight_uuid, invalidated);
+        cache.sync(vec![other.clone()], fetched);
+        assert!(cache.aircraft(&other.aircraft_id).is_none());
+        cache.refresh(&other.aircraft_id, vec![other.clone()], fetched);
+        assert!(cache.aircraft(&other.aircraft_id).is_none());
+
+        let fetched = invalidated + Duration::try_seconds(1).unwrap();
+        cache.sync(vec![other.clone()], fetched);
+        assert!(cache.aircraft(&other.aircraft_id).is_some());
+    }
+
+    #[test]
+    fn test_cache_policy() {
+        let config = crate::config::Config::default();
+        let policy = CachePolicy::from(&config);
+        assert_eq!(policy, CachePolicy::default());
+        assert_eq!(policy.lookbehind, Duration::try_minutes(60).unwrap());
+        assert_eq!(policy.max_age, Duration::try_seconds(30).unwrap());
+
+        let now = Utc::now();
+        let (start, end) = policy.window(now);
+        assert_eq!(end - start, Duration::try_minutes(120).unwrap());
+
+        let (cached_start, cached_end) = policy.cached_window(now);
+        assert_eq!(start - cached_start, policy.max_age);
+        assert_eq!(cached_end - end, policy.max_age);
+
+        // Flights departing before the cached window may still arrive in it
+        let (fetched_start, fetched_end) = policy.fetched_window(now);
+        assert_eq!(
+            cached_start - fetched_start,
+            Duration::try_minutes(SCHEDULE_LOOKBEHIND_MINUTES).unwrap()
+        );
+        assert_eq!(fetched_end, cached_end);
+    }
+
+    #[test]
+    fn test_uses_vertiport() {
+        let now = Utc::now();
+        let plan = mock_plan(&PATH, now);
+        let origin = plan.origin_vertiport_id.clone();
+        let target = plan.target_vertiport_id.clone();
+        let minute = Duration::try_minutes(1).unwrap();
+
+        assert!(uses_vertiport(&plan, &origin, now - minute, now + minute));
+        assert!(!uses_vertiport(&plan, &target, now - minute, now + minute));
+        assert!(uses_vertiport(
+            &plan,
+            &target,
+            plan.target_timeslot_start,
+            plan.target_timeslot_start + minute
+        ));
+        assert!(!uses_vertiport(&plan, "other", now - minute, now + minute));
+    }
+
+    #[tokio::test]
+    async fn test_get_vertiport_plans() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let policy = CachePolicy::default();
+        let now = Utc::now();
+        let (start, end) = policy.window(now);
+
+        let data = mock_flight_plan_data(
+            &[(-39.8, 145.8, 100.0), (-39.8, 145.82, 100.0)],
+            now,
+            now + Duration::try_minutes(10).unwrap(),
+        );
+        let vertiport_id = data.target_vertiport_id.clone().unwrap();
+        grpc_clients
+            .storage
+            .flight_plan
+            .insert(data.clone())
+            .await
+            .unwrap();
+
+        sync_plan_cache(&policy, &mock_minima(), &grpc_clients)
+            .await
+            .unwrap();
+        let plans = get_vertiport_plans(&vertiport_id, start, end, &policy, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(plans.len(), 1);
+
+        // Arrivals outside of the window do not occupy the vertiport
+        let plans = get_vertiport_plans(
+            &vertiport_id,
+            start,
+            now - Duration::try_minutes(1).unwrap(),
+            &policy,
+            &grpc_clients,
+        )
+        .await
+        .unwrap();
+        assert!(plans.is_empty());
+
+        // Outdated caches are fetched again
+        grpc_clients.storage.flight_plan.insert(data).await.unwrap();
+        let outdated = CachePolicy {
+            max_age: Duration::zero(),
+            ..policy
+        };
+        let plans = get_vertiport_plans(&vertiport_id, start, end, &outdated, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(plans.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_aircraft_plans() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let policy = CachePolicy {
+            lookbehind: Duration::try_minutes(60).unwrap(),
+            lookahead: Duration::try_minutes(60).unwrap(),
+            max_age: Duration::try_minutes(10).unwrap(),
+        };
+        let now = Utc::now();
+        let (start, end) = policy.window(now);
+
+        let data = mock_flight_plan_data(
+            &[(-39.5, 145.5, 100.0), (-39.5, 145.52, 100.0)],
+            now,
+            now + Duration::try_minutes(10).unwrap(),
+        );
+        let aircraft_id = data.vehicle_id.clone();
+        let fp_id = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data.clone())
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        sync_plan_cache(&policy, &mock_minima(), &grpc_clients)
+            .await
+            .unwrap();
+        let cached = get_aircraft_plans(&aircraft_id, start, end, &policy, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(cached.plans.len(), 1);
+        assert_eq!(cached.plans[0].flight_uuid, fp_id);
+
+        grpc_clients.storage.flight_plan.insert(data).await.unwrap();
+
+        // An invalidated aircraft is fetched again
+        invalidate(&fp_id).await;
+        let cached = get_aircraft_plans(&aircraft_id, start, end, &policy, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(cached.plans.len(), 2);
+        assert!(cached.fetched >= now);
+
+        // Outdated caches are fetched again
+        let outdated = CachePolicy {
+            max_age: Duration::zero(),
+            ..policy
+        };
+        let cached = get_aircraft_plans(&aircraft_id, start, end, &outdated, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(cached.plans.len(), 2);
+
+        // Windows outside of the cached window are fetched from svc-storage
+        let departure = now + Duration::try_hours(3).unwrap();
+        let mut data = mock_flight_plan_data(
+            &[(-39.5, 145.5, 100.0), (-39.5, 145.52, 100.0)],
+            departure,
+            departure + Duration::try_minutes(10).unwrap(),
+        );
+        data.vehicle_id = aircraft_id.clone();
+        grpc_clients.storage.flight_plan.insert(data).await.unwrap();
+
+        let cached = get_aircraft_plans(&aircraft_id, start, end, &policy, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(cached.plans.len(), 2);
+
+        let cached = get_aircraft_plans(
+            &aircraft_id,
+            start,
+            now + Duration::try_hours(4).unwrap(),
+            &policy,
+            &grpc_clients,
+        )
+        .await
+        .unwrap();
+        assert_eq!(cached.plans.len(), 3);
+        assert!(cached.plans[2].origin_timeslot_start > end);
+
+        // Flight plans denied by the carrier are not returned to the aircraft
+        crate::common::deny_flight(
+            lib_common::uuid::Uuid::parse_str(&fp_id).unwrap(),
+            crate::rest::api::DenyReason::Maintenance,
+            &grpc_clients,
+        )
+        .await
+        .unwrap();
+        let cached = get_aircraft_plans(&aircraft_id, start, end, &policy, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(cached.plans.len(), 1);
+        assert!(cached.plans.iter().all(|plan| plan.flight_uuid != fp_id));
+    }
+}
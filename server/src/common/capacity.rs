@@ -0,0 +1,1153 @@
+//! Airspace capacity management and demand/capacity balancing
+//!
+//! Sectors limit the flights entering them and vertiports limit their
+//!  takeoffs and landings, per 15 minute period. The demand is counted
+//!  from the flight plans scheduled in svc-storage. Periods where the
+//!  demand exceeds the capacity are reported as hotspots, and ground
+//!  delays are proposed to the flights that have not departed yet to
+//!  bring the demand under capacity.
+
+use super::conflict::Trajectory;
+use super::deconfliction::{get_scheduled_plans, CheckError, SCHEDULE_LOOKBEHIND_MINUTES};
+use super::geo;
+use super::lifecycle::get_lifecycles;
+use super::zones::point_in_polygon;
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::{
+    CapacityReport, CapacityResourceType, FlightPlan, FlightState, GroundDelay, Hotspot, PointZ,
+    Sector, VertiportCapacity,
+};
+use crate::Config;
+use lib_common::time::{DateTime, Duration, Utc};
+use lib_common::uuid::Uuid;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use tokio::sync::{OnceCell, RwLock};
+
+/// Length of the periods in which operations are counted
+pub const CAPACITY_PERIOD_MINUTES: i64 = 15;
+
+/// Step between proposed ground delays in seconds
+pub const GROUND_DELAY_STEP_SECONDS: i64 = 60;
+
+/// Interval at which trajectories are sampled to find sector entries
+pub const SECTOR_SAMPLE_SECONDS: i64 = 10;
+
+/// Sector and vertiport capacities set by operators
+static CAPACITIES: OnceCell<RwLock<CapacityRegistry>> = OnceCell::const_new();
+
+/// Returns the capacity registry shared by the REST and gRPC servers
+pub async fn get_capacities() -> &'static RwLock<CapacityRegistry> {
+    CAPACITIES
+        .get_or_init(|| async { RwLock::new(CapacityRegistry::default()) })
+        .await
+}
+
+/// Errors adding a sector or vertiport capacity
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CapacityError {
+    /// Sector has fewer than three valid vertices
+    Vertices,
+
+    /// Sector floor is not below its ceiling
+    Altitude,
+
+    /// Vertiport ID is not a valid UUID
+    VertiportId,
+}
+
+impl Display for CapacityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CapacityError::Vertices => write!(f, "sector needs at least three valid vertices."),
+            CapacityError::Altitude => write!(f, "sector floor must be below its ceiling."),
+            CapacityError::VertiportId => write!(f, "invalid vertiport ID."),
+        }
+    }
+}
+
+/// Settings used to balance demand and capacity
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CapacityPolicy {
+    /// Capacity of the vertiports without a configured capacity
+    pub vertiport_capacity: u32,
+
+    /// Maximum departure delay proposed to a flight
+    pub max_ground_delay: Duration,
+}
+
+impl From<&Config> for CapacityPolicy {
+    fn from(config: &Config) -> Self {
+        CapacityPolicy {
+            vertiport_capacity: config.vertiport_capacity as u32,
+            max_ground_delay: Duration::try_minutes(config.max_ground_delay_minutes as i64)
+                .unwrap_or(Duration::zero()),
+        }
+    }
+}
+
+impl Default for CapacityPolicy {
+    fn default() -> Self {
+        CapacityPolicy::from(&Config::new())
+    }
+}
+
+/// Checks that a sector can be used to count entries
+pub fn validate(sector: &Sector) -> Result<(), CapacityError> {
+    let valid_vertices = sector.vertices.len() >= 3
+        && sector.vertices.iter().all(|vertex| {
+            (-90.0..=90.0).contains(&vertex.latitude)
+                && (-180.0..=180.0).contains(&vertex.longitude)
+        });
+
+    if !valid_vertices {
+        return Err(CapacityError::Vertices);
+    }
+
+    if !sector.floor_meters.is_finite()
+        || !sector.ceiling_meters.is_finite()
+        || sector.floor_meters >= sector.ceiling_meters
+    {
+        return Err(CapacityError::Altitude);
+    }
+
+    Ok(())
+}
+
+/// Sectors and vertiport capacities, keyed by ID
+#[derive(Debug, Default)]
+pub struct CapacityRegistry {
+    sectors: HashMap<String, Sector>,
+    vertiports: HashMap<String, u32>,
+}
+
+impl CapacityRegistry {
+    /// Adds or replaces a sector, assigning an ID if it has none
+    ///
+    /// Returns the stored sector.
+    pub fn insert_sector(&mut self, mut sector: Sector) -> Result<Sector, CapacityError> {
+        validate(&sector)?;
+
+        if sector.id.is_empty() {
+            sector.id = Uuid::new_v4().to_string();
+        }
+
+        self.sectors.insert(sector.id.clone(), sector.clone());
+        Ok(sector)
+    }
+
+    /// Removes a sector
+    pub fn remove_sector(&mut self, sector_id: &str) -> Option<Sector> {
+        self.sectors.remove(sector_id)
+    }
+
+    /// Returns every sector, ordered by ID
+    pub fn sectors(&self) -> Vec<Sector> {
+        let mut sectors = self.sectors.values().cloned().collect::<Vec<Sector>>();
+        sectors.sort_by(|a, b| a.id.cmp(&b.id));
+        sectors
+    }
+
+    /// Sets the capacity of a vertiport
+    pub fn set_vertiport(&mut self, capacity: VertiportCapacity) -> Result<(), CapacityError> {
+        let vertiport_id = Uuid::parse_str(&capacity.vertiport_id)
+            .map_err(|_| CapacityError::VertiportId)?
+            .to_string();
+
+        self.vertiports.insert(vertiport_id, capacity.capacity);
+        Ok(())
+    }
+
+    /// Removes the capacity of a vertiport, which falls back to the
+    ///  configured default
+    pub fn remove_vertiport(&mut self, vertiport_id: &str) -> Option<VertiportCapacity> {
+        self.vertiports
+            .remove_entry(vertiport_id)
+            .map(|(vertiport_id, capacity)| VertiportCapacity {
+                vertiport_id,
+                capacity,
+            })
+    }
+
+    /// Returns every configured vertiport capacity, ordered by vertiport ID
+    pub fn vertiports(&self) -> Vec<VertiportCapacity> {
+        let mut vertiports = self
+            .vertiports
+            .iter()
+            .map(|(vertiport_id, capacity)| VertiportCapacity {
+                vertiport_id: vertiport_id.clone(),
+                capacity: *capacity,
+            })
+            .collect::<Vec<VertiportCapacity>>();
+
+        vertiports.sort_by(|a, b| a.vertiport_id.cmp(&b.vertiport_id));
+        vertiports
+    }
+
+    /// Capacity of a sector or vertiport per period
+    ///
+    /// Vertiports without a configured capacity use the default of the
+    ///  policy. Returns `None` for unknown sectors.
+    pub fn capacity(
+        &self,
+        resource_type: CapacityResourceType,
+        resource_id: &str,
+        policy: &CapacityPolicy,
+    ) -> Option<u32> {
+        match resource_type {
+            CapacityResourceType::Sector => {
+                self.sectors.get(resource_id).map(|sector| sector.capacity)
+            }
+            CapacityResourceType::Vertiport => Some(
+                self.vertiports
+                    .get(resource_id)
+                    .copied()
+                    .unwrap_or(policy.vertiport_capacity),
+            ),
+        }
+    }
+}
+
+/// Index of the period containing the given time
+pub fn period(time: DateTime<Utc>) -> i64 {
+    time.timestamp().div_euclid(CAPACITY_PERIOD_MINUTES * 60)
+}
+
+/// Start and end of a period
+pub fn period_bounds(period: i64) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let length = CAPACITY_PERIOD_MINUTES * 60;
+    let start = DateTime::from_timestamp(period * length, 0)?;
+    let end = DateTime::from_timestamp((period + 1) * length, 0)?;
+    Some((start, end))
+}
+
+/// Takeoff, landing or sector entry counted against a capacity
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation {
+    /// Type of the resource used
+    pub resource_type: CapacityResourceType,
+
+    /// Sector or vertiport ID
+    pub resource_id: String,
+
+    /// Time of the takeoff, landing or sector entry
+    pub time: DateTime<Utc>,
+}
+
+/// Operations of a flight on the sectors and vertiports
+#[derive(Debug, Clone)]
+pub struct Demand {
+    /// Flight plan ID, empty for candidate flight plans
+    pub flight_uuid: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Planned takeoff time
+    pub departure: DateTime<Utc>,
+
+    /// Takeoffs, landings and sector entries, ordered by time
+    pub operations: Vec<Operation>,
+}
+
+impl Demand {
+    /// Counts the takeoff from the origin vertiport, the landing at the
+    ///  target vertiport and the sector entries along a trajectory
+    pub fn new(
+        origin_vertiport_id: &str,
+        target_vertiport_id: &str,
+        trajectory: &Trajectory,
+        sectors: &[Sector],
+    ) -> Self {
+        let mut operations = vec![
+            Operation {
+                resource_type: CapacityResourceType::Vertiport,
+                resource_id: origin_vertiport_id.to_string(),
+                time: trajectory.start(),
+            },
+            Operation {
+                resource_type: CapacityResourceType::Vertiport,
+                resource_id: target_vertiport_id.to_string(),
+                time: trajectory.end(),
+            },
+        ];
+
+        for sector in sectors.iter() {
+            operations.extend(sector_entries(sector, trajectory).into_iter().map(|time| {
+                Operation {
+                    resource_type: CapacityResourceType::Sector,
+                    resource_id: sector.id.clone(),
+                    time,
+                }
+            }));
+        }
+
+        operations.sort_by_key(|operation| operation.time);
+        Demand {
+            flight_uuid: trajectory.flight_uuid.clone(),
+            aircraft_id: trajectory.aircraft_id.clone(),
+            departure: trajectory.start(),
+            operations,
+        }
+    }
+
+    /// Operations with the departure delayed by `delay`
+    pub fn shifted(&self, delay: Duration) -> Vec<Operation> {
+        self.operations
+            .iter()
+            .map(|operation| Operation {
+                time: operation.time + delay,
+                ..operation.clone()
+            })
+            .collect()
+    }
+}
+
+/// Returns the times at which a trajectory enters a sector
+///
+/// The trajectory is sampled every [`SECTOR_SAMPLE_SECONDS`], a trajectory
+///  starting within the sector enters it at its start.
+pub fn sector_entries(sector: &Sector, trajectory: &Trajectory) -> Vec<DateTime<Utc>> {
+    let origin = match sector.vertices.first() {
+        Some(vertex) => PointZ {
+            latitude: vertex.latitude,
+            longitude: vertex.longitude,
+            altitude_meters: 0.0,
+        },
+        None => return vec![],
+    };
+
+    let polygon = sector
+        .vertices
+        .iter()
+        .map(|vertex| {
+            geo::to_local_meters(
+                &origin,
+                &PointZ {
+                    latitude: vertex.latitude,
+                    longitude: vertex.longitude,
+                    altitude_meters: 0.0,
+                },
+            )
+        })
+        .collect::<Vec<(f64, f64)>>();
+
+    let Some(step) = Duration::try_seconds(SECTOR_SAMPLE_SECONDS) else {
+        return vec![];
+    };
+
+    let mut entries = vec![];
+    let mut inside = false;
+    let mut time = trajectory.start();
+    loop {
+        // Always sample the end of the trajectory
+        let sample = time.min(trajectory.end());
+        let within = trajectory.position_at(sample).is_some_and(|point| {
+            point.altitude_meters >= sector.floor_meters
+                && point.altitude_meters <= sector.ceiling_meters
+                && point_in_polygon(geo::to_local_meters(&origin, &point), &polygon)
+        });
+
+        if within && !inside {
+            entries.push(sample);
+        }
+
+        inside = within;
+        if sample >= trajectory.end() {
+            break;
+        }
+
+        time += step;
+    }
+
+    entries
+}
+
+/// Key of a period of a sector or vertiport
+type PeriodKey = (CapacityResourceType, String, i64);
+
+/// Flight plans counted in each period of each sector and vertiport
+#[derive(Debug, Default, Clone)]
+pub struct DemandCounter {
+    periods: HashMap<PeriodKey, Vec<String>>,
+}
+
+/// Number of operations of a flight in each period
+fn tally(operations: &[Operation]) -> HashMap<PeriodKey, u32> {
+    let mut counts = HashMap::new();
+    for operation in operations.iter() {
+        let key = (
+            operation.resource_type,
+            operation.resource_id.clone(),
+            period(operation.time),
+        );
+
+        *counts.entry(key).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+impl DemandCounter {
+    /// Counts the operations of a flight
+    pub fn add(&mut self, flight_uuid: &str, operations: &[Operation]) {
+        for operation in operations.iter() {
+            let key = (
+                operation.resource_type,
+                operation.resource_id.clone(),
+                period(operation.time),
+            );
+
+            self.periods
+                .entry(key)
+                .or_default()
+                .push(flight_uuid.to_string());
+        }
+    }
+
+    /// Number of operations counted in a period of a sector or vertiport
+    pub fn count(
+        &self,
+        resource_type: CapacityResourceType,
+        resource_id: &str,
+        period: i64,
+    ) -> u32 {
+        self.periods
+            .get(&(resource_type, resource_id.to_string(), period))
+            .map_or(0, |flights| flights.len() as u32)
+    }
+
+    /// True if the operations of a flight can be added without exceeding
+    ///  any capacity
+    ///
+    /// Operations on unknown sectors are not limited.
+    pub fn fits(
+        &self,
+        operations: &[Operation],
+        registry: &CapacityRegistry,
+        policy: &CapacityPolicy,
+    ) -> bool {
+        tally(operations)
+            .into_iter()
+            .all(|((resource_type, resource_id, period), count)| {
+                match registry.capacity(resource_type, &resource_id, policy) {
+                    Some(capacity) => {
+                        self.count(resource_type, &resource_id, period) + count <= capacity
+                    }
+                    None => true,
+                }
+            })
+    }
+
+    /// Hotspot of a period with `extra` operations not counted yet, `None`
+    ///  if the demand is within capacity
+    fn hotspot(
+        &self,
+        key: &PeriodKey,
+        extra: u32,
+        registry: &CapacityRegistry,
+        policy: &CapacityPolicy,
+    ) -> Option<Hotspot> {
+        let (resource_type, resource_id, period) = key;
+        let capacity = registry.capacity(*resource_type, resource_id, policy)?;
+        let flight_uuids = self.periods.get(key).cloned().unwrap_or_default();
+        let demand = flight_uuids.len() as u32 + extra;
+        if demand <= capacity {
+            return None;
+        }
+
+        let (period_start, period_end) = period_bounds(*period)?;
+        Some(Hotspot {
+            resource_type: *resource_type,
+            resource_id: resource_id.clone(),
+            period_start,
+            period_end,
+            capacity,
+            demand,
+            flight_uuids,
+        })
+    }
+
+    /// Returns the periods overlapping the window where the demand exceeds
+    ///  the capacity, earliest first
+    pub fn hotspots(
+        &self,
+        registry: &CapacityRegistry,
+        policy: &CapacityPolicy,
+        window_start: DateTime<Utc>,
+        window_end: DateTime<Utc>,
+    ) -> Vec<Hotspot> {
+        let mut hotspots = self
+            .periods
+            .keys()
+            .filter_map(|key| self.hotspot(key, 0, registry, policy))
+            .filter(|hotspot| {
+                hotspot.period_end > window_start && hotspot.period_start < window_end
+            })
+            .collect::<Vec<Hotspot>>();
+
+        hotspots.sort_by(|a, b| {
+            a.period_start
+                .cmp(&b.period_start)
+                .then_with(|| a.resource_id.cmp(&b.resource_id))
+        });
+
+        hotspots
+    }
+}
+
+/// Smallest delay, in steps of [`GROUND_DELAY_STEP_SECONDS`] up to the
+///  maximum ground delay, at which the operations of a flight fit within
+///  the remaining capacity
+fn earliest_fit(
+    demand: &Demand,
+    counter: &DemandCounter,
+    registry: &CapacityRegistry,
+    policy: &CapacityPolicy,
+) -> Option<Duration> {
+    let max_steps = policy.max_ground_delay.num_seconds() / GROUND_DELAY_STEP_SECONDS;
+    (0..=max_steps)
+        .filter_map(|step| Duration::try_seconds(step * GROUND_DELAY_STEP_SECONDS))
+        .find(|delay| counter.fits(&demand.shifted(*delay), registry, policy))
+}
+
+/// Proposes ground delays bringing the demand under capacity
+///
+/// Flights are served in the order they were planned to depart. The
+///  operations of fixed flights are counted at their planned times, and
+///  each delayable flight is given the smallest delay at which its
+///  operations fit within the remaining capacity. Flights that don't fit
+///  within the maximum ground delay keep their planned times.
+///
+/// Returns the demand with the proposed delays applied and the delays.
+pub fn balance(
+    fixed: &[Demand],
+    delayable: &[Demand],
+    registry: &CapacityRegistry,
+    policy: &CapacityPolicy,
+) -> (DemandCounter, Vec<GroundDelay>) {
+    let mut counter = DemandCounter::default();
+    for demand in fixed.iter() {
+        counter.add(&demand.flight_uuid, &demand.operations);
+    }
+
+    let mut queue = delayable.iter().collect::<Vec<&Demand>>();
+    queue.sort_by(|a, b| {
+        a.departure
+            .cmp(&b.departure)
+            .then_with(|| a.flight_uuid.cmp(&b.flight_uuid))
+    });
+
+    let mut ground_delays = vec![];
+    for demand in queue {
+        let delay = earliest_fit(demand, &counter, registry, policy).unwrap_or_else(|| {
+            common_warn!(
+                "flight plan {} does not fit within the maximum ground delay.",
+                demand.flight_uuid
+            );
+            Duration::zero()
+        });
+
+        counter.add(&demand.flight_uuid, &demand.shifted(delay));
+        if delay > Duration::zero() {
+            ground_delays.push(GroundDelay {
+                flight_uuid: demand.flight_uuid.clone(),
+                aircraft_id: demand.aircraft_id.clone(),
+                planned_departure: demand.departure,
+                proposed_departure: demand.departure + delay,
+                delay_seconds: delay.num_seconds(),
+            });
+        }
+    }
+
+    (counter, ground_delays)
+}
+
+/// Result of checking a candidate flight plan against the capacities
+#[derive(Debug, Clone)]
+pub struct CapacityCheck {
+    /// Periods the candidate would oversubscribe, with the candidate
+    ///  counted in the demand
+    pub hotspots: Vec<Hotspot>,
+
+    /// Smallest departure delay keeping the candidate within capacity,
+    ///  `None` if there is none within the maximum ground delay
+    pub ground_delay: Option<Duration>,
+}
+
+impl CapacityCheck {
+    /// True if the candidate flight plan fits within every capacity
+    pub fn approved(&self) -> bool {
+        self.hotspots.is_empty()
+    }
+}
+
+/// Checks the operations of a candidate flight plan against the demand of
+///  the scheduled flight plans
+pub fn check_candidate(
+    candidate: &Demand,
+    scheduled: &[Demand],
+    registry: &CapacityRegistry,
+    policy: &CapacityPolicy,
+) -> CapacityCheck {
+    let mut counter = DemandCounter::default();
+    for demand in scheduled.iter() {
+        counter.add(&demand.flight_uuid, &demand.operations);
+    }
+
+    let mut hotspots = tally(&candidate.operations)
+        .iter()
+        .filter_map(|(key, count)| counter.hotspot(key, *count, registry, policy))
+        .collect::<Vec<Hotspot>>();
+
+    hotspots.sort_by(|a, b| {
+        a.period_start
+            .cmp(&b.period_start)
+            .then_with(|| a.resource_id.cmp(&b.resource_id))
+    });
+
+    CapacityCheck {
+        hotspots,
+        ground_delay: earliest_fit(candidate, &counter, registry, policy),
+    }
+}
+
+/// Gets the flight plans scheduled in svc-storage that may operate within
+///  the given window, with their current lifecycle state
+///
+/// Cancelled flight plans are left out.
+async fn get_planned_flights(
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+    grpc_clients: &GrpcClients,
+) -> Result<Vec<(FlightPlan, FlightState)>, CheckError> {
+    let lookbehind =
+        Duration::try_minutes(SCHEDULE_LOOKBEHIND_MINUTES).ok_or(CheckError::Internal)?;
+    let plans = get_scheduled_plans(window_start - lookbehind, window_end, grpc_clients).await?;
+
+    let lifecycles = get_lifecycles().await.lock().await;
+    let flights = plans
+        .into_iter()
+        .map(|plan| {
+            let state = lifecycles.state(&plan.flight_uuid).unwrap_or(plan.state);
+            (plan, state)
+        })
+        .filter(|(_, state)| *state != FlightState::Cancelled)
+        .collect();
+
+    Ok(flights)
+}
+
+/// Counts the operations of a flight plan, `None` if it has no valid path
+fn plan_demand(plan: &FlightPlan, sectors: &[Sector]) -> Option<Demand> {
+    let trajectory = Trajectory::try_from(plan)
+        .map_err(|e| common_warn!("skipping flight plan {}: {e}", plan.flight_uuid))
+        .ok()?;
+
+    Some(Demand::new(
+        &plan.origin_vertiport_id,
+        &plan.target_vertiport_id,
+        &trajectory,
+        sectors,
+    ))
+}
+
+/// Reports the hotspots within a window and proposes ground delays to the
+///  flight plans scheduled in svc-storage
+///
+/// Flight plans that are scheduled or acknowledged and have not departed
+///  yet can be delayed. Flight plans departing after the window are
+///  counted up to the maximum ground delay, so that delayed flights are
+///  not moved into hotspots.
+pub async fn get_capacity_report(
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+    now: DateTime<Utc>,
+    policy: &CapacityPolicy,
+    grpc_clients: &GrpcClients,
+) -> Result<CapacityReport, CheckError> {
+    let flights = get_planned_flights(
+        window_start,
+        window_end + policy.max_ground_delay,
+        grpc_clients,
+    )
+    .await?;
+
+    let registry = get_capacities().await.read().await;
+    let sectors = registry.sectors();
+    let mut planned = DemandCounter::default();
+    let mut fixed = vec![];
+    let mut delayable = vec![];
+    for (plan, state) in flights.iter() {
+        let Some(demand) = plan_demand(plan, &sectors) else {
+            continue;
+        };
+
+        planned.add(&demand.flight_uuid, &demand.operations);
+        let on_ground = matches!(state, FlightState::Scheduled | FlightState::Acknowledged);
+        if on_ground && demand.departure > now {
+            delayable.push(demand);
+        } else {
+            fixed.push(demand);
+        }
+    }
+
+    let (balanced, ground_delays) = balance(&fixed, &delayable, &registry, policy);
+    let hotspots = planned.hotspots(&registry, policy, window_start, window_end);
+    let remaining_hotspots = balanced.hotspots(&registry, policy, window_start, window_end);
+    let ground_delays = ground_delays
+        .into_iter()
+        .filter(|delay| delay.planned_departure <= window_end)
+        .collect::<Vec<GroundDelay>>();
+
+    common_debug!(
+        "{} hotspots, {} ground delays, {} remaining hotspots.",
+        hotspots.len(),
+        ground_delays.len(),
+        remaining_hotspots.len()
+    );
+
+    Ok(CapacityReport {
+        window_start,
+        window_end,
+        hotspots,
+        ground_delays,
+        remaining_hotspots,
+    })
+}
+
+/// Checks a candidate flight plan against the capacities and the demand
+///  of the flight plans scheduled in svc-storage
+pub async fn check_capacity(
+    origin_vertiport_id: &str,
+    target_vertiport_id: &str,
+    candidate: &Trajectory,
+    policy: &CapacityPolicy,
+    grpc_clients: &GrpcClients,
+) -> Result<CapacityCheck, CheckError> {
+    let flights = get_planned_flights(
+        candidate.start(),
+        candidate.end() + policy.max_ground_delay,
+        grpc_clients,
+    )
+    .await?;
+
+    let registry = get_capacities().await.read().await;
+    let sectors = registry.sectors();
+    let candidate = Demand::new(
+        origin_vertiport_id,
+        target_vertiport_id,
+        candidate,
+        &sectors,
+    );
+
+    let scheduled = flights
+        .iter()
+        .filter(|(plan, _)| plan.flight_uuid != candidate.flight_uuid)
+        .filter_map(|(plan, _)| plan_demand(plan, &sectors))
+        .collect::<Vec<Demand>>();
+
+    common_debug!(
+        "checking candidate against the demand of {} scheduled plans.",
+        scheduled.len()
+    );
+
+    Ok(check_candidate(&candidate, &scheduled, &registry, policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::Point;
+    use crate::test_util::{mock_flight_plan, mock_flight_plan_data};
+
+    /// Square of roughly 2.2 km around (52.0, 4.0), up to 300 meters
+    fn sector(capacity: u32) -> Sector {
+        Sector {
+            id: Uuid::new_v4().to_string(),
+            name: "test".to_string(),
+            vertices: vec![
+                Point {
+                    latitude: 51.99,
+                    longitude: 3.99,
+                },
+                Point {
+                    latitude: 51.99,
+                    longitude: 4.01,
+                },
+                Point {
+                    latitude: 52.01,
+                    longitude: 4.01,
+                },
+                Point {
+                    latitude: 52.01,
+                    longitude: 3.99,
+                },
+            ],
+            floor_meters: 0.0,
+            ceiling_meters: 300.0,
+            capacity,
+        }
+    }
+
+    /// Flight from `origin` to `target` crossing the test sector from west
+    ///  to east, taking off at `departure` and landing ten minutes later
+    fn demand(origin: &str, target: &str, departure: DateTime<Utc>, sectors: &[Sector]) -> Demand {
+        let plan = mock_flight_plan(
+            &[(52.0, 3.97, 100.0), (52.0, 4.03, 100.0)],
+            departure,
+            departure + Duration::try_minutes(10).unwrap(),
+        );
+
+        let trajectory = Trajectory::try_from(&plan).unwrap();
+        Demand::new(origin, target, &trajectory, sectors)
+    }
+
+    /// Start of a period well away from the current time
+    fn period_start() -> DateTime<Utc> {
+        let (start, _) = period_bounds(period(Utc::now()) + 8).unwrap();
+        start
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = CapacityRegistry::default();
+        let policy = CapacityPolicy::default();
+
+        let mut invalid = sector(2);
+        invalid.vertices.truncate(2);
+        assert_eq!(
+            registry.insert_sector(invalid).unwrap_err(),
+            CapacityError::Vertices
+        );
+
+        let mut invalid = sector(2);
+        invalid.floor_meters = invalid.ceiling_meters;
+        assert_eq!(
+            registry.insert_sector(invalid).unwrap_err(),
+            CapacityError::Altitude
+        );
+
+        let mut unnamed = sector(2);
+        unnamed.id = String::new();
+        let stored = registry.insert_sector(unnamed).unwrap();
+        assert!(!stored.id.is_empty());
+        assert_eq!(
+            registry.capacity(CapacityResourceType::Sector, &stored.id, &policy),
+            Some(2)
+        );
+        assert_eq!(
+            registry.capacity(CapacityResourceType::Sector, "unknown", &policy),
+            None
+        );
+
+        let vertiport_id = Uuid::new_v4().to_string();
+        assert_eq!(
+            registry.capacity(CapacityResourceType::Vertiport, &vertiport_id, &policy),
+            Some(policy.vertiport_capacity)
+        );
+
+        assert_eq!(
+            registry
+                .set_vertiport(VertiportCapacity {
+                    vertiport_id: "invalid".to_string(),
+                    capacity: 1,
+                })
+                .unwrap_err(),
+            CapacityError::VertiportId
+        );
+
+        registry
+            .set_vertiport(VertiportCapacity {
+                vertiport_id: vertiport_id.clone(),
+                capacity: 1,
+            })
+            .unwrap();
+        assert_eq!(
+            registry.capacity(CapacityResourceType::Vertiport, &vertiport_id, &policy),
+            Some(1)
+        );
+        assert_eq!(registry.vertiports().len(), 1);
+
+        assert!(registry.remove_vertiport(&vertiport_id).is_some());
+        assert!(registry.remove_sector(&stored.id).is_some());
+        assert!(registry.sectors().is_empty());
+        assert!(registry.vertiports().is_empty());
+    }
+
+    #[test]
+    fn test_period() {
+        let start = period_start();
+        let (period_start, period_end) = period_bounds(period(start)).unwrap();
+        assert_eq!(period_start, start);
+        assert_eq!(
+            period_end - period_start,
+            Duration::try_minutes(CAPACITY_PERIOD_MINUTES).unwrap()
+        );
+        assert_eq!(
+            period(period_end - Duration::try_seconds(1).unwrap()),
+            period(start)
+        );
+        assert_eq!(period(period_end), period(start) + 1);
+    }
+
+    #[test]
+    fn test_demand() {
+        let departure = period_start();
+        let sectors = vec![sector(2)];
+        let demand = demand("origin", "target", departure, &sectors);
+
+        assert_eq!(demand.departure, departure);
+        assert_eq!(demand.operations.len(), 3);
+        assert_eq!(demand.operations[0].resource_id, "origin");
+        assert_eq!(demand.operations[0].time, departure);
+        assert_eq!(demand.operations[2].resource_id, "target");
+
+        // Path starts 2 km west of the sector and spans 4 km in ten minutes
+        let entry = &demand.operations[1];
+        assert_eq!(entry.resource_type, CapacityResourceType::Sector);
+        assert_eq!(entry.resource_id, sectors[0].id);
+        let offset = (entry.time - departure).num_seconds();
+        assert!((180..=220).contains(&offset), "{offset}");
+
+        // Above the ceiling
+        let mut high = sector(2);
+        high.floor_meters = 500.0;
+        high.ceiling_meters = 600.0;
+        let trajectory = Trajectory::try_from(&mock_flight_plan(
+            &[(52.0, 3.97, 100.0), (52.0, 4.03, 100.0)],
+            departure,
+            departure + Duration::try_minutes(10).unwrap(),
+        ))
+        .unwrap();
+        assert!(sector_entries(&high, &trajectory).is_empty());
+    }
+
+    #[test]
+    fn test_balance() {
+        let start = period_start();
+        let policy = CapacityPolicy::default();
+        let mut registry = CapacityRegistry::default();
+        let sector = registry.insert_sector(sector(2)).unwrap();
+        let sectors = vec![sector.clone()];
+
+        // Three flights enter the sector within the same period
+        let airborne = demand("a", "b", start, &sectors);
+        let first = demand(
+            "c",
+            "d",
+            start + Duration::try_minutes(1).unwrap(),
+            &sectors,
+        );
+        let second = demand(
+            "e",
+            "f",
+            start + Duration::try_minutes(2).unwrap(),
+            &sectors,
+        );
+
+        let mut planned = DemandCounter::default();
+        for demand in [&airborne, &first, &second] {
+            planned.add(&demand.flight_uuid, &demand.operations);
+        }
+
+        let window_end = start + Duration::try_hours(1).unwrap();
+        let hotspots = planned.hotspots(&registry, &policy, start, window_end);
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].resource_type, CapacityResourceType::Sector);
+        assert_eq!(hotspots[0].resource_id, sector.id);
+        assert_eq!(hotspots[0].period_start, start);
+        assert_eq!(hotspots[0].capacity, 2);
+        assert_eq!(hotspots[0].demand, 3);
+        assert_eq!(hotspots[0].flight_uuids.len(), 3);
+
+        // Outside of the window
+        let later = start + Duration::try_minutes(CAPACITY_PERIOD_MINUTES).unwrap();
+        assert!(planned
+            .hotspots(&registry, &policy, later, window_end)
+            .is_empty());
+
+        // The last flight to depart enters the sector in the next period
+        let (balanced, delays) = balance(
+            std::slice::from_ref(&airborne),
+            &[second.clone(), first.clone()],
+            &registry,
+            &policy,
+        );
+        assert_eq!(delays.len(), 1);
+        assert_eq!(delays[0].flight_uuid, second.flight_uuid);
+        assert_eq!(delays[0].planned_departure, second.departure);
+        assert_eq!(delays[0].delay_seconds % GROUND_DELAY_STEP_SECONDS, 0);
+        assert_eq!(
+            delays[0].proposed_departure - delays[0].planned_departure,
+            Duration::try_seconds(delays[0].delay_seconds).unwrap()
+        );
+        assert!(balanced
+            .hotspots(&registry, &policy, start, window_end)
+            .is_empty());
+
+        // Fixed flights are never delayed
+        let (balanced, delays) = balance(
+            &[airborne.clone(), first.clone(), second.clone()],
+            &[],
+            &registry,
+            &policy,
+        );
+        assert!(delays.is_empty());
+        assert_eq!(
+            balanced
+                .hotspots(&registry, &policy, start, window_end)
+                .len(),
+            1
+        );
+
+        // No delay within the maximum
+        let policy = CapacityPolicy {
+            max_ground_delay: Duration::try_minutes(1).unwrap(),
+            ..policy
+        };
+        let (balanced, delays) = balance(&[airborne, first], &[second], &registry, &policy);
+        assert!(delays.is_empty());
+        assert_eq!(
+            balanced
+                .hotspots(&registry, &policy, start, window_end)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_check_candidate() {
+        let start = period_start();
+        let policy = CapacityPolicy::default();
+        let mut registry = CapacityRegistry::default();
+        let vertiport_id = Uuid::new_v4().to_string();
+        registry
+            .set_vertiport(VertiportCapacity {
+                vertiport_id: vertiport_id.clone(),
+                capacity: 1,
+            })
+            .unwrap();
+
+        let scheduled = vec![demand(&vertiport_id, "other", start, &[])];
+        let candidate = demand(
+            &vertiport_id,
+            "other",
+            start + Duration::try_minutes(5).unwrap(),
+            &[],
+        );
+
+        let result = check_candidate(&candidate, &scheduled, &registry, &policy);
+        assert!(!result.approved());
+        assert_eq!(result.hotspots.len(), 1);
+        assert_eq!(result.hotspots[0].resource_id, vertiport_id);
+        assert_eq!(result.hotspots[0].demand, 2);
+        assert_eq!(
+            result.hotspots[0].flight_uuids,
+            vec![scheduled[0].flight_uuid.clone()]
+        );
+
+        // Next period starts ten minutes after the candidate departure
+        assert_eq!(
+            result.ground_delay,
+            Some(Duration::try_minutes(10).unwrap())
+        );
+
+        let result = check_candidate(&candidate, &[], &registry, &policy);
+        assert!(result.approved());
+        assert_eq!(result.ground_delay, Some(Duration::zero()));
+    }
+
+    #[tokio::test]
+    async fn test_get_capacity_report() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let policy = CapacityPolicy::default();
+        let vertiport_id = Uuid::new_v4().to_string();
+        get_capacities()
+            .await
+            .write()
+            .await
+            .set_vertiport(VertiportCapacity {
+                vertiport_id: vertiport_id.clone(),
+                capacity: 2,
+            })
+            .unwrap();
+
+        // Three takeoffs from the vertiport within the same period
+        let start = period_start();
+        for minutes in [1, 2, 3] {
+            let departure = start + Duration::try_minutes(minutes).unwrap();
+            let mut data = mock_flight_plan_data(
+                &[(53.0, 5.0, 100.0), (53.0, 5.02, 100.0)],
+                departure,
+                departure + Duration::try_minutes(10).unwrap(),
+            );
+            data.origin_vertiport_id = Some(vertiport_id.clone());
+            grpc_clients.storage.flight_plan.insert(data).await.unwrap();
+        }
+
+        let window_end = start + Duration::try_hours(1).unwrap();
+        let report = get_capacity_report(start, window_end, Utc::now(), &policy, &grpc_clients)
+            .await
+            .unwrap();
+
+        assert_eq!(report.window_start, start);
+        let hotspot = report
+            .hotspots
+            .iter()
+            .find(|hotspot| hotspot.resource_id == vertiport_id)
+            .unwrap();
+        assert_eq!(hotspot.demand, 3);
+        assert_eq!(hotspot.capacity, 2);
+
+        let delayed = report
+            .ground_delays
+            .iter()
+            .filter(|delay| hotspot.flight_uuids.contains(&delay.flight_uuid))
+            .collect::<Vec<&GroundDelay>>();
+        assert_eq!(delayed.len(), 1);
+        assert_eq!(
+            delayed[0].planned_departure,
+            start + Duration::try_minutes(3).unwrap()
+        );
+        assert!(delayed[0].proposed_departure >= hotspot.period_end);
+        assert!(!report
+            .remaining_hotspots
+            .iter()
+            .any(|hotspot| hotspot.resource_id == vertiport_id));
+
+        // A candidate taking off in the same period must wait for the next one
+        let departure = start + Duration::try_minutes(4).unwrap();
+        let candidate = Trajectory::try_from(&mock_flight_plan(
+            &[(53.0, 5.0, 100.0), (53.0, 5.02, 100.0)],
+            departure,
+            departure + Duration::try_minutes(10).unwrap(),
+        ))
+        .unwrap();
+        let result = check_capacity(&vertiport_id, "other", &candidate, &policy, &grpc_clients)
+            .await
+            .unwrap();
+        assert!(!result.approved());
+        assert_eq!(
+            result.ground_delay,
+            Some(Duration::try_minutes(11).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_capacity_error_display() {
+        assert_eq!(
+            CapacityError::Vertices.to_string(),
+            "sector needs at least three valid vertices."
+        );
+        assert_eq!(
+            CapacityError::Altitude.to_string(),
+            "sector floor must be below its ceiling."
+        );
+        assert_eq!(
+            CapacityError::VertiportId.to_string(),
+            "invalid vertiport ID."
+        );
+    }
+}
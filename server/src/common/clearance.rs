@@ -0,0 +1,839 @@
+//! Takeoff and landing clearances at vertipads
+//!
+//! An aircraft requests clearance before taking off from its origin vertipad
+//!  or landing on its target vertipad. Clearance is denied if the request
+//!  does not match an acknowledged flight plan, held while the vertipad is
+//!  occupied or other aircraft are flying nearby, and granted otherwise.
+//!  A granted clearance reserves the vertipad until it expires.
+
+use super::cache::CachePolicy;
+use super::deconfliction::CheckError;
+use super::geo;
+use super::holding::get_holding;
+use super::lifecycle::{apply_transition, get_lifecycles, lock_flight};
+use super::sequencing::get_pad_operations;
+use super::tactical::airborne_positions;
+use super::traffic::get_traffic;
+use super::{get_flight_data, AckError};
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::{
+    AircraftPosition, Clearance, ClearanceReason, ClearanceRequest, ClearanceStatus, ClearanceType,
+    FlightPlan, FlightState, PadOperation,
+};
+use crate::Config;
+use lib_common::time::{DateTime, Duration, Utc};
+use lib_common::uuid::Uuid;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use svc_storage_client_grpc::prelude::*;
+use tokio::sync::{Mutex, OnceCell};
+
+/// Minutes after a clearance expired, or was held or denied, that it is
+///  kept with its flight plan
+pub const CLEARANCE_RETENTION_MINUTES: i64 = 60;
+
+/// Clearances issued by this service
+static CLEARANCES: OnceCell<Mutex<ClearanceStore>> = OnceCell::const_new();
+
+/// Returns the clearances issued by this service
+pub async fn get_clearances() -> &'static Mutex<ClearanceStore> {
+    CLEARANCES
+        .get_or_init(|| async { Mutex::new(ClearanceStore::default()) })
+        .await
+}
+
+/// Errors requesting a clearance
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ClearanceError {
+    /// Internal Error
+    Internal,
+
+    /// Dependencies not available
+    Unavailable,
+
+    /// Flight Plan Not Found
+    NotFound,
+}
+
+impl Display for ClearanceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ClearanceError::Internal => write!(f, "Internal Error"),
+            ClearanceError::Unavailable => write!(f, "Dependencies not available"),
+            ClearanceError::NotFound => write!(f, "Flight Plan Not Found"),
+        }
+    }
+}
+
+/// Settings used to decide on clearance requests
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ClearancePolicy {
+    /// Time a granted clearance remains valid
+    pub validity: Duration,
+
+    /// Distance from the vertipad within which other aircraft hold clearances
+    pub traffic_radius_meters: f64,
+
+    /// Age after which a position report is ignored
+    pub stale_threshold: Duration,
+
+    /// Window of the flight plans checked for vertipad operations
+    pub plans: CachePolicy,
+}
+
+impl From<&Config> for ClearancePolicy {
+    fn from(config: &Config) -> Self {
+        ClearancePolicy {
+            validity: Duration::try_seconds(config.clearance_validity_seconds as i64)
+                .unwrap_or(Duration::zero()),
+            traffic_radius_meters: config.clearance_traffic_radius_meters,
+            stale_threshold: Duration::try_seconds(config.telemetry_stale_seconds as i64)
+                .unwrap_or(Duration::zero()),
+            plans: CachePolicy::from(config),
+        }
+    }
+}
+
+impl Default for ClearancePolicy {
+    fn default() -> Self {
+        ClearancePolicy::from(&Config::new())
+    }
+}
+
+/// Latest clearances issued for each flight plan
+#[derive(Debug, Default)]
+pub struct ClearanceStore {
+    /// Clearances keyed by flight plan ID, one per clearance type
+    clearances: HashMap<String, Vec<Clearance>>,
+}
+
+impl ClearanceStore {
+    /// Records a clearance, replacing the previous one of the same type
+    pub fn record(&mut self, clearance: Clearance) {
+        let clearances = self.clearances.entry(clearance.fp_id.clone()).or_default();
+        clearances.retain(|existing| existing.clearance_type != clearance.clearance_type);
+        clearances.push(clearance);
+    }
+
+    /// Clearances issued for a flight plan
+    pub fn get(&self, fp_id: &str) -> Vec<Clearance> {
+        self.clearances.get(fp_id).cloned().unwrap_or_default()
+    }
+
+    /// Removes the clearance of a type issued for a flight plan
+    pub fn remove(&mut self, fp_id: &str, clearance_type: ClearanceType) -> Option<Clearance> {
+        let clearances = self.clearances.get_mut(fp_id)?;
+        let index = clearances
+            .iter()
+            .position(|clearance| clearance.clearance_type == clearance_type)?;
+        let removed = clearances.remove(index);
+
+        if clearances.is_empty() {
+            self.clearances.remove(fp_id);
+        }
+
+        Some(removed)
+    }
+
+    /// Removes the clearances issued for a flight plan
+    pub fn remove_flight(&mut self, fp_id: &str) -> Vec<Clearance> {
+        self.clearances.remove(fp_id).unwrap_or_default()
+    }
+
+    /// Forgets the clearances that expired, or were held or denied, more
+    ///  than the retention period before `now`
+    ///
+    /// Returns the number of forgotten clearances.
+    pub fn expire(&mut self, now: DateTime<Utc>) -> usize {
+        let retention =
+            Duration::try_minutes(CLEARANCE_RETENTION_MINUTES).unwrap_or(Duration::zero());
+        let mut count = 0;
+        self.clearances.retain(|_, clearances| {
+            let before = clearances.len();
+            clearances.retain(|clearance| {
+                clearance.expires.unwrap_or(clearance.issued) + retention > now
+            });
+            count += before - clearances.len();
+            !clearances.is_empty()
+        });
+
+        count
+    }
+
+    /// Returns the unexpired clearance reserving a vertipad for another flight plan
+    pub fn pad_holder(
+        &self,
+        vertipad_id: &str,
+        fp_id: &str,
+        now: DateTime<Utc>,
+    ) -> Option<&Clearance> {
+        self.clearances
+            .iter()
+            .filter(|(id, _)| id.as_str() != fp_id)
+            .flat_map(|(_, clearances)| clearances.iter())
+            .find(|clearance| {
+                clearance.status == ClearanceStatus::Granted
+                    && clearance.vertipad_id == vertipad_id
+                    && clearance
+                        .expires
+                        .map(|expires| expires > now)
+                        .unwrap_or(false)
+            })
+    }
+}
+
+/// Returns the time until which a vertipad is occupied by other flight plans
+///
+/// A vertipad is occupied while another flight plan holds an unexpired
+///  clearance for it or while the current time is within the timeslot of
+///  another flight plan on it. Returns `None` if the vertipad is free.
+pub fn pad_occupied_until(
+    vertipad_id: &str,
+    fp_id: &str,
+    store: &ClearanceStore,
+    operations: &[PadOperation],
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let holder = store
+        .pad_holder(vertipad_id, fp_id, now)
+        .and_then(|clearance| clearance.expires);
+
+    operations
+        .iter()
+        .filter(|operation| {
+            operation.vertipad_id == vertipad_id
+                && operation.flight_uuid != fp_id
+                && operation.slot_start <= now
+                && now <= operation.slot_end
+        })
+        .map(|operation| operation.slot_end)
+        .chain(holder)
+        .max()
+}
+
+/// Decides on a clearance request for a flight plan
+///
+/// `state` is the lifecycle state of the flight plan, `operations` are the
+///  vertipad operations of the vertiport and `traffic` the latest positions
+///  of the aircraft tracked nearby.
+#[allow(clippy::too_many_arguments)]
+pub fn evaluate(
+    request: &ClearanceRequest,
+    plan: &FlightPlan,
+    state: FlightState,
+    store: &ClearanceStore,
+    operations: &[PadOperation],
+    traffic: &[AircraftPosition],
+    policy: &ClearancePolicy,
+    now: DateTime<Utc>,
+) -> Clearance {
+    let mut clearance = Clearance {
+        fp_id: plan.flight_uuid.clone(),
+        aircraft_id: request.aircraft_id.clone(),
+        vertipad_id: request.vertipad_id.clone(),
+        clearance_type: request.clearance_type,
+        status: ClearanceStatus::Denied,
+        reason: None,
+        issued: now,
+        expires: None,
+        priority: false,
+    };
+
+    let (vertipad_id, pad) = match request.clearance_type {
+        ClearanceType::Takeoff => (&plan.origin_vertipad_id, plan.path.first()),
+        ClearanceType::Landing => (&plan.target_vertipad_id, plan.path.last()),
+    };
+
+    let reason = if request.aircraft_id != plan.aircraft_id {
+        Some(ClearanceReason::AircraftMismatch)
+    } else if &request.vertipad_id != vertipad_id {
+        Some(ClearanceReason::VertipadMismatch)
+    } else if state == FlightState::Scheduled {
+        Some(ClearanceReason::NotAcknowledged)
+    } else if !match request.clearance_type {
+        ClearanceType::Takeoff => matches!(state, FlightState::Acknowledged | FlightState::Cleared),
+        ClearanceType::Landing => matches!(
+            state,
+            FlightState::Acknowledged | FlightState::Cleared | FlightState::Airborne
+        ),
+    } {
+        Some(ClearanceReason::InvalidState)
+    } else {
+        None
+    };
+
+    if reason.is_some() {
+        clearance.reason = reason;
+        return clearance;
+    }
+
+    let occupied =
+        pad_occupied_until(vertipad_id, &plan.flight_uuid, store, operations, now).is_some();
+
+    let nearby = pad.is_some_and(|pad| {
+        traffic.iter().any(|position| {
+            position.aircraft_id != plan.aircraft_id
+                && geo::distance_meters(pad, &position.position) < policy.traffic_radius_meters
+        })
+    });
+
+    if occupied {
+        clearance.status = ClearanceStatus::Hold;
+        clearance.reason = Some(ClearanceReason::PadOccupied);
+    } else if nearby {
+        clearance.status = ClearanceStatus::Hold;
+        clearance.reason = Some(ClearanceReason::TrafficNearby);
+    } else {
+        clearance.status = ClearanceStatus::Granted;
+        clearance.expires = Some(now + policy.validity);
+    }
+
+    clearance
+}
+
+/// Records the takeoff clearance of a flight plan in svc-storage, next to
+///  its carrier acknowledgement
+async fn release_flight(
+    fp_id: Uuid,
+    mut data: flight_plan::Data,
+    now: DateTime<Utc>,
+    grpc_clients: &GrpcClients,
+) -> Result<(), ClearanceError> {
+    data.flight_release_approval = Some(now.into());
+    let request = flight_plan::UpdateObject {
+        id: fp_id.to_string(),
+        data: Some(data),
+        mask: Some(FieldMask {
+            paths: vec!["flight_release_approval".to_string()],
+        }),
+    };
+
+    grpc_clients
+        .storage
+        .flight_plan
+        .update(request)
+        .await
+        .map_err(|e| {
+            common_error!("{}", e);
+            ClearanceError::Unavailable
+        })?;
+
+    Ok(())
+}
+
+/// Decides on a clearance request and records the clearance with the flight plan
+pub async fn request_clearance(
+    fp_id: Uuid,
+    request: &ClearanceRequest,
+    policy: &ClearancePolicy,
+    grpc_clients: &GrpcClients,
+) -> Result<Clearance, ClearanceError> {
+    let _guard = lock_flight(&fp_id.to_string()).await;
+    let data = get_flight_data(fp_id, grpc_clients)
+        .await
+        .map_err(|e| match e {
+            AckError::NotFound => ClearanceError::NotFound,
+            _ => ClearanceError::Internal,
+        })?;
+
+    let state = get_lifecycles()
+        .await
+        .lock()
+        .await
+        .state(&fp_id.to_string())
+        .unwrap_or_default();
+
+    let object = flight_plan::Object {
+        id: fp_id.to_string(),
+        data: Some(data.clone()),
+    };
+
+    let plan = FlightPlan::try_from(object).map_err(|e| {
+        common_error!("could not get flight plan {fp_id}: {e}");
+        ClearanceError::Internal
+    })?;
+
+    let vertiport_id = match request.clearance_type {
+        ClearanceType::Takeoff => &plan.origin_vertiport_id,
+        ClearanceType::Landing => &plan.target_vertiport_id,
+    };
+
+    let now = Utc::now();
+    let operations = get_pad_operations(vertiport_id, now, &policy.plans, grpc_clients)
+        .await
+        .map_err(|e| match e {
+            CheckError::Unavailable => ClearanceError::Unavailable,
+            CheckError::Internal => ClearanceError::Internal,
+        })?;
+
+    let traffic = get_traffic()
+        .await
+        .read()
+        .await
+        .tracks()
+        .filter(|track| !track.is_stale(now, policy.stale_threshold))
+        .map(|track| track.position.clone())
+        .collect::<Vec<AircraftPosition>>();
+
+    // Aircraft parked on nearby vertipads are not traffic
+    let traffic = airborne_positions(traffic).await;
+
+    let mut store = get_clearances().await.lock().await;
+    let clearance = evaluate(
+        request,
+        &plan,
+        state,
+        &store,
+        &operations,
+        &traffic,
+        policy,
+        now,
+    );
+
+    common_info!(
+        "{:?} clearance for flight plan {fp_id} on vertipad {}: {:?} ({:?}).",
+        clearance.clearance_type,
+        clearance.vertipad_id,
+        clearance.status,
+        clearance.reason
+    );
+
+    store.record(clearance.clone());
+    drop(store);
+
+    // Aircraft cleared for takeoff are cleared until airborne
+    if clearance.clearance_type == ClearanceType::Takeoff
+        && clearance.status == ClearanceStatus::Granted
+    {
+        if let Err(e) = release_flight(fp_id, data, now, grpc_clients).await {
+            get_clearances()
+                .await
+                .lock()
+                .await
+                .remove(&plan.flight_uuid, ClearanceType::Takeoff);
+            return Err(e);
+        }
+
+        apply_transition(&plan.flight_uuid, FlightState::Cleared)
+            .await
+            .map_err(|_| ClearanceError::Internal)?;
+    }
+
+    // Aircraft cleared to land leave their holding stack
+    if clearance.clearance_type == ClearanceType::Landing
+        && clearance.status == ClearanceStatus::Granted
+    {
+        if let Some(pattern) = get_holding().await.lock().await.remove(&plan.flight_uuid) {
+            common_info!(
+                "flight plan {fp_id} left holding level {} for vertipad {}.",
+                pattern.level,
+                pattern.vertipad_id
+            );
+        }
+    }
+
+    Ok(clearance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::{PadOperationType, PointZ};
+    use crate::test_util::{mock_flight_plan_data, mock_plan};
+
+    /// Flight plan from pad-1 to pad-2 departing at `now`
+    fn plan_with_pads(now: DateTime<Utc>) -> FlightPlan {
+        let mut plan = mock_plan(&[(52.0, 4.0, 0.0), (52.0, 4.02, 0.0)], now);
+        plan.origin_vertipad_id = "pad-1".to_string();
+        plan.target_vertipad_id = "pad-2".to_string();
+        plan
+    }
+
+    fn request(plan: &FlightPlan, clearance_type: ClearanceType) -> ClearanceRequest {
+        ClearanceRequest {
+            fp_id: plan.flight_uuid.clone(),
+            aircraft_id: plan.aircraft_id.clone(),
+            vertipad_id: match clearance_type {
+                ClearanceType::Takeoff => plan.origin_vertipad_id.clone(),
+                ClearanceType::Landing => plan.target_vertipad_id.clone(),
+            },
+            clearance_type,
+        }
+    }
+
+    fn position(aircraft_id: &str, latitude: f64, longitude: f64) -> AircraftPosition {
+        AircraftPosition {
+            aircraft_id: aircraft_id.to_string(),
+            position: PointZ {
+                latitude,
+                longitude,
+                altitude_meters: 50.0,
+            },
+            ground_speed_mps: 10.0,
+            vertical_speed_mps: 0.0,
+            heading_degrees: 0.0,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_granted() {
+        let now = Utc::now();
+        let plan = plan_with_pads(now);
+        let policy = ClearancePolicy::default();
+        let store = ClearanceStore::default();
+
+        // Own aircraft and distant traffic don't hold the clearance
+        let traffic = vec![
+            position(&plan.aircraft_id, 52.0, 4.0),
+            position("other", 52.1, 4.0),
+        ];
+
+        for clearance_type in [ClearanceType::Takeoff, ClearanceType::Landing] {
+            let request = request(&plan, clearance_type);
+            let clearance = evaluate(
+                &request,
+                &plan,
+                FlightState::Acknowledged,
+                &store,
+                &[],
+                &traffic,
+                &policy,
+                now,
+            );
+            assert_eq!(clearance.status, ClearanceStatus::Granted);
+            assert_eq!(clearance.reason, None);
+            assert_eq!(clearance.expires, Some(now + policy.validity));
+        }
+    }
+
+    #[test]
+    fn test_evaluate_denied() {
+        let now = Utc::now();
+        let plan = plan_with_pads(now);
+        let policy = ClearancePolicy::default();
+        let store = ClearanceStore::default();
+
+        let mut tmp = request(&plan, ClearanceType::Takeoff);
+        tmp.aircraft_id = "other".to_string();
+        let clearance = evaluate(
+            &tmp,
+            &plan,
+            FlightState::Acknowledged,
+            &store,
+            &[],
+            &[],
+            &policy,
+            now,
+        );
+        assert_eq!(clearance.status, ClearanceStatus::Denied);
+        assert_eq!(clearance.reason, Some(ClearanceReason::AircraftMismatch));
+
+        // Takeoff from the target vertipad
+        let mut tmp = request(&plan, ClearanceType::Takeoff);
+        tmp.vertipad_id = plan.target_vertipad_id.clone();
+        let clearance = evaluate(
+            &tmp,
+            &plan,
+            FlightState::Acknowledged,
+            &store,
+            &[],
+            &[],
+            &policy,
+            now,
+        );
+        assert_eq!(clearance.reason, Some(ClearanceReason::VertipadMismatch));
+
+        let tmp = request(&plan, ClearanceType::Landing);
+        let clearance = evaluate(
+            &tmp,
+            &plan,
+            FlightState::Scheduled,
+            &store,
+            &[],
+            &[],
+            &policy,
+            now,
+        );
+        assert_eq!(clearance.status, ClearanceStatus::Denied);
+        assert_eq!(clearance.reason, Some(ClearanceReason::NotAcknowledged));
+        assert_eq!(clearance.expires, None);
+
+        // Airborne aircraft can land but not take off
+        let clearance = evaluate(
+            &tmp,
+            &plan,
+            FlightState::Airborne,
+            &store,
+            &[],
+            &[],
+            &policy,
+            now,
+        );
+        assert_eq!(clearance.status, ClearanceStatus::Granted);
+
+        let tmp = request(&plan, ClearanceType::Takeoff);
+        for state in [FlightState::Airborne, FlightState::Cancelled] {
+            let clearance = evaluate(&tmp, &plan, state, &store, &[], &[], &policy, now);
+            assert_eq!(clearance.status, ClearanceStatus::Denied);
+            assert_eq!(clearance.reason, Some(ClearanceReason::InvalidState));
+        }
+    }
+
+    #[test]
+    fn test_evaluate_hold() {
+        let now = Utc::now();
+        let plan = plan_with_pads(now);
+        let other = {
+            let mut other = plan_with_pads(now);
+            other.origin_vertipad_id = "pad-2".to_string();
+            other
+        };
+        let policy = ClearancePolicy::default();
+        let request = request(&plan, ClearanceType::Landing);
+
+        // Traffic close to the target vertipad
+        let store = ClearanceStore::default();
+        let traffic = vec![position("other", 52.0, 4.021)];
+        let clearance = evaluate(
+            &request,
+            &plan,
+            FlightState::Acknowledged,
+            &store,
+            &[],
+            &traffic,
+            &policy,
+            now,
+        );
+        assert_eq!(clearance.status, ClearanceStatus::Hold);
+        assert_eq!(clearance.reason, Some(ClearanceReason::TrafficNearby));
+
+        // Another flight plan occupying the vertipad
+        let operations = vec![PadOperation {
+            flight_uuid: other.flight_uuid.clone(),
+            aircraft_id: other.aircraft_id.clone(),
+            vertipad_id: "pad-2".to_string(),
+            operation: PadOperationType::Departure,
+            slot_start: now - Duration::try_minutes(1).unwrap(),
+            slot_end: now + Duration::try_minutes(1).unwrap(),
+            planned_time: now,
+            recommended_time: now,
+            sequence: 1,
+        }];
+        let clearance = evaluate(
+            &request,
+            &plan,
+            FlightState::Acknowledged,
+            &store,
+            &operations,
+            &[],
+            &policy,
+            now,
+        );
+        assert_eq!(clearance.status, ClearanceStatus::Hold);
+        assert_eq!(clearance.reason, Some(ClearanceReason::PadOccupied));
+
+        // Another aircraft was cleared to take off from the vertipad
+        let mut store = ClearanceStore::default();
+        let granted = evaluate(
+            &self::request(&other, ClearanceType::Takeoff),
+            &other,
+            FlightState::Acknowledged,
+            &store,
+            &[],
+            &[],
+            &policy,
+            now,
+        );
+        assert_eq!(granted.status, ClearanceStatus::Granted);
+        store.record(granted);
+
+        let clearance = evaluate(
+            &request,
+            &plan,
+            FlightState::Acknowledged,
+            &store,
+            &[],
+            &[],
+            &policy,
+            now,
+        );
+        assert_eq!(clearance.reason, Some(ClearanceReason::PadOccupied));
+
+        // Reservation ends when the clearance expires
+        let later = now + policy.validity + Duration::try_seconds(1).unwrap();
+        let clearance = evaluate(
+            &request,
+            &plan,
+            FlightState::Acknowledged,
+            &store,
+            &[],
+            &[],
+            &policy,
+            later,
+        );
+        assert_eq!(clearance.status, ClearanceStatus::Granted);
+    }
+
+    #[test]
+    fn test_clearance_store() {
+        let now = Utc::now();
+        let plan = plan_with_pads(now);
+        let policy = ClearancePolicy::default();
+        let mut store = ClearanceStore::default();
+
+        for clearance_type in [
+            ClearanceType::Takeoff,
+            ClearanceType::Landing,
+            ClearanceType::Takeoff,
+        ] {
+            let request = request(&plan, clearance_type);
+            let clearance = evaluate(
+                &request,
+                &plan,
+                FlightState::Acknowledged,
+                &store,
+                &[],
+                &[],
+                &policy,
+                now,
+            );
+            store.record(clearance);
+        }
+
+        // One clearance per type
+        assert_eq!(store.get(&plan.flight_uuid).len(), 2);
+        assert!(store.get("unknown").is_empty());
+
+        // A flight plan does not hold its own clearance
+        assert!(store
+            .pad_holder(&plan.origin_vertipad_id, &plan.flight_uuid, now)
+            .is_none());
+        assert!(store
+            .pad_holder(&plan.origin_vertipad_id, "other", now)
+            .is_some());
+
+        let removed = store
+            .remove(&plan.flight_uuid, ClearanceType::Takeoff)
+            .unwrap();
+        assert_eq!(removed.clearance_type, ClearanceType::Takeoff);
+        assert!(store
+            .remove(&plan.flight_uuid, ClearanceType::Takeoff)
+            .is_none());
+        assert_eq!(store.get(&plan.flight_uuid).len(), 1);
+        assert!(store
+            .remove(&plan.flight_uuid, ClearanceType::Landing)
+            .is_some());
+        assert!(store.get(&plan.flight_uuid).is_empty());
+    }
+
+    #[test]
+    fn test_clearance_store_expire() {
+        let now = Utc::now();
+        let policy = ClearancePolicy::default();
+        let retention = Duration::try_minutes(CLEARANCE_RETENTION_MINUTES).unwrap();
+        let plan = plan_with_pads(now);
+        let mut store = ClearanceStore::default();
+
+        for clearance_type in [ClearanceType::Takeoff, ClearanceType::Landing] {
+            let clearance = evaluate(
+                &request(&plan, clearance_type),
+                &plan,
+                FlightState::Acknowledged,
+                &store,
+                &[],
+                &[],
+                &policy,
+                now,
+            );
+            store.record(clearance);
+        }
+
+        assert_eq!(store.expire(now + retention), 0);
+        assert_eq!(store.expire(now + policy.validity + retention), 2);
+        assert!(store.get(&plan.flight_uuid).is_empty());
+
+        // Clearances of a flight plan are dropped together
+        let clearance = evaluate(
+            &request(&plan, ClearanceType::Takeoff),
+            &plan,
+            FlightState::Acknowledged,
+            &store,
+            &[],
+            &[],
+            &policy,
+            now,
+        );
+        store.record(clearance.clone());
+        assert_eq!(store.remove_flight(&plan.flight_uuid), vec![clearance]);
+        assert!(store.remove_flight(&plan.flight_uuid).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_request_clearance() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let policy = ClearancePolicy::default();
+        let now = Utc::now();
+
+        // Far from the traffic used by other tests
+        let mut data = mock_flight_plan_data(
+            &[(-35.0, 149.0, 0.0), (-35.0, 149.02, 0.0)],
+            now,
+            now + Duration::try_minutes(10).unwrap(),
+        );
+        data.carrier_ack = Some(now.into());
+        let object = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap();
+        let plan = FlightPlan::try_from(object).unwrap();
+        let fp_id = Uuid::parse_str(&plan.flight_uuid).unwrap();
+
+        // Aircraft parked on the vertiport are not traffic
+        let mut parked = position(&Uuid::new_v4().to_string(), -35.0, 149.0001);
+        parked.position.altitude_meters = 0.0;
+        parked.ground_speed_mps = 0.0;
+        get_traffic()
+            .await
+            .write()
+            .await
+            .update(parked, crate::common::traffic::MaxSkew::default())
+            .unwrap();
+
+        let request = request(&plan, ClearanceType::Takeoff);
+        let clearance = request_clearance(fp_id, &request, &policy, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(clearance.status, ClearanceStatus::Granted);
+
+        let clearances = get_clearances().await.lock().await.get(&plan.flight_uuid);
+        assert_eq!(clearances, vec![clearance]);
+
+        let state = get_lifecycles().await.lock().await.state(&plan.flight_uuid);
+        assert_eq!(state, Some(FlightState::Cleared));
+
+        let error = request_clearance(Uuid::new_v4(), &request, &policy, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ClearanceError::NotFound);
+    }
+
+    #[test]
+    fn test_clearance_error_display() {
+        assert_eq!(ClearanceError::Internal.to_string(), "Internal Error");
+        assert_eq!(
+            ClearanceError::Unavailable.to_string(),
+            "Dependencies not available"
+        );
+        assert_eq!(
+            ClearanceError::NotFound.to_string(),
+            "Flight Plan Not Found"
+        );
+    }
+}
@@ -0,0 +1,756 @@
+//! Strategic 4D conflict detection between flight plans
+//!
+//! Each flight plan path is interpolated into a 4D trajectory, assuming the
+//!  aircraft departs at the end of its origin timeslot, arrives at the start
+//!  of its target timeslot, and flies the path at a constant speed.
+//! Pairs of trajectories that are not airborne at the same time, or whose
+//!  bounding boxes are further apart than the separation minima, are
+//!  skipped before their positions are sampled.
+
+use super::geo;
+use crate::rest::api::{FlightPlan, PointZ};
+use crate::Config;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::fmt::{self, Display, Formatter};
+
+/// Interval at which two trajectories are compared
+pub const SAMPLE_INTERVAL_MILLISECONDS: i64 = 1000;
+
+/// Minimum separation to be maintained between two aircraft
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SeparationMinima {
+    /// Minimum horizontal distance in meters
+    pub horizontal_meters: f64,
+
+    /// Minimum vertical distance in meters
+    pub vertical_meters: f64,
+}
+
+impl From<&Config> for SeparationMinima {
+    fn from(config: &Config) -> Self {
+        SeparationMinima {
+            horizontal_meters: config.separation_horizontal_meters,
+            vertical_meters: config.separation_vertical_meters,
+        }
+    }
+}
+
+impl Default for SeparationMinima {
+    fn default() -> Self {
+        SeparationMinima::from(&Config::new())
+    }
+}
+
+impl SeparationMinima {
+    /// Returns true if the two points are closer than the separation minima
+    pub fn is_violated(&self, a: &PointZ, b: &PointZ) -> bool {
+        geo::horizontal_distance_meters(a, b) < self.horizontal_meters
+            && geo::vertical_distance_meters(a, b) < self.vertical_meters
+    }
+}
+
+/// Smallest box enclosing a set of points, aligned with the meridians
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox {
+    /// Corner with the lowest latitude, longitude and altitude
+    pub min: PointZ,
+
+    /// Corner with the highest latitude, longitude and altitude
+    pub max: PointZ,
+}
+
+impl BoundingBox {
+    /// Smallest box enclosing the given points
+    ///
+    /// Returns `None` if there are no points.
+    pub fn enclosing<'a>(points: impl IntoIterator<Item = &'a PointZ>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+        let bounds = BoundingBox {
+            min: first,
+            max: first,
+        };
+
+        Some(points.fold(bounds, |bounds, point| bounds.including(point)))
+    }
+
+    /// Returns this box grown to enclose the given point
+    fn including(mut self, point: &PointZ) -> BoundingBox {
+        self.min.latitude = self.min.latitude.min(point.latitude);
+        self.min.longitude = self.min.longitude.min(point.longitude);
+        self.min.altitude_meters = self.min.altitude_meters.min(point.altitude_meters);
+        self.max.latitude = self.max.latitude.max(point.latitude);
+        self.max.longitude = self.max.longitude.max(point.longitude);
+        self.max.altitude_meters = self.max.altitude_meters.max(point.altitude_meters);
+        self
+    }
+
+    /// Returns this box grown by the separation minima on every side
+    pub fn expanded(&self, minima: &SeparationMinima) -> BoundingBox {
+        let north = (minima.horizontal_meters / geo::EARTH_RADIUS_METERS).to_degrees();
+
+        // Degrees of longitude shrink away from the equator, so the margin
+        //  is sized at the latitude farthest from it
+        let latitude = (self.min.latitude.abs().max(self.max.latitude.abs()) + north).min(90.0);
+        let scale = geo::EARTH_RADIUS_METERS * latitude.to_radians().cos();
+        let east = if scale > minima.horizontal_meters {
+            (minima.horizontal_meters / scale).to_degrees()
+        } else {
+            360.0
+        };
+
+        BoundingBox {
+            min: PointZ {
+                latitude: self.min.latitude - north,
+                longitude: self.min.longitude - east,
+                altitude_meters: self.min.altitude_meters - minima.vertical_meters,
+            },
+            max: PointZ {
+                latitude: self.max.latitude + north,
+                longitude: self.max.longitude + east,
+                altitude_meters: self.max.altitude_meters + minima.vertical_meters,
+            },
+        }
+    }
+
+    /// Returns true if the two boxes overlap
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.latitude <= other.max.latitude
+            && other.min.latitude <= self.max.latitude
+            && self.min.longitude <= other.max.longitude
+            && other.min.longitude <= self.max.longitude
+            && self.min.altitude_meters <= other.max.altitude_meters
+            && other.min.altitude_meters <= self.max.altitude_meters
+    }
+}
+
+/// Errors building a trajectory from a flight plan
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TrajectoryError {
+    /// Flight plan has no path
+    EmptyPath,
+
+    /// Flight plan timeslots are missing, or it arrives before it departs
+    InvalidTimeslot,
+}
+
+impl Display for TrajectoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TrajectoryError::EmptyPath => write!(f, "flight plan has no path."),
+            TrajectoryError::InvalidTimeslot => {
+                write!(f, "flight plan timeslots are invalid.")
+            }
+        }
+    }
+}
+
+/// A position along a trajectory at a given time
+#[derive(Debug, Copy, Clone)]
+pub struct Waypoint4D {
+    /// Position of the aircraft
+    pub point: PointZ,
+
+    /// Time at which the aircraft is at this position
+    pub time: DateTime<Utc>,
+}
+
+/// A flight plan path interpolated over time
+#[derive(Debug, Clone)]
+pub struct Trajectory {
+    /// Flight plan ID
+    pub flight_uuid: String,
+
+    /// Aircraft ID
+    pub aircraft_id: String,
+
+    /// Timestamped waypoints, ordered by time
+    pub waypoints: Vec<Waypoint4D>,
+}
+
+impl TryFrom<&FlightPlan> for Trajectory {
+    type Error = TrajectoryError;
+
+    fn try_from(plan: &FlightPlan) -> Result<Self, Self::Error> {
+        let departure = plan.origin_timeslot_end;
+        let arrival = plan.target_timeslot_start;
+        if arrival < departure {
+            return Err(TrajectoryError::InvalidTimeslot);
+        }
+
+        Trajectory::new(
+            plan.flight_uuid.clone(),
+            plan.aircraft_id.clone(),
+            &plan.path,
+            departure,
+            arrival,
+        )
+    }
+}
+
+impl Trajectory {
+    /// Distributes a path over the given time interval, assuming a constant speed
+    pub fn new(
+        flight_uuid: String,
+        aircraft_id: String,
+        path: &[PointZ],
+        departure: DateTime<Utc>,
+        arrival: DateTime<Utc>,
+    ) -> Result<Self, TrajectoryError> {
+        if path.is_empty() {
+            return Err(TrajectoryError::EmptyPath);
+        }
+
+        if arrival < departure {
+            return Err(TrajectoryError::InvalidTimeslot);
+        }
+
+        let mut cumulative = vec![0.0];
+        for leg in path.windows(2) {
+            let last = cumulative.last().copied().unwrap_or(0.0);
+            cumulative.push(last + geo::distance_meters(&leg[0], &leg[1]));
+        }
+
+        let total = cumulative.last().copied().unwrap_or(0.0);
+        let duration_ms = (arrival - departure).num_milliseconds() as f64;
+        let waypoints = path
+            .iter()
+            .zip(cumulative.iter())
+            .map(|(point, distance)| {
+                let ratio = if total > 0.0 { distance / total } else { 0.0 };
+                let offset = Duration::try_milliseconds((duration_ms * ratio).round() as i64)
+                    .unwrap_or(Duration::zero());
+                Waypoint4D {
+                    point: *point,
+                    time: departure + offset,
+                }
+            })
+            .collect();
+
+        Ok(Trajectory {
+            flight_uuid,
+            aircraft_id,
+            waypoints,
+        })
+    }
+
+    /// Time at which the trajectory starts
+    pub fn start(&self) -> DateTime<Utc> {
+        self.waypoints[0].time
+    }
+
+    /// Time at which the trajectory ends
+    pub fn end(&self) -> DateTime<Utc> {
+        self.waypoints[self.waypoints.len() - 1].time
+    }
+
+    /// Smallest box enclosing every position of the trajectory
+    ///
+    /// Positions are interpolated between waypoints, and never leave the
+    ///  box enclosing them.
+    pub fn bounds(&self) -> BoundingBox {
+        let first = self.waypoints[0].point;
+        let bounds = BoundingBox {
+            min: first,
+            max: first,
+        };
+
+        self.waypoints
+            .iter()
+            .fold(bounds, |bounds, waypoint| bounds.including(&waypoint.point))
+    }
+
+    /// Returns a copy of this trajectory with every waypoint moved by `offset`
+    pub fn shifted(&self, offset: Duration) -> Trajectory {
+        Trajectory {
+            flight_uuid: self.flight_uuid.clone(),
+            aircraft_id: self.aircraft_id.clone(),
+            waypoints: self
+                .waypoints
+                .iter()
+                .map(|waypoint| Waypoint4D {
+                    point: waypoint.point,
+                    time: waypoint.time + offset,
+                })
+                .collect(),
+        }
+    }
+
+    /// Position of the aircraft at the given time
+    ///
+    /// Returns `None` if the aircraft is not airborne at that time.
+    pub fn position_at(&self, time: DateTime<Utc>) -> Option<PointZ> {
+        if time < self.start() || time > self.end() {
+            return None;
+        }
+
+        for leg in self.waypoints.windows(2) {
+            let (a, b) = (&leg[0], &leg[1]);
+            if time > b.time {
+                continue;
+            }
+
+            let leg_ms = (b.time - a.time).num_milliseconds();
+            if leg_ms <= 0 {
+                return Some(b.point);
+            }
+
+            let ratio = (time - a.time).num_milliseconds() as f64 / leg_ms as f64;
+            return Some(geo::interpolate(&a.point, &b.point, ratio));
+        }
+
+        Some(self.waypoints[self.waypoints.len() - 1].point)
+    }
+}
+
+/// A loss of separation between two trajectories
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    /// Flight plan ID of the first aircraft
+    pub flight_uuid_a: String,
+
+    /// ID of the first aircraft
+    pub aircraft_id_a: String,
+
+    /// Flight plan ID of the second aircraft
+    pub flight_uuid_b: String,
+
+    /// ID of the second aircraft
+    pub aircraft_id_b: String,
+
+    /// Time of closest approach
+    pub time: DateTime<Utc>,
+
+    /// Position of the first aircraft at closest approach
+    pub location_a: PointZ,
+
+    /// Position of the second aircraft at closest approach
+    pub location_b: PointZ,
+
+    /// Horizontal distance at closest approach
+    pub horizontal_distance_meters: f64,
+
+    /// Vertical distance at closest approach
+    pub vertical_distance_meters: f64,
+}
+
+/// Finds the closest approach between two trajectories that violates the
+///  separation minima within the given window
+///
+/// Trajectories whose bounding boxes are further apart than the separation
+///  minima can't conflict, and are not sampled.
+pub fn closest_approach(
+    a: &Trajectory,
+    b: &Trajectory,
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+    minima: &SeparationMinima,
+) -> Option<Conflict> {
+    if !a.bounds().expanded(minima).intersects(&b.bounds()) {
+        return None;
+    }
+
+    sample_closest_approach(a, b, window_start, window_end, minima)
+}
+
+/// Samples two trajectories while both are airborne within the given
+///  window, see [`closest_approach`]
+fn sample_closest_approach(
+    a: &Trajectory,
+    b: &Trajectory,
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+    minima: &SeparationMinima,
+) -> Option<Conflict> {
+    let start = a.start().max(b.start()).max(window_start);
+    let end = a.end().min(b.end()).min(window_end);
+    if start > end {
+        return None;
+    }
+
+    let step = Duration::try_milliseconds(SAMPLE_INTERVAL_MILLISECONDS)?;
+    let mut closest: Option<Conflict> = None;
+    let mut time = start;
+    loop {
+        if let (Some(location_a), Some(location_b)) = (a.position_at(time), b.position_at(time)) {
+            let horizontal = geo::horizontal_distance_meters(&location_a, &location_b);
+            let vertical = geo::vertical_distance_meters(&location_a, &location_b);
+            let closer = match &closest {
+                Some(conflict) => horizontal < conflict.horizontal_distance_meters,
+                None => true,
+            };
+
+            if closer && minima.is_violated(&location_a, &location_b) {
+                closest = Some(Conflict {
+                    flight_uuid_a: a.flight_uuid.clone(),
+                    aircraft_id_a: a.aircraft_id.clone(),
+                    flight_uuid_b: b.flight_uuid.clone(),
+                    aircraft_id_b: b.aircraft_id.clone(),
+                    time,
+                    location_a,
+                    location_b,
+                    horizontal_distance_meters: horizontal,
+                    vertical_distance_meters: vertical,
+                });
+            }
+        }
+
+        if time >= end {
+            break;
+        }
+
+        time = (time + step).min(end);
+    }
+
+    closest
+}
+
+/// Compares every pair of trajectories and reports those that violate the
+///  separation minima within the given window
+///
+/// Trajectories are swept in order of departure, so that only the pairs
+///  airborne at the same time within the window are compared, and pairs
+///  with bounding boxes further apart than the separation minima are
+///  skipped. Conflicts are listed, and name their trajectories, in the
+///  order the trajectories were given.
+pub fn detect_trajectory_conflicts(
+    trajectories: &[Trajectory],
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+    minima: &SeparationMinima,
+) -> Vec<Conflict> {
+    // (index, trajectory, bounding box) of the trajectories airborne within
+    //  the window, earliest departure first
+    let mut candidates = trajectories
+        .iter()
+        .enumerate()
+        .filter(|(_, trajectory)| {
+            trajectory.start() <= window_end && trajectory.end() >= window_start
+        })
+        .map(|(index, trajectory)| (index, trajectory, trajectory.bounds()))
+        .collect::<Vec<(usize, &Trajectory, BoundingBox)>>();
+    candidates.sort_by_key(|(_, trajectory, _)| trajectory.start());
+
+    let mut conflicts = vec![];
+    for (position, (index_a, a, bounds_a)) in candidates.iter().enumerate() {
+        let end = a.end().min(window_end);
+        let bounds_a = bounds_a.expanded(minima);
+        for (index_b, b, bounds_b) in candidates.iter().skip(position + 1) {
+            // Later trajectories depart after this one has landed
+            if b.start() > end {
+                break;
+            }
+
+            // An aircraft can't lose separation with itself
+            if a.aircraft_id == b.aircraft_id {
+                continue;
+            }
+
+            if !bounds_a.intersects(bounds_b) {
+                continue;
+            }
+
+            let pair = if index_a < index_b {
+                (*index_a, *index_b, a, b)
+            } else {
+                (*index_b, *index_a, b, a)
+            };
+
+            if let Some(conflict) =
+                sample_closest_approach(pair.2, pair.3, window_start, window_end, minima)
+            {
+                conflicts.push((pair.0, pair.1, conflict));
+            }
+        }
+    }
+
+    conflicts.sort_by_key(|(first, second, _)| (*first, *second));
+    conflicts
+        .into_iter()
+        .map(|(_, _, conflict)| conflict)
+        .collect()
+}
+
+/// Compares every pair of flight plans and reports those that violate the
+///  separation minima within the given window
+///
+/// Flight plans that can't be converted into a trajectory are skipped.
+pub fn detect_conflicts(
+    plans: &[FlightPlan],
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+    minima: &SeparationMinima,
+) -> Vec<Conflict> {
+    let trajectories = plans
+        .iter()
+        .filter_map(|plan| match Trajectory::try_from(plan) {
+            Ok(trajectory) => Some(trajectory),
+            Err(e) => {
+                common_warn!("skipping flight plan {}: {}", plan.flight_uuid, e);
+                None
+            }
+        })
+        .collect::<Vec<Trajectory>>();
+
+    detect_trajectory_conflicts(&trajectories, window_start, window_end, minima)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{mock_eastbound_plan, mock_flight_plan, mock_minima};
+
+    #[test]
+    fn test_trajectory_from_flight_plan() {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+        let plan = mock_flight_plan(
+            &[(52.0, 4.0, 100.0), (52.0, 4.01, 100.0), (52.0, 4.02, 100.0)],
+            departure,
+            arrival,
+        );
+
+        let trajectory = Trajectory::try_from(&plan).unwrap();
+        assert_eq!(trajectory.flight_uuid, plan.flight_uuid);
+        assert_eq!(trajectory.aircraft_id, plan.aircraft_id);
+        assert_eq!(trajectory.waypoints.len(), 3);
+        assert_eq!(trajectory.start(), departure);
+        assert_eq!(trajectory.end(), arrival);
+
+        // Equal leg lengths, so the middle waypoint is reached halfway
+        let halfway = departure + Duration::try_minutes(5).unwrap();
+        assert!(
+            (trajectory.waypoints[1].time - halfway)
+                .num_milliseconds()
+                .abs()
+                <= 1
+        );
+
+        let position = trajectory.position_at(halfway).unwrap();
+        assert!((position.longitude - 4.01).abs() < 1e-6);
+        assert_eq!(position.altitude_meters, 100.0);
+
+        assert!(trajectory
+            .position_at(departure - Duration::try_seconds(1).unwrap())
+            .is_none());
+        assert!(trajectory
+            .position_at(arrival + Duration::try_seconds(1).unwrap())
+            .is_none());
+
+        let offset = Duration::try_minutes(3).unwrap();
+        let shifted = trajectory.shifted(offset);
+        assert_eq!(shifted.start(), departure + offset);
+        assert_eq!(shifted.end(), arrival + offset);
+        assert_eq!(shifted.waypoints.len(), trajectory.waypoints.len());
+    }
+
+    #[test]
+    fn test_trajectory_invalid() {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+
+        let plan = mock_flight_plan(&[], departure, arrival);
+        assert_eq!(
+            Trajectory::try_from(&plan).unwrap_err(),
+            TrajectoryError::EmptyPath
+        );
+
+        let plan = mock_flight_plan(&[(52.0, 4.0, 100.0)], arrival, departure);
+        assert_eq!(
+            Trajectory::try_from(&plan).unwrap_err(),
+            TrajectoryError::InvalidTimeslot
+        );
+    }
+
+    #[test]
+    fn test_detect_conflicts_head_on() {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+        let a = mock_eastbound_plan(departure, arrival);
+        let b = mock_flight_plan(
+            &[(52.0, 4.02, 110.0), (52.0, 4.0, 110.0)],
+            departure,
+            arrival,
+        );
+
+        let conflicts =
+            detect_conflicts(&[a.clone(), b.clone()], departure, arrival, &mock_minima());
+        assert_eq!(conflicts.len(), 1);
+
+        let conflict = &conflicts[0];
+        assert_eq!(conflict.flight_uuid_a, a.flight_uuid);
+        assert_eq!(conflict.flight_uuid_b, b.flight_uuid);
+        assert!(conflict.horizontal_distance_meters < 10.0);
+        assert_eq!(conflict.vertical_distance_meters, 10.0);
+        assert!((conflict.location_a.longitude - 4.01).abs() < 1e-3);
+
+        // Closest approach is halfway through both flights
+        let halfway = departure + Duration::try_minutes(5).unwrap();
+        assert!((conflict.time - halfway).num_seconds().abs() <= 1);
+    }
+
+    #[test]
+    fn test_detect_conflicts_separated() {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+        let a = mock_eastbound_plan(departure, arrival);
+
+        // Vertically separated
+        let b = mock_flight_plan(
+            &[(52.0, 4.02, 200.0), (52.0, 4.0, 200.0)],
+            departure,
+            arrival,
+        );
+        assert!(detect_conflicts(&[a.clone(), b], departure, arrival, &mock_minima()).is_empty());
+
+        // Separated in time
+        let delay = Duration::try_minutes(30).unwrap();
+        let b = mock_flight_plan(
+            &[(52.0, 4.02, 100.0), (52.0, 4.0, 100.0)],
+            departure + delay,
+            arrival + delay,
+        );
+        assert!(
+            detect_conflicts(&[a.clone(), b], departure, arrival + delay, &mock_minima())
+                .is_empty()
+        );
+
+        // Laterally separated
+        let b = mock_flight_plan(
+            &[(52.01, 4.02, 100.0), (52.01, 4.0, 100.0)],
+            departure,
+            arrival,
+        );
+        assert!(detect_conflicts(&[a, b], departure, arrival, &mock_minima()).is_empty());
+    }
+
+    #[test]
+    fn test_detect_conflicts_window() {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+        let a = mock_eastbound_plan(departure, arrival);
+        let b = mock_flight_plan(
+            &[(52.0, 4.02, 100.0), (52.0, 4.0, 100.0)],
+            departure,
+            arrival,
+        );
+
+        // Closest approach happens after the window
+        let window_end = departure + Duration::try_minutes(2).unwrap();
+        let conflicts = detect_conflicts(&[a, b], departure, window_end, &mock_minima());
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        assert!(BoundingBox::enclosing(&Vec::<PointZ>::new()).is_none());
+
+        let points = [
+            PointZ {
+                latitude: 52.0,
+                longitude: 4.02,
+                altitude_meters: 100.0,
+            },
+            PointZ {
+                latitude: 52.01,
+                longitude: 4.0,
+                altitude_meters: 150.0,
+            },
+        ];
+        let bounds = BoundingBox::enclosing(&points).unwrap();
+        assert_eq!(bounds.min.latitude, 52.0);
+        assert_eq!(bounds.min.longitude, 4.0);
+        assert_eq!(bounds.min.altitude_meters, 100.0);
+        assert_eq!(bounds.max.latitude, 52.01);
+        assert_eq!(bounds.max.longitude, 4.02);
+        assert_eq!(bounds.max.altitude_meters, 150.0);
+
+        // Points just inside the separation minima of the box
+        let minima = mock_minima();
+        let expanded = bounds.expanded(&minima);
+        let margin = 0.9;
+        let near = [
+            geo::from_local_meters(
+                &bounds.max,
+                minima.horizontal_meters * margin,
+                minima.horizontal_meters * margin,
+                150.0 + minima.vertical_meters * margin,
+            ),
+            geo::from_local_meters(
+                &bounds.min,
+                -minima.horizontal_meters * margin,
+                -minima.horizontal_meters * margin,
+                100.0 - minima.vertical_meters * margin,
+            ),
+        ];
+        for point in near {
+            let other = BoundingBox::enclosing(&[point]).unwrap();
+            assert!(expanded.intersects(&other));
+            assert!(other.intersects(&expanded));
+            assert!(!bounds.intersects(&other));
+        }
+
+        // Points beyond the separation minima of the box
+        let far = geo::from_local_meters(&bounds.max, minima.horizontal_meters * 2.0, 0.0, 150.0);
+        assert!(!expanded.intersects(&BoundingBox::enclosing(&[far]).unwrap()));
+    }
+
+    #[test]
+    fn test_detect_conflicts_prefilter() {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+        let delay = Duration::try_minutes(30).unwrap();
+
+        // Departs after the others have landed
+        let late = mock_flight_plan(
+            &[(52.0, 4.02, 100.0), (52.0, 4.0, 100.0)],
+            departure + delay,
+            arrival + delay,
+        );
+
+        // Far away from the others
+        let distant = mock_flight_plan(
+            &[(53.0, 5.02, 100.0), (53.0, 5.0, 100.0)],
+            departure,
+            arrival,
+        );
+
+        // Head on with the eastbound flight plan, but departs slightly later
+        let offset = Duration::try_seconds(10).unwrap();
+        let westbound = mock_flight_plan(
+            &[(52.0, 4.02, 110.0), (52.0, 4.0, 110.0)],
+            departure + offset,
+            arrival + offset,
+        );
+        let eastbound = mock_eastbound_plan(departure, arrival);
+
+        let plans = [late, distant, westbound.clone(), eastbound.clone()];
+        let conflicts = detect_conflicts(&plans, departure, arrival + delay, &mock_minima());
+        assert_eq!(conflicts.len(), 1);
+
+        // Named in the order the flight plans were given
+        assert_eq!(conflicts[0].flight_uuid_a, westbound.flight_uuid);
+        assert_eq!(conflicts[0].flight_uuid_b, eastbound.flight_uuid);
+
+        // Bounding boxes apart
+        let a = Trajectory::try_from(&plans[1]).unwrap();
+        let b = Trajectory::try_from(&eastbound).unwrap();
+        assert!(!a.bounds().expanded(&mock_minima()).intersects(&b.bounds()));
+        assert!(closest_approach(&a, &b, departure, arrival, &mock_minima()).is_none());
+    }
+
+    #[test]
+    fn test_detect_conflicts_same_aircraft() {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+        let a = mock_eastbound_plan(departure, arrival);
+        let mut b = mock_flight_plan(
+            &[(52.0, 4.02, 100.0), (52.0, 4.0, 100.0)],
+            departure,
+            arrival,
+        );
+        b.aircraft_id = a.aircraft_id.clone();
+
+        assert!(detect_conflicts(&[a, b], departure, arrival, &mock_minima()).is_empty());
+    }
+}
@@ -0,0 +1,770 @@
+//! Conformance monitoring of live positions against acknowledged flight plans
+//!
+//! Acknowledged flight plans are registered with the monitor. Position
+//!  reports from the traffic picture are compared against the planned
+//!  trajectory, and alerts are raised when an aircraft strays from the
+//!  planned path or altitude, or is projected to miss its target timeslot.
+//! Aircraft waiting on their origin vertipad during the origin timeslot
+//!  are not reported as early.
+
+use super::conflict::{Trajectory, TrajectoryError};
+use super::geo;
+use super::lifecycle::get_lifecycles;
+use super::tactical::MIN_AIRBORNE_HEIGHT_METERS;
+use super::traffic::get_traffic;
+use crate::rest::api::{
+    AircraftPosition, ConformanceAlert, ConformanceAlertType, FlightPlan, FlightState,
+};
+use crate::Config;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet, VecDeque};
+use tokio::sync::{broadcast, Mutex, OnceCell};
+
+/// Maximum number of alerts kept in memory
+pub const MAX_ALERTS: usize = 1000;
+
+/// Number of alerts buffered for each subscriber
+pub const ALERT_CHANNEL_CAPACITY: usize = 100;
+
+/// Legs with a lateral deviation within this many meters of the closest leg
+///  are considered equally close, and the leg closest in time is chosen
+const LEG_TIE_METERS: f64 = 1.0;
+
+/// Conformance monitor shared by the REST and gRPC servers
+static MONITOR: OnceCell<Mutex<ConformanceMonitor>> = OnceCell::const_new();
+
+/// Returns the conformance monitor shared by the REST and gRPC servers
+pub async fn get_conformance_monitor() -> &'static Mutex<ConformanceMonitor> {
+    MONITOR
+        .get_or_init(|| async { Mutex::new(ConformanceMonitor::default()) })
+        .await
+}
+
+/// Maximum deviations from a flight plan before an alert is raised
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ConformanceTolerances {
+    /// Maximum horizontal distance from the planned path in meters
+    pub lateral_meters: f64,
+
+    /// Maximum distance above or below the planned altitude in meters
+    pub altitude_meters: f64,
+
+    /// Maximum time the projected arrival may fall outside of the target
+    ///  timeslot in seconds
+    pub schedule_seconds: f64,
+}
+
+impl From<&Config> for ConformanceTolerances {
+    fn from(config: &Config) -> Self {
+        ConformanceTolerances {
+            lateral_meters: config.conformance_lateral_meters,
+            altitude_meters: config.conformance_altitude_meters,
+            schedule_seconds: config.conformance_schedule_seconds as f64,
+        }
+    }
+}
+
+impl Default for ConformanceTolerances {
+    fn default() -> Self {
+        ConformanceTolerances::from(&Config::new())
+    }
+}
+
+/// Deviation of a position report from a flight plan
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Deviation {
+    /// Horizontal distance from the closest point of the planned path in meters
+    pub lateral_meters: f64,
+
+    /// Distance above the planned altitude at the closest point in meters,
+    ///  negative when below
+    pub altitude_meters: f64,
+
+    /// Time by which the projected arrival falls outside of the target
+    ///  timeslot in seconds, positive when late and negative when early
+    pub schedule_seconds: f64,
+}
+
+/// An acknowledged flight plan registered with the monitor
+#[derive(Debug, Clone)]
+pub struct MonitoredPlan {
+    /// Planned trajectory
+    pub trajectory: Trajectory,
+
+    /// Origin Time Start
+    pub origin_timeslot_start: DateTime<Utc>,
+
+    /// Origin Time End
+    pub origin_timeslot_end: DateTime<Utc>,
+
+    /// Target Time Start
+    pub target_timeslot_start: DateTime<Utc>,
+
+    /// Target Time End
+    pub target_timeslot_end: DateTime<Utc>,
+}
+
+impl TryFrom<&FlightPlan> for MonitoredPlan {
+    type Error = TrajectoryError;
+
+    fn try_from(plan: &FlightPlan) -> Result<Self, Self::Error> {
+        Ok(MonitoredPlan {
+            trajectory: Trajectory::try_from(plan)?,
+            origin_timeslot_start: plan.origin_timeslot_start,
+            origin_timeslot_end: plan.origin_timeslot_end,
+            target_timeslot_start: plan.target_timeslot_start,
+            target_timeslot_end: plan.target_timeslot_end,
+        })
+    }
+}
+
+impl MonitoredPlan {
+    /// Measures how far a position report deviates from this flight plan
+    ///
+    /// The position is matched with the closest point of the planned path.
+    /// The difference between the reported time and the planned time at
+    ///  that point is applied to the planned arrival to project the arrival
+    ///  time.
+    pub fn deviation(&self, position: &AircraftPosition) -> Deviation {
+        let point = &position.position;
+        let waypoints = &self.trajectory.waypoints;
+
+        // (lateral deviation, closest point, planned time at the closest point)
+        let mut closest = (
+            geo::horizontal_distance_meters(point, &waypoints[0].point),
+            waypoints[0].point,
+            waypoints[0].time,
+        );
+
+        for leg in waypoints.windows(2) {
+            let (a, b) = (&leg[0], &leg[1]);
+            let (leg_east, leg_north) = geo::to_local_meters(&a.point, &b.point);
+            let (east, north) = geo::to_local_meters(&a.point, point);
+            let length_squared = leg_east.powi(2) + leg_north.powi(2);
+            let ratio = if length_squared > 0.0 {
+                ((east * leg_east + north * leg_north) / length_squared).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+
+            let candidate = geo::interpolate(&a.point, &b.point, ratio);
+            let leg_ms = (b.time - a.time).num_milliseconds() as f64;
+            let offset = Duration::try_milliseconds((leg_ms * ratio).round() as i64)
+                .unwrap_or(Duration::zero());
+            let candidate = (
+                geo::horizontal_distance_meters(point, &candidate),
+                candidate,
+                a.time + offset,
+            );
+
+            // Paths may double back, prefer the leg closest in time
+            let closer = if (candidate.0 - closest.0).abs() < LEG_TIE_METERS {
+                (position.timestamp - candidate.2).abs() < (position.timestamp - closest.2).abs()
+            } else {
+                candidate.0 < closest.0
+            };
+
+            if closer {
+                closest = candidate;
+            }
+        }
+
+        let (lateral_meters, expected, expected_time) = closest;
+        let delay = position.timestamp - expected_time;
+        let projected_arrival = self.trajectory.end() + delay;
+        let schedule = if projected_arrival < self.target_timeslot_start {
+            projected_arrival - self.target_timeslot_start
+        } else if projected_arrival > self.target_timeslot_end {
+            projected_arrival - self.target_timeslot_end
+        } else {
+            Duration::zero()
+        };
+
+        Deviation {
+            lateral_meters,
+            altitude_meters: point.altitude_meters - expected.altitude_meters,
+            schedule_seconds: schedule.num_milliseconds() as f64 / 1000.0,
+        }
+    }
+
+    /// True if the aircraft may be flying this plan, or waiting to depart,
+    ///  at the given time
+    fn is_active(&self, time: DateTime<Utc>, tolerances: &ConformanceTolerances) -> bool {
+        let margin = Duration::try_milliseconds((tolerances.schedule_seconds * 1000.0) as i64)
+            .unwrap_or(Duration::zero());
+
+        time >= self.origin_timeslot_start - margin && time <= self.target_timeslot_end + margin
+    }
+
+    /// True if the aircraft is on its origin vertipad before the end of
+    ///  the origin timeslot
+    fn is_waiting(&self, position: &AircraftPosition, tolerances: &ConformanceTolerances) -> bool {
+        let origin = &self.trajectory.waypoints[0].point;
+        let point = &position.position;
+
+        position.timestamp <= self.origin_timeslot_end
+            && geo::horizontal_distance_meters(point, origin) <= tolerances.lateral_meters
+            && point.altitude_meters - origin.altitude_meters < MIN_AIRBORNE_HEIGHT_METERS
+    }
+}
+
+/// Compares position reports against acknowledged flight plans
+#[derive(Debug)]
+pub struct ConformanceMonitor {
+    /// Monitored flight plans, keyed by flight plan ID
+    plans: HashMap<String, MonitoredPlan>,
+
+    /// Time of the latest position report checked for each aircraft with
+    ///  a monitored flight plan
+    last_checked: HashMap<String, DateTime<Utc>>,
+
+    /// Alerts that have been raised and not yet cleared
+    active: HashSet<(String, ConformanceAlertType)>,
+
+    /// Most recent alerts, oldest first
+    alerts: VecDeque<ConformanceAlert>,
+
+    /// Publishes new alerts to subscribers
+    sender: broadcast::Sender<ConformanceAlert>,
+}
+
+impl Default for ConformanceMonitor {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(ALERT_CHANNEL_CAPACITY);
+        ConformanceMonitor {
+            plans: HashMap::new(),
+            last_checked: HashMap::new(),
+            active: HashSet::new(),
+            alerts: VecDeque::new(),
+            sender,
+        }
+    }
+}
+
+impl ConformanceMonitor {
+    /// Starts monitoring an acknowledged flight plan
+    pub fn register(&mut self, plan: &FlightPlan) -> Result<(), TrajectoryError> {
+        let monitored = MonitoredPlan::try_from(plan)?;
+        common_debug!(
+            "monitoring conformance of flight plan {}.",
+            plan.flight_uuid
+        );
+        self.plans.insert(plan.flight_uuid.clone(), monitored);
+        Ok(())
+    }
+
+    /// True if the flight plan is monitored
+    pub fn is_registered(&self, fp_id: &str) -> bool {
+        self.plans.contains_key(fp_id)
+    }
+
+    /// Stops monitoring a flight plan
+    ///
+    /// The last checked report of the aircraft is forgotten once none of
+    ///  its flight plans are monitored.
+    pub fn deregister(&mut self, fp_id: &str) {
+        let Some(removed) = self.plans.remove(fp_id) else {
+            return;
+        };
+
+        self.active.retain(|(id, _)| id != fp_id);
+        let aircraft_id = &removed.trajectory.aircraft_id;
+        if !self.is_monitored(aircraft_id) {
+            self.last_checked.remove(aircraft_id);
+        }
+    }
+
+    /// True if a flight plan of the aircraft is monitored
+    fn is_monitored(&self, aircraft_id: &str) -> bool {
+        self.plans
+            .values()
+            .any(|plan| plan.trajectory.aircraft_id == aircraft_id)
+    }
+
+    /// Number of monitored flight plans
+    pub fn len(&self) -> usize {
+        self.plans.len()
+    }
+
+    /// True if no flight plans are monitored
+    pub fn is_empty(&self) -> bool {
+        self.plans.is_empty()
+    }
+
+    /// Most recent alerts, oldest first
+    pub fn alerts(&self) -> impl Iterator<Item = &ConformanceAlert> {
+        self.alerts.iter()
+    }
+
+    /// Subscribes to new alerts
+    pub fn subscribe(&self) -> broadcast::Receiver<ConformanceAlert> {
+        self.sender.subscribe()
+    }
+
+    /// Checks a position report against the flight plans of the aircraft
+    ///
+    /// An alert is raised once when a tolerance is first exceeded, and
+    ///  cleared when the aircraft is back within tolerance. Reports that are
+    ///  not newer than the last checked report of the aircraft are ignored,
+    ///  as are reports of aircraft without a monitored flight plan.
+    ///
+    /// Returns the alerts raised by this report.
+    pub fn check(
+        &mut self,
+        position: &AircraftPosition,
+        tolerances: &ConformanceTolerances,
+    ) -> Vec<ConformanceAlert> {
+        if !self.is_monitored(&position.aircraft_id) {
+            return vec![];
+        }
+
+        if let Some(last) = self.last_checked.get(&position.aircraft_id) {
+            if position.timestamp <= *last {
+                return vec![];
+            }
+        }
+
+        self.last_checked
+            .insert(position.aircraft_id.clone(), position.timestamp);
+
+        let deviations = self
+            .plans
+            .iter()
+            .filter(|(_, plan)| plan.trajectory.aircraft_id == position.aircraft_id)
+            .filter(|(_, plan)| plan.is_active(position.timestamp, tolerances))
+            .map(|(fp_id, plan)| {
+                let mut deviation = plan.deviation(position);
+
+                // Aircraft waiting on their origin vertipad are not early
+                if deviation.schedule_seconds < 0.0 && plan.is_waiting(position, tolerances) {
+                    deviation.schedule_seconds = 0.0;
+                }
+
+                (fp_id.clone(), deviation)
+            })
+            .collect::<Vec<(String, Deviation)>>();
+
+        let mut raised = vec![];
+        for (fp_id, deviation) in deviations {
+            let checks = [
+                (
+                    ConformanceAlertType::Lateral,
+                    deviation.lateral_meters,
+                    tolerances.lateral_meters,
+                ),
+                (
+                    ConformanceAlertType::Altitude,
+                    deviation.altitude_meters,
+                    tolerances.altitude_meters,
+                ),
+                (
+                    ConformanceAlertType::Schedule,
+                    deviation.schedule_seconds,
+                    tolerances.schedule_seconds,
+                ),
+            ];
+
+            for (alert_type, value, tolerance) in checks {
+                let key = (fp_id.clone(), alert_type);
+                if value.abs() <= tolerance {
+                    if self.active.remove(&key) {
+                        common_info!(
+                            "aircraft {} back within {:?} tolerance of flight plan {}.",
+                            position.aircraft_id,
+                            alert_type,
+                            fp_id
+                        );
+                    }
+
+                    continue;
+                }
+
+                if !self.active.insert(key) {
+                    continue;
+                }
+
+                let alert = ConformanceAlert {
+                    fp_id: fp_id.clone(),
+                    aircraft_id: position.aircraft_id.clone(),
+                    alert_type,
+                    deviation: value,
+                    tolerance,
+                    position: position.position,
+                    timestamp: position.timestamp,
+                };
+
+                common_warn!("conformance alert: {:?}", alert);
+                raised.push(alert);
+            }
+        }
+
+        for alert in raised.iter() {
+            if self.alerts.len() >= MAX_ALERTS {
+                self.alerts.pop_front();
+            }
+
+            self.alerts.push_back(alert.clone());
+
+            // No subscribers is not an error
+            let _ = self.sender.send(alert.clone());
+        }
+
+        raised
+    }
+
+    /// Stops monitoring flight plans that ended before the given time
+    pub fn expire(&mut self, now: DateTime<Utc>, tolerances: &ConformanceTolerances) {
+        let expired = self
+            .plans
+            .iter()
+            .filter(|(_, plan)| !plan.is_active(now, tolerances) && now > plan.target_timeslot_end)
+            .map(|(fp_id, _)| fp_id.clone())
+            .collect::<Vec<String>>();
+
+        for fp_id in expired {
+            common_debug!("flight plan {} expired from conformance monitoring.", fp_id);
+            self.deregister(&fp_id);
+        }
+    }
+}
+
+/// Starts monitoring the acknowledged, cleared, airborne and diverted
+///  flight plans that are not monitored yet
+///
+/// Flight plans are registered by `ack_flight`, this picks up the flight
+///  plans acknowledged before this service started or through another
+///  instance. Flight plans whose target timeslot has passed are skipped.
+///
+/// Returns the number of flight plans registered.
+pub async fn register_plans(plans: &[FlightPlan], now: DateTime<Utc>) -> usize {
+    let states = {
+        let lifecycles = get_lifecycles().await.lock().await;
+        plans
+            .iter()
+            .map(|plan| lifecycles.state(&plan.flight_uuid).unwrap_or(plan.state))
+            .collect::<Vec<FlightState>>()
+    };
+
+    let mut monitor = get_conformance_monitor().await.lock().await;
+    let mut count = 0;
+    for (plan, state) in plans.iter().zip(states) {
+        if !matches!(
+            state,
+            FlightState::Acknowledged
+                | FlightState::Cleared
+                | FlightState::Airborne
+                | FlightState::Diverted
+        ) {
+            continue;
+        }
+
+        if now > plan.target_timeslot_end || monitor.is_registered(&plan.flight_uuid) {
+            continue;
+        }
+
+        match monitor.register(plan) {
+            Ok(()) => count += 1,
+            Err(e) => common_warn!(
+                "could not monitor conformance of flight plan {}: {e}",
+                plan.flight_uuid
+            ),
+        }
+    }
+
+    count
+}
+
+/// Checks the latest position of every aircraft in the traffic picture
+pub async fn monitor_traffic(tolerances: &ConformanceTolerances) {
+    let positions = get_traffic()
+        .await
+        .read()
+        .await
+        .tracks()
+        .map(|track| track.position.clone())
+        .collect::<Vec<AircraftPosition>>();
+
+    let mut monitor = get_conformance_monitor().await.lock().await;
+    for position in positions.iter() {
+        monitor.check(position, tolerances);
+    }
+
+    monitor.expire(Utc::now(), tolerances);
+}
+
+/// Periodically checks the traffic picture for conformance with
+///  acknowledged flight plans
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) infinite loop, tested through monitor_traffic
+pub async fn conformance_loop(config: Config) {
+    let tolerances = ConformanceTolerances::from(&config);
+    let period = std::time::Duration::from_secs(config.conformance_interval_seconds.max(1) as u64);
+    let mut interval = tokio::time::interval(period);
+
+    common_info!("conformance monitor started.");
+    loop {
+        interval.tick().await;
+        monitor_traffic(&tolerances).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::traffic::MaxSkew;
+    use crate::rest::api::PointZ;
+    use crate::test_util::{mock_plan, MOCK_EASTBOUND_PATH};
+
+    fn tolerances() -> ConformanceTolerances {
+        ConformanceTolerances {
+            lateral_meters: 100.0,
+            altitude_meters: 50.0,
+            schedule_seconds: 120.0,
+        }
+    }
+
+    fn position(plan: &FlightPlan, point: PointZ, timestamp: DateTime<Utc>) -> AircraftPosition {
+        AircraftPosition {
+            aircraft_id: plan.aircraft_id.clone(),
+            position: point,
+            ground_speed_mps: 2.3,
+            vertical_speed_mps: 0.0,
+            heading_degrees: 90.0,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_deviation_on_plan() {
+        let plan = mock_plan(&MOCK_EASTBOUND_PATH, Utc::now());
+        let monitored = MonitoredPlan::try_from(&plan).unwrap();
+        let midpoint = geo::interpolate(&plan.path[0], &plan.path[1], 0.5);
+        let time = plan.origin_timeslot_end + Duration::try_minutes(5).unwrap();
+
+        let deviation = monitored.deviation(&position(&plan, midpoint, time));
+        assert!(deviation.lateral_meters < 1.0);
+        assert!(deviation.altitude_meters.abs() < 1.0);
+        assert_eq!(deviation.schedule_seconds, 0.0);
+    }
+
+    #[test]
+    fn test_deviation_off_plan() {
+        let plan = mock_plan(&MOCK_EASTBOUND_PATH, Utc::now());
+        let monitored = MonitoredPlan::try_from(&plan).unwrap();
+        let midpoint = geo::interpolate(&plan.path[0], &plan.path[1], 0.5);
+
+        // 200 meters north, 80 meters high, and 10 minutes late
+        let point = geo::from_local_meters(&midpoint, 0.0, 200.0, 180.0);
+        let time = plan.origin_timeslot_end + Duration::try_minutes(15).unwrap();
+
+        let deviation = monitored.deviation(&position(&plan, point, time));
+        assert!((deviation.lateral_meters - 200.0).abs() < 1.0);
+        assert!((deviation.altitude_meters - 80.0).abs() < 1.0);
+
+        // Arrives 10 minutes late, 5 minutes after the end of the target timeslot
+        assert!((deviation.schedule_seconds - 300.0).abs() < 1.0);
+
+        // Arrives 10 minutes early
+        let time = plan.origin_timeslot_end - Duration::try_minutes(5).unwrap();
+        let deviation = monitored.deviation(&position(&plan, point, time));
+        assert!((deviation.schedule_seconds + 600.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_monitor_raises_and_clears_alerts() {
+        let plan = mock_plan(&MOCK_EASTBOUND_PATH, Utc::now());
+        let mut monitor = ConformanceMonitor::default();
+        let mut receiver = monitor.subscribe();
+        monitor.register(&plan).unwrap();
+        assert_eq!(monitor.len(), 1);
+
+        let start = plan.origin_timeslot_end;
+        let on_path = |ratio: f64| geo::interpolate(&plan.path[0], &plan.path[1], ratio);
+        let at = |seconds: i64| start + Duration::try_seconds(seconds).unwrap();
+
+        // Conforming
+        let alerts = monitor.check(&position(&plan, on_path(0.1), at(60)), &tolerances());
+        assert!(alerts.is_empty());
+
+        // Drifts 150 meters south
+        let drifted = geo::from_local_meters(&on_path(0.2), 0.0, -150.0, 100.0);
+        let alerts = monitor.check(&position(&plan, drifted, at(120)), &tolerances());
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].alert_type, ConformanceAlertType::Lateral);
+        assert_eq!(alerts[0].fp_id, plan.flight_uuid);
+        assert!((alerts[0].deviation - 150.0).abs() < 1.0);
+        assert_eq!(
+            receiver.try_recv().unwrap().alert_type,
+            ConformanceAlertType::Lateral
+        );
+
+        // Still drifting, no duplicate alert
+        let drifted = geo::from_local_meters(&on_path(0.3), 0.0, -150.0, 100.0);
+        let alerts = monitor.check(&position(&plan, drifted, at(180)), &tolerances());
+        assert!(alerts.is_empty());
+
+        // Older report is ignored
+        let alerts = monitor.check(&position(&plan, drifted, at(170)), &tolerances());
+        assert!(alerts.is_empty());
+
+        // Back on the path, then drifts again
+        let alerts = monitor.check(&position(&plan, on_path(0.4), at(240)), &tolerances());
+        assert!(alerts.is_empty());
+        let drifted = geo::from_local_meters(&on_path(0.5), 0.0, 150.0, 100.0);
+        let alerts = monitor.check(&position(&plan, drifted, at(300)), &tolerances());
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(monitor.alerts().count(), 2);
+
+        // Other aircraft are not checked against this plan
+        let mut other = position(&plan, drifted, at(300));
+        other.aircraft_id = "other".to_string();
+        assert!(monitor.check(&other, &tolerances()).is_empty());
+    }
+
+    #[test]
+    fn test_monitor_altitude_and_schedule_alerts() {
+        let plan = mock_plan(&MOCK_EASTBOUND_PATH, Utc::now());
+        let mut monitor = ConformanceMonitor::default();
+        monitor.register(&plan).unwrap();
+
+        // On the path, 100 meters high, 10 minutes late
+        let midpoint = geo::interpolate(&plan.path[0], &plan.path[1], 0.5);
+        let point = geo::from_local_meters(&midpoint, 0.0, 0.0, 200.0);
+        let time = plan.origin_timeslot_end + Duration::try_minutes(15).unwrap();
+
+        let mut alerts = monitor
+            .check(&position(&plan, point, time), &tolerances())
+            .into_iter()
+            .map(|alert| alert.alert_type)
+            .collect::<Vec<ConformanceAlertType>>();
+        alerts.sort_by_key(|alert_type| *alert_type as u8);
+        assert_eq!(
+            alerts,
+            vec![
+                ConformanceAlertType::Altitude,
+                ConformanceAlertType::Schedule
+            ]
+        );
+    }
+
+    #[test]
+    fn test_monitor_expire() {
+        let plan = mock_plan(&MOCK_EASTBOUND_PATH, Utc::now());
+        let mut monitor = ConformanceMonitor::default();
+        monitor.register(&plan).unwrap();
+
+        monitor.expire(plan.target_timeslot_end, &tolerances());
+        assert_eq!(monitor.len(), 1);
+
+        monitor.expire(
+            plan.target_timeslot_end + Duration::try_minutes(3).unwrap(),
+            &tolerances(),
+        );
+        assert!(monitor.is_empty());
+
+        monitor.register(&plan).unwrap();
+        monitor.deregister(&plan.flight_uuid);
+        assert!(monitor.is_empty());
+    }
+
+    #[test]
+    fn test_monitor_waiting_on_origin_pad() {
+        let plan = mock_plan(&MOCK_EASTBOUND_PATH, Utc::now());
+        let mut monitor = ConformanceMonitor::default();
+        monitor.register(&plan).unwrap();
+
+        // On the origin vertipad four minutes before departure
+        let time = plan.origin_timeslot_end - Duration::try_minutes(4).unwrap();
+        let alerts = monitor.check(&position(&plan, plan.path[0], time), &tolerances());
+        assert!(alerts.is_empty());
+
+        // 200 meters off the origin vertipad three minutes before departure
+        let point = geo::from_local_meters(&plan.path[0], 0.0, 200.0, 100.0);
+        let time = plan.origin_timeslot_end - Duration::try_minutes(3).unwrap();
+        let alerts = monitor.check(&position(&plan, point, time), &tolerances());
+        assert!(alerts
+            .iter()
+            .any(|alert| alert.alert_type == ConformanceAlertType::Schedule));
+    }
+
+    #[tokio::test]
+    async fn test_register_plans() {
+        let now = Utc::now();
+        let mut acknowledged = mock_plan(&MOCK_EASTBOUND_PATH, now);
+        acknowledged.state = FlightState::Acknowledged;
+        let mut scheduled = mock_plan(&MOCK_EASTBOUND_PATH, now);
+        scheduled.state = FlightState::Scheduled;
+        let mut ended = mock_plan(&MOCK_EASTBOUND_PATH, now - Duration::try_hours(1).unwrap());
+        ended.state = FlightState::Airborne;
+
+        let plans = vec![acknowledged.clone(), scheduled.clone(), ended.clone()];
+        assert_eq!(register_plans(&plans, now).await, 1);
+
+        // Monitored flight plans are not registered again
+        assert_eq!(register_plans(&plans, now).await, 0);
+
+        let monitor = get_conformance_monitor().await.lock().await;
+        assert!(monitor.is_registered(&acknowledged.flight_uuid));
+        assert!(!monitor.is_registered(&scheduled.flight_uuid));
+        assert!(!monitor.is_registered(&ended.flight_uuid));
+    }
+
+    #[test]
+    fn test_monitor_forgets_last_checked() {
+        let now = Utc::now();
+        let first = mock_plan(&MOCK_EASTBOUND_PATH, now);
+        let mut second = mock_plan(&MOCK_EASTBOUND_PATH, now);
+        second.aircraft_id = first.aircraft_id.clone();
+        let report = position(&first, first.path[0], first.origin_timeslot_end);
+
+        // Aircraft without a monitored flight plan are not tracked
+        let mut monitor = ConformanceMonitor::default();
+        monitor.check(&report, &tolerances());
+        assert!(monitor.last_checked.is_empty());
+
+        monitor.register(&first).unwrap();
+        monitor.register(&second).unwrap();
+        monitor.check(&report, &tolerances());
+        assert!(monitor.last_checked.contains_key(&first.aircraft_id));
+
+        // Kept while another flight plan of the aircraft is monitored
+        monitor.deregister(&first.flight_uuid);
+        assert!(monitor.last_checked.contains_key(&first.aircraft_id));
+
+        monitor.expire(
+            second.target_timeslot_end + Duration::try_minutes(3).unwrap(),
+            &tolerances(),
+        );
+        assert!(monitor.is_empty());
+        assert!(monitor.last_checked.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_monitor_traffic() {
+        // Departed five minutes ago, reports must not be dated in the future
+        let departure = Utc::now() - Duration::try_minutes(5).unwrap();
+        let plan = mock_plan(&MOCK_EASTBOUND_PATH, departure);
+        get_conformance_monitor()
+            .await
+            .lock()
+            .await
+            .register(&plan)
+            .unwrap();
+
+        // 500 meters off the path
+        let midpoint = geo::interpolate(&plan.path[0], &plan.path[1], 0.5);
+        let point = geo::from_local_meters(&midpoint, 0.0, 500.0, 100.0);
+        let time = plan.origin_timeslot_end + Duration::try_minutes(5).unwrap();
+        get_traffic()
+            .await
+            .write()
+            .await
+            .update(position(&plan, point, time), MaxSkew::default())
+            .unwrap();
+
+        monitor_traffic(&tolerances()).await;
+        let monitor = get_conformance_monitor().await.lock().await;
+        assert!(monitor.alerts().any(|alert| alert.fp_id == plan.flight_uuid
+            && alert.alert_type == ConformanceAlertType::Lateral));
+    }
+}
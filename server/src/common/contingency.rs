@@ -0,0 +1,763 @@
+//! Lost-link contingency procedures
+//!
+//! Every flight plan carries the procedure its aircraft follows if the
+//!  command and control link is lost: continue along the planned path,
+//!  return to the origin vertipad, or divert to an alternate vertipad.
+//!  Operators assign the procedure of each flight plan, and aircraft
+//!  without one continue to their target vertipad.
+//!
+//! A watchdog raises an alert when an airborne aircraft stops reporting
+//!  its position. Until the aircraft reports again, deconfliction assumes
+//!  it flies its contingency procedure from the last reported position.
+
+use super::cache::get_plan_cache;
+use super::emergency::get_vertipads;
+use super::geo;
+use super::lifecycle::get_lifecycles;
+use super::traffic::{get_traffic, Track};
+use super::{get_flight_data, AckError};
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::{
+    AircraftPosition, Contingency, ContingencyAction, ContingencyRequest, FlightPlan, FlightState,
+    LostLinkAlert, PointZ,
+};
+use crate::Config;
+use lib_common::time::{DateTime, Duration, Utc};
+use lib_common::uuid::Uuid;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use svc_storage_client_grpc::prelude::*;
+use tokio::sync::{Mutex, OnceCell};
+
+/// Ground speed in meters per second below which the reported speed of an
+///  aircraft is ignored, and the planned speed is assumed instead
+pub const MIN_GROUND_SPEED_MPS: f64 = 1.0;
+
+/// Hours after the end of the target timeslot that an assigned procedure
+///  is kept
+pub const ASSIGNMENT_RETENTION_HOURS: i64 = 24;
+
+/// Contingency procedures and lost-link alerts shared by the REST server
+///  and the watchdog
+static CONTINGENCIES: OnceCell<Mutex<ContingencyStore>> = OnceCell::const_new();
+
+/// Returns the contingency procedures and lost-link alerts
+pub async fn get_contingencies() -> &'static Mutex<ContingencyStore> {
+    CONTINGENCIES
+        .get_or_init(|| async { Mutex::new(ContingencyStore::default()) })
+        .await
+}
+
+/// Errors assigning a contingency procedure
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ContingencyError {
+    /// Flight plan not found or without a path
+    FlightPlan,
+
+    /// Alternate vertipad missing or not an enabled vertipad
+    Alternate,
+
+    /// Dependencies not available
+    Unavailable,
+}
+
+impl Display for ContingencyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ContingencyError::FlightPlan => write!(f, "flight plan not found or without a path."),
+            ContingencyError::Alternate => {
+                write!(f, "alternate vertipad missing or not enabled.")
+            }
+            ContingencyError::Unavailable => write!(f, "dependencies not available."),
+        }
+    }
+}
+
+/// Time without a position report before an airborne aircraft is assumed
+///  to have lost its link
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LostLinkPolicy {
+    /// Maximum age of the latest position report
+    pub threshold: Duration,
+}
+
+impl From<&Config> for LostLinkPolicy {
+    fn from(config: &Config) -> Self {
+        LostLinkPolicy {
+            threshold: Duration::try_seconds(config.lost_link_threshold_seconds as i64)
+                .unwrap_or(Duration::zero()),
+        }
+    }
+}
+
+impl Default for LostLinkPolicy {
+    fn default() -> Self {
+        LostLinkPolicy::from(&Config::new())
+    }
+}
+
+/// Builds the contingency procedure of a flight plan, None if the flight
+///  plan has no path or an alternate vertipad is needed and not given
+///
+/// The alternate is given as (vertiport ID, vertipad ID, location).
+pub fn procedure(
+    plan: &FlightPlan,
+    action: ContingencyAction,
+    alternate: Option<(&str, &str, PointZ)>,
+) -> Option<Contingency> {
+    let (vertiport_id, vertipad_id, destination) = match action {
+        ContingencyAction::Continue => (
+            plan.target_vertiport_id.as_str(),
+            plan.target_vertipad_id.as_str(),
+            *plan.path.last()?,
+        ),
+        ContingencyAction::ReturnToOrigin => (
+            plan.origin_vertiport_id.as_str(),
+            plan.origin_vertipad_id.as_str(),
+            *plan.path.first()?,
+        ),
+        ContingencyAction::DivertToAlternate => alternate?,
+    };
+
+    Some(Contingency {
+        action,
+        vertiport_id: vertiport_id.to_string(),
+        vertipad_id: vertipad_id.to_string(),
+        destination,
+    })
+}
+
+/// Index of the planned path leg closest to a position
+fn closest_leg(path: &[PointZ], point: &PointZ) -> usize {
+    let mut closest = (0, f64::MAX);
+    for (index, leg) in path.windows(2).enumerate() {
+        let (a, b) = (&leg[0], &leg[1]);
+        let (leg_east, leg_north) = geo::to_local_meters(a, b);
+        let (east, north) = geo::to_local_meters(a, point);
+        let length_squared = leg_east.powi(2) + leg_north.powi(2);
+        let ratio = if length_squared > 0.0 {
+            ((east * leg_east + north * leg_north) / length_squared).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let distance = geo::horizontal_distance_meters(point, &geo::interpolate(a, b, ratio));
+        if distance < closest.1 {
+            closest = (index, distance);
+        }
+    }
+
+    closest.0
+}
+
+/// Length of a path in meters
+fn path_length_meters(path: &[PointZ]) -> f64 {
+    path.windows(2)
+        .map(|leg| geo::distance_meters(&leg[0], &leg[1]))
+        .sum()
+}
+
+/// Path an aircraft that lost its link is assumed to fly from its last
+///  reported position, and the time at which it reaches the destination
+///
+/// Aircraft continuing rejoin the planned path after the closest leg.
+///  Aircraft returning or diverting fly directly to the destination at
+///  their last reported altitude. The last reported ground speed is
+///  assumed, or the planned speed if the aircraft was hovering.
+pub fn assumed_path(
+    plan: &FlightPlan,
+    contingency: &Contingency,
+    last: &AircraftPosition,
+) -> (Vec<PointZ>, DateTime<Utc>) {
+    let position = last.position;
+    let path = match contingency.action {
+        ContingencyAction::Continue if plan.path.len() >= 2 => {
+            let next = closest_leg(&plan.path, &position) + 1;
+            std::iter::once(position)
+                .chain(plan.path[next..].iter().copied())
+                .collect()
+        }
+        _ => {
+            let mut above = contingency.destination;
+            above.altitude_meters = position.altitude_meters;
+            vec![position, above, contingency.destination]
+        }
+    };
+
+    let speed_mps = if last.ground_speed_mps >= MIN_GROUND_SPEED_MPS {
+        last.ground_speed_mps
+    } else {
+        let planned_seconds = (plan.target_timeslot_start - plan.origin_timeslot_end)
+            .num_milliseconds() as f64
+            / 1000.0;
+        (path_length_meters(&plan.path) / planned_seconds).max(MIN_GROUND_SPEED_MPS)
+    };
+
+    let flight_ms = (path_length_meters(&path) / speed_mps * 1000.0).round() as i64;
+    let arrival =
+        last.timestamp + Duration::try_milliseconds(flight_ms).unwrap_or(Duration::zero());
+    (path, arrival)
+}
+
+/// Assigned contingency procedures and active lost-link alerts
+#[derive(Debug, Default)]
+pub struct ContingencyStore {
+    /// Procedures assigned by operators and the end of the target timeslot
+    ///  of their flight plan, keyed by flight plan ID
+    assigned: HashMap<String, (Contingency, DateTime<Utc>)>,
+
+    /// Active lost-link alerts, keyed by flight plan ID
+    lost: HashMap<String, LostLinkAlert>,
+}
+
+impl ContingencyStore {
+    /// Assigns a contingency procedure to a flight plan
+    pub fn assign(&mut self, plan: &FlightPlan, contingency: Contingency) {
+        self.assigned.insert(
+            plan.flight_uuid.clone(),
+            (contingency, plan.target_timeslot_end),
+        );
+    }
+
+    /// Contingency procedure of a flight plan, continuing to the target
+    ///  vertipad if none was assigned
+    pub fn get(&self, plan: &FlightPlan) -> Option<Contingency> {
+        match self.assigned.get(&plan.flight_uuid) {
+            Some((contingency, _)) => Some(contingency.clone()),
+            None => procedure(plan, ContingencyAction::Continue, None),
+        }
+    }
+
+    /// Active lost-link alert of a flight plan
+    pub fn lost(&self, fp_id: &str) -> Option<&LostLinkAlert> {
+        self.lost.get(fp_id)
+    }
+
+    /// Active lost-link alerts, oldest first
+    pub fn alerts(&self) -> Vec<LostLinkAlert> {
+        let mut alerts = self.lost.values().cloned().collect::<Vec<_>>();
+        alerts.sort_by_key(|alert| alert.raised);
+        alerts
+    }
+
+    /// Checks the tracks of airborne aircraft for lost links
+    ///
+    /// An alert is raised once when the track of an airborne aircraft goes
+    ///  stale, and cleared when the aircraft reports again or is no longer
+    ///  airborne. Aircraft that never reported their position are not
+    ///  checked.
+    ///
+    /// Returns the alerts raised by this check.
+    pub fn check(
+        &mut self,
+        airborne: &[FlightPlan],
+        tracks: &HashMap<String, Track>,
+        now: DateTime<Utc>,
+        policy: &LostLinkPolicy,
+    ) -> Vec<LostLinkAlert> {
+        let mut raised = vec![];
+        for plan in airborne {
+            let Some(track) = tracks.get(&plan.aircraft_id) else {
+                continue;
+            };
+
+            if !track.is_stale(now, policy.threshold) {
+                if self.lost.remove(&plan.flight_uuid).is_some() {
+                    common_info!(
+                        "link with aircraft {} restored on flight plan {}.",
+                        plan.aircraft_id,
+                        plan.flight_uuid
+                    );
+                }
+
+                continue;
+            }
+
+            if self.lost.contains_key(&plan.flight_uuid) {
+                continue;
+            }
+
+            let Some(contingency) = self.get(plan) else {
+                continue;
+            };
+
+            let (assumed_path, assumed_arrival) = assumed_path(plan, &contingency, &track.position);
+            let alert = LostLinkAlert {
+                fp_id: plan.flight_uuid.clone(),
+                aircraft_id: plan.aircraft_id.clone(),
+                contingency,
+                last_position: track.position.clone(),
+                assumed_path,
+                assumed_arrival,
+                raised: now,
+            };
+
+            common_warn!("lost-link alert: {:?}", alert);
+            self.lost.insert(plan.flight_uuid.clone(), alert.clone());
+            raised.push(alert);
+        }
+
+        let airborne = airborne
+            .iter()
+            .map(|plan| plan.flight_uuid.as_str())
+            .collect::<HashSet<&str>>();
+        self.lost
+            .retain(|fp_id, _| airborne.contains(fp_id.as_str()));
+
+        let retention = Duration::try_hours(ASSIGNMENT_RETENTION_HOURS).unwrap_or(Duration::zero());
+        self.assigned.retain(|_, (_, end)| *end + retention > now);
+
+        raised
+    }
+
+    /// Replaces the path and timeslots of flight plans whose aircraft lost
+    ///  their link with the contingency path they are assumed to fly
+    pub fn assume(&self, plans: &mut [FlightPlan]) {
+        for plan in plans.iter_mut() {
+            let Some(alert) = self.lost.get(&plan.flight_uuid) else {
+                continue;
+            };
+
+            let departure = alert.last_position.timestamp;
+            plan.path = alert.assumed_path.clone();
+            plan.origin_timeslot_start = plan.origin_timeslot_start.min(departure);
+            plan.origin_timeslot_end = departure;
+            plan.target_vertiport_id = alert.contingency.vertiport_id.clone();
+            plan.target_vertipad_id = alert.contingency.vertipad_id.clone();
+            plan.target_timeslot_start = alert.assumed_arrival;
+            plan.target_timeslot_end = plan.target_timeslot_end.max(alert.assumed_arrival);
+        }
+    }
+}
+
+/// Assigns a contingency procedure to a flight plan
+///
+/// The procedure assumed for an aircraft that already lost its link is
+///  not changed, as the aircraft can not receive the new one.
+pub async fn assign_contingency(
+    fp_id: Uuid,
+    request: &ContingencyRequest,
+    grpc_clients: &GrpcClients,
+) -> Result<Contingency, ContingencyError> {
+    let data = get_flight_data(fp_id, grpc_clients)
+        .await
+        .map_err(|e| match e {
+            AckError::NotFound => ContingencyError::FlightPlan,
+            _ => ContingencyError::Unavailable,
+        })?;
+
+    let object = flight_plan::Object {
+        id: fp_id.to_string(),
+        data: Some(data),
+    };
+
+    let plan = FlightPlan::try_from(object).map_err(|e| {
+        common_error!("could not convert flight plan {fp_id}: {e}");
+        ContingencyError::FlightPlan
+    })?;
+
+    let alternate = match request.action {
+        ContingencyAction::DivertToAlternate => {
+            let vertipad_id = request
+                .alternate_vertipad_id
+                .as_deref()
+                .ok_or(ContingencyError::Alternate)?;
+
+            let pads = get_vertipads(grpc_clients)
+                .await
+                .map_err(|_| ContingencyError::Unavailable)?;
+
+            let pad = pads
+                .into_iter()
+                .find(|pad| pad.id == vertipad_id)
+                .ok_or(ContingencyError::Alternate)?;
+
+            Some(pad)
+        }
+        _ => None,
+    };
+
+    let contingency = procedure(
+        &plan,
+        request.action,
+        alternate
+            .as_ref()
+            .map(|pad| (pad.vertiport_id.as_str(), pad.id.as_str(), pad.position)),
+    )
+    .ok_or(ContingencyError::FlightPlan)?;
+
+    common_info!(
+        "assigned {:?} contingency to flight plan {fp_id}.",
+        contingency.action
+    );
+
+    get_contingencies()
+        .await
+        .lock()
+        .await
+        .assign(&plan, contingency.clone());
+
+    Ok(contingency)
+}
+
+/// Checks the airborne aircraft in the plan cache for lost links,
+///  including aircraft diverted to an alternate
+pub async fn monitor_lost_link(policy: &LostLinkPolicy) -> Vec<LostLinkAlert> {
+    let plans = get_plan_cache().await.lock().await.plans();
+    let airborne = {
+        let lifecycles = get_lifecycles().await.lock().await;
+        plans
+            .into_iter()
+            .filter(|plan| {
+                matches!(
+                    lifecycles.state(&plan.flight_uuid),
+                    Some(FlightState::Airborne | FlightState::Diverted)
+                )
+            })
+            .collect::<Vec<FlightPlan>>()
+    };
+
+    let tracks = {
+        let traffic = get_traffic().await.read().await;
+        airborne
+            .iter()
+            .filter_map(|plan| {
+                traffic
+                    .get(&plan.aircraft_id)
+                    .map(|track| (plan.aircraft_id.clone(), track.clone()))
+            })
+            .collect::<HashMap<String, Track>>()
+    };
+
+    get_contingencies()
+        .await
+        .lock()
+        .await
+        .check(&airborne, &tracks, Utc::now(), policy)
+}
+
+/// Periodically checks airborne aircraft for lost links
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) infinite loop, tested through monitor_lost_link
+pub async fn lost_link_loop(config: Config) {
+    let policy = LostLinkPolicy::from(&config);
+    let period = std::time::Duration::from_secs(config.lost_link_interval_seconds.max(1) as u64);
+    let mut interval = tokio::time::interval(period);
+
+    common_info!("lost-link watchdog started.");
+    loop {
+        interval.tick().await;
+        monitor_lost_link(&policy).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::traffic::{MaxSkew, TrafficPicture};
+    use crate::test_util::{mock_flight_plan_data, mock_plan};
+
+    fn minutes(minutes: i64) -> Duration {
+        Duration::try_minutes(minutes).unwrap()
+    }
+
+    /// Path heading east along latitude -45.0
+    const PATH: [(f64, f64, f64); 4] = [
+        (-45.0, 136.0, 0.0),
+        (-45.0, 136.0, 100.0),
+        (-45.0, 136.05, 100.0),
+        (-45.0, 136.05, 0.0),
+    ];
+
+    fn position(
+        plan: &FlightPlan,
+        longitude: f64,
+        ground_speed_mps: f64,
+        timestamp: DateTime<Utc>,
+    ) -> AircraftPosition {
+        AircraftPosition {
+            aircraft_id: plan.aircraft_id.clone(),
+            position: PointZ {
+                latitude: -45.0,
+                longitude,
+                altitude_meters: 100.0,
+            },
+            ground_speed_mps,
+            vertical_speed_mps: 0.0,
+            heading_degrees: 90.0,
+            timestamp,
+        }
+    }
+
+    fn tracks(positions: Vec<AircraftPosition>) -> HashMap<String, Track> {
+        let mut traffic = TrafficPicture::default();
+        for position in positions {
+            traffic.update(position, MaxSkew::default()).unwrap();
+        }
+
+        traffic
+            .tracks()
+            .map(|track| (track.position.aircraft_id.clone(), track.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_procedure() {
+        let plan = mock_plan(&PATH, Utc::now() - minutes(5));
+        let alternate = PointZ {
+            latitude: -45.1,
+            longitude: 136.02,
+            altitude_meters: 0.0,
+        };
+
+        let contingency = procedure(&plan, ContingencyAction::Continue, None).unwrap();
+        assert_eq!(contingency.vertipad_id, plan.target_vertipad_id);
+        assert_eq!(contingency.destination, *plan.path.last().unwrap());
+
+        let contingency = procedure(&plan, ContingencyAction::ReturnToOrigin, None).unwrap();
+        assert_eq!(contingency.vertiport_id, plan.origin_vertiport_id);
+        assert_eq!(contingency.destination, plan.path[0]);
+
+        let contingency = procedure(
+            &plan,
+            ContingencyAction::DivertToAlternate,
+            Some(("port", "pad", alternate)),
+        )
+        .unwrap();
+        assert_eq!(contingency.vertipad_id, "pad");
+        assert_eq!(contingency.destination, alternate);
+
+        assert!(procedure(&plan, ContingencyAction::DivertToAlternate, None).is_none());
+
+        let mut empty = plan.clone();
+        empty.path.clear();
+        assert!(procedure(&empty, ContingencyAction::Continue, None).is_none());
+    }
+
+    #[test]
+    fn test_assumed_path() {
+        let now = Utc::now();
+        let plan = mock_plan(&PATH, now - minutes(5));
+        let last = position(&plan, 136.02, 20.0, now);
+
+        // Continuing rejoins the planned path after the closest leg
+        let contingency = procedure(&plan, ContingencyAction::Continue, None).unwrap();
+        let (path, arrival) = assumed_path(&plan, &contingency, &last);
+        assert_eq!(path.len(), 3);
+        assert_eq!(path[0], last.position);
+        assert_eq!(path[1], plan.path[2]);
+        let seconds = (arrival - now).num_seconds();
+        let expected = (path_length_meters(&path) / 20.0) as i64;
+        assert!((seconds - expected).abs() <= 1);
+
+        // Returning flies directly back at the last altitude, then descends
+        let contingency = procedure(&plan, ContingencyAction::ReturnToOrigin, None).unwrap();
+        let (path, _) = assumed_path(&plan, &contingency, &last);
+        assert_eq!(path.len(), 3);
+        assert_eq!(path[1].longitude, 136.0);
+        assert_eq!(path[1].altitude_meters, 100.0);
+        assert_eq!(path[2], plan.path[0]);
+
+        // A hovering aircraft is assumed to fly at the planned speed
+        let hovering = position(&plan, 136.02, 0.0, now);
+        let (_, arrival) = assumed_path(&plan, &contingency, &hovering);
+        assert!(arrival > now + minutes(3));
+    }
+
+    #[test]
+    fn test_check_and_assume() {
+        let now = Utc::now();
+        let policy = LostLinkPolicy {
+            threshold: Duration::try_seconds(30).unwrap(),
+        };
+        let lost = mock_plan(&PATH, now - minutes(5));
+        let reporting = mock_plan(&PATH, now - minutes(5));
+        let silent = mock_plan(&PATH, now - minutes(5));
+        let mut store = ContingencyStore::default();
+        store.assign(
+            &lost,
+            procedure(&lost, ContingencyAction::ReturnToOrigin, None).unwrap(),
+        );
+
+        let airborne = vec![lost.clone(), reporting.clone(), silent.clone()];
+        let stale = tracks(vec![
+            position(&lost, 136.02, 20.0, now - minutes(1)),
+            position(&reporting, 136.02, 20.0, now),
+        ]);
+
+        let raised = store.check(&airborne, &stale, now, &policy);
+        assert_eq!(raised.len(), 1);
+        assert_eq!(raised[0].fp_id, lost.flight_uuid);
+        assert_eq!(
+            raised[0].contingency.action,
+            ContingencyAction::ReturnToOrigin
+        );
+        assert!(store.lost(&lost.flight_uuid).is_some());
+
+        // The alert is raised once
+        assert!(store.check(&airborne, &stale, now, &policy).is_empty());
+        assert_eq!(store.alerts().len(), 1);
+
+        // Deconfliction assumes the contingency path
+        let mut plans = vec![lost.clone(), reporting.clone()];
+        store.assume(&mut plans);
+        assert_eq!(plans[0].path, raised[0].assumed_path);
+        assert_eq!(plans[0].origin_timeslot_end, now - minutes(1));
+        assert_eq!(plans[0].target_timeslot_start, raised[0].assumed_arrival);
+        assert_eq!(plans[0].target_vertipad_id, lost.origin_vertipad_id);
+        assert_eq!(plans[1].path, reporting.path);
+
+        // The alert is cleared when the aircraft reports again
+        let fresh = tracks(vec![position(&lost, 136.01, 20.0, now)]);
+        assert!(store.check(&airborne, &fresh, now, &policy).is_empty());
+        assert!(store.lost(&lost.flight_uuid).is_none());
+
+        // Or when the aircraft is no longer airborne
+        store.check(&airborne, &stale, now, &policy);
+        store.check(&[], &stale, now, &policy);
+        assert!(store.alerts().is_empty());
+
+        // Assigned procedures are kept until the flight plan has long ended
+        assert_eq!(
+            store.get(&lost).unwrap().action,
+            ContingencyAction::ReturnToOrigin
+        );
+        store.check(&[], &stale, now + minutes(60 * 25), &policy);
+        assert_eq!(
+            store.get(&lost).unwrap().action,
+            ContingencyAction::Continue
+        );
+    }
+
+    #[tokio::test]
+    async fn test_assign_contingency() {
+        let grpc_clients = GrpcClients::default(Config::default());
+        let now = Utc::now();
+        let mut data = mock_flight_plan_data(
+            &[(-45.5, 135.5, 0.0), (-45.5, 135.55, 0.0)],
+            now,
+            now + minutes(10),
+        );
+        data.flight_status = flight_plan::FlightStatus::InFlight as i32;
+        let object = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap();
+        let fp_id = Uuid::parse_str(&object.id).unwrap();
+
+        let pad = grpc_clients
+            .storage
+            .vertipad
+            .insert(vertipad::Data {
+                vertiport_id: Uuid::new_v4().to_string(),
+                name: "alternate".to_string(),
+                geo_location: Some(GeoPointZ {
+                    x: 135.52,
+                    y: -45.52,
+                    z: 0.0,
+                }),
+                schedule: None,
+                enabled: true,
+                occupied: false,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap();
+
+        let request = |action, alternate_vertipad_id: Option<&str>| ContingencyRequest {
+            action,
+            alternate_vertipad_id: alternate_vertipad_id.map(str::to_string),
+        };
+
+        let contingency = assign_contingency(
+            fp_id,
+            &request(ContingencyAction::DivertToAlternate, Some(&pad.id)),
+            &grpc_clients,
+        )
+        .await
+        .unwrap();
+        assert_eq!(contingency.vertipad_id, pad.id);
+        assert_eq!(contingency.destination.latitude, -45.52);
+
+        let error = assign_contingency(
+            fp_id,
+            &request(ContingencyAction::DivertToAlternate, None),
+            &grpc_clients,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, ContingencyError::Alternate);
+
+        let error = assign_contingency(
+            fp_id,
+            &request(
+                ContingencyAction::DivertToAlternate,
+                Some(&Uuid::new_v4().to_string()),
+            ),
+            &grpc_clients,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, ContingencyError::Alternate);
+
+        // The assigned procedure is kept for the flight plan
+        let plan = FlightPlan::try_from(object).unwrap();
+        let contingencies = get_contingencies().await.lock().await;
+        assert_eq!(contingencies.get(&plan), Some(contingency.clone()));
+        drop(contingencies);
+
+        // The watchdog alerts when the airborne aircraft stops reporting
+        get_plan_cache()
+            .await
+            .lock()
+            .await
+            .refresh(&plan.aircraft_id, vec![plan.clone()], now);
+        get_traffic()
+            .await
+            .write()
+            .await
+            .update(
+                AircraftPosition {
+                    aircraft_id: plan.aircraft_id.clone(),
+                    position: plan.path[0],
+                    ground_speed_mps: 20.0,
+                    vertical_speed_mps: 0.0,
+                    heading_degrees: 90.0,
+                    timestamp: now - minutes(2),
+                },
+                MaxSkew::default(),
+            )
+            .unwrap();
+
+        let raised = monitor_lost_link(&LostLinkPolicy::default()).await;
+        let alert = raised
+            .iter()
+            .find(|alert| alert.fp_id == plan.flight_uuid)
+            .unwrap();
+        assert_eq!(alert.contingency, contingency);
+        assert_eq!(alert.assumed_path.last(), Some(&contingency.destination));
+    }
+
+    #[test]
+    fn test_contingency_error_display() {
+        assert_eq!(
+            ContingencyError::FlightPlan.to_string(),
+            "flight plan not found or without a path."
+        );
+        assert_eq!(
+            ContingencyError::Alternate.to_string(),
+            "alternate vertipad missing or not enabled."
+        );
+        assert_eq!(
+            ContingencyError::Unavailable.to_string(),
+            "dependencies not available."
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Pre-flight deconfliction of candidate flight plans
+//!
+//! A candidate flight plan is compared against the flight plans already
+//!  scheduled in svc-storage and the restricted zones before it is booked.
+
+use super::conflict::{closest_approach, Conflict, SeparationMinima, Trajectory};
+use super::contingency::get_contingencies;
+use super::zones::{get_zones, ZoneRegistry};
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::{FlightPlan, ZoneViolation};
+use lib_common::time::{DateTime, Duration, Utc};
+use std::fmt::{self, Display, Formatter};
+use svc_storage_client_grpc::prelude::*;
+
+/// Step between suggested time shifts in seconds
+pub const TIME_SHIFT_STEP_SECONDS: i64 = 60;
+
+/// Maximum number of steps to shift a candidate flight plan in either direction
+pub const MAX_TIME_SHIFT_STEPS: i64 = 15;
+
+/// Maximum number of time shifts to suggest
+pub const MAX_SUGGESTED_TIME_SHIFTS: usize = 3;
+
+/// How far before the candidate departure to look for scheduled flight plans
+///  that may still be airborne
+pub const SCHEDULE_LOOKBEHIND_MINUTES: i64 = 120;
+
+/// Errors checking a candidate flight plan
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CheckError {
+    /// Could not build the search window
+    Internal,
+
+    /// Dependencies not available
+    Unavailable,
+}
+
+impl Display for CheckError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckError::Internal => write!(f, "Internal Error"),
+            CheckError::Unavailable => write!(f, "Dependencies not available"),
+        }
+    }
+}
+
+/// Result of checking a candidate flight plan
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    /// Conflicts with scheduled flight plans, the candidate is always aircraft `a`
+    pub conflicts: Vec<Conflict>,
+
+    /// Path segments of the candidate crossing active restricted zones
+    pub zone_violations: Vec<ZoneViolation>,
+
+    /// Time shifts that would resolve all conflicts and zone violations,
+    ///  smallest first
+    pub suggested_time_shifts: Vec<Duration>,
+}
+
+impl CheckResult {
+    /// True if the candidate flight plan has no conflicts and crosses no
+    ///  active restricted zones
+    pub fn approved(&self) -> bool {
+        self.conflicts.is_empty() && self.zone_violations.is_empty()
+    }
+}
+
+/// Compares a candidate trajectory against every scheduled trajectory
+fn find_conflicts(
+    candidate: &Trajectory,
+    scheduled: &[Trajectory],
+    minima: &SeparationMinima,
+) -> Vec<Conflict> {
+    scheduled
+        .iter()
+        .filter(|trajectory| trajectory.aircraft_id != candidate.aircraft_id)
+        .filter_map(|trajectory| {
+            closest_approach(
+                candidate,
+                trajectory,
+                candidate.start(),
+                candidate.end(),
+                minima,
+            )
+        })
+        .collect()
+}
+
+/// Checks a candidate trajectory against scheduled trajectories and
+///  restricted zones
+///
+/// If conflicts or zone violations are found, the candidate is shifted
+///  earlier and later in steps of [`TIME_SHIFT_STEP_SECONDS`] to find
+///  departure times that resolve all of them.
+pub fn check_candidate(
+    candidate: &Trajectory,
+    scheduled: &[Trajectory],
+    zones: &ZoneRegistry,
+    minima: &SeparationMinima,
+) -> CheckResult {
+    let conflicts = find_conflicts(candidate, scheduled, minima);
+    let zone_violations = zones.check(candidate);
+    let mut suggested_time_shifts = vec![];
+    if conflicts.is_empty() && zone_violations.is_empty() {
+        return CheckResult {
+            conflicts,
+            zone_violations,
+            suggested_time_shifts,
+        };
+    }
+
+    // Prefer the smallest shift, and delays over earlier departures
+    let shifts = (1..=MAX_TIME_SHIFT_STEPS)
+        .flat_map(|step| [step, -step])
+        .filter_map(|step| Duration::try_seconds(step * TIME_SHIFT_STEP_SECONDS));
+
+    for shift in shifts {
+        if suggested_time_shifts.len() >= MAX_SUGGESTED_TIME_SHIFTS {
+            break;
+        }
+
+        let shifted = candidate.shifted(shift);
+        if find_conflicts(&shifted, scheduled, minima).is_empty()
+            && zones.check(&shifted).is_empty()
+        {
+            suggested_time_shifts.push(shift);
+        }
+    }
+
+    CheckResult {
+        conflicts,
+        zone_violations,
+        suggested_time_shifts,
+    }
+}
+
+/// Flight statuses of flight plans that will not be flown
+const INACTIVE_FLIGHT_STATUSES: [flight_plan::FlightStatus; 3] = [
+    flight_plan::FlightStatus::Cancelled,
+    flight_plan::FlightStatus::Draft,
+    flight_plan::FlightStatus::Finished,
+];
+
+/// If a flight plan is scheduled to be flown or is being flown
+pub fn is_scheduled(data: &flight_plan::Data) -> bool {
+    !INACTIVE_FLIGHT_STATUSES
+        .iter()
+        .any(|status| data.flight_status == *status as i32)
+}
+
+/// Gets flight plans from svc-storage with an origin timeslot starting
+///  within the given window, skipping cancelled, draft and finished plans
+pub async fn get_scheduled_plans(
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+    grpc_clients: &GrpcClients,
+) -> Result<Vec<FlightPlan>, CheckError> {
+    let filter = AdvancedSearchFilter::search_between(
+        "origin_timeslot_start".to_owned(),
+        window_start.to_string(),
+        window_end.to_string(),
+    );
+
+    let plans = grpc_clients
+        .storage
+        .flight_plan
+        .search(filter)
+        .await
+        .map_err(|e| {
+            common_error!("svc-storage failure: {e}");
+            CheckError::Unavailable
+        })?
+        .into_inner()
+        .list
+        .into_iter()
+        .filter(|object| object.data.as_ref().is_some_and(is_scheduled))
+        .filter_map(|object| FlightPlan::try_from(object).ok())
+        .collect();
+
+    Ok(plans)
+}
+
+/// Checks a candidate trajectory against the flight plans scheduled in
+///  svc-storage and the restricted zones
+pub async fn check_flight_plan(
+    candidate: &Trajectory,
+    minima: &SeparationMinima,
+    grpc_clients: &GrpcClients,
+) -> Result<CheckResult, CheckError> {
+    let max_shift = Duration::try_seconds(MAX_TIME_SHIFT_STEPS * TIME_SHIFT_STEP_SECONDS)
+        .ok_or(CheckError::Internal)?;
+    let lookbehind =
+        Duration::try_minutes(SCHEDULE_LOOKBEHIND_MINUTES).ok_or(CheckError::Internal)?;
+
+    let window_start = candidate.start() - max_shift - lookbehind;
+    let window_end = candidate.end() + max_shift;
+    let mut scheduled = get_scheduled_plans(window_start, window_end, grpc_clients).await?;
+    get_contingencies()
+        .await
+        .lock()
+        .await
+        .assume(&mut scheduled);
+
+    let scheduled = scheduled
+        .iter()
+        .filter_map(|plan| Trajectory::try_from(plan).ok())
+        .collect::<Vec<Trajectory>>();
+
+    common_debug!(
+        "checking candidate against {} scheduled plans.",
+        scheduled.len()
+    );
+
+    let zones = get_zones().await.read().await;
+    Ok(check_candidate(candidate, &scheduled, &zones, minima))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{mock_eastbound_plan, mock_flight_plan, mock_minima};
+
+    #[test]
+    fn test_check_candidate_approved() {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+        let candidate = mock_eastbound_plan(departure, arrival);
+        let scheduled = mock_flight_plan(
+            &[(52.0, 4.02, 200.0), (52.0, 4.0, 200.0)],
+            departure,
+            arrival,
+        );
+
+        let candidate = Trajectory::try_from(&candidate).unwrap();
+        let scheduled = vec![Trajectory::try_from(&scheduled).unwrap()];
+        let result = check_candidate(
+            &candidate,
+            &scheduled,
+            &ZoneRegistry::default(),
+            &mock_minima(),
+        );
+        assert!(result.approved());
+        assert!(result.suggested_time_shifts.is_empty());
+    }
+
+    #[test]
+    fn test_check_candidate_rejected() {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+
+        // Scheduled flight crosses the candidate path perpendicularly
+        let candidate = mock_eastbound_plan(departure, arrival);
+        let scheduled = mock_flight_plan(
+            &[(51.99, 4.01, 100.0), (52.01, 4.01, 100.0)],
+            departure,
+            arrival,
+        );
+
+        let candidate = Trajectory::try_from(&candidate).unwrap();
+        let scheduled = vec![Trajectory::try_from(&scheduled).unwrap()];
+        let result = check_candidate(
+            &candidate,
+            &scheduled,
+            &ZoneRegistry::default(),
+            &mock_minima(),
+        );
+        assert!(!result.approved());
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].flight_uuid_a, candidate.flight_uuid);
+        assert_eq!(result.conflicts[0].flight_uuid_b, scheduled[0].flight_uuid);
+
+        assert!(!result.suggested_time_shifts.is_empty());
+        assert!(result.suggested_time_shifts.len() <= MAX_SUGGESTED_TIME_SHIFTS);
+        for shift in result.suggested_time_shifts {
+            assert!(
+                find_conflicts(&candidate.shifted(shift), &scheduled, &mock_minima()).is_empty()
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_candidate_same_aircraft() {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+        let candidate = mock_eastbound_plan(departure, arrival);
+        let mut scheduled = mock_flight_plan(
+            &[(52.0, 4.02, 100.0), (52.0, 4.0, 100.0)],
+            departure,
+            arrival,
+        );
+        scheduled.aircraft_id = candidate.aircraft_id.clone();
+
+        let candidate = Trajectory::try_from(&candidate).unwrap();
+        let scheduled = vec![Trajectory::try_from(&scheduled).unwrap()];
+        assert!(check_candidate(
+            &candidate,
+            &scheduled,
+            &ZoneRegistry::default(),
+            &mock_minima()
+        )
+        .approved());
+    }
+
+    #[tokio::test]
+    async fn test_check_flight_plan() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+        let candidate = mock_eastbound_plan(departure, arrival);
+        let candidate = Trajectory::try_from(&candidate).unwrap();
+
+        let result = check_flight_plan(&candidate, &mock_minima(), &grpc_clients).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_scheduled_plans_status() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        let departure = Utc::now() + Duration::try_days(3).unwrap();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+        let mut ids = vec![];
+        for status in [
+            flight_plan::FlightStatus::Ready,
+            flight_plan::FlightStatus::InFlight,
+            flight_plan::FlightStatus::Cancelled,
+            flight_plan::FlightStatus::Draft,
+            flight_plan::FlightStatus::Finished,
+        ] {
+            let mut data = crate::test_util::mock_flight_plan_data(
+                &crate::test_util::MOCK_EASTBOUND_PATH,
+                departure,
+                arrival,
+            );
+            data.flight_status = status as i32;
+            let id = grpc_clients
+                .storage
+                .flight_plan
+                .insert(data)
+                .await
+                .unwrap()
+                .into_inner()
+                .object
+                .unwrap()
+                .id;
+            ids.push(id);
+        }
+
+        let plans = get_scheduled_plans(
+            departure - Duration::try_minutes(10).unwrap(),
+            departure,
+            &grpc_clients,
+        )
+        .await
+        .unwrap();
+        let found = ids
+            .iter()
+            .map(|id| plans.iter().any(|plan| &plan.flight_uuid == id))
+            .collect::<Vec<bool>>();
+        assert_eq!(found, vec![true, true, false, false, false]);
+    }
+
+    #[test]
+    fn test_check_error_display() {
+        assert_eq!(CheckError::Internal.to_string(), "Internal Error");
+        assert_eq!(
+            CheckError::Unavailable.to_string(),
+            "Dependencies not available"
+        );
+    }
+}
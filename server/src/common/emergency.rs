@@ -0,0 +1,918 @@
+//! Emergency declarations
+//!
+//! An aircraft in distress declares an emergency with its current position.
+//!  It is assigned the nearest vertipad able to receive it and granted a
+//!  priority landing clearance reserving the vertipad, and the other
+//!  aircraft scheduled on the vertipad are advised to delay until the
+//!  reservation ends. Every emergency event is written to the audit stream,
+//!  a file with one JSON object per line.
+
+use super::cache::{get_plan_cache, get_vertiport_plans, CachePolicy};
+use super::clearance::{get_clearances, ClearanceStore};
+use super::geo;
+use super::holding::get_holding;
+use super::sequencing::pad_operations;
+use super::weather::{get_weather, wind_mps, WeatherLimits, WeatherStore};
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::{
+    Advisory, Clearance, ClearanceStatus, ClearanceType, EmergencyDeclaration, EmergencyEvent,
+    EmergencyEventType, EmergencyResponse, FlightPlan, ManeuverType, PadOperationType, PointZ,
+};
+use crate::Config;
+use lib_common::time::{DateTime, Duration, Utc};
+use lib_common::uuid::{to_uuid, Uuid};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Display, Formatter};
+use svc_storage_client_grpc::prelude::*;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, Mutex, OnceCell};
+use tokio::task::JoinHandle;
+
+/// Maximum number of audit events kept in memory
+pub const AUDIT_CAPACITY: usize = 1000;
+
+/// Emergencies declared to this service and the audit stream
+static EMERGENCIES: OnceCell<Mutex<EmergencyStore>> = OnceCell::const_new();
+
+/// Returns the emergencies declared to this service
+pub async fn get_emergencies() -> &'static Mutex<EmergencyStore> {
+    EMERGENCIES
+        .get_or_init(|| async { Mutex::new(EmergencyStore::default()) })
+        .await
+}
+
+/// Errors declaring an emergency
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EmergencyError {
+    /// Aircraft ID is not a valid UUID
+    AircraftId,
+
+    /// Position is out of range
+    Position,
+
+    /// No vertipad within range can receive the aircraft
+    NoSuitablePad,
+
+    /// Dependencies not available
+    Unavailable,
+}
+
+impl Display for EmergencyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EmergencyError::AircraftId => write!(f, "invalid aircraft ID."),
+            EmergencyError::Position => write!(f, "position out of range."),
+            EmergencyError::NoSuitablePad => write!(f, "no suitable vertipad within range."),
+            EmergencyError::Unavailable => write!(f, "dependencies not available."),
+        }
+    }
+}
+
+/// Settings used to handle emergency declarations
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EmergencyPolicy {
+    /// Time the assigned vertipad is reserved for the aircraft
+    pub reservation: Duration,
+
+    /// Maximum distance from the aircraft to the vertipads considered
+    pub search_radius_meters: f64,
+
+    /// Wind and visibility limits at the vertipads considered
+    pub weather: WeatherLimits,
+
+    /// Maximum age of the cached flight plans checked for delays
+    pub plans: CachePolicy,
+}
+
+impl From<&Config> for EmergencyPolicy {
+    fn from(config: &Config) -> Self {
+        EmergencyPolicy {
+            reservation: Duration::try_seconds(config.emergency_reservation_seconds as i64)
+                .unwrap_or(Duration::zero()),
+            search_radius_meters: config.emergency_search_radius_meters,
+            weather: WeatherLimits::from(config),
+            plans: CachePolicy::from(config),
+        }
+    }
+}
+
+impl Default for EmergencyPolicy {
+    fn default() -> Self {
+        EmergencyPolicy::from(&Config::new())
+    }
+}
+
+/// Vertipad registered in svc-storage
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vertipad {
+    /// Vertipad ID
+    pub id: String,
+
+    /// Vertiport ID
+    pub vertiport_id: String,
+
+    /// Location of the vertipad
+    pub position: PointZ,
+
+    /// False if the vertipad is closed
+    pub enabled: bool,
+
+    /// True if an aircraft is parked on the vertipad
+    pub occupied: bool,
+}
+
+impl Vertipad {
+    /// Converts a svc-storage vertipad, None if it has no location
+    pub fn from_object(object: vertipad::Object) -> Option<Self> {
+        let data = object.data?;
+        let location = data.geo_location?;
+        Some(Vertipad {
+            id: object.id,
+            vertiport_id: data.vertiport_id,
+            position: PointZ {
+                latitude: location.y,
+                longitude: location.x,
+                altitude_meters: location.z,
+            },
+            enabled: data.enabled,
+            occupied: data.occupied,
+        })
+    }
+}
+
+/// Emergencies declared to this service and the audit events they raised
+#[derive(Debug, Default)]
+pub struct EmergencyStore {
+    /// Emergencies keyed by emergency ID
+    emergencies: HashMap<String, EmergencyResponse>,
+
+    /// Latest audit events, oldest first
+    events: VecDeque<EmergencyEvent>,
+
+    /// Sequence number of the latest audit event
+    sequence: u64,
+
+    /// Task appending audit events to a file, None to keep them in memory only
+    audit_writer: Option<mpsc::UnboundedSender<EmergencyEvent>>,
+}
+
+impl EmergencyStore {
+    /// Sets the file audit events are appended to
+    ///
+    /// Events are written by a dedicated task in the order they were
+    ///  numbered, so the store is never locked while writing. The task ends
+    ///  once the store is dropped or the audit file is replaced.
+    pub fn set_audit_file(&mut self, path: &str) -> JoinHandle<()> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.audit_writer = Some(sender);
+        tokio::spawn(write_audit_file(path.to_string(), receiver))
+    }
+
+    /// Numbers an audit event and writes it to the audit stream
+    ///
+    /// Events that can not be written to the audit file are still kept in
+    ///  memory, an emergency is never refused because of the audit stream.
+    pub fn audit(&mut self, mut event: EmergencyEvent) -> u64 {
+        self.sequence += 1;
+        event.sequence = self.sequence;
+
+        common_warn!(
+            "emergency {} of aircraft {}: {:?} ({:?}).",
+            event.emergency_id,
+            event.aircraft_id,
+            event.event_type,
+            event.emergency_type
+        );
+
+        if let Some(writer) = &self.audit_writer {
+            if writer.send(event.clone()).is_err() {
+                common_error!(
+                    "emergency audit writer stopped, event {} not written to file.",
+                    event.sequence
+                );
+            }
+        }
+
+        if self.events.len() >= AUDIT_CAPACITY {
+            self.events.pop_front();
+        }
+
+        self.events.push_back(event);
+        self.sequence
+    }
+
+    /// Audit events kept in memory, oldest first
+    pub fn events(&self) -> Vec<EmergencyEvent> {
+        self.events.iter().cloned().collect()
+    }
+
+    /// Records a handled emergency
+    pub fn insert(&mut self, response: EmergencyResponse) {
+        self.emergencies
+            .insert(response.emergency_id.clone(), response);
+    }
+
+    /// Removes the emergencies whose vertipad reservation has ended
+    pub fn expire(&mut self, now: DateTime<Utc>) {
+        self.emergencies
+            .retain(|_, response| response.clearance.expires.is_some_and(|end| end > now));
+    }
+
+    /// Delays advised to a flight plan for the ongoing emergencies
+    pub fn advisories(&self, flight_uuid: &str, now: DateTime<Utc>) -> Vec<Advisory> {
+        self.emergencies
+            .values()
+            .filter(|response| response.clearance.expires.is_some_and(|end| end > now))
+            .flat_map(|response| response.advisories.iter())
+            .filter(|advisory| advisory.flight_uuid == flight_uuid)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Appends the audit events received to a file until the store is dropped
+async fn write_audit_file(path: String, mut events: mpsc::UnboundedReceiver<EmergencyEvent>) {
+    while let Some(event) = events.recv().await {
+        if let Err(e) = append_line(&path, &event).await {
+            common_error!("could not write emergency event to {path}: {e}");
+        }
+    }
+}
+
+/// Appends an audit event to a file as a single line of JSON
+async fn append_line(path: &str, event: &EmergencyEvent) -> std::io::Result<()> {
+    if let Some(parent) = std::path::Path::new(path).parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let line = format!("{}\n", serde_json::to_string(event)?);
+    tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?
+        .write_all(line.as_bytes())
+        .await
+}
+
+/// Checks that a declaration contains a usable aircraft ID and position
+fn validate(declaration: &EmergencyDeclaration) -> Result<(), EmergencyError> {
+    if to_uuid(&declaration.aircraft_id).is_none() {
+        return Err(EmergencyError::AircraftId);
+    }
+
+    let position = &declaration.position;
+    if !position.altitude_meters.is_finite()
+        || !(-90.0..=90.0).contains(&position.latitude)
+        || !(-180.0..=180.0).contains(&position.longitude)
+    {
+        return Err(EmergencyError::Position);
+    }
+
+    Ok(())
+}
+
+/// Returns false if the wind or visibility at a vertipad is outside of limits
+///
+/// Vertipads without a nearby weather grid point are allowed.
+pub fn weather_allows(
+    pad: &Vertipad,
+    weather: &WeatherStore,
+    limits: &WeatherLimits,
+    now: DateTime<Utc>,
+) -> bool {
+    weather
+        .sample_near(&pad.position, now, limits.sample_radius_meters)
+        .map(|sample| {
+            wind_mps(sample) <= limits.max_wind_mps
+                && sample.visibility_meters >= limits.min_visibility_meters
+        })
+        .unwrap_or(true)
+}
+
+/// Returns the nearest vertipad within range able to receive an aircraft,
+///  with its horizontal distance in meters
+///
+/// Vertipads that are closed, occupied, or reserved by a clearance issued
+///  to another flight plan are skipped. `key` is the flight plan ID the
+///  priority clearance will be recorded under.
+pub fn select_pad<'a>(
+    position: &PointZ,
+    pads: &'a [Vertipad],
+    key: &str,
+    store: &ClearanceStore,
+    policy: &EmergencyPolicy,
+    now: DateTime<Utc>,
+) -> Option<(&'a Vertipad, f64)> {
+    pads.iter()
+        .filter(|pad| pad.enabled && !pad.occupied)
+        .filter(|pad| store.pad_holder(&pad.id, key, now).is_none())
+        .map(|pad| {
+            (
+                pad,
+                geo::horizontal_distance_meters(position, &pad.position),
+            )
+        })
+        .filter(|(_, distance)| *distance <= policy.search_radius_meters)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Advises the aircraft scheduled on a vertipad while it is reserved to
+///  delay until the reservation ends
+///
+/// `conflicting_flight_uuid` is recorded in the advisories as the flight
+///  plan they make way for. Departing aircraft wait on the ground, arriving
+///  aircraft fly for longer.
+pub fn delay_advisories(
+    plans: &[FlightPlan],
+    vertipad: &Vertipad,
+    aircraft_id: &str,
+    conflicting_flight_uuid: &str,
+    reserved_until: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Vec<Advisory> {
+    let mut advisories: Vec<Advisory> = vec![];
+    let operations = pad_operations(&vertipad.vertiport_id, plans);
+    for operation in operations.iter().filter(|operation| {
+        operation.vertipad_id == vertipad.id
+            && operation.aircraft_id != aircraft_id
+            && operation.slot_start <= reserved_until
+            && operation.slot_end >= now
+    }) {
+        let Some(plan) = plans
+            .iter()
+            .find(|plan| plan.flight_uuid == operation.flight_uuid)
+        else {
+            continue;
+        };
+
+        let seconds = (reserved_until - operation.slot_start).num_seconds() as f64;
+        let added_flight_time_seconds = match operation.operation {
+            PadOperationType::Departure => 0.0,
+            PadOperationType::Arrival => seconds,
+        };
+
+        // Flight plans departing from and arriving at the vertipad are
+        //  delayed once, by the longer delay
+        if let Some(existing) = advisories
+            .iter_mut()
+            .find(|advisory| advisory.flight_uuid == plan.flight_uuid)
+        {
+            if seconds > existing.value {
+                existing.value = seconds;
+                existing.added_flight_time_seconds = added_flight_time_seconds;
+            }
+            continue;
+        }
+
+        advisories.push(Advisory {
+            flight_uuid: plan.flight_uuid.clone(),
+            aircraft_id: plan.aircraft_id.clone(),
+            conflicting_flight_uuid: conflicting_flight_uuid.to_string(),
+            maneuver: ManeuverType::Delay,
+            value: seconds,
+            added_flight_time_seconds,
+            added_energy_meters: 0.0,
+            path: plan.path.clone(),
+            issued: now,
+        });
+    }
+
+    advisories
+}
+
+/// Gets the enabled vertipads from svc-storage
+pub async fn get_vertipads(grpc_clients: &GrpcClients) -> Result<Vec<Vertipad>, EmergencyError> {
+    let filter = AdvancedSearchFilter::search_equals("enabled".to_owned(), "true".to_owned());
+    let pads = grpc_clients
+        .storage
+        .vertipad
+        .search(filter)
+        .await
+        .map_err(|e| {
+            common_error!("svc-storage failure: {e}");
+            EmergencyError::Unavailable
+        })?
+        .into_inner()
+        .list
+        .into_iter()
+        .filter_map(Vertipad::from_object)
+        .collect();
+
+    Ok(pads)
+}
+
+/// Handles an emergency declared by an aircraft
+///
+/// The aircraft is granted a priority landing clearance on the nearest
+///  suitable vertipad, and the other aircraft scheduled on it are advised
+///  to delay. The delays are pushed to them with their flight plan updates.
+pub async fn declare_emergency(
+    declaration: &EmergencyDeclaration,
+    policy: &EmergencyPolicy,
+    grpc_clients: &GrpcClients,
+) -> Result<EmergencyResponse, EmergencyError> {
+    validate(declaration)?;
+
+    let now = Utc::now();
+    let emergency_id = Uuid::new_v4().to_string();
+    let event = |event_type| EmergencyEvent {
+        sequence: 0,
+        emergency_id: emergency_id.clone(),
+        aircraft_id: declaration.aircraft_id.clone(),
+        emergency_type: declaration.emergency_type,
+        event_type,
+        flight_uuid: None,
+        vertipad_id: None,
+        position: None,
+        delay_seconds: None,
+        timestamp: now,
+    };
+
+    // Flight plan the aircraft is currently flying, if known
+    let fp_id = get_plan_cache()
+        .await
+        .lock()
+        .await
+        .plans()
+        .into_iter()
+        .find(|plan| {
+            plan.aircraft_id == declaration.aircraft_id
+                && plan.origin_timeslot_start <= now
+                && now <= plan.target_timeslot_end
+        })
+        .map(|plan| plan.flight_uuid);
+
+    get_emergencies().await.lock().await.audit(EmergencyEvent {
+        flight_uuid: fp_id.clone(),
+        position: Some(declaration.position),
+        ..event(EmergencyEventType::Declared)
+    });
+
+    let mut pads = get_vertipads(grpc_clients).await?;
+    {
+        let weather = get_weather().await.lock().await;
+        pads.retain(|pad| weather_allows(pad, &weather, &policy.weather, now));
+    }
+
+    // Aircraft without a flight plan hold the clearance under the emergency ID
+    let key = fp_id.clone().unwrap_or_else(|| emergency_id.clone());
+    let reserved_until = now + policy.reservation;
+    let selected = {
+        let mut store = get_clearances().await.lock().await;
+        let selected = select_pad(&declaration.position, &pads, &key, &store, policy, now)
+            .map(|(pad, distance)| (pad.clone(), distance));
+
+        selected.map(|(pad, distance)| {
+            let clearance = Clearance {
+                fp_id: key.clone(),
+                aircraft_id: declaration.aircraft_id.clone(),
+                vertipad_id: pad.id.clone(),
+                clearance_type: ClearanceType::Landing,
+                status: ClearanceStatus::Granted,
+                reason: None,
+                issued: now,
+                expires: Some(reserved_until),
+                priority: true,
+            };
+
+            store.record(clearance.clone());
+            (pad, distance, clearance)
+        })
+    };
+
+    let Some((pad, distance_meters, clearance)) = selected else {
+        get_emergencies().await.lock().await.audit(EmergencyEvent {
+            flight_uuid: fp_id,
+            ..event(EmergencyEventType::NoSuitablePad)
+        });
+        return Err(EmergencyError::NoSuitablePad);
+    };
+
+    // Aircraft cleared to land leave their holding stack
+    if let Some(fp_id) = &fp_id {
+        get_holding().await.lock().await.remove(fp_id);
+    }
+
+    // Delays are best effort, the aircraft is cleared to land without them
+    let advisories = match get_vertiport_plans(
+        &pad.vertiport_id,
+        now - policy.reservation,
+        reserved_until,
+        &policy.plans,
+        grpc_clients,
+    )
+    .await
+    {
+        Ok(plans) => delay_advisories(
+            &plans,
+            &pad,
+            &declaration.aircraft_id,
+            &key,
+            reserved_until,
+            now,
+        ),
+        Err(e) => {
+            common_warn!(
+                "could not get flight plans of vertiport {}: {e}",
+                pad.vertiport_id
+            );
+            vec![]
+        }
+    };
+
+    let response = EmergencyResponse {
+        emergency_id: emergency_id.clone(),
+        aircraft_id: declaration.aircraft_id.clone(),
+        emergency_type: declaration.emergency_type,
+        fp_id: fp_id.clone(),
+        vertiport_id: pad.vertiport_id.clone(),
+        vertipad_id: pad.id.clone(),
+        distance_meters,
+        clearance,
+        advisories,
+        declared: now,
+    };
+
+    let mut emergencies = get_emergencies().await.lock().await;
+    emergencies.audit(EmergencyEvent {
+        flight_uuid: fp_id,
+        vertipad_id: Some(pad.id.clone()),
+        ..event(EmergencyEventType::PadAssigned)
+    });
+
+    for advisory in response.advisories.iter() {
+        emergencies.audit(EmergencyEvent {
+            flight_uuid: Some(advisory.flight_uuid.clone()),
+            vertipad_id: Some(pad.id.clone()),
+            delay_seconds: Some(advisory.value),
+            ..event(EmergencyEventType::DelayAdvised)
+        });
+    }
+
+    emergencies.expire(now);
+    emergencies.insert(response.clone());
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::{EmergencyType, Point, WeatherReport, WeatherSample};
+    use crate::test_util::{mock_flight_plan, mock_flight_plan_data};
+
+    fn pad(latitude: f64, longitude: f64) -> Vertipad {
+        Vertipad {
+            id: Uuid::new_v4().to_string(),
+            vertiport_id: "vertiport".to_string(),
+            position: PointZ {
+                latitude,
+                longitude,
+                altitude_meters: 0.0,
+            },
+            enabled: true,
+            occupied: false,
+        }
+    }
+
+    fn position(latitude: f64, longitude: f64) -> PointZ {
+        PointZ {
+            latitude,
+            longitude,
+            altitude_meters: 100.0,
+        }
+    }
+
+    fn declaration(latitude: f64, longitude: f64) -> EmergencyDeclaration {
+        EmergencyDeclaration {
+            aircraft_id: Uuid::new_v4().to_string(),
+            emergency_type: EmergencyType::LowBattery,
+            position: position(latitude, longitude),
+        }
+    }
+
+    #[test]
+    fn test_select_pad() {
+        let now = Utc::now();
+        let policy = EmergencyPolicy::default();
+        let mut store = ClearanceStore::default();
+
+        let mut closed = pad(52.0, 4.001);
+        closed.enabled = false;
+        let mut occupied = pad(52.0, 4.002);
+        occupied.occupied = true;
+        let held = pad(52.0, 4.003);
+        let nearest = pad(52.0, 4.01);
+        let farther = pad(52.0, 4.05);
+        let out_of_range = pad(53.0, 4.0);
+
+        store.record(Clearance {
+            fp_id: "other".to_string(),
+            aircraft_id: "other".to_string(),
+            vertipad_id: held.id.clone(),
+            clearance_type: ClearanceType::Landing,
+            status: ClearanceStatus::Granted,
+            reason: None,
+            issued: now,
+            expires: Some(now + policy.reservation),
+            priority: false,
+        });
+
+        let pads = vec![
+            farther.clone(),
+            closed,
+            occupied,
+            held.clone(),
+            nearest.clone(),
+        ];
+        let (selected, distance) =
+            select_pad(&position(52.0, 4.0), &pads, "own", &store, &policy, now).unwrap();
+        assert_eq!(selected, &nearest);
+        assert!(distance > 600.0 && distance < 700.0);
+
+        // A clearance held by the same flight plan does not block the pad
+        let (selected, _) =
+            select_pad(&position(52.0, 4.0), &pads, "other", &store, &policy, now).unwrap();
+        assert_eq!(selected, &held);
+
+        assert!(select_pad(
+            &position(52.0, 4.0),
+            &[out_of_range],
+            "own",
+            &store,
+            &policy,
+            now
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_weather_allows() {
+        let now = Utc::now();
+        let hour = Duration::try_hours(1).unwrap();
+        let limits = WeatherLimits::default();
+        let sample = |latitude, longitude, wind_speed_mps, visibility_meters| WeatherSample {
+            point: Point {
+                latitude,
+                longitude,
+            },
+            wind_speed_mps,
+            wind_gust_mps: None,
+            wind_direction_degrees: 0.0,
+            visibility_meters,
+        };
+
+        let mut weather = WeatherStore::default();
+        weather
+            .ingest(
+                WeatherReport {
+                    source: "test".to_string(),
+                    valid_from: now - hour,
+                    valid_until: now + hour,
+                    samples: vec![
+                        sample(52.0, 4.0, 30.0, 10000.0),
+                        sample(52.0, 5.0, 5.0, 100.0),
+                        sample(52.0, 6.0, 5.0, 10000.0),
+                    ],
+                    hazards: vec![],
+                },
+                now,
+            )
+            .unwrap();
+
+        assert!(!weather_allows(&pad(52.0, 4.0), &weather, &limits, now));
+        assert!(!weather_allows(&pad(52.0, 5.0), &weather, &limits, now));
+        assert!(weather_allows(&pad(52.0, 6.0), &weather, &limits, now));
+
+        // Vertipads without weather are allowed
+        assert!(weather_allows(&pad(10.0, 10.0), &weather, &limits, now));
+    }
+
+    #[test]
+    fn test_delay_advisories() {
+        let now = Utc::now();
+        let minutes = |m| Duration::try_minutes(m).unwrap();
+        let reserved_until = now + minutes(15);
+        let vertipad = pad(52.0, 4.0);
+        let path = [(52.1, 4.0, 0.0), (52.0, 4.0, 0.0)];
+
+        let mut arrival = mock_flight_plan(&path, now - minutes(20), now + minutes(5));
+        arrival.target_vertiport_id = vertipad.vertiport_id.clone();
+        arrival.target_vertipad_id = vertipad.id.clone();
+
+        let mut departure = mock_flight_plan(&path, now + minutes(10), now + minutes(30));
+        departure.origin_vertiport_id = vertipad.vertiport_id.clone();
+        departure.origin_vertipad_id = vertipad.id.clone();
+
+        let mut later = arrival.clone();
+        later.flight_uuid = Uuid::new_v4().to_string();
+        later.target_timeslot_start = now + minutes(20);
+        later.target_timeslot_end = now + minutes(25);
+
+        let mut own = arrival.clone();
+        own.flight_uuid = Uuid::new_v4().to_string();
+        own.aircraft_id = "own".to_string();
+
+        let mut other_pad = arrival.clone();
+        other_pad.flight_uuid = Uuid::new_v4().to_string();
+        other_pad.target_vertipad_id = "other".to_string();
+
+        let plans = vec![arrival.clone(), departure.clone(), later, own, other_pad];
+        let advisories =
+            delay_advisories(&plans, &vertipad, "own", "emergency", reserved_until, now);
+        assert_eq!(advisories.len(), 2);
+
+        let advisory = &advisories[0];
+        assert_eq!(advisory.flight_uuid, arrival.flight_uuid);
+        assert_eq!(advisory.conflicting_flight_uuid, "emergency");
+        assert_eq!(advisory.maneuver, ManeuverType::Delay);
+        assert_eq!(advisory.value, 600.0);
+        assert_eq!(advisory.added_flight_time_seconds, 600.0);
+
+        // Departing aircraft wait on the ground
+        let advisory = &advisories[1];
+        assert_eq!(advisory.flight_uuid, departure.flight_uuid);
+        assert_eq!(advisory.value, 600.0);
+        assert_eq!(advisory.added_flight_time_seconds, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_audit() {
+        let now = Utc::now();
+        let path = std::env::temp_dir().join(format!("{}.log", Uuid::new_v4()));
+        let event = EmergencyEvent {
+            sequence: 0,
+            emergency_id: "emergency".to_string(),
+            aircraft_id: "aircraft".to_string(),
+            emergency_type: EmergencyType::Medical,
+            event_type: EmergencyEventType::Declared,
+            flight_uuid: None,
+            vertipad_id: None,
+            position: None,
+            delay_seconds: None,
+            timestamp: now,
+        };
+
+        let mut store = EmergencyStore::default();
+        let writer = store.set_audit_file(path.to_str().unwrap());
+        assert_eq!(store.audit(event.clone()), 1);
+        assert_eq!(
+            store.audit(EmergencyEvent {
+                event_type: EmergencyEventType::NoSuitablePad,
+                ..event.clone()
+            }),
+            2
+        );
+
+        let events = store.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].sequence, 2);
+
+        // Dropping the sender lets the writer finish the queued events
+        store.audit_writer = None;
+        writer.await.unwrap();
+
+        let lines = std::fs::read_to_string(&path).unwrap();
+        let written = lines
+            .lines()
+            .map(|line| serde_json::from_str::<EmergencyEvent>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(written, events);
+        std::fs::remove_file(path).unwrap();
+
+        // Only the latest events are kept in memory
+        for _ in 0..AUDIT_CAPACITY {
+            store.audit(event.clone());
+        }
+        assert_eq!(store.events().len(), AUDIT_CAPACITY);
+        assert_eq!(store.events()[0].sequence, 3);
+    }
+
+    #[tokio::test]
+    async fn test_declare_emergency() {
+        let grpc_clients = GrpcClients::default(Config::default());
+        let policy = EmergencyPolicy::default();
+        let now = Utc::now();
+        let vertiport_id = Uuid::new_v4().to_string();
+
+        let mut pad_ids = vec![];
+        for longitude in [138.01, 138.05] {
+            let object = grpc_clients
+                .storage
+                .vertipad
+                .insert(vertipad::Data {
+                    vertiport_id: vertiport_id.clone(),
+                    name: "pad".to_string(),
+                    geo_location: Some(GeoPointZ {
+                        x: longitude,
+                        y: -44.0,
+                        z: 0.0,
+                    }),
+                    schedule: None,
+                    enabled: true,
+                    occupied: false,
+                })
+                .await
+                .unwrap()
+                .into_inner()
+                .object
+                .unwrap();
+            pad_ids.push(object.id);
+        }
+
+        // Another aircraft is about to land on the nearest pad
+        let mut data = mock_flight_plan_data(
+            &[(-44.1, 138.0, 0.0), (-44.0, 138.01, 0.0)],
+            now - Duration::try_minutes(20).unwrap(),
+            now + Duration::try_minutes(5).unwrap(),
+        );
+        data.target_vertiport_id = Some(vertiport_id.clone());
+        data.target_vertipad_id = pad_ids[0].clone();
+        let other = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap();
+
+        let first = declaration(-44.0, 138.0);
+        let response = declare_emergency(&first, &policy, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(response.vertiport_id, vertiport_id);
+        assert_eq!(response.vertipad_id, pad_ids[0]);
+        assert_eq!(response.fp_id, None);
+        assert!(response.clearance.priority);
+        assert_eq!(response.clearance.status, ClearanceStatus::Granted);
+        assert_eq!(response.clearance.fp_id, response.emergency_id);
+        assert_eq!(response.advisories.len(), 1);
+        assert_eq!(response.advisories[0].flight_uuid, other.id);
+
+        // The delay is attached to the flight plan of the other aircraft
+        let emergencies = get_emergencies().await.lock().await;
+        assert_eq!(emergencies.advisories(&other.id, Utc::now()).len(), 1);
+        let events = emergencies
+            .events()
+            .into_iter()
+            .filter(|event| event.emergency_id == response.emergency_id)
+            .map(|event| event.event_type)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec![
+                EmergencyEventType::Declared,
+                EmergencyEventType::PadAssigned,
+                EmergencyEventType::DelayAdvised
+            ]
+        );
+        drop(emergencies);
+
+        // The reserved pad is skipped by the next emergency
+        let response = declare_emergency(&declaration(-44.0, 138.0), &policy, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(response.vertipad_id, pad_ids[1]);
+
+        let error = declare_emergency(&declaration(-10.0, -10.0), &policy, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, EmergencyError::NoSuitablePad);
+
+        let mut invalid = declaration(-44.0, 138.0);
+        invalid.position.latitude = 91.0;
+        let error = declare_emergency(&invalid, &policy, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, EmergencyError::Position);
+
+        invalid.aircraft_id = "invalid".to_string();
+        let error = declare_emergency(&invalid, &policy, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, EmergencyError::AircraftId);
+    }
+
+    #[test]
+    fn test_emergency_error_display() {
+        assert_eq!(
+            EmergencyError::AircraftId.to_string(),
+            "invalid aircraft ID."
+        );
+        assert_eq!(
+            EmergencyError::Position.to_string(),
+            "position out of range."
+        );
+        assert_eq!(
+            EmergencyError::NoSuitablePad.to_string(),
+            "no suitable vertipad within range."
+        );
+        assert_eq!(
+            EmergencyError::Unavailable.to_string(),
+            "dependencies not available."
+        );
+    }
+}
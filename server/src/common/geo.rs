@@ -0,0 +1,136 @@
+//! Geometry helpers for WGS-84 points
+//!
+//! Distances are computed on a spherical earth model, which is accurate
+//!  enough for the short distances flown by VTOL aircraft.
+
+use crate::rest::api::PointZ;
+
+/// Mean radius of the earth in meters
+pub const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+
+/// Great-circle distance in meters between two points, ignoring altitude
+pub fn horizontal_distance_meters(a: &PointZ, b: &PointZ) -> f64 {
+    let lat_a = a.latitude.to_radians();
+    let lat_b = b.latitude.to_radians();
+    let delta_lat = (b.latitude - a.latitude).to_radians();
+    let delta_lon = (b.longitude - a.longitude).to_radians();
+
+    let h = (delta_lat / 2.0).sin().powi(2)
+        + lat_a.cos() * lat_b.cos() * (delta_lon / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_METERS * h.sqrt().min(1.0).asin()
+}
+
+/// Absolute altitude difference in meters between two points
+pub fn vertical_distance_meters(a: &PointZ, b: &PointZ) -> f64 {
+    (a.altitude_meters - b.altitude_meters).abs()
+}
+
+/// Straight-line distance in meters between two points, including altitude
+pub fn distance_meters(a: &PointZ, b: &PointZ) -> f64 {
+    horizontal_distance_meters(a, b).hypot(vertical_distance_meters(a, b))
+}
+
+/// Linear interpolation between two points
+///
+/// A `ratio` of 0.0 returns `a`, a `ratio` of 1.0 returns `b`.
+pub fn interpolate(a: &PointZ, b: &PointZ, ratio: f64) -> PointZ {
+    PointZ {
+        latitude: a.latitude + (b.latitude - a.latitude) * ratio,
+        longitude: a.longitude + (b.longitude - a.longitude) * ratio,
+        altitude_meters: a.altitude_meters + (b.altitude_meters - a.altitude_meters) * ratio,
+    }
+}
+
+/// Projects a point onto a local tangent plane centered on `origin`
+///
+/// Returns the (east, north) offset in meters.
+pub fn to_local_meters(origin: &PointZ, point: &PointZ) -> (f64, f64) {
+    let east = (point.longitude - origin.longitude).to_radians()
+        * EARTH_RADIUS_METERS
+        * origin.latitude.to_radians().cos();
+    let north = (point.latitude - origin.latitude).to_radians() * EARTH_RADIUS_METERS;
+
+    (east, north)
+}
+
+/// Inverse of [`to_local_meters`]
+pub fn from_local_meters(origin: &PointZ, east: f64, north: f64, altitude_meters: f64) -> PointZ {
+    let latitude = origin.latitude + (north / EARTH_RADIUS_METERS).to_degrees();
+    let longitude = origin.longitude
+        + (east / (EARTH_RADIUS_METERS * origin.latitude.to_radians().cos())).to_degrees();
+
+    PointZ {
+        latitude,
+        longitude,
+        altitude_meters,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(latitude: f64, longitude: f64, altitude_meters: f64) -> PointZ {
+        PointZ {
+            latitude,
+            longitude,
+            altitude_meters,
+        }
+    }
+
+    #[test]
+    fn test_horizontal_distance_meters() {
+        let a = point(52.0, 4.0, 0.0);
+        assert_eq!(horizontal_distance_meters(&a, &a), 0.0);
+
+        // One degree of latitude is ~111.19 km
+        let b = point(53.0, 4.0, 500.0);
+        let distance = horizontal_distance_meters(&a, &b);
+        assert!((distance - 111_195.0).abs() < 10.0);
+        assert_eq!(distance, horizontal_distance_meters(&b, &a));
+    }
+
+    #[test]
+    fn test_vertical_and_total_distance_meters() {
+        let a = point(52.0, 4.0, 100.0);
+        let b = point(52.0, 4.0, 40.0);
+        assert_eq!(vertical_distance_meters(&a, &b), 60.0);
+        assert_eq!(distance_meters(&a, &b), 60.0);
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let a = point(52.0, 4.0, 100.0);
+        let b = point(53.0, 5.0, 200.0);
+
+        let mid = interpolate(&a, &b, 0.5);
+        assert_eq!(mid.latitude, 52.5);
+        assert_eq!(mid.longitude, 4.5);
+        assert_eq!(mid.altitude_meters, 150.0);
+
+        let start = interpolate(&a, &b, 0.0);
+        assert_eq!(start.latitude, a.latitude);
+        let end = interpolate(&a, &b, 1.0);
+        assert_eq!(end.longitude, b.longitude);
+    }
+
+    #[test]
+    fn test_local_meters_round_trip() {
+        let origin = point(52.0, 4.0, 0.0);
+        let target = point(52.01, 4.02, 50.0);
+
+        let (east, north) = to_local_meters(&origin, &target);
+        assert!(east > 0.0);
+        assert!(north > 0.0);
+
+        // Local projection should agree with the great-circle distance
+        let distance = horizontal_distance_meters(&origin, &target);
+        assert!((east.hypot(north) - distance).abs() < 1.0);
+
+        let result = from_local_meters(&origin, east, north, 50.0);
+        assert!((result.latitude - target.latitude).abs() < 1e-9);
+        assert!((result.longitude - target.longitude).abs() < 1e-9);
+        assert_eq!(result.altitude_meters, 50.0);
+    }
+}
@@ -0,0 +1,816 @@
+//! Holding patterns for arrivals waiting on an occupied vertipad
+//!
+//! An arriving aircraft whose target vertipad is still occupied is assigned
+//!  an orbit around a fix back along its inbound track, clear of the
+//!  vertipad. Aircraft holding for the same vertipad are stacked at
+//!  increasing heights above the vertipad and released for approach from
+//!  the lowest level up as the vertipad frees up.
+
+use super::cache::CachePolicy;
+use super::clearance::{get_clearances, pad_occupied_until};
+use super::deconfliction::CheckError;
+use super::geo;
+use super::sequencing::get_pad_operations;
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::{
+    Clearance, ClearanceStatus, ClearanceType, FlightPlan, HoldingPattern, PointZ,
+};
+use crate::Config;
+use lib_common::time::{DateTime, Duration, Utc};
+use lib_common::uuid::Uuid;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use svc_storage_client_grpc::prelude::*;
+use tokio::sync::{Mutex, OnceCell};
+
+/// Number of waypoints on a holding orbit, excluding the closing waypoint
+const ORBIT_WAYPOINTS: usize = 8;
+
+/// Holding patterns assigned by this service
+static HOLDING: OnceCell<Mutex<HoldingStacks>> = OnceCell::const_new();
+
+/// Returns the holding patterns assigned by this service
+pub async fn get_holding() -> &'static Mutex<HoldingStacks> {
+    HOLDING
+        .get_or_init(|| async { Mutex::new(HoldingStacks::default()) })
+        .await
+}
+
+/// Errors assigning a holding pattern
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HoldingError {
+    /// Internal Error
+    Internal,
+
+    /// Dependencies not available
+    Unavailable,
+
+    /// Flight Plan Not Found
+    NotFound,
+
+    /// Target vertipad is not occupied
+    PadAvailable,
+}
+
+impl Display for HoldingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            HoldingError::Internal => write!(f, "Internal Error"),
+            HoldingError::Unavailable => write!(f, "Dependencies not available"),
+            HoldingError::NotFound => write!(f, "Flight Plan Not Found"),
+            HoldingError::PadAvailable => write!(f, "Target vertipad is not occupied"),
+        }
+    }
+}
+
+impl From<CheckError> for HoldingError {
+    fn from(error: CheckError) -> Self {
+        match error {
+            CheckError::Unavailable => HoldingError::Unavailable,
+            CheckError::Internal => HoldingError::Internal,
+        }
+    }
+}
+
+/// Settings used to build holding stacks
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HoldingPolicy {
+    /// Radius of the holding orbit in meters
+    pub radius_meters: f64,
+
+    /// Height of the lowest level above the vertipad in meters
+    pub base_altitude_meters: f64,
+
+    /// Distance from the vertipad to the fix in meters
+    pub fix_offset_meters: f64,
+
+    /// Vertical distance between levels in meters
+    pub stack_separation_meters: f64,
+
+    /// Time between consecutive approaches to the same vertipad
+    pub spacing: Duration,
+
+    /// Window of the flight plans checked for vertipad operations
+    pub plans: CachePolicy,
+}
+
+impl From<&Config> for HoldingPolicy {
+    fn from(config: &Config) -> Self {
+        HoldingPolicy {
+            radius_meters: config.holding_radius_meters,
+            base_altitude_meters: config.holding_base_altitude_meters,
+            fix_offset_meters: config.holding_fix_offset_meters,
+            stack_separation_meters: config.holding_stack_separation_meters,
+            spacing: Duration::try_seconds(config.vertiport_min_spacing_seconds as i64)
+                .unwrap_or(Duration::zero()),
+            plans: CachePolicy::from(config),
+        }
+    }
+}
+
+impl Default for HoldingPolicy {
+    fn default() -> Self {
+        HoldingPolicy::from(&Config::new())
+    }
+}
+
+impl HoldingPolicy {
+    /// Altitude of a level in the holding stack, starting at 1, over a
+    ///  vertipad at `elevation_meters`
+    pub fn altitude_meters(&self, level: u32, elevation_meters: f64) -> f64 {
+        elevation_meters
+            + self.base_altitude_meters
+            + level.saturating_sub(1) as f64 * self.stack_separation_meters
+    }
+}
+
+/// Places the holding fix `offset_meters` from the target vertipad, back
+///  along the inbound track of a path, at the elevation of the vertipad
+///
+/// The fix is placed north of the vertipad if the path has no horizontal
+///  leg. Returns `None` if the path is empty.
+pub fn holding_fix(path: &[PointZ], offset_meters: f64) -> Option<PointZ> {
+    let pad = path.last()?;
+    let (east, north) = path
+        .iter()
+        .rev()
+        .map(|point| geo::to_local_meters(pad, point))
+        .find(|(east, north)| east.hypot(*north) >= 1.0)
+        .unwrap_or((0.0, 1.0));
+
+    let length = east.hypot(north);
+    Some(geo::from_local_meters(
+        pad,
+        east / length * offset_meters,
+        north / length * offset_meters,
+        pad.altitude_meters,
+    ))
+}
+
+/// Builds a closed orbit around a fix, flown clockwise starting north of the fix
+pub fn orbit(fix: &PointZ, radius_meters: f64, altitude_meters: f64) -> Vec<PointZ> {
+    (0..=ORBIT_WAYPOINTS)
+        .map(|index| {
+            let bearing = std::f64::consts::TAU * index as f64 / ORBIT_WAYPOINTS as f64;
+            geo::from_local_meters(
+                fix,
+                radius_meters * bearing.sin(),
+                radius_meters * bearing.cos(),
+                altitude_meters,
+            )
+        })
+        .collect()
+}
+
+/// Moves a holding pattern to a level of the stack
+fn set_level(pattern: &mut HoldingPattern, level: u32, policy: &HoldingPolicy) {
+    pattern.level = level;
+    pattern.altitude_meters = policy.altitude_meters(level, pattern.fix.altitude_meters);
+    pattern.orbit = orbit(&pattern.fix, pattern.radius_meters, pattern.altitude_meters);
+}
+
+/// Holding stacks keyed by vertipad, lowest level first
+#[derive(Debug, Default)]
+pub struct HoldingStacks {
+    /// Holding patterns keyed by target vertipad ID
+    stacks: HashMap<String, Vec<HoldingPattern>>,
+}
+
+impl HoldingStacks {
+    /// Assigns a holding pattern at the top of the stack of the target vertipad
+    ///
+    /// The first aircraft in the stack is expected to approach when the
+    ///  vertipad frees up at `free_at`, and each following aircraft one
+    ///  spacing later. Returns the existing holding pattern if the flight
+    ///  plan is already holding, or `None` if the flight plan has no path.
+    pub fn assign(
+        &mut self,
+        plan: &FlightPlan,
+        free_at: DateTime<Utc>,
+        policy: &HoldingPolicy,
+        now: DateTime<Utc>,
+    ) -> Option<HoldingPattern> {
+        if let Some(pattern) = self.get(&plan.flight_uuid) {
+            return Some(pattern.clone());
+        }
+
+        let fix = holding_fix(&plan.path, policy.fix_offset_meters)?;
+        let stack = self
+            .stacks
+            .entry(plan.target_vertipad_id.clone())
+            .or_default();
+
+        let expected_approach = match stack.last() {
+            Some(previous) => free_at.max(previous.expected_approach + policy.spacing),
+            None => free_at,
+        };
+
+        let mut pattern = HoldingPattern {
+            fp_id: plan.flight_uuid.clone(),
+            aircraft_id: plan.aircraft_id.clone(),
+            vertiport_id: plan.target_vertiport_id.clone(),
+            vertipad_id: plan.target_vertipad_id.clone(),
+            fix,
+            radius_meters: policy.radius_meters,
+            level: 0,
+            altitude_meters: 0.0,
+            orbit: vec![],
+            assigned: now,
+            expected_approach,
+        };
+
+        set_level(&mut pattern, stack.len() as u32 + 1, policy);
+        stack.push(pattern.clone());
+        Some(pattern)
+    }
+
+    /// Releases the lowest aircraft holding for a vertipad
+    ///
+    /// The remaining aircraft descend one level and are expected to
+    ///  approach one spacing after each other.
+    pub fn release(
+        &mut self,
+        vertipad_id: &str,
+        policy: &HoldingPolicy,
+        now: DateTime<Utc>,
+    ) -> Option<HoldingPattern> {
+        let stack = self.stacks.get_mut(vertipad_id)?;
+        if stack.is_empty() {
+            return None;
+        }
+
+        let released = stack.remove(0);
+        let mut expected_approach = now;
+        for (index, pattern) in stack.iter_mut().enumerate() {
+            expected_approach += policy.spacing;
+            pattern.expected_approach = pattern.expected_approach.min(expected_approach);
+            set_level(pattern, index as u32 + 1, policy);
+        }
+
+        if stack.is_empty() {
+            self.stacks.remove(vertipad_id);
+        }
+
+        Some(released)
+    }
+
+    /// Removes a flight plan from its holding stack
+    ///
+    /// Aircraft above it descend one level.
+    pub fn remove(&mut self, fp_id: &str) -> Option<HoldingPattern> {
+        let (vertipad_id, index) = self.stacks.iter().find_map(|(vertipad_id, stack)| {
+            stack
+                .iter()
+                .position(|pattern| pattern.fp_id == fp_id)
+                .map(|index| (vertipad_id.clone(), index))
+        })?;
+
+        let stack = self.stacks.get_mut(&vertipad_id)?;
+        let removed = stack.remove(index);
+        let mut below = (removed.level, removed.altitude_meters);
+        for pattern in stack.iter_mut().skip(index) {
+            let current = (pattern.level, pattern.altitude_meters);
+            (pattern.level, pattern.altitude_meters) = below;
+            pattern.orbit = orbit(&pattern.fix, pattern.radius_meters, pattern.altitude_meters);
+            below = current;
+        }
+
+        if stack.is_empty() {
+            self.stacks.remove(&vertipad_id);
+        }
+
+        Some(removed)
+    }
+
+    /// Returns the holding pattern of a flight plan
+    pub fn get(&self, fp_id: &str) -> Option<&HoldingPattern> {
+        self.stacks
+            .values()
+            .flat_map(|stack| stack.iter())
+            .find(|pattern| pattern.fp_id == fp_id)
+    }
+
+    /// Returns the holding patterns at a vertiport, by vertipad and level
+    pub fn list(&self, vertiport_id: &str) -> Vec<HoldingPattern> {
+        let mut patterns = self
+            .stacks
+            .values()
+            .flat_map(|stack| stack.iter())
+            .filter(|pattern| pattern.vertiport_id == vertiport_id)
+            .cloned()
+            .collect::<Vec<HoldingPattern>>();
+
+        patterns.sort_by(|a, b| {
+            a.vertipad_id
+                .cmp(&b.vertipad_id)
+                .then(a.level.cmp(&b.level))
+        });
+        patterns
+    }
+
+    /// Returns the holding stacks, lowest level first
+    pub fn stacks(&self) -> impl Iterator<Item = &Vec<HoldingPattern>> {
+        self.stacks.values()
+    }
+
+    /// Number of aircraft holding
+    pub fn len(&self) -> usize {
+        self.stacks.values().map(|stack| stack.len()).sum()
+    }
+
+    /// Returns true if no aircraft are holding
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Assigns a holding pattern to an arrival whose target vertipad is occupied
+pub async fn assign_holding(
+    fp_id: Uuid,
+    policy: &HoldingPolicy,
+    grpc_clients: &GrpcClients,
+) -> Result<HoldingPattern, HoldingError> {
+    let object = grpc_clients
+        .storage
+        .flight_plan
+        .get_by_id(Id {
+            id: fp_id.to_string(),
+        })
+        .await
+        .map_err(|e| {
+            common_error!("{}", e);
+            HoldingError::NotFound
+        })?
+        .into_inner();
+
+    let plan = FlightPlan::try_from(object).map_err(|e| {
+        common_error!("could not get flight plan {fp_id}: {e}");
+        HoldingError::Internal
+    })?;
+
+    let now = Utc::now();
+    let operations =
+        get_pad_operations(&plan.target_vertiport_id, now, &policy.plans, grpc_clients).await?;
+    let free_at = {
+        let clearances = get_clearances().await.lock().await;
+        pad_occupied_until(
+            &plan.target_vertipad_id,
+            &plan.flight_uuid,
+            &clearances,
+            &operations,
+            now,
+        )
+        .ok_or(HoldingError::PadAvailable)?
+    };
+
+    let pattern = get_holding()
+        .await
+        .lock()
+        .await
+        .assign(&plan, free_at, policy, now)
+        .ok_or_else(|| {
+            common_error!("flight plan {fp_id} has no path.");
+            HoldingError::Internal
+        })?;
+
+    common_info!(
+        "flight plan {fp_id} holding for vertipad {} at level {}, expected approach at {}.",
+        pattern.vertipad_id,
+        pattern.level,
+        pattern.expected_approach
+    );
+
+    Ok(pattern)
+}
+
+/// Releases the lowest aircraft of each holding stack whose vertipad is free
+///
+/// Timeslots of aircraft in the stack are ignored, as they are occupying
+///  the vertipad only once released. Released aircraft are granted a
+///  landing clearance reserving the vertipad for one spacing.
+pub async fn monitor_holding(
+    policy: &HoldingPolicy,
+    grpc_clients: &GrpcClients,
+) -> Vec<HoldingPattern> {
+    let now = Utc::now();
+    let stacks = get_holding()
+        .await
+        .lock()
+        .await
+        .stacks()
+        .cloned()
+        .collect::<Vec<Vec<HoldingPattern>>>();
+
+    let mut released = vec![];
+    for stack in stacks.iter() {
+        let Some(lowest) = stack.first() else {
+            continue;
+        };
+
+        let operations = match get_pad_operations(
+            &lowest.vertiport_id,
+            now,
+            &policy.plans,
+            grpc_clients,
+        )
+        .await
+        {
+            Ok(operations) => operations,
+            Err(e) => {
+                common_warn!(
+                    "could not get operations of vertiport {}: {e}",
+                    lowest.vertiport_id
+                );
+                continue;
+            }
+        };
+
+        let operations = operations
+            .into_iter()
+            .filter(|operation| {
+                !stack
+                    .iter()
+                    .any(|pattern| pattern.fp_id == operation.flight_uuid)
+            })
+            .collect::<Vec<_>>();
+
+        let mut clearances = get_clearances().await.lock().await;
+        let occupied = pad_occupied_until(
+            &lowest.vertipad_id,
+            &lowest.fp_id,
+            &clearances,
+            &operations,
+            now,
+        )
+        .is_some();
+
+        if occupied {
+            continue;
+        }
+
+        let Some(pattern) =
+            get_holding()
+                .await
+                .lock()
+                .await
+                .release(&lowest.vertipad_id, policy, now)
+        else {
+            continue;
+        };
+
+        // Reserve the vertipad for the approach, so that the next aircraft
+        //  is released one spacing later at the earliest
+        clearances.record(Clearance {
+            fp_id: pattern.fp_id.clone(),
+            aircraft_id: pattern.aircraft_id.clone(),
+            vertipad_id: pattern.vertipad_id.clone(),
+            clearance_type: ClearanceType::Landing,
+            status: ClearanceStatus::Granted,
+            reason: None,
+            issued: now,
+            expires: Some(now + policy.spacing),
+            priority: false,
+        });
+
+        common_info!(
+            "flight plan {} released from holding for vertipad {}.",
+            pattern.fp_id,
+            pattern.vertipad_id
+        );
+        released.push(pattern);
+    }
+
+    released
+}
+
+/// Periodically releases holding aircraft as their vertipads free up
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) infinite loop, tested through monitor_holding
+pub async fn holding_loop(config: Config) {
+    let grpc_clients = crate::grpc::client::get_clients().await;
+    let policy = HoldingPolicy::from(&config);
+    let period = std::time::Duration::from_secs(config.holding_interval_seconds.max(1) as u64);
+    let mut interval = tokio::time::interval(period);
+
+    common_info!("holding monitor started.");
+    loop {
+        interval.tick().await;
+        monitor_holding(&policy, grpc_clients).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::ClearanceRequest;
+    use crate::test_util::{mock_flight_plan_data, mock_plan};
+
+    fn arrival(now: DateTime<Utc>, vertipad_id: &str) -> FlightPlan {
+        let mut plan = mock_plan(
+            &[(52.0, 4.0, 100.0), (52.0, 4.02, 0.0)],
+            now - Duration::try_minutes(10).unwrap(),
+        );
+        plan.target_vertipad_id = vertipad_id.to_string();
+        plan
+    }
+
+    #[test]
+    fn test_orbit() {
+        let fix = PointZ {
+            latitude: 52.0,
+            longitude: 4.0,
+            altitude_meters: 0.0,
+        };
+
+        let orbit = orbit(&fix, 1000.0, 150.0);
+        assert_eq!(orbit.len(), ORBIT_WAYPOINTS + 1);
+        assert_eq!(orbit.first(), orbit.last());
+        for point in orbit.iter() {
+            assert!((geo::horizontal_distance_meters(&fix, point) - 1000.0).abs() < 1.0);
+            assert_eq!(point.altitude_meters, 150.0);
+        }
+
+        // Clockwise: north, then east
+        assert!(orbit[0].latitude > fix.latitude);
+        assert!(orbit[2].longitude > fix.longitude);
+    }
+
+    #[test]
+    fn test_holding_fix() {
+        // Arriving eastbound, the fix is west of the vertipad
+        let path = [
+            PointZ {
+                latitude: 52.0,
+                longitude: 4.0,
+                altitude_meters: 300.0,
+            },
+            PointZ {
+                latitude: 52.0,
+                longitude: 4.05,
+                altitude_meters: 400.0,
+            },
+            PointZ {
+                latitude: 52.0,
+                longitude: 4.05,
+                altitude_meters: 300.0,
+            },
+        ];
+        let pad = path[2];
+        let fix = holding_fix(&path, 2000.0).unwrap();
+        let (east, north) = geo::to_local_meters(&pad, &fix);
+        assert!((east + 2000.0).abs() < 1.0);
+        assert!(north.abs() < 1.0);
+        assert_eq!(fix.altitude_meters, 300.0);
+
+        // Without a horizontal leg, the fix is north of the vertipad
+        let fix = holding_fix(&path[2..], 2000.0).unwrap();
+        let (east, north) = geo::to_local_meters(&pad, &fix);
+        assert!(east.abs() < 1.0);
+        assert!((north - 2000.0).abs() < 1.0);
+        assert!(holding_fix(&[], 2000.0).is_none());
+    }
+
+    #[test]
+    fn test_holding_over_elevated_vertipad() {
+        let now = Utc::now();
+        let policy = HoldingPolicy::default();
+        let mut plan = arrival(now, "pad-1");
+        for point in plan.path.iter_mut() {
+            point.altitude_meters += 300.0;
+        }
+
+        let pattern = HoldingStacks::default()
+            .assign(&plan, now, &policy, now)
+            .unwrap();
+        assert_eq!(pattern.altitude_meters, 300.0 + policy.base_altitude_meters);
+
+        // The orbit stays clear of the vertipad
+        let pad = plan.path.last().unwrap();
+        assert!(pattern.orbit.iter().all(|point| {
+            point.altitude_meters > pad.altitude_meters
+                && geo::horizontal_distance_meters(pad, point)
+                    >= policy.fix_offset_meters - policy.radius_meters - 1.0
+        }));
+    }
+
+    #[test]
+    fn test_holding_stack() {
+        let now = Utc::now();
+        let policy = HoldingPolicy::default();
+        let free_at = now + Duration::try_minutes(2).unwrap();
+        let mut stacks = HoldingStacks::default();
+
+        let plans = (0..3).map(|_| arrival(now, "pad-1")).collect::<Vec<_>>();
+        let patterns = plans
+            .iter()
+            .map(|plan| stacks.assign(plan, free_at, &policy, now).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(stacks.len(), 3);
+        for (index, pattern) in patterns.iter().enumerate() {
+            assert_eq!(pattern.level, index as u32 + 1);
+            assert_eq!(
+                pattern.altitude_meters,
+                policy.base_altitude_meters + index as f64 * policy.stack_separation_meters
+            );
+            assert_eq!(
+                pattern.expected_approach,
+                free_at + policy.spacing * index as i32
+            );
+            assert_eq!(
+                Some(pattern.fix),
+                holding_fix(&plans[index].path, policy.fix_offset_meters)
+            );
+        }
+
+        // Assigning again returns the existing pattern
+        let pattern = stacks.assign(&plans[1], now, &policy, now).unwrap();
+        assert_eq!(pattern, patterns[1]);
+        assert_eq!(stacks.len(), 3);
+
+        // Lowest aircraft is released first, the others descend
+        let later = now + Duration::try_minutes(1).unwrap();
+        let released = stacks.release("pad-1", &policy, later).unwrap();
+        assert_eq!(released.fp_id, plans[0].flight_uuid);
+
+        let pattern = stacks.get(&plans[1].flight_uuid).unwrap();
+        assert_eq!(pattern.level, 1);
+        assert_eq!(pattern.altitude_meters, policy.base_altitude_meters);
+        assert_eq!(pattern.expected_approach, later + policy.spacing);
+        assert!(pattern
+            .orbit
+            .iter()
+            .all(|point| point.altitude_meters == policy.base_altitude_meters));
+
+        // Removing a holder lowers the aircraft above
+        let removed = stacks.remove(&plans[1].flight_uuid).unwrap();
+        assert_eq!(removed.fp_id, plans[1].flight_uuid);
+        let pattern = stacks.get(&plans[2].flight_uuid).unwrap();
+        assert_eq!(pattern.level, 1);
+        assert_eq!(pattern.altitude_meters, policy.base_altitude_meters);
+        assert!(stacks.remove(&plans[1].flight_uuid).is_none());
+
+        assert!(stacks.release("pad-1", &policy, later).is_some());
+        assert!(stacks.release("pad-1", &policy, later).is_none());
+        assert!(stacks.is_empty());
+    }
+
+    #[test]
+    fn test_holding_list() {
+        let now = Utc::now();
+        let policy = HoldingPolicy::default();
+        let mut stacks = HoldingStacks::default();
+
+        let mut plans = [arrival(now, "pad-2"), arrival(now, "pad-1")];
+        plans[1].target_vertiport_id = plans[0].target_vertiport_id.clone();
+        let other = arrival(now, "pad-1");
+        for plan in plans.iter().chain(std::iter::once(&other)) {
+            stacks.assign(plan, now, &policy, now).unwrap();
+        }
+
+        let list = stacks.list(&plans[0].target_vertiport_id);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].vertipad_id, "pad-1");
+        assert_eq!(list[1].vertipad_id, "pad-2");
+        assert!(stacks.list("unknown").is_empty());
+
+        let mut plan = arrival(now, "pad-3");
+        plan.path = vec![];
+        assert!(stacks.assign(&plan, now, &policy, now).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_assign_and_release_holding() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let policy = HoldingPolicy::default();
+        let now = Utc::now();
+
+        let data = mock_flight_plan_data(
+            &[(-36.0, 148.0, 100.0), (-36.0, 148.02, 0.0)],
+            now - Duration::try_minutes(10).unwrap(),
+            now + Duration::try_minutes(1).unwrap(),
+        );
+        let object = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap();
+        let plan = FlightPlan::try_from(object).unwrap();
+        let fp_id = Uuid::parse_str(&plan.flight_uuid).unwrap();
+
+        // Target vertipad is free
+        let error = assign_holding(fp_id, &policy, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, HoldingError::PadAvailable);
+
+        // Another aircraft is cleared to land on the target vertipad
+        let other = arrival(now, &plan.target_vertipad_id);
+        let request = ClearanceRequest {
+            fp_id: other.flight_uuid.clone(),
+            aircraft_id: other.aircraft_id.clone(),
+            vertipad_id: plan.target_vertipad_id.clone(),
+            clearance_type: ClearanceType::Landing,
+        };
+        let clearance = crate::common::clearance::evaluate(
+            &request,
+            &other,
+            crate::rest::api::FlightState::Acknowledged,
+            &Default::default(),
+            &[],
+            &[],
+            &Default::default(),
+            now,
+        );
+        get_clearances()
+            .await
+            .lock()
+            .await
+            .record(clearance.clone());
+
+        let pattern = assign_holding(fp_id, &policy, &grpc_clients).await.unwrap();
+        assert_eq!(pattern.level, 1);
+        assert_eq!(Some(pattern.expected_approach), clearance.expires);
+
+        // Not released while the vertipad is occupied
+        let released = monitor_holding(&policy, &grpc_clients).await;
+        assert!(!released.iter().any(|tmp| tmp.fp_id == plan.flight_uuid));
+
+        // Released once the other aircraft has landed
+        let mut landed = clearance;
+        landed.expires = Some(now);
+        get_clearances().await.lock().await.record(landed);
+        let released = monitor_holding(&policy, &grpc_clients).await;
+        assert!(released.iter().any(|tmp| tmp.fp_id == plan.flight_uuid));
+        assert!(get_holding()
+            .await
+            .lock()
+            .await
+            .get(&plan.flight_uuid)
+            .is_none());
+
+        let error = assign_holding(Uuid::new_v4(), &policy, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, HoldingError::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_release_reserves_vertipad() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let policy = HoldingPolicy::default();
+        let now = Utc::now();
+
+        // Two aircraft holding for a vertipad that is already free
+        let vertipad_id = Uuid::new_v4().to_string();
+        let first = arrival(now, &vertipad_id);
+        let mut second = arrival(now, &vertipad_id);
+        second.target_vertiport_id = first.target_vertiport_id.clone();
+        {
+            let mut stacks = get_holding().await.lock().await;
+            stacks.assign(&first, now, &policy, now).unwrap();
+            stacks.assign(&second, now, &policy, now).unwrap();
+        }
+
+        // Only the lowest aircraft is released, the vertipad is reserved
+        //  for its approach
+        monitor_holding(&policy, &grpc_clients).await;
+        monitor_holding(&policy, &grpc_clients).await;
+        {
+            let stacks = get_holding().await.lock().await;
+            assert!(stacks.get(&first.flight_uuid).is_none());
+            assert_eq!(stacks.get(&second.flight_uuid).unwrap().level, 1);
+        }
+
+        let clearances = get_clearances().await.lock().await;
+        let holder = clearances
+            .pad_holder(&vertipad_id, &second.flight_uuid, now)
+            .unwrap();
+        assert_eq!(holder.fp_id, first.flight_uuid);
+        assert_eq!(holder.clearance_type, ClearanceType::Landing);
+        assert!(holder.expires.unwrap() >= now + policy.spacing);
+        drop(clearances);
+
+        get_holding().await.lock().await.remove(&second.flight_uuid);
+    }
+
+    #[test]
+    fn test_holding_error_display() {
+        assert_eq!(HoldingError::Internal.to_string(), "Internal Error");
+        assert_eq!(
+            HoldingError::Unavailable.to_string(),
+            "Dependencies not available"
+        );
+        assert_eq!(HoldingError::NotFound.to_string(), "Flight Plan Not Found");
+        assert_eq!(
+            HoldingError::PadAvailable.to_string(),
+            "Target vertipad is not occupied"
+        );
+    }
+}
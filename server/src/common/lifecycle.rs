@@ -0,0 +1,594 @@
+//! Lifecycle state machine of flight plans
+//!
+//! A flight plan is scheduled until acknowledged by its carrier, cleared
+//!  for takeoff, airborne, and finally landed. It can be cancelled until
+//!  the aircraft takes off, and diverted once airborne. Every transition
+//!  is validated and logged with the time at which it occurred. Landed and
+//!  cancelled flight plans are forgotten after a retention period, their
+//!  state is then derived again from svc-storage.
+
+use super::{cancel_flight, clearance, conformance, get_flight_data, updates, AckError};
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::{FlightHistory, FlightState, FlightTransition};
+use lib_common::time::{DateTime, Duration, Utc};
+use lib_common::uuid::Uuid;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+use svc_storage_client_grpc::prelude::*;
+use tokio::sync::{Mutex, OnceCell, OwnedMutexGuard};
+
+/// Hours after a flight plan landed or was cancelled that its transition
+///  log is kept
+pub const HISTORY_RETENTION_HOURS: i64 = 24;
+
+/// Lifecycles of the flight plans handled by this service
+static LIFECYCLES: OnceCell<Mutex<FlightLifecycles>> = OnceCell::const_new();
+
+/// Returns the lifecycles of the flight plans handled by this service
+pub async fn get_lifecycles() -> &'static Mutex<FlightLifecycles> {
+    LIFECYCLES
+        .get_or_init(|| async { Mutex::new(FlightLifecycles::default()) })
+        .await
+}
+
+/// Locks serializing the changes to each flight plan, keyed by flight
+///  plan ID
+static FLIGHT_LOCKS: OnceCell<Mutex<HashMap<String, Arc<Mutex<()>>>>> = OnceCell::const_new();
+
+/// Locks a flight plan until the returned guard is dropped
+///
+/// The guard is held from the lifecycle check until the transition is
+///  applied, so that concurrent requests for the same flight plan can not
+///  both pass the check before either updates svc-storage.
+pub async fn lock_flight(fp_id: &str) -> OwnedMutexGuard<()> {
+    let lock = {
+        let mut locks = FLIGHT_LOCKS
+            .get_or_init(|| async { Mutex::new(HashMap::new()) })
+            .await
+            .lock()
+            .await;
+
+        // Locks neither held nor awaited are dropped
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        locks.entry(fp_id.to_string()).or_default().clone()
+    };
+
+    lock.lock_owned().await
+}
+
+/// Transition not allowed by the lifecycle state machine
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct IllegalTransition {
+    /// Current state of the flight plan
+    pub from: FlightState,
+
+    /// Requested state
+    pub to: FlightState,
+}
+
+impl Display for IllegalTransition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot transition from {:?} to {:?}.",
+            self.from, self.to
+        )
+    }
+}
+
+/// Returns true if a flight plan may move from one state to another
+pub fn is_allowed(from: FlightState, to: FlightState) -> bool {
+    use FlightState::*;
+
+    matches!(
+        (from, to),
+        (Scheduled, Acknowledged)
+            | (Scheduled, Cancelled)
+            | (Acknowledged, Scheduled)
+            | (Acknowledged, Cleared)
+            | (Acknowledged, Cancelled)
+            | (Cleared, Airborne)
+            | (Cleared, Cancelled)
+            | (Airborne, Landed)
+            | (Airborne, Diverted)
+            | (Diverted, Landed)
+    )
+}
+
+/// Returns true if a flight plan can not leave a state
+pub fn is_terminal(state: FlightState) -> bool {
+    matches!(state, FlightState::Landed | FlightState::Cancelled)
+}
+
+/// Lifecycle state of a flight plan not yet handled by this service,
+///  derived from its svc-storage record
+pub fn initial_state(data: &flight_plan::Data) -> FlightState {
+    if data.flight_status == flight_plan::FlightStatus::Cancelled as i32 {
+        FlightState::Cancelled
+    } else if data.flight_status == flight_plan::FlightStatus::Finished as i32 {
+        FlightState::Landed
+    } else if data.flight_status == flight_plan::FlightStatus::InFlight as i32 {
+        FlightState::Airborne
+    } else if data.carrier_ack.is_some() {
+        FlightState::Acknowledged
+    } else {
+        FlightState::Scheduled
+    }
+}
+
+/// Lifecycle states and transition logs keyed by flight plan ID
+#[derive(Debug, Default)]
+pub struct FlightLifecycles {
+    /// Flight plan histories keyed by flight plan ID
+    flights: HashMap<String, FlightHistory>,
+
+    /// Time at which flight plans were first seen landed or cancelled,
+    ///  keyed by flight plan ID
+    ended: HashMap<String, DateTime<Utc>>,
+}
+
+impl FlightLifecycles {
+    /// Starts tracking a flight plan in the given state, if not tracked yet
+    pub fn seed(&mut self, fp_id: &str, state: FlightState) {
+        self.flights
+            .entry(fp_id.to_string())
+            .or_insert_with(|| FlightHistory {
+                fp_id: fp_id.to_string(),
+                state,
+                transitions: vec![],
+            });
+    }
+
+    /// Current state of a flight plan
+    pub fn state(&self, fp_id: &str) -> Option<FlightState> {
+        self.flights.get(fp_id).map(|history| history.state)
+    }
+
+    /// State and transition log of a flight plan
+    pub fn history(&self, fp_id: &str) -> Option<FlightHistory> {
+        self.flights.get(fp_id).cloned()
+    }
+
+    /// Checks that a flight plan may move to a state
+    ///
+    /// Untracked flight plans are considered scheduled. Moving to the
+    ///  current state is allowed and has no effect.
+    pub fn check(&self, fp_id: &str, to: FlightState) -> Result<(), IllegalTransition> {
+        let from = self.state(fp_id).unwrap_or_default();
+        if from == to || is_allowed(from, to) {
+            Ok(())
+        } else {
+            Err(IllegalTransition { from, to })
+        }
+    }
+
+    /// Moves a flight plan to a state
+    ///
+    /// Returns the logged transition, or `None` if the flight plan was
+    ///  already in that state.
+    pub fn transition(
+        &mut self,
+        fp_id: &str,
+        to: FlightState,
+        now: DateTime<Utc>,
+    ) -> Result<Option<FlightTransition>, IllegalTransition> {
+        self.check(fp_id, to)?;
+        self.seed(fp_id, FlightState::default());
+
+        let history = self.flights.get_mut(fp_id).ok_or(IllegalTransition {
+            from: FlightState::default(),
+            to,
+        })?;
+
+        if history.state == to {
+            return Ok(None);
+        }
+
+        let transition = FlightTransition {
+            from: history.state,
+            to,
+            timestamp: now,
+        };
+
+        history.state = to;
+        history.transitions.push(transition.clone());
+        Ok(Some(transition))
+    }
+
+    /// Forgets the flight plans landed or cancelled more than the
+    ///  retention period before `now`
+    ///
+    /// Flight plans tracked in a terminal state without a logged transition
+    ///  are retained from the first call that sees them. Returns the IDs of
+    ///  the forgotten flight plans.
+    pub fn expire(&mut self, now: DateTime<Utc>) -> Vec<String> {
+        for (fp_id, history) in self.flights.iter() {
+            if is_terminal(history.state) {
+                self.ended.entry(fp_id.clone()).or_insert_with(|| {
+                    history
+                        .transitions
+                        .last()
+                        .map_or(now, |transition| transition.timestamp)
+                });
+            }
+        }
+
+        let retention = Duration::try_hours(HISTORY_RETENTION_HOURS).unwrap_or(Duration::zero());
+        let expired = self
+            .ended
+            .iter()
+            .filter(|(_, ended)| **ended + retention <= now)
+            .map(|(fp_id, _)| fp_id.clone())
+            .collect::<Vec<String>>();
+
+        for fp_id in expired.iter() {
+            self.flights.remove(fp_id);
+            self.ended.remove(fp_id);
+        }
+
+        expired
+    }
+}
+
+/// Checks that a flight plan may move to a state
+pub async fn check_transition(fp_id: &str, to: FlightState) -> Result<(), AckError> {
+    get_lifecycles()
+        .await
+        .lock()
+        .await
+        .check(fp_id, to)
+        .map_err(|e| {
+            common_warn!("flight plan {fp_id}: {e}");
+            AckError::InvalidState
+        })
+}
+
+/// Moves a flight plan to a state and logs the transition
+pub async fn apply_transition(fp_id: &str, to: FlightState) -> Result<(), AckError> {
+    let transition = get_lifecycles()
+        .await
+        .lock()
+        .await
+        .transition(fp_id, to, Utc::now())
+        .map_err(|e| {
+            common_warn!("flight plan {fp_id}: {e}");
+            AckError::InvalidState
+        })?;
+
+    if let Some(transition) = transition {
+        common_info!(
+            "flight plan {fp_id} moved from {:?} to {:?}.",
+            transition.from,
+            transition.to
+        );
+        updates::publish_state(fp_id, transition.to).await;
+    }
+
+    Ok(())
+}
+
+/// Registers a state reported for a flight plan by its aircraft
+///
+/// Only [`FlightState::Airborne`], [`FlightState::Landed`],
+///  [`FlightState::Diverted`] and [`FlightState::Cancelled`] can be
+///  reported, other states are reached through acknowledgements and
+///  clearances. The flight status is updated in svc-storage.
+pub async fn report_flight_state(
+    fp_id: Uuid,
+    to: FlightState,
+    grpc_clients: &GrpcClients,
+) -> Result<FlightHistory, AckError> {
+    let id = fp_id.to_string();
+    let status = match to {
+        FlightState::Cancelled => {
+            cancel_flight(fp_id, grpc_clients).await?;
+            None
+        }
+        FlightState::Airborne => Some(flight_plan::FlightStatus::InFlight),
+        FlightState::Landed => Some(flight_plan::FlightStatus::Finished),
+        FlightState::Diverted => None,
+        _ => {
+            common_warn!("flight plan {fp_id}: {to:?} can not be reported.");
+            return Err(AckError::InvalidState);
+        }
+    };
+
+    if to != FlightState::Cancelled {
+        let _guard = lock_flight(&id).await;
+        let mut data = get_flight_data(fp_id, grpc_clients).await?;
+        check_transition(&id, to).await?;
+
+        if let Some(status) = status {
+            data.flight_status = status as i32;
+            let request = flight_plan::UpdateObject {
+                id: id.clone(),
+                data: Some(data),
+                mask: Some(FieldMask {
+                    paths: vec!["flight_status".to_string()],
+                }),
+            };
+
+            grpc_clients
+                .storage
+                .flight_plan
+                .update(request)
+                .await
+                .map_err(|e| {
+                    common_error!("{}", e);
+                    AckError::Internal
+                })?;
+        }
+
+        apply_transition(&id, to).await?;
+    }
+
+    // Diverted and landed aircraft no longer fly the acknowledged flight plan
+    if matches!(to, FlightState::Landed | FlightState::Diverted) {
+        conformance::get_conformance_monitor()
+            .await
+            .lock()
+            .await
+            .deregister(&id);
+    }
+
+    // Landed aircraft no longer reserve their vertipads
+    if to == FlightState::Landed {
+        clearance::get_clearances()
+            .await
+            .lock()
+            .await
+            .remove_flight(&id);
+    }
+
+    get_flight_history(fp_id, grpc_clients).await
+}
+
+/// Returns the lifecycle state and transition log of a flight plan
+pub async fn get_flight_history(
+    fp_id: Uuid,
+    grpc_clients: &GrpcClients,
+) -> Result<FlightHistory, AckError> {
+    let id = fp_id.to_string();
+    if let Some(history) = get_lifecycles().await.lock().await.history(&id) {
+        return Ok(history);
+    }
+
+    get_flight_data(fp_id, grpc_clients).await?;
+    get_lifecycles()
+        .await
+        .lock()
+        .await
+        .history(&id)
+        .ok_or(AckError::Internal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_allowed() {
+        use FlightState::*;
+
+        assert!(is_allowed(Scheduled, Acknowledged));
+        assert!(is_allowed(Acknowledged, Cleared));
+        assert!(is_allowed(Cleared, Airborne));
+        assert!(is_allowed(Airborne, Landed));
+        assert!(is_allowed(Airborne, Diverted));
+        assert!(is_allowed(Diverted, Landed));
+
+        assert!(!is_allowed(Cancelled, Acknowledged));
+        assert!(!is_allowed(Scheduled, Airborne));
+        assert!(!is_allowed(Airborne, Cancelled));
+        assert!(!is_allowed(Landed, Airborne));
+        assert!(!is_allowed(Landed, Cancelled));
+    }
+
+    #[test]
+    fn test_initial_state() {
+        let mut data = flight_plan::mock::get_data_obj();
+        data.carrier_ack = None;
+        data.flight_status = flight_plan::FlightStatus::Ready as i32;
+        assert_eq!(initial_state(&data), FlightState::Scheduled);
+
+        data.carrier_ack = Some(Utc::now().into());
+        assert_eq!(initial_state(&data), FlightState::Acknowledged);
+
+        data.flight_status = flight_plan::FlightStatus::InFlight as i32;
+        assert_eq!(initial_state(&data), FlightState::Airborne);
+
+        data.flight_status = flight_plan::FlightStatus::Finished as i32;
+        assert_eq!(initial_state(&data), FlightState::Landed);
+
+        data.flight_status = flight_plan::FlightStatus::Cancelled as i32;
+        assert_eq!(initial_state(&data), FlightState::Cancelled);
+    }
+
+    #[test]
+    fn test_lifecycle_transitions() {
+        let now = Utc::now();
+        let mut lifecycles = FlightLifecycles::default();
+        let fp_id = "flight";
+
+        assert_eq!(lifecycles.state(fp_id), None);
+        assert!(lifecycles.check(fp_id, FlightState::Acknowledged).is_ok());
+
+        for state in [
+            FlightState::Acknowledged,
+            FlightState::Cleared,
+            FlightState::Airborne,
+            FlightState::Landed,
+        ] {
+            let transition = lifecycles.transition(fp_id, state, now).unwrap().unwrap();
+            assert_eq!(transition.to, state);
+            assert_eq!(lifecycles.state(fp_id), Some(state));
+        }
+
+        // Repeating the current state has no effect
+        assert_eq!(
+            lifecycles.transition(fp_id, FlightState::Landed, now),
+            Ok(None)
+        );
+
+        let history = lifecycles.history(fp_id).unwrap();
+        assert_eq!(history.state, FlightState::Landed);
+        assert_eq!(history.transitions.len(), 4);
+        assert_eq!(history.transitions[0].from, FlightState::Scheduled);
+        assert_eq!(history.transitions[3].from, FlightState::Airborne);
+
+        let error = lifecycles
+            .transition(fp_id, FlightState::Cancelled, now)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            IllegalTransition {
+                from: FlightState::Landed,
+                to: FlightState::Cancelled
+            }
+        );
+        assert_eq!(lifecycles.history(fp_id).unwrap().transitions.len(), 4);
+    }
+
+    #[test]
+    fn test_lifecycle_seed() {
+        let mut lifecycles = FlightLifecycles::default();
+        lifecycles.seed("flight", FlightState::Cancelled);
+        lifecycles.seed("flight", FlightState::Scheduled);
+        assert_eq!(lifecycles.state("flight"), Some(FlightState::Cancelled));
+
+        // A cancelled flight can not be acknowledged
+        assert!(lifecycles
+            .check("flight", FlightState::Acknowledged)
+            .is_err());
+        assert!(lifecycles.history("flight").unwrap().transitions.is_empty());
+    }
+
+    #[test]
+    fn test_lifecycle_expire() {
+        let now = Utc::now();
+        let retention = Duration::try_hours(HISTORY_RETENTION_HOURS).unwrap();
+        let mut lifecycles = FlightLifecycles::default();
+
+        lifecycles.seed("scheduled", FlightState::Scheduled);
+        for state in [
+            FlightState::Acknowledged,
+            FlightState::Cleared,
+            FlightState::Airborne,
+            FlightState::Landed,
+        ] {
+            lifecycles.transition("landed", state, now).unwrap();
+        }
+
+        assert!(lifecycles.expire(now).is_empty());
+
+        // Flights seeded as cancelled are retained from the first expiry
+        //  that sees them
+        lifecycles.seed("cancelled", FlightState::Cancelled);
+        let later = now + retention;
+        assert_eq!(lifecycles.expire(later), vec!["landed".to_string()]);
+        assert_eq!(lifecycles.state("landed"), None);
+        assert_eq!(lifecycles.state("cancelled"), Some(FlightState::Cancelled));
+
+        let expired = lifecycles.expire(later + retention);
+        assert_eq!(expired, vec!["cancelled".to_string()]);
+        assert_eq!(lifecycles.state("scheduled"), Some(FlightState::Scheduled));
+    }
+
+    #[tokio::test]
+    async fn test_lock_flight() {
+        let wait = std::time::Duration::from_millis(50);
+        let fp_id = Uuid::new_v4().to_string();
+        let guard = lock_flight(&fp_id).await;
+
+        // Other flight plans are not blocked
+        let other = Uuid::new_v4().to_string();
+        assert!(tokio::time::timeout(wait, lock_flight(&other))
+            .await
+            .is_ok());
+
+        assert!(tokio::time::timeout(wait, lock_flight(&fp_id))
+            .await
+            .is_err());
+        drop(guard);
+        assert!(tokio::time::timeout(wait, lock_flight(&fp_id))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_report_flight_state() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        let error = report_flight_state(Uuid::new_v4(), FlightState::Airborne, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, AckError::NotFound);
+
+        let mut data = flight_plan::mock::get_data_obj();
+        data.carrier_ack = None;
+        let id = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+        let fp_id = Uuid::parse_str(&id).unwrap();
+
+        // Not cleared for takeoff yet
+        let error = report_flight_state(fp_id, FlightState::Airborne, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, AckError::InvalidState);
+
+        let error = report_flight_state(fp_id, FlightState::Cleared, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, AckError::InvalidState);
+
+        apply_transition(&id, FlightState::Acknowledged)
+            .await
+            .unwrap();
+        apply_transition(&id, FlightState::Cleared).await.unwrap();
+        let history = report_flight_state(fp_id, FlightState::Airborne, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(history.state, FlightState::Airborne);
+        assert_eq!(history.transitions.len(), 3);
+
+        let data = grpc_clients
+            .storage
+            .flight_plan
+            .get_by_id(Id { id: id.clone() })
+            .await
+            .unwrap()
+            .into_inner()
+            .data
+            .unwrap();
+        assert_eq!(
+            data.flight_status,
+            flight_plan::FlightStatus::InFlight as i32
+        );
+
+        let history = get_flight_history(fp_id, &grpc_clients).await.unwrap();
+        assert_eq!(history.fp_id, id);
+        assert_eq!(history.state, FlightState::Airborne);
+    }
+
+    #[test]
+    fn test_illegal_transition_display() {
+        let error = IllegalTransition {
+            from: FlightState::Cancelled,
+            to: FlightState::Acknowledged,
+        };
+        assert_eq!(
+            error.to_string(),
+            "cannot transition from Cancelled to Acknowledged."
+        );
+    }
+}
@@ -2,12 +2,98 @@
 
 #[macro_use]
 pub mod macros;
+pub mod cache;
+pub mod capacity;
+pub mod clearance;
+pub mod conflict;
+pub mod conformance;
+pub mod contingency;
+pub mod deconfliction;
+pub mod emergency;
+pub mod geo;
+pub mod holding;
+pub mod lifecycle;
+pub mod performance;
+pub mod resolution;
+pub mod restrictions;
+pub mod sequencing;
+pub mod tactical;
+pub mod traffic;
+pub mod updates;
+pub mod vertiports;
+pub mod weather;
+pub mod zones;
 
 use crate::grpc::client::GrpcClients;
-use lib_common::time::Utc;
+use crate::rest::api::{
+    Cargo, DenyReason, FlightDenial, FlightPlan, FlightState, FlightType, Passenger,
+};
+use futures::stream::{self, StreamExt};
+use lib_common::time::{DateTime, Duration, Utc};
 use lib_common::uuid::Uuid;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use svc_storage_client_grpc::prelude::*;
+use tokio::sync::{Mutex, OnceCell};
+
+/// Maximum number of IDs in a single svc-storage search
+pub const STORAGE_SEARCH_BATCH_SIZE: usize = 50;
+
+/// Maximum number of svc-storage searches in flight at once
+pub const STORAGE_SEARCH_CONCURRENCY: usize = 4;
+
+/// Hours after a rejection is registered that it is kept
+pub const DENIAL_RETENTION_HOURS: i64 = 24;
+
+/// Rejections registered by carriers, keyed by flight plan ID
+///
+/// svc-storage has no field for the reason code, rejections are kept in
+///  memory and lost when the service restarts. They are dropped when the
+///  flight plan is acknowledged or cancelled, or after the retention
+///  period.
+static DENIALS: OnceCell<Mutex<HashMap<Uuid, Denial>>> = OnceCell::const_new();
+
+/// Returns the map of flight plan rejections registered with this service
+pub async fn get_denials() -> &'static Mutex<HashMap<Uuid, Denial>> {
+    DENIALS
+        .get_or_init(|| async { Mutex::new(HashMap::new()) })
+        .await
+}
+
+/// A flight plan rejection registered by a carrier
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Denial {
+    /// The rejected flight plan
+    pub fp_id: Uuid,
+
+    /// Reason code provided by the carrier
+    pub reason: DenyReason,
+
+    /// Time at which the rejection was registered
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Forgets the rejections registered more than the retention period
+///  before `now`
+///
+/// Returns the number of forgotten rejections.
+pub async fn expire_denials(now: DateTime<Utc>) -> usize {
+    let retention = Duration::try_hours(DENIAL_RETENTION_HOURS).unwrap_or(Duration::zero());
+    let mut denials = get_denials().await.lock().await;
+    let count = denials.len();
+    denials.retain(|_, denial| denial.timestamp + retention > now);
+    count - denials.len()
+}
+
+impl From<Denial> for FlightDenial {
+    fn from(denial: Denial) -> Self {
+        FlightDenial {
+            fp_id: denial.fp_id.to_string(),
+            reason: denial.reason,
+            timestamp: denial.timestamp,
+        }
+    }
+}
 
 /// Error type for ack_flight
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -20,6 +106,9 @@ pub enum AckError {
 
     /// Flight Plan Not Found
     NotFound,
+
+    /// Flight Plan state does not allow the request
+    InvalidState,
 }
 
 impl fmt::Display for AckError {
@@ -28,14 +117,33 @@ impl fmt::Display for AckError {
             AckError::Internal => write!(f, "Internal Error"),
             AckError::Unavailable => write!(f, "Dependencies not available"),
             AckError::NotFound => write!(f, "Flight Plan Not Found"),
+            AckError::InvalidState => write!(f, "Flight Plan state does not allow the request"),
         }
     }
 }
 
-/// This request might come in over REST, or through GRPC someday
-///  if another microservice has a software-hardware link to a radio antenna
-pub async fn ack_flight(fp_id: Uuid, grpc_clients: &GrpcClients) -> Result<(), AckError> {
-    let mut data = grpc_clients
+/// Error fetching the cargo or passengers of flight plans
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PayloadError {
+    /// Dependencies not available
+    Unavailable,
+}
+
+impl fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayloadError::Unavailable => write!(f, "Dependencies not available"),
+        }
+    }
+}
+
+/// Gets the svc-storage record of a flight plan and starts tracking its
+///  lifecycle if not tracked yet
+pub async fn get_flight_data(
+    fp_id: Uuid,
+    grpc_clients: &GrpcClients,
+) -> Result<flight_plan::Data, AckError> {
+    let data = grpc_clients
         .storage
         .flight_plan
         .get_by_id(Id {
@@ -53,6 +161,413 @@ pub async fn ack_flight(fp_id: Uuid, grpc_clients: &GrpcClients) -> Result<(), A
             AckError::Internal
         })?;
 
+    lifecycle::get_lifecycles()
+        .await
+        .lock()
+        .await
+        .seed(&fp_id.to_string(), lifecycle::initial_state(&data));
+
+    Ok(data)
+}
+
+/// Groups the parcel rows found by each batch search by flight plan ID
+///
+/// Batches whose search failed are skipped, the flight plans in them are
+///  left without cargo.
+fn group_parcel_rows<E: fmt::Display>(
+    results: Vec<Result<Vec<flight_plan_parcel::RowData>, E>>,
+) -> HashMap<String, Vec<flight_plan_parcel::RowData>> {
+    let mut parcels: HashMap<String, Vec<flight_plan_parcel::RowData>> = HashMap::new();
+    for result in results {
+        let rows = match result {
+            Ok(rows) => rows,
+            Err(e) => {
+                common_error!("svc-storage failure, flight plans returned without cargo: {e}");
+                continue;
+            }
+        };
+
+        for row in rows {
+            parcels
+                .entry(row.flight_plan_id.clone())
+                .or_default()
+                .push(row);
+        }
+    }
+
+    parcels
+}
+
+/// Attaches the parcels acquired and delivered by each flight plan
+///
+/// Parcels are searched for batches of flight plans at once, with a
+///  bounded number of searches in flight, and joined to the flight plans
+///  in memory. Parcels are best effort, a failed search only leaves the
+///  flight plans of its batch without cargo.
+pub async fn attach_parcels(plans: &mut [FlightPlan], grpc_clients: &GrpcClients) {
+    let batches = plans
+        .chunks(STORAGE_SEARCH_BATCH_SIZE)
+        .map(|batch| {
+            batch
+                .iter()
+                .map(|plan| plan.flight_uuid.clone())
+                .collect::<Vec<String>>()
+        })
+        .collect::<Vec<_>>();
+
+    let results = stream::iter(batches)
+        .map(|fp_ids| {
+            let filter = AdvancedSearchFilter::search_in("flight_plan_id".to_owned(), fp_ids);
+            grpc_clients.storage.flight_plan_parcel.search(filter)
+        })
+        .buffer_unordered(STORAGE_SEARCH_CONCURRENCY)
+        .map(|result| result.map(|response| response.into_inner().list))
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut parcels = group_parcel_rows(results);
+
+    let parcel_ids = parcels
+        .values()
+        .flatten()
+        .map(|row| row.parcel_id.clone())
+        .collect::<HashSet<String>>();
+    let records = get_parcel_records(parcel_ids.into_iter().collect(), grpc_clients).await;
+
+    for plan in plans.iter_mut() {
+        let Some(rows) = parcels.remove(&plan.flight_uuid) else {
+            continue;
+        };
+
+        for parcel in rows {
+            let cargo = Cargo {
+                weight_g: records.get(&parcel.parcel_id).map(|data| data.weight_grams),
+                // svc-storage does not record dimensions or dangerous goods yet
+                dimensions: None,
+                dangerous_goods: None,
+                id: parcel.parcel_id,
+            };
+
+            if parcel.acquire {
+                plan.acquire.push(cargo.clone())
+            }
+
+            if parcel.deliver {
+                plan.deliver.push(cargo)
+            }
+        }
+    }
+}
+
+/// Gets the svc-storage records of parcels, by parcel ID
+///
+/// Records are best effort, parcels whose records could not be fetched
+///  are left out.
+async fn get_parcel_records(
+    parcel_ids: Vec<String>,
+    grpc_clients: &GrpcClients,
+) -> HashMap<String, parcel::Data> {
+    let batches = parcel_ids
+        .chunks(STORAGE_SEARCH_BATCH_SIZE)
+        .map(<[String]>::to_vec)
+        .collect::<Vec<_>>();
+
+    let results = stream::iter(batches)
+        .map(|parcel_ids| {
+            let filter = AdvancedSearchFilter::search_in("parcel_id".to_owned(), parcel_ids);
+            grpc_clients.storage.parcel.search(filter)
+        })
+        .buffer_unordered(STORAGE_SEARCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut records = HashMap::new();
+    for result in results {
+        match result {
+            Ok(response) => records.extend(
+                response
+                    .into_inner()
+                    .list
+                    .into_iter()
+                    .filter_map(|object| Some((object.id, object.data?))),
+            ),
+            Err(e) => common_warn!("could not get parcel records: {e}"),
+        }
+    }
+
+    records
+}
+
+/// Attaches the boarding list of passengers to each flight plan
+///
+/// Itineraries are searched for batches of flight plans at once, like
+///  parcels. Passengers of cancelled itineraries do not board. No
+///  passengers are attached if any search fails.
+pub async fn attach_passengers(
+    plans: &mut [FlightPlan],
+    grpc_clients: &GrpcClients,
+) -> Result<(), PayloadError> {
+    let batches = plans
+        .chunks(STORAGE_SEARCH_BATCH_SIZE)
+        .map(|batch| {
+            batch
+                .iter()
+                .map(|plan| plan.flight_uuid.clone())
+                .collect::<Vec<String>>()
+        })
+        .collect::<Vec<_>>();
+
+    let results = stream::iter(batches)
+        .map(|fp_ids| {
+            let filter = AdvancedSearchFilter::search_in("flight_plan_id".to_owned(), fp_ids);
+            grpc_clients.storage.itinerary_flight_plan.search(filter)
+        })
+        .buffer_unordered(STORAGE_SEARCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut links: HashMap<String, Vec<String>> = HashMap::new();
+    for result in results {
+        let rows = result
+            .map_err(|e| {
+                common_error!("svc-storage failure: {e}");
+                PayloadError::Unavailable
+            })?
+            .into_inner()
+            .list;
+
+        for row in rows {
+            links
+                .entry(row.flight_plan_id)
+                .or_default()
+                .push(row.itinerary_id);
+        }
+    }
+
+    let itinerary_ids = links
+        .values()
+        .flatten()
+        .cloned()
+        .collect::<HashSet<String>>();
+    let records = get_itinerary_records(itinerary_ids.into_iter().collect(), grpc_clients).await?;
+
+    for plan in plans.iter_mut() {
+        let Some(itinerary_ids) = links.remove(&plan.flight_uuid) else {
+            continue;
+        };
+
+        for itinerary_id in itinerary_ids {
+            let Some(data) = records.get(&itinerary_id) else {
+                common_warn!(
+                    "no record of itinerary {itinerary_id} on flight plan {}.",
+                    plan.flight_uuid
+                );
+                continue;
+            };
+
+            if data.status != itinerary::ItineraryStatus::Active as i32 {
+                continue;
+            }
+
+            plan.passengers.push(Passenger {
+                id: data.user_id.clone(),
+                itinerary_id,
+                // svc-storage does not record seats or baggage yet
+                seat: None,
+                baggage_weight_g: None,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets the svc-storage records of itineraries, by itinerary ID
+///
+/// Unlike parcel records these are required, as the passenger of an
+///  itinerary is only known from its record.
+async fn get_itinerary_records(
+    itinerary_ids: Vec<String>,
+    grpc_clients: &GrpcClients,
+) -> Result<HashMap<String, itinerary::Data>, PayloadError> {
+    let batches = itinerary_ids
+        .chunks(STORAGE_SEARCH_BATCH_SIZE)
+        .map(<[String]>::to_vec)
+        .collect::<Vec<_>>();
+
+    let results = stream::iter(batches)
+        .map(|itinerary_ids| {
+            let filter = AdvancedSearchFilter::search_in("itinerary_id".to_owned(), itinerary_ids);
+            grpc_clients.storage.itinerary.search(filter)
+        })
+        .buffer_unordered(STORAGE_SEARCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut records = HashMap::new();
+    for result in results {
+        let objects = result
+            .map_err(|e| {
+                common_error!("svc-storage failure: {e}");
+                PayloadError::Unavailable
+            })?
+            .into_inner()
+            .list;
+
+        records.extend(
+            objects
+                .into_iter()
+                .filter_map(|object| Some((object.id, object.data?))),
+        );
+    }
+
+    Ok(records)
+}
+
+/// Attaches the cargo and passengers carried by each flight plan, and
+///  sets the flight type and payload weight from them
+///
+/// Cargo and passengers are best effort, flight plans whose boarding list
+///  could not be fetched are marked as such rather than failing.
+pub async fn attach_payload(plans: &mut [FlightPlan], grpc_clients: &GrpcClients) {
+    attach_parcels(plans, grpc_clients).await;
+    if let Err(e) = attach_passengers(plans, grpc_clients).await {
+        common_warn!("could not get the boarding lists of flight plans: {e}");
+        plans
+            .iter_mut()
+            .for_each(|plan| plan.passengers_unavailable = true);
+    }
+
+    for plan in plans.iter_mut() {
+        plan.flight_type = flight_type(plan);
+        plan.payload_weight_g = payload_weight_g(&plan.acquire, &plan.deliver)
+            .zip(baggage_weight_g(&plan.passengers))
+            .map(|(cargo, baggage)| cargo + baggage)
+            .filter(|_| !plan.passengers_unavailable);
+    }
+}
+
+/// Kind of payload carried on a flight plan
+pub fn flight_type(plan: &FlightPlan) -> FlightType {
+    let cargo = !plan.acquire.is_empty() || !plan.deliver.is_empty();
+    match (cargo, !plan.passengers.is_empty()) {
+        (false, false) => FlightType::Ferry,
+        (true, false) => FlightType::Cargo,
+        (false, true) => FlightType::Rideshare,
+        (true, true) => FlightType::Mixed,
+    }
+}
+
+/// Total weight in grams of the baggage of passengers on a flight
+///
+/// None if the baggage weight of any passenger is unknown.
+pub fn baggage_weight_g(passengers: &[Passenger]) -> Option<u64> {
+    passengers
+        .iter()
+        .map(|passenger| passenger.baggage_weight_g.map(u64::from))
+        .sum()
+}
+
+/// Total weight in grams of the cargo acquired or delivered on a flight
+///
+/// Cargo both acquired and delivered is counted once. None if the
+///  weight of any cargo is unknown.
+pub fn payload_weight_g(acquire: &[Cargo], deliver: &[Cargo]) -> Option<u64> {
+    let mut counted = HashSet::new();
+    acquire
+        .iter()
+        .chain(deliver.iter())
+        .filter(|cargo| counted.insert(cargo.id.as_str()))
+        .map(|cargo| cargo.weight_g.map(u64::from))
+        .sum()
+}
+
+/// Attaches the advisories, contingency procedures, alternates,
+///  restriction notices, clearances, holding patterns, weather alerts and
+///  lifecycle states known to this service to flight plans
+///
+/// Advisories are issued by the flight plan cache task, flight plans are
+///  annotated without searching svc-storage.
+pub async fn annotate_flight_plans(
+    plans: &mut [FlightPlan],
+    alternates: &vertiports::AlternatePolicy,
+) {
+    {
+        let advisories = resolution::get_advisories().await.lock().await;
+        for plan in plans.iter_mut() {
+            plan.advisories = advisories.get(&plan.flight_uuid);
+        }
+    }
+
+    {
+        let emergencies = emergency::get_emergencies().await.lock().await;
+        let now = Utc::now();
+        for plan in plans.iter_mut() {
+            plan.advisories
+                .extend(emergencies.advisories(&plan.flight_uuid, now));
+        }
+    }
+
+    {
+        let contingencies = contingency::get_contingencies().await.lock().await;
+        for plan in plans.iter_mut() {
+            plan.contingency = contingencies.get(plan);
+        }
+    }
+
+    vertiports::attach_alternates(plans, alternates).await;
+
+    {
+        let restrictions = restrictions::get_restrictions().await.lock().await;
+        for plan in plans.iter_mut() {
+            plan.restrictions = restrictions.notices(&plan.flight_uuid);
+        }
+    }
+
+    {
+        let clearances = clearance::get_clearances().await.lock().await;
+        for plan in plans.iter_mut() {
+            plan.clearances = clearances.get(&plan.flight_uuid);
+        }
+    }
+
+    {
+        let holding = holding::get_holding().await.lock().await;
+        for plan in plans.iter_mut() {
+            plan.holding = holding.get(&plan.flight_uuid).cloned();
+        }
+    }
+
+    {
+        let weather = weather::get_weather().await.lock().await;
+        for plan in plans.iter_mut() {
+            plan.weather_alerts = weather.alerts(&plan.flight_uuid);
+        }
+    }
+
+    {
+        let denials = get_denials().await.lock().await;
+        for plan in plans.iter_mut() {
+            plan.denial = Uuid::parse_str(&plan.flight_uuid)
+                .ok()
+                .and_then(|fp_id| denials.get(&fp_id).copied())
+                .map(FlightDenial::from);
+        }
+    }
+
+    let lifecycles = lifecycle::get_lifecycles().await.lock().await;
+    for plan in plans.iter_mut() {
+        plan.state = lifecycles.state(&plan.flight_uuid).unwrap_or(plan.state);
+    }
+}
+
+/// This request might come in over REST, or through GRPC someday
+///  if another microservice has a software-hardware link to a radio antenna
+pub async fn ack_flight(fp_id: Uuid, grpc_clients: &GrpcClients) -> Result<(), AckError> {
+    let _guard = lifecycle::lock_flight(&fp_id.to_string()).await;
+    let mut data = get_flight_data(fp_id, grpc_clients).await?;
+    lifecycle::check_transition(&fp_id.to_string(), FlightState::Acknowledged).await?;
+
     data.carrier_ack = Some(Utc::now().into());
 
     //
@@ -60,7 +575,7 @@ pub async fn ack_flight(fp_id: Uuid, grpc_clients: &GrpcClients) -> Result<(), A
     //
     let request = flight_plan::UpdateObject {
         id: fp_id.to_string(),
-        data: Some(data),
+        data: Some(data.clone()),
         mask: Some(FieldMask {
             paths: vec!["carrier_ack".to_string()],
         }),
@@ -76,6 +591,137 @@ pub async fn ack_flight(fp_id: Uuid, grpc_clients: &GrpcClients) -> Result<(), A
             AckError::Internal
         })?;
 
+    lifecycle::apply_transition(&fp_id.to_string(), FlightState::Acknowledged).await?;
+    cache::invalidate(&fp_id.to_string()).await;
+    get_denials().await.lock().await.remove(&fp_id);
+
+    //
+    // Monitor that the aircraft flies the acknowledged flight plan
+    //
+    let object = flight_plan::Object {
+        id: fp_id.to_string(),
+        data: Some(data),
+    };
+
+    match FlightPlan::try_from(object) {
+        Ok(plan) => {
+            if let Err(e) = conformance::get_conformance_monitor()
+                .await
+                .lock()
+                .await
+                .register(&plan)
+            {
+                common_warn!("could not monitor conformance of flight plan {fp_id}: {e}");
+            }
+        }
+        Err(e) => common_warn!("could not monitor conformance of flight plan {fp_id}: {e}"),
+    }
+
+    Ok(())
+}
+
+/// Registers a carrier's rejection of a flight plan
+///
+/// The carrier acknowledgement is cleared and the flight plan is returned
+///  to draft status so that it can be re-planned.
+pub async fn deny_flight(
+    fp_id: Uuid,
+    reason: DenyReason,
+    grpc_clients: &GrpcClients,
+) -> Result<Denial, AckError> {
+    let _guard = lifecycle::lock_flight(&fp_id.to_string()).await;
+    let mut data = get_flight_data(fp_id, grpc_clients).await?;
+    lifecycle::check_transition(&fp_id.to_string(), FlightState::Scheduled).await?;
+
+    data.carrier_ack = None;
+    data.flight_status = flight_plan::FlightStatus::Draft as i32;
+
+    //
+    // Update the flight plan record to show that it needs to be re-planned
+    //
+    let request = flight_plan::UpdateObject {
+        id: fp_id.to_string(),
+        data: Some(data),
+        mask: Some(FieldMask {
+            paths: vec!["carrier_ack".to_string(), "flight_status".to_string()],
+        }),
+    };
+
+    grpc_clients
+        .storage
+        .flight_plan
+        .update(request)
+        .await
+        .map_err(|e| {
+            common_error!("{}", e);
+            AckError::Internal
+        })?;
+
+    lifecycle::apply_transition(&fp_id.to_string(), FlightState::Scheduled).await?;
+    cache::invalidate(&fp_id.to_string()).await;
+
+    let denial = Denial {
+        fp_id,
+        reason,
+        timestamp: Utc::now(),
+    };
+
+    common_info!("flight plan {} denied by carrier: {:?}.", fp_id, reason);
+    conformance::get_conformance_monitor()
+        .await
+        .lock()
+        .await
+        .deregister(&fp_id.to_string());
+    get_denials().await.lock().await.insert(fp_id, denial);
+
+    Ok(denial)
+}
+
+/// Cancels a flight plan in svc-storage and stops monitoring its conformance
+pub async fn cancel_flight(fp_id: Uuid, grpc_clients: &GrpcClients) -> Result<(), AckError> {
+    let _guard = lifecycle::lock_flight(&fp_id.to_string()).await;
+    let mut data = get_flight_data(fp_id, grpc_clients).await?;
+    lifecycle::check_transition(&fp_id.to_string(), FlightState::Cancelled).await?;
+
+    data.flight_status = flight_plan::FlightStatus::Cancelled as i32;
+
+    let request = flight_plan::UpdateObject {
+        id: fp_id.to_string(),
+        data: Some(data),
+        mask: Some(FieldMask {
+            paths: vec!["flight_status".to_string()],
+        }),
+    };
+
+    grpc_clients
+        .storage
+        .flight_plan
+        .update(request)
+        .await
+        .map_err(|e| {
+            common_error!("{}", e);
+            AckError::Internal
+        })?;
+
+    lifecycle::apply_transition(&fp_id.to_string(), FlightState::Cancelled).await?;
+    cache::invalidate(&fp_id.to_string()).await;
+
+    get_denials().await.lock().await.remove(&fp_id);
+
+    // Cancelled flights no longer reserve their vertipads
+    clearance::get_clearances()
+        .await
+        .lock()
+        .await
+        .remove_flight(&fp_id.to_string());
+
+    common_info!("flight plan {} cancelled.", fp_id);
+    conformance::get_conformance_monitor()
+        .await
+        .lock()
+        .await
+        .deregister(&fp_id.to_string());
+
     Ok(())
 }
 
@@ -105,5 +751,368 @@ mod tests {
 
         let fp_id = Uuid::parse_str(&fp_id).unwrap();
         let _ = ack_flight(fp_id, &grpc_clients).await.unwrap();
+
+        // Acknowledging again has no effect
+        let _ = ack_flight(fp_id, &grpc_clients).await.unwrap();
+        let history = lifecycle::get_lifecycles()
+            .await
+            .lock()
+            .await
+            .history(&fp_id.to_string())
+            .unwrap();
+        assert_eq!(history.state, FlightState::Acknowledged);
+        assert_eq!(history.transitions.len(), 1);
+
+        // A cancelled flight plan can not be acknowledged
+        cancel_flight(fp_id, &grpc_clients).await.unwrap();
+        let error = ack_flight(fp_id, &grpc_clients).await.unwrap_err();
+        assert_eq!(error, AckError::InvalidState);
+    }
+
+    #[tokio::test]
+    async fn test_deny_flight() {
+        let fp_id = Uuid::new_v4();
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let error = deny_flight(fp_id, DenyReason::Weather, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, AckError::NotFound);
+        assert!(!get_denials().await.lock().await.contains_key(&fp_id));
+
+        let data = flight_plan::mock::get_data_obj();
+        let fp_id = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        let fp_id = Uuid::parse_str(&fp_id).unwrap();
+        let denial = deny_flight(fp_id, DenyReason::Weather, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(denial.fp_id, fp_id);
+        assert_eq!(denial.reason, DenyReason::Weather);
+
+        assert_eq!(get_denials().await.lock().await.get(&fp_id), Some(&denial));
+
+        // The rejection is cleared once the flight plan is acknowledged
+        ack_flight(fp_id, &grpc_clients).await.unwrap();
+        assert!(!get_denials().await.lock().await.contains_key(&fp_id));
+
+        // or cancelled
+        deny_flight(fp_id, DenyReason::Weather, &grpc_clients)
+            .await
+            .unwrap();
+        cancel_flight(fp_id, &grpc_clients).await.unwrap();
+        assert!(!get_denials().await.lock().await.contains_key(&fp_id));
+    }
+
+    #[tokio::test]
+    async fn test_expire_denials() {
+        let now = Utc::now();
+        let retention = Duration::try_hours(DENIAL_RETENTION_HOURS).unwrap();
+        let fp_id = Uuid::new_v4();
+        get_denials().await.lock().await.insert(
+            fp_id,
+            Denial {
+                fp_id,
+                reason: DenyReason::Maintenance,
+                timestamp: now - retention,
+            },
+        );
+
+        assert!(expire_denials(now).await >= 1);
+        assert!(!get_denials().await.lock().await.contains_key(&fp_id));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_flight() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let error = cancel_flight(Uuid::new_v4(), &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, AckError::NotFound);
+
+        let data = flight_plan::mock::get_data_obj();
+        let fp_id = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        cancel_flight(Uuid::parse_str(&fp_id).unwrap(), &grpc_clients)
+            .await
+            .unwrap();
+
+        let data = grpc_clients
+            .storage
+            .flight_plan
+            .get_by_id(Id { id: fp_id })
+            .await
+            .unwrap()
+            .into_inner()
+            .data
+            .unwrap();
+        assert_eq!(
+            data.flight_status,
+            flight_plan::FlightStatus::Cancelled as i32
+        );
+    }
+
+    #[tokio::test]
+    async fn test_attach_parcels() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let now = Utc::now();
+
+        // More flight plans than fit in a single search
+        let mut plans = (0..STORAGE_SEARCH_BATCH_SIZE * 2 + 1)
+            .map(|_| {
+                crate::test_util::mock_flight_plan(
+                    &[(-40.0, 144.0, 100.0), (-40.0, 144.02, 100.0)],
+                    now,
+                    now + lib_common::time::Duration::try_minutes(10).unwrap(),
+                )
+            })
+            .collect::<Vec<FlightPlan>>();
+
+        // The parcel of the last flight plan has no svc-storage record
+        for (index, acquire, deliver, weight_grams) in [
+            (0, true, false, Some(1500)),
+            (0, false, true, Some(2500)),
+            (plans.len() - 1, true, true, None),
+        ] {
+            let parcel_id = match weight_grams {
+                Some(weight_grams) => {
+                    let mut data = parcel::mock::get_data_obj();
+                    data.weight_grams = weight_grams;
+                    grpc_clients
+                        .storage
+                        .parcel
+                        .insert(data)
+                        .await
+                        .unwrap()
+                        .into_inner()
+                        .object
+                        .unwrap()
+                        .id
+                }
+                None => Uuid::new_v4().to_string(),
+            };
+
+            grpc_clients
+                .storage
+                .flight_plan_parcel
+                .insert(flight_plan_parcel::RowData {
+                    flight_plan_id: plans[index].flight_uuid.clone(),
+                    parcel_id,
+                    acquire,
+                    deliver,
+                })
+                .await
+                .unwrap();
+        }
+
+        attach_parcels(&mut plans, &grpc_clients).await;
+        assert_eq!(plans[0].acquire.len(), 1);
+        assert_eq!(plans[0].deliver.len(), 1);
+        assert_ne!(plans[0].acquire[0].id, plans[0].deliver[0].id);
+        assert_eq!(plans[0].acquire[0].weight_g, Some(1500));
+        assert_eq!(plans[0].deliver[0].weight_g, Some(2500));
+        assert_eq!(
+            payload_weight_g(&plans[0].acquire, &plans[0].deliver),
+            Some(4000)
+        );
+
+        let last = plans.last().unwrap();
+        assert_eq!(last.acquire.len(), 1);
+        assert_eq!(last.acquire[0].id, last.deliver[0].id);
+        assert_eq!(last.acquire[0].weight_g, None);
+        assert_eq!(payload_weight_g(&last.acquire, &last.deliver), None);
+        assert!(plans[1..plans.len() - 1]
+            .iter()
+            .all(|plan| plan.acquire.is_empty() && plan.deliver.is_empty()));
+
+        attach_parcels(&mut [], &grpc_clients).await;
+    }
+
+    #[test]
+    fn test_group_parcel_rows() {
+        let row = |flight_plan_id: &str| flight_plan_parcel::RowData {
+            flight_plan_id: flight_plan_id.to_string(),
+            parcel_id: Uuid::new_v4().to_string(),
+            acquire: true,
+            deliver: false,
+        };
+
+        // The failed batch does not drop the rows of the other batches
+        let parcels = group_parcel_rows(vec![
+            Ok(vec![row("a"), row("b"), row("a")]),
+            Err("svc-storage unavailable"),
+            Ok(vec![row("c")]),
+        ]);
+        assert_eq!(parcels.len(), 3);
+        assert_eq!(parcels["a"].len(), 2);
+        assert_eq!(parcels["b"].len(), 1);
+        assert_eq!(parcels["c"].len(), 1);
+
+        assert!(group_parcel_rows::<&str>(vec![Err("svc-storage unavailable")]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_attach_payload() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let now = Utc::now();
+
+        // Rideshare, mixed and ferry flight plans
+        let mut plans = (0..3)
+            .map(|_| {
+                crate::test_util::mock_flight_plan(
+                    &[(-41.0, 142.0, 100.0), (-41.0, 142.02, 100.0)],
+                    now,
+                    now + lib_common::time::Duration::try_minutes(10).unwrap(),
+                )
+            })
+            .collect::<Vec<FlightPlan>>();
+
+        let user_ids = (0..3)
+            .map(|_| Uuid::new_v4().to_string())
+            .collect::<Vec<_>>();
+        for (index, user_id, status) in [
+            (0, &user_ids[0], itinerary::ItineraryStatus::Active),
+            (0, &user_ids[1], itinerary::ItineraryStatus::Cancelled),
+            (1, &user_ids[2], itinerary::ItineraryStatus::Active),
+        ] {
+            let itinerary_id = grpc_clients
+                .storage
+                .itinerary
+                .insert(itinerary::Data {
+                    user_id: user_id.clone(),
+                    status: status as i32,
+                })
+                .await
+                .unwrap()
+                .into_inner()
+                .object
+                .unwrap()
+                .id;
+
+            grpc_clients
+                .storage
+                .itinerary_flight_plan
+                .insert(itinerary_flight_plan::RowData {
+                    itinerary_id,
+                    flight_plan_id: plans[index].flight_uuid.clone(),
+                })
+                .await
+                .unwrap();
+        }
+
+        let mut data = parcel::mock::get_data_obj();
+        data.weight_grams = 1200;
+        let parcel_id = grpc_clients
+            .storage
+            .parcel
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        grpc_clients
+            .storage
+            .flight_plan_parcel
+            .insert(flight_plan_parcel::RowData {
+                flight_plan_id: plans[1].flight_uuid.clone(),
+                parcel_id,
+                acquire: true,
+                deliver: true,
+            })
+            .await
+            .unwrap();
+
+        attach_payload(&mut plans, &grpc_clients).await;
+        assert!(plans.iter().all(|plan| !plan.passengers_unavailable));
+
+        // Passengers of cancelled itineraries do not board
+        assert_eq!(plans[0].flight_type, FlightType::Rideshare);
+        assert_eq!(plans[0].passengers.len(), 1);
+        assert_eq!(plans[0].passengers[0].id, user_ids[0]);
+        assert_eq!(plans[0].passengers[0].seat, None);
+
+        // svc-storage does not record baggage, so the total is unknown
+        assert_eq!(plans[0].payload_weight_g, None);
+
+        assert_eq!(plans[1].flight_type, FlightType::Mixed);
+        assert_eq!(plans[1].passengers[0].id, user_ids[2]);
+        assert_eq!(plans[1].payload_weight_g, None);
+
+        assert_eq!(plans[2].flight_type, FlightType::Ferry);
+        assert!(plans[2].passengers.is_empty());
+        assert_eq!(plans[2].payload_weight_g, Some(0));
+    }
+
+    #[test]
+    fn test_payload_error_display() {
+        assert_eq!(
+            PayloadError::Unavailable.to_string(),
+            "Dependencies not available"
+        );
+    }
+
+    #[test]
+    fn test_baggage_weight_g() {
+        let passenger = |baggage_weight_g: Option<u32>| Passenger {
+            id: Uuid::new_v4().to_string(),
+            itinerary_id: Uuid::new_v4().to_string(),
+            seat: None,
+            baggage_weight_g,
+        };
+
+        assert_eq!(baggage_weight_g(&[]), Some(0));
+        let mut passengers = vec![passenger(Some(8000)), passenger(Some(u32::MAX))];
+        assert_eq!(baggage_weight_g(&passengers), Some(8000 + u32::MAX as u64));
+
+        passengers.push(passenger(None));
+        assert_eq!(baggage_weight_g(&passengers), None);
+    }
+
+    #[test]
+    fn test_payload_weight_g() {
+        let cargo = |id: &str, weight_g: Option<u32>| Cargo {
+            id: id.to_string(),
+            weight_g,
+            dimensions: None,
+            dangerous_goods: None,
+        };
+
+        assert_eq!(payload_weight_g(&[], &[]), Some(0));
+
+        // Cargo carried for the whole flight is counted once
+        let acquire = [cargo("a", Some(1000))];
+        let deliver = [cargo("a", Some(1000)), cargo("c", Some(u32::MAX))];
+        assert_eq!(
+            payload_weight_g(&acquire, &deliver),
+            Some(1000 + u32::MAX as u64)
+        );
+
+        // The total is unknown if any cargo weight is unknown
+        let acquire = [cargo("a", Some(1000)), cargo("b", None)];
+        assert_eq!(payload_weight_g(&acquire, &deliver), None);
     }
 }
@@ -0,0 +1,689 @@
+//! Flight plan validation against aircraft performance envelopes
+//!
+//! Each aircraft type has a performance profile, loaded from a JSON file at
+//!  startup. Flight plans from svc-storage are timed with the climb and
+//!  descent rates of the aircraft type over their timeslots and each leg
+//!  of the path is checked against the profile of the aircraft type before
+//!  the flight plans are returned to aircraft.
+
+use super::conflict::Trajectory;
+use super::geo;
+use super::{STORAGE_SEARCH_BATCH_SIZE, STORAGE_SEARCH_CONCURRENCY};
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::{FlightPlan, PerformanceViolation, PerformanceViolationType};
+use futures::stream::{self, StreamExt};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use svc_storage_client_grpc::prelude::*;
+use tokio::sync::{OnceCell, RwLock};
+
+/// Altitude change in meters below which a leg is flown level, at cruise
+///  speed
+pub const LEVEL_TOLERANCE_METERS: f64 = 1.0;
+
+/// Horizontal distance in meters below which a leg is flown vertically, at
+///  the climb or descent rate
+pub const VERTICAL_TOLERANCE_METERS: f64 = 1.0;
+
+/// Performance profiles of aircraft types, keyed by vehicle model ID
+static PROFILES: OnceCell<RwLock<ProfileRegistry>> = OnceCell::const_new();
+
+/// Returns the performance profiles shared by the REST and gRPC servers
+pub async fn get_profiles() -> &'static RwLock<ProfileRegistry> {
+    PROFILES
+        .get_or_init(|| async { RwLock::new(ProfileRegistry::default()) })
+        .await
+}
+
+/// Errors loading a performance profile
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PerformanceError {
+    /// Profile limits are not positive, or the cruise speed range is empty
+    Profile,
+
+    /// Profiles file could not be read
+    File,
+
+    /// Profiles file is not valid JSON
+    Json,
+}
+
+impl Display for PerformanceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PerformanceError::Profile => write!(f, "invalid performance profile limits."),
+            PerformanceError::File => write!(f, "could not read performance profiles file."),
+            PerformanceError::Json => write!(f, "invalid performance profiles file."),
+        }
+    }
+}
+
+/// Performance envelope of an aircraft type
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PerformanceProfile {
+    /// svc-storage vehicle model ID of the aircraft type
+    pub vehicle_model_id: String,
+
+    /// Maximum climb rate in meters per second
+    pub max_climb_rate_mps: f64,
+
+    /// Maximum descent rate in meters per second
+    pub max_descent_rate_mps: f64,
+
+    /// Minimum ground speed in meters per second in level flight
+    pub min_cruise_speed_mps: f64,
+
+    /// Maximum ground speed in meters per second
+    pub max_cruise_speed_mps: f64,
+
+    /// Maximum altitude in meters
+    pub service_ceiling_meters: f64,
+
+    /// Maximum flight duration in seconds
+    pub endurance_seconds: u32,
+}
+
+/// Checks that a profile can be used to validate flight plans
+pub fn validate(profile: &PerformanceProfile) -> Result<(), PerformanceError> {
+    let positive = [
+        profile.max_climb_rate_mps,
+        profile.max_descent_rate_mps,
+        profile.max_cruise_speed_mps,
+        profile.service_ceiling_meters,
+    ]
+    .iter()
+    .all(|limit| limit.is_finite() && *limit > 0.0);
+
+    let cruise = profile.min_cruise_speed_mps.is_finite()
+        && profile.min_cruise_speed_mps >= 0.0
+        && profile.min_cruise_speed_mps <= profile.max_cruise_speed_mps;
+
+    if !positive || !cruise || profile.endurance_seconds == 0 {
+        return Err(PerformanceError::Profile);
+    }
+
+    Ok(())
+}
+
+/// Rate at which a distance is covered, unbounded if no time is available
+fn rate(distance: f64, seconds: f64) -> f64 {
+    if seconds > 0.0 {
+        distance / seconds
+    } else if distance > 0.0 {
+        f64::MAX
+    } else {
+        0.0
+    }
+}
+
+/// Seconds an aircraft type spends on each leg of a trajectory
+///
+/// Vertical legs, such as takeoffs and landings, are flown at the maximum
+///  climb or descent rate of the profile. The rest of the time between
+///  departure and arrival is spread over the other legs by horizontal
+///  distance. If the vertical legs alone take longer than the flight, they
+///  are compressed to fit it and the other legs are left no time.
+fn leg_seconds(trajectory: &Trajectory, profile: &PerformanceProfile) -> Vec<f64> {
+    let total = (trajectory.end() - trajectory.start()).num_milliseconds() as f64 / 1000.0;
+    let legs = trajectory
+        .waypoints
+        .windows(2)
+        .map(|leg| {
+            let climb = leg[1].point.altitude_meters - leg[0].point.altitude_meters;
+            let distance = geo::horizontal_distance_meters(&leg[0].point, &leg[1].point);
+            (climb, distance)
+        })
+        .collect::<Vec<(f64, f64)>>();
+
+    let vertical = |(climb, distance): (f64, f64)| {
+        if distance >= VERTICAL_TOLERANCE_METERS {
+            None
+        } else if climb > 0.0 {
+            Some(climb / profile.max_climb_rate_mps)
+        } else {
+            Some(-climb / profile.max_descent_rate_mps)
+        }
+    };
+
+    let vertical_seconds = legs.iter().filter_map(|leg| vertical(*leg)).sum::<f64>();
+    let scale = if vertical_seconds > total {
+        total / vertical_seconds
+    } else {
+        1.0
+    };
+
+    let remaining = (total - vertical_seconds).max(0.0);
+    let horizontal_meters = legs
+        .iter()
+        .filter(|leg| vertical(**leg).is_none())
+        .map(|(_, distance)| distance)
+        .sum::<f64>();
+
+    legs.iter()
+        .map(|leg| match vertical(*leg) {
+            Some(seconds) => seconds * scale,
+            None if horizontal_meters > 0.0 => remaining * leg.1 / horizontal_meters,
+            None => 0.0,
+        })
+        .collect()
+}
+
+/// Flight time in seconds an aircraft type has left at the end of a
+///  trajectory, negative if the trajectory exceeds its endurance
+pub fn endurance_reserve_seconds(trajectory: &Trajectory, profile: &PerformanceProfile) -> i64 {
+    i64::from(profile.endurance_seconds) - (trajectory.end() - trajectory.start()).num_seconds()
+}
+
+/// Finds the legs of a trajectory outside of the performance envelope of
+///  an aircraft type
+///
+/// Legs are timed with the climb and descent rates of the aircraft type,
+///  see [`leg_seconds`].
+pub fn check(trajectory: &Trajectory, profile: &PerformanceProfile) -> Vec<PerformanceViolation> {
+    let mut violations = vec![];
+    let mut flag = |violation_type, segment_index, value, limit| {
+        violations.push(PerformanceViolation {
+            violation_type,
+            segment_index,
+            value,
+            limit,
+        })
+    };
+
+    let legs = trajectory
+        .waypoints
+        .windows(2)
+        .zip(leg_seconds(trajectory, profile));
+
+    for (index, (leg, seconds)) in legs.enumerate() {
+        let (a, b) = (&leg[0], &leg[1]);
+        let climb = b.point.altitude_meters - a.point.altitude_meters;
+        let speed = rate(geo::horizontal_distance_meters(&a.point, &b.point), seconds);
+
+        if climb > 0.0 && rate(climb, seconds) > profile.max_climb_rate_mps {
+            flag(
+                PerformanceViolationType::ClimbRate,
+                Some(index),
+                rate(climb, seconds),
+                profile.max_climb_rate_mps,
+            );
+        }
+
+        if climb < 0.0 && rate(-climb, seconds) > profile.max_descent_rate_mps {
+            flag(
+                PerformanceViolationType::DescentRate,
+                Some(index),
+                rate(-climb, seconds),
+                profile.max_descent_rate_mps,
+            );
+        }
+
+        // Vertical legs are not flown at cruise speed
+        let level = climb.abs() < LEVEL_TOLERANCE_METERS;
+        if level && speed > 0.0 && speed < profile.min_cruise_speed_mps {
+            flag(
+                PerformanceViolationType::BelowCruiseSpeed,
+                Some(index),
+                speed,
+                profile.min_cruise_speed_mps,
+            );
+        }
+
+        if speed > profile.max_cruise_speed_mps {
+            flag(
+                PerformanceViolationType::AboveCruiseSpeed,
+                Some(index),
+                speed,
+                profile.max_cruise_speed_mps,
+            );
+        }
+
+        let altitude = a.point.altitude_meters.max(b.point.altitude_meters);
+        if altitude > profile.service_ceiling_meters {
+            flag(
+                PerformanceViolationType::ServiceCeiling,
+                Some(index),
+                altitude,
+                profile.service_ceiling_meters,
+            );
+        }
+    }
+
+    let duration = (trajectory.end() - trajectory.start()).num_seconds();
+    if duration > i64::from(profile.endurance_seconds) {
+        flag(
+            PerformanceViolationType::Endurance,
+            None,
+            duration as f64,
+            f64::from(profile.endurance_seconds),
+        );
+    }
+
+    violations
+}
+
+/// Replaces the profiles in the shared registry with those in a JSON file
+///
+/// Returns the number of profiles loaded.
+pub async fn load_profiles_file(path: &str) -> Result<usize, PerformanceError> {
+    let data = std::fs::read_to_string(path).map_err(|e| {
+        common_warn!("could not read performance profiles file {path}: {e}");
+        PerformanceError::File
+    })?;
+
+    let profiles: Vec<PerformanceProfile> = serde_json::from_str(&data).map_err(|e| {
+        common_error!("could not parse performance profiles: {e}");
+        PerformanceError::Json
+    })?;
+
+    let mut registry = ProfileRegistry::default();
+    for profile in profiles {
+        registry.insert(profile)?;
+    }
+
+    let count = registry.len();
+    *get_profiles().await.write().await = registry;
+    common_info!("loaded {count} performance profiles from {path}.");
+
+    Ok(count)
+}
+
+/// Performance profiles, keyed by vehicle model ID
+#[derive(Debug, Default)]
+pub struct ProfileRegistry {
+    profiles: HashMap<String, PerformanceProfile>,
+}
+
+impl ProfileRegistry {
+    /// Adds or replaces the profile of an aircraft type
+    pub fn insert(&mut self, profile: PerformanceProfile) -> Result<(), PerformanceError> {
+        validate(&profile)?;
+        self.profiles
+            .insert(profile.vehicle_model_id.clone(), profile);
+        Ok(())
+    }
+
+    /// Returns the profile of an aircraft type
+    pub fn get(&self, vehicle_model_id: &str) -> Option<&PerformanceProfile> {
+        self.profiles.get(vehicle_model_id)
+    }
+
+    /// Number of profiles
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    /// True if there are no profiles
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+}
+
+/// Gets the vehicle model of aircraft from svc-storage, by aircraft ID
+///
+/// Models are best effort, aircraft whose records could not be fetched
+///  are left out.
+async fn get_vehicle_models(
+    aircraft_ids: Vec<String>,
+    grpc_clients: &GrpcClients,
+) -> HashMap<String, String> {
+    let batches = aircraft_ids
+        .chunks(STORAGE_SEARCH_BATCH_SIZE)
+        .map(<[String]>::to_vec)
+        .collect::<Vec<_>>();
+
+    let results = stream::iter(batches)
+        .map(|aircraft_ids| {
+            let filter = AdvancedSearchFilter::search_in("vehicle_id".to_owned(), aircraft_ids);
+            grpc_clients.storage.vehicle.search(filter)
+        })
+        .buffer_unordered(STORAGE_SEARCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut models = HashMap::new();
+    for result in results {
+        match result {
+            Ok(response) => models.extend(
+                response
+                    .into_inner()
+                    .list
+                    .into_iter()
+                    .filter_map(|object| Some((object.id, object.data?.vehicle_model_id))),
+            ),
+            Err(e) => common_warn!("could not get vehicle records: {e}"),
+        }
+    }
+
+    models
+}
+
+/// Validates flight plans against the performance profiles of their
+///  aircraft types
+///
+/// Flight plans of aircraft types without a profile are not validated.
+pub async fn check_flight_plans(plans: &mut [FlightPlan], grpc_clients: &GrpcClients) {
+    if plans.is_empty() || get_profiles().await.read().await.is_empty() {
+        return;
+    }
+
+    let aircraft_ids = plans
+        .iter()
+        .map(|plan| plan.aircraft_id.clone())
+        .collect::<HashSet<String>>();
+    let models = get_vehicle_models(aircraft_ids.into_iter().collect(), grpc_clients).await;
+
+    let profiles = get_profiles().await.read().await;
+    for plan in plans.iter_mut() {
+        let Some(profile) = models
+            .get(&plan.aircraft_id)
+            .and_then(|model_id| profiles.get(model_id))
+        else {
+            continue;
+        };
+
+        let trajectory = match Trajectory::try_from(&*plan) {
+            Ok(trajectory) => trajectory,
+            Err(e) => {
+                common_warn!("could not validate flight plan {}: {e}", plan.flight_uuid);
+                continue;
+            }
+        };
+
+        plan.performance_violations = check(&trajectory, profile);
+        plan.endurance_reserve_seconds = Some(endurance_reserve_seconds(&trajectory, profile));
+        if !plan.performance_violations.is_empty() {
+            common_warn!(
+                "flight plan {} exceeds the performance of aircraft {}.",
+                plan.flight_uuid,
+                plan.aircraft_id
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{mock_flight_plan, mock_plan};
+    use lib_common::time::Utc;
+    use lib_common::uuid::Uuid;
+
+    /// Take off, cruise for about 8.3 km and land, flown in 10 minutes
+    const PATH: [(f64, f64, f64); 4] = [
+        (-42.0, 141.0, 100.0),
+        (-42.0, 141.0, 400.0),
+        (-42.0, 141.1, 400.0),
+        (-42.0, 141.1, 0.0),
+    ];
+
+    /// Profile within which a flight plan along [`PATH`] is flyable
+    fn profile() -> PerformanceProfile {
+        PerformanceProfile {
+            vehicle_model_id: Uuid::new_v4().to_string(),
+            max_climb_rate_mps: 20.0,
+            max_descent_rate_mps: 20.0,
+            min_cruise_speed_mps: 5.0,
+            max_cruise_speed_mps: 60.0,
+            service_ceiling_meters: 1000.0,
+            endurance_seconds: 3600,
+        }
+    }
+
+    /// Violation types and segments found along [`PATH`] with a profile
+    fn violations(profile: PerformanceProfile) -> Vec<(PerformanceViolationType, Option<usize>)> {
+        let trajectory = Trajectory::try_from(&mock_plan(&PATH, Utc::now())).unwrap();
+        check(&trajectory, &profile)
+            .into_iter()
+            .map(|violation| (violation.violation_type, violation.segment_index))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&profile()).is_ok());
+
+        let invalid = [
+            PerformanceProfile {
+                max_climb_rate_mps: 0.0,
+                ..profile()
+            },
+            PerformanceProfile {
+                max_descent_rate_mps: f64::NAN,
+                ..profile()
+            },
+            PerformanceProfile {
+                min_cruise_speed_mps: 70.0,
+                ..profile()
+            },
+            PerformanceProfile {
+                service_ceiling_meters: -1.0,
+                ..profile()
+            },
+            PerformanceProfile {
+                endurance_seconds: 0,
+                ..profile()
+            },
+        ];
+
+        for profile in invalid {
+            assert_eq!(validate(&profile).unwrap_err(), PerformanceError::Profile);
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        use PerformanceViolationType::*;
+
+        assert!(violations(profile()).is_empty());
+
+        // Takeoff and landing take 35 seconds, the cruise is flown at
+        //  about 14.6 m/s
+        assert_eq!(
+            violations(PerformanceProfile {
+                min_cruise_speed_mps: 20.0,
+                ..profile()
+            }),
+            vec![(BelowCruiseSpeed, Some(1))]
+        );
+        assert_eq!(
+            violations(PerformanceProfile {
+                max_cruise_speed_mps: 10.0,
+                ..profile()
+            }),
+            vec![(AboveCruiseSpeed, Some(1))]
+        );
+
+        // Slow vertical legs leave less time to cruise
+        assert_eq!(
+            violations(PerformanceProfile {
+                max_climb_rate_mps: 2.0,
+                max_descent_rate_mps: 2.0,
+                max_cruise_speed_mps: 30.0,
+                ..profile()
+            }),
+            vec![(AboveCruiseSpeed, Some(1))]
+        );
+
+        // Vertical legs too slow for the flight time
+        assert_eq!(
+            violations(PerformanceProfile {
+                max_climb_rate_mps: 0.5,
+                max_descent_rate_mps: 0.5,
+                ..profile()
+            }),
+            vec![
+                (ClimbRate, Some(0)),
+                (AboveCruiseSpeed, Some(1)),
+                (DescentRate, Some(2))
+            ]
+        );
+
+        assert_eq!(
+            violations(PerformanceProfile {
+                service_ceiling_meters: 350.0,
+                ..profile()
+            }),
+            vec![
+                (ServiceCeiling, Some(0)),
+                (ServiceCeiling, Some(1)),
+                (ServiceCeiling, Some(2))
+            ]
+        );
+
+        let trajectory = Trajectory::try_from(&mock_plan(&PATH, Utc::now())).unwrap();
+        let violations = check(
+            &trajectory,
+            &PerformanceProfile {
+                endurance_seconds: 300,
+                ..profile()
+            },
+        );
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].violation_type, Endurance);
+        assert_eq!(violations[0].segment_index, None);
+        assert_eq!(violations[0].value, 600.0);
+        assert_eq!(violations[0].limit, 300.0);
+    }
+
+    #[test]
+    fn test_check_vtol_profile() {
+        // Climbing at 5 m/s and descending at 4 m/s leaves 440 seconds to
+        //  cruise at about 18.8 m/s
+        let vtol = PerformanceProfile {
+            max_climb_rate_mps: 5.0,
+            max_descent_rate_mps: 4.0,
+            min_cruise_speed_mps: 10.0,
+            max_cruise_speed_mps: 40.0,
+            ..profile()
+        };
+        assert!(violations(vtol.clone()).is_empty());
+
+        // Climbing 1 km along the cruise takes more than 1 m/s
+        let now = Utc::now();
+        let plan = mock_plan(&[(-42.0, 141.0, 0.0), (-42.0, 141.1, 1000.0)], now);
+        let trajectory = Trajectory::try_from(&plan).unwrap();
+        let violations = check(
+            &trajectory,
+            &PerformanceProfile {
+                max_climb_rate_mps: 1.0,
+                ..vtol
+            },
+        );
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].violation_type,
+            PerformanceViolationType::ClimbRate
+        );
+        assert_eq!(violations[0].segment_index, Some(0));
+    }
+
+    #[test]
+    fn test_check_instant_leg() {
+        let now = Utc::now();
+        let plan = mock_flight_plan(&[(-42.0, 141.0, 100.0), (-42.0, 141.1, 100.0)], now, now);
+        let trajectory = Trajectory::try_from(&plan).unwrap();
+        let violations = check(&trajectory, &profile());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].violation_type,
+            PerformanceViolationType::AboveCruiseSpeed
+        );
+        assert_eq!(violations[0].value, f64::MAX);
+    }
+
+    #[tokio::test]
+    async fn test_check_flight_plans() {
+        let error = load_profiles_file("/nonsense/performance_profiles.json")
+            .await
+            .unwrap_err();
+        assert_eq!(error, PerformanceError::File);
+
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let profile = PerformanceProfile {
+            max_cruise_speed_mps: 10.0,
+            ..profile()
+        };
+
+        let path = std::env::temp_dir().join(format!("{}.json", Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            serde_json::json!([{
+                "vehicle_model_id": profile.vehicle_model_id,
+                "max_climb_rate_mps": profile.max_climb_rate_mps,
+                "max_descent_rate_mps": profile.max_descent_rate_mps,
+                "min_cruise_speed_mps": profile.min_cruise_speed_mps,
+                "max_cruise_speed_mps": profile.max_cruise_speed_mps,
+                "service_ceiling_meters": profile.service_ceiling_meters,
+                "endurance_seconds": profile.endurance_seconds,
+            }])
+            .to_string(),
+        )
+        .unwrap();
+
+        let count = load_profiles_file(path.to_str().unwrap()).await.unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(
+            get_profiles()
+                .await
+                .read()
+                .await
+                .get(&profile.vehicle_model_id),
+            Some(&profile)
+        );
+
+        std::fs::write(&path, "[{}]").unwrap();
+        let error = load_profiles_file(path.to_str().unwrap())
+            .await
+            .unwrap_err();
+        assert_eq!(error, PerformanceError::Json);
+        std::fs::remove_file(&path).unwrap();
+
+        let mut data = vehicle::mock::get_data_obj();
+        data.vehicle_model_id = profile.vehicle_model_id.clone();
+        let aircraft_id = grpc_clients
+            .storage
+            .vehicle
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        // The second aircraft has no svc-storage record
+        let mut plans = vec![mock_plan(&PATH, Utc::now()), mock_plan(&PATH, Utc::now())];
+        plans[0].aircraft_id = aircraft_id;
+        check_flight_plans(&mut plans, &grpc_clients).await;
+
+        assert_eq!(plans[0].performance_violations.len(), 1);
+        assert_eq!(
+            plans[0].performance_violations[0].violation_type,
+            PerformanceViolationType::AboveCruiseSpeed
+        );
+        assert!(plans[1].performance_violations.is_empty());
+
+        // Ten minutes of the one hour endurance are flown
+        assert_eq!(plans[0].endurance_reserve_seconds, Some(3000));
+        assert_eq!(plans[1].endurance_reserve_seconds, None);
+    }
+
+    #[test]
+    fn test_performance_error_display() {
+        assert_eq!(
+            PerformanceError::Profile.to_string(),
+            "invalid performance profile limits."
+        );
+        assert_eq!(
+            PerformanceError::File.to_string(),
+            "could not read performance profiles file."
+        );
+        assert_eq!(
+            PerformanceError::Json.to_string(),
+            "invalid performance profiles file."
+        );
+    }
+}
@@ -0,0 +1,642 @@
+//! Resolution advisories for conflicting flight plans
+//!
+//! For a pair of conflicting trajectories, candidate maneuvers are generated
+//!  for each aircraft: changing altitude, slowing down to arrive later, or
+//!  offsetting the path laterally. Maneuvers that resolve the conflict are
+//!  ranked by the flight time and energy they add, and the best one is
+//!  advised to each aircraft.
+
+use super::conflict::{closest_approach, SeparationMinima, Trajectory};
+use super::contingency::get_contingencies;
+use super::geo;
+use crate::rest::api::{Advisory, FlightPlan, ManeuverType, PointZ};
+use lib_common::time::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use tokio::sync::{Mutex, OnceCell};
+
+/// Number of altitude changes and lateral offsets to try in each direction,
+///  in multiples of the separation minima
+pub const MAX_MANEUVER_STEPS: u32 = 3;
+
+/// Step between candidate delays in seconds
+pub const DELAY_STEP_SECONDS: i64 = 30;
+
+/// Maximum number of candidate delays
+pub const MAX_DELAY_STEPS: i64 = 10;
+
+/// Vertical speed assumed when changing altitude, in meters per second
+pub const VERTICAL_SPEED_MPS: f64 = 2.5;
+
+/// Energy needed to climb one meter, expressed as meters of level flight
+pub const CLIMB_ENERGY_FACTOR: f64 = 10.0;
+
+/// Energy cost equivalent to one second of added flight time, expressed as
+///  meters of level flight
+pub const ENERGY_METERS_PER_SECOND: f64 = 20.0;
+
+/// Advisories shared by the REST and gRPC servers
+static ADVISORIES: OnceCell<Mutex<AdvisoryStore>> = OnceCell::const_new();
+
+/// Returns the advisories issued by this service
+pub async fn get_advisories() -> &'static Mutex<AdvisoryStore> {
+    ADVISORIES
+        .get_or_init(|| async { Mutex::new(AdvisoryStore::default()) })
+        .await
+}
+
+/// Advisories issued for each flight plan
+#[derive(Debug, Default)]
+pub struct AdvisoryStore {
+    /// Advisories keyed by flight plan ID
+    advisories: HashMap<String, Vec<Advisory>>,
+}
+
+impl AdvisoryStore {
+    /// Adds an advisory, replacing any advisory for the same pair of flight plans
+    pub fn insert(&mut self, advisory: Advisory) {
+        let advisories = self
+            .advisories
+            .entry(advisory.flight_uuid.clone())
+            .or_default();
+
+        advisories.retain(|existing| {
+            existing.conflicting_flight_uuid != advisory.conflicting_flight_uuid
+        });
+        advisories.push(advisory);
+    }
+
+    /// Removes the advisory for a pair of flight plans
+    pub fn remove(&mut self, flight_uuid: &str, conflicting_flight_uuid: &str) {
+        if let Some(advisories) = self.advisories.get_mut(flight_uuid) {
+            advisories
+                .retain(|existing| existing.conflicting_flight_uuid != conflicting_flight_uuid);
+        }
+    }
+
+    /// Replaces all advisories, advisories already issued with the same
+    ///  maneuver keep their issue time
+    pub fn replace(&mut self, advisories: Vec<Advisory>) {
+        let previous = std::mem::take(&mut self.advisories);
+        for mut advisory in advisories {
+            let existing = previous.get(&advisory.flight_uuid).and_then(|existing| {
+                existing
+                    .iter()
+                    .find(|existing| {
+                        existing.conflicting_flight_uuid == advisory.conflicting_flight_uuid
+                            && existing.maneuver == advisory.maneuver
+                            && existing.value == advisory.value
+                    })
+                    .map(|existing| existing.issued)
+            });
+
+            if let Some(issued) = existing {
+                advisory.issued = issued;
+            }
+
+            self.insert(advisory);
+        }
+    }
+
+    /// Advisories issued for a flight plan
+    pub fn get(&self, flight_uuid: &str) -> Vec<Advisory> {
+        self.advisories
+            .get(flight_uuid)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// A maneuver applied to a trajectory
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// Type of maneuver
+    pub maneuver: ManeuverType,
+
+    /// Size of the maneuver, see [`Advisory::value`]
+    pub value: f64,
+
+    /// Path flown when following the maneuver
+    pub path: Vec<PointZ>,
+
+    /// Trajectory flown when following the maneuver
+    pub trajectory: Trajectory,
+
+    /// Flight time added by the maneuver in seconds
+    pub added_flight_time_seconds: f64,
+
+    /// Energy added by the maneuver, expressed as meters of level flight
+    pub added_energy_meters: f64,
+}
+
+impl Candidate {
+    /// Cost used to rank maneuvers, lower is better
+    pub fn score(&self) -> f64 {
+        self.added_flight_time_seconds + self.added_energy_meters / ENERGY_METERS_PER_SECOND
+    }
+
+    /// Converts the maneuver into an advisory for the given conflict
+    pub fn to_advisory(&self, conflicting_flight_uuid: &str, issued: DateTime<Utc>) -> Advisory {
+        Advisory {
+            flight_uuid: self.trajectory.flight_uuid.clone(),
+            aircraft_id: self.trajectory.aircraft_id.clone(),
+            conflicting_flight_uuid: conflicting_flight_uuid.to_string(),
+            maneuver: self.maneuver,
+            value: self.value,
+            added_flight_time_seconds: self.added_flight_time_seconds,
+            added_energy_meters: self.added_energy_meters,
+            path: self.path.clone(),
+            issued,
+        }
+    }
+}
+
+/// Path of a trajectory
+fn path_of(trajectory: &Trajectory) -> Vec<PointZ> {
+    trajectory
+        .waypoints
+        .iter()
+        .map(|waypoint| waypoint.point)
+        .collect()
+}
+
+/// Length of a path in meters, including altitude changes
+fn path_length(path: &[PointZ]) -> f64 {
+    path.windows(2)
+        .map(|leg| geo::distance_meters(&leg[0], &leg[1]))
+        .sum()
+}
+
+/// Builds a trajectory flying `path` with the same departure as `trajectory`
+fn rebuild(
+    trajectory: &Trajectory,
+    path: &[PointZ],
+    added_flight_time_seconds: f64,
+) -> Option<Trajectory> {
+    let added = Duration::try_milliseconds((added_flight_time_seconds * 1000.0).round() as i64)?;
+    Trajectory::new(
+        trajectory.flight_uuid.clone(),
+        trajectory.aircraft_id.clone(),
+        path,
+        trajectory.start(),
+        trajectory.end() + added,
+    )
+    .ok()
+}
+
+/// Flies the path between the vertipads `delta_meters` higher, or lower if
+///  negative, keeping the first and last points
+///
+/// A direct path gets explicit climb and descent legs over the vertipads.
+///  Descents never go below the higher of the two vertipads.
+fn altitude_change(trajectory: &Trajectory, delta_meters: f64) -> Option<Candidate> {
+    let mut path = path_of(trajectory);
+    let first = *path.first()?;
+    let last = *path.last()?;
+    if path.len() < 2 {
+        return None;
+    }
+
+    // Climb from and descend to the vertipads of a direct path
+    if path.len() == 2 {
+        path.insert(1, last);
+        path.insert(1, first);
+    }
+
+    let count = path.len();
+    for point in path.iter_mut().skip(1).take(count - 2) {
+        point.altitude_meters += delta_meters;
+    }
+
+    let floor = first.altitude_meters.max(last.altitude_meters);
+    if path[1..count - 1]
+        .iter()
+        .any(|point| point.altitude_meters < floor)
+    {
+        return None;
+    }
+
+    // Climb to and descend from the new altitude
+    let added_flight_time_seconds = 2.0 * delta_meters.abs() / VERTICAL_SPEED_MPS;
+    let added_energy_meters = CLIMB_ENERGY_FACTOR * delta_meters.max(0.0);
+
+    Some(Candidate {
+        maneuver: ManeuverType::AltitudeChange,
+        value: delta_meters,
+        trajectory: rebuild(trajectory, &path, added_flight_time_seconds)?,
+        path,
+        added_flight_time_seconds,
+        added_energy_meters,
+    })
+}
+
+/// Flies the path slower, arriving `seconds` later
+fn delay(trajectory: &Trajectory, seconds: f64) -> Option<Candidate> {
+    let path = path_of(trajectory);
+    Some(Candidate {
+        maneuver: ManeuverType::Delay,
+        value: seconds,
+        trajectory: rebuild(trajectory, &path, seconds)?,
+        path,
+        added_flight_time_seconds: seconds,
+        added_energy_meters: 0.0,
+    })
+}
+
+/// Offsets the path `offset_meters` to the right of the track, or to the
+///  left if negative, keeping the first and last points
+fn lateral_offset(trajectory: &Trajectory, offset_meters: f64) -> Option<Candidate> {
+    let mut path = path_of(trajectory);
+    let first = *path.first()?;
+    let last = *path.last()?;
+    let (east, north) = geo::to_local_meters(&first, &last);
+    let length = east.hypot(north);
+    if length <= 0.0 {
+        return None;
+    }
+
+    // Offset the midpoint of a direct path
+    if path.len() == 2 {
+        path.insert(1, geo::interpolate(&first, &last, 0.5));
+    }
+
+    // Unit vector to the right of the track
+    let (right_east, right_north) = (north / length, -east / length);
+    let count = path.len();
+    for point in path.iter_mut().skip(1).take(count - 2) {
+        *point = geo::from_local_meters(
+            point,
+            right_east * offset_meters,
+            right_north * offset_meters,
+            point.altitude_meters,
+        );
+    }
+
+    let planned_length = path_length(&path_of(trajectory));
+    let added_distance = path_length(&path) - planned_length;
+    let duration_seconds =
+        (trajectory.end() - trajectory.start()).num_milliseconds() as f64 / 1000.0;
+    let added_flight_time_seconds = if planned_length > 0.0 {
+        added_distance * duration_seconds / planned_length
+    } else {
+        0.0
+    };
+
+    Some(Candidate {
+        maneuver: ManeuverType::LateralOffset,
+        value: offset_meters,
+        trajectory: rebuild(trajectory, &path, added_flight_time_seconds)?,
+        path,
+        added_flight_time_seconds,
+        added_energy_meters: added_distance,
+    })
+}
+
+/// Generates candidate maneuvers for a trajectory, best first
+pub fn candidates(trajectory: &Trajectory, minima: &SeparationMinima) -> Vec<Candidate> {
+    let mut candidates = vec![];
+    for step in 1..=MAX_MANEUVER_STEPS {
+        let step = step as f64;
+        for sign in [1.0, -1.0] {
+            candidates.extend(altitude_change(
+                trajectory,
+                sign * step * minima.vertical_meters,
+            ));
+            candidates.extend(lateral_offset(
+                trajectory,
+                sign * step * minima.horizontal_meters,
+            ));
+        }
+    }
+
+    for step in 1..=MAX_DELAY_STEPS {
+        candidates.extend(delay(trajectory, (step * DELAY_STEP_SECONDS) as f64));
+    }
+
+    candidates.sort_by(|a, b| a.score().total_cmp(&b.score()));
+    candidates
+}
+
+/// True if two trajectories violate the separation minima
+fn conflicts(a: &Trajectory, b: &Trajectory, minima: &SeparationMinima) -> bool {
+    let window_start = a.start().min(b.start());
+    let window_end = a.end().max(b.end());
+    closest_approach(a, b, window_start, window_end, minima).is_some()
+}
+
+/// Maneuvers of `own` that resolve its conflict with `other`, best first
+pub fn resolve(own: &Trajectory, other: &Trajectory, minima: &SeparationMinima) -> Vec<Candidate> {
+    candidates(own, minima)
+        .into_iter()
+        .filter(|candidate| !conflicts(&candidate.trajectory, other, minima))
+        .collect()
+}
+
+/// Advises a maneuver to each aircraft of a conflicting pair
+///
+/// Each advisory resolves the conflict on its own. The advisory for `b`
+///  also keeps separation if `a` follows its advisory.
+pub fn advise(
+    a: &Trajectory,
+    b: &Trajectory,
+    minima: &SeparationMinima,
+    issued: DateTime<Utc>,
+) -> Vec<Advisory> {
+    let mut advisories = vec![];
+    let best_a = resolve(a, b, minima).into_iter().next();
+    let best_b = resolve(b, a, minima)
+        .into_iter()
+        .find(|candidate| match &best_a {
+            Some(best_a) => !conflicts(&candidate.trajectory, &best_a.trajectory, minima),
+            None => true,
+        });
+
+    if let Some(candidate) = best_a {
+        advisories.push(candidate.to_advisory(&b.flight_uuid, issued));
+    }
+
+    if let Some(candidate) = best_b {
+        advisories.push(candidate.to_advisory(&a.flight_uuid, issued));
+    }
+
+    advisories
+}
+
+/// Checks every pair of scheduled flight plans for conflicts and returns
+///  the advisories issued to both aircraft of each conflict
+///
+/// Flight plans that ended before `now` are not checked.
+pub fn find_advisories(
+    plans: &[FlightPlan],
+    minima: &SeparationMinima,
+    now: DateTime<Utc>,
+) -> Vec<Advisory> {
+    let trajectories = plans
+        .iter()
+        .filter_map(|plan| Trajectory::try_from(plan).ok())
+        .filter(|trajectory| trajectory.end() > now)
+        .collect::<Vec<Trajectory>>();
+
+    let mut advisories = vec![];
+    for (index, own) in trajectories.iter().enumerate() {
+        for other in trajectories.iter().skip(index + 1) {
+            if other.aircraft_id == own.aircraft_id || !conflicts(own, other, minima) {
+                continue;
+            }
+
+            common_info!(
+                "flight plans {} and {} in conflict, issuing advisories.",
+                own.flight_uuid,
+                other.flight_uuid
+            );
+
+            advisories.extend(advise(own, other, minima, now));
+        }
+    }
+
+    advisories
+}
+
+/// Checks the scheduled flight plans for conflicts and replaces the
+///  advisories issued by this service, returns the number of advisories
+///
+/// Flight plans of aircraft that lost their link are checked along the
+///  trajectory of their contingency procedure. Advisories of flight plans
+///  that ended or are no longer scheduled are dropped.
+pub async fn advise_scheduled_plans(
+    plans: &[FlightPlan],
+    minima: &SeparationMinima,
+    now: DateTime<Utc>,
+) -> usize {
+    let mut scheduled = plans.to_vec();
+    get_contingencies()
+        .await
+        .lock()
+        .await
+        .assume(&mut scheduled);
+
+    let advisories = find_advisories(&scheduled, minima, now);
+    let count = advisories.len();
+    get_advisories().await.lock().await.replace(advisories);
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{mock_eastbound_plan, mock_flight_plan, mock_minima};
+
+    /// Two aircraft crossing perpendicularly at the same time and altitude
+    fn crossing() -> (Trajectory, Trajectory) {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+        let a = mock_eastbound_plan(departure, arrival);
+        let b = mock_flight_plan(
+            &[(51.99, 4.01, 100.0), (52.01, 4.01, 100.0)],
+            departure,
+            arrival,
+        );
+
+        (
+            Trajectory::try_from(&a).unwrap(),
+            Trajectory::try_from(&b).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_candidates() {
+        let (a, _) = crossing();
+        let candidates = candidates(&a, &mock_minima());
+
+        // The path is flown at the altitude of the vertipads, descents
+        //  would go below them
+        assert_eq!(
+            candidates.len(),
+            (3 * MAX_MANEUVER_STEPS) as usize + MAX_DELAY_STEPS as usize
+        );
+        assert!(candidates
+            .windows(2)
+            .all(|pair| pair[0].score() <= pair[1].score()));
+
+        // Maneuvers keep the origin and destination
+        for candidate in candidates.iter() {
+            let first = candidate.path.first().unwrap();
+            let last = candidate.path.last().unwrap();
+            assert!(geo::horizontal_distance_meters(first, &a.waypoints[0].point) < 1.0);
+            assert!(geo::horizontal_distance_meters(last, &a.waypoints[1].point) < 1.0);
+            assert!(candidate.trajectory.end() >= a.end());
+        }
+    }
+
+    #[test]
+    fn test_lateral_offset() {
+        let (a, _) = crossing();
+        let candidate = lateral_offset(&a, 300.0).unwrap();
+        assert_eq!(candidate.path.len(), 3);
+
+        // Track is eastbound, right of track is south
+        let midpoint = geo::interpolate(&a.waypoints[0].point, &a.waypoints[1].point, 0.5);
+        let (east, north) = geo::to_local_meters(&midpoint, &candidate.path[1]);
+        assert!(east.abs() < 1.0);
+        assert!((north + 300.0).abs() < 1.0);
+        assert!(candidate.added_energy_meters > 0.0);
+        assert!(candidate.added_flight_time_seconds > 0.0);
+    }
+
+    #[test]
+    fn test_altitude_change() {
+        // A direct path climbs from and descends to the vertipads
+        let (a, _) = crossing();
+        let candidate = altitude_change(&a, 60.0).unwrap();
+        let altitudes = candidate
+            .path
+            .iter()
+            .map(|point| point.altitude_meters)
+            .collect::<Vec<f64>>();
+        assert_eq!(altitudes, vec![100.0, 160.0, 160.0, 100.0]);
+        assert_eq!(candidate.path[0], a.waypoints[0].point);
+        assert_eq!(candidate.path[3], a.waypoints[1].point);
+        assert!(candidate.added_energy_meters > 0.0);
+
+        // Descents stay above the vertipads
+        assert!(altitude_change(&a, -30.0).is_none());
+
+        let departure = Utc::now();
+        let plan = mock_flight_plan(
+            &[
+                (52.0, 4.0, 10.0),
+                (52.0, 4.0, 100.0),
+                (52.0, 4.02, 100.0),
+                (52.0, 4.02, 0.0),
+            ],
+            departure,
+            departure + Duration::try_minutes(10).unwrap(),
+        );
+        let trajectory = Trajectory::try_from(&plan).unwrap();
+        let candidate = altitude_change(&trajectory, -60.0).unwrap();
+        let altitudes = candidate
+            .path
+            .iter()
+            .map(|point| point.altitude_meters)
+            .collect::<Vec<f64>>();
+        assert_eq!(altitudes, vec![10.0, 40.0, 40.0, 0.0]);
+        assert_eq!(candidate.added_energy_meters, 0.0);
+        assert!(altitude_change(&trajectory, -95.0).is_none());
+    }
+
+    #[test]
+    fn test_advise() {
+        let (a, b) = crossing();
+        assert!(conflicts(&a, &b, &mock_minima()));
+
+        let advisories = advise(&a, &b, &mock_minima(), Utc::now());
+        assert_eq!(advisories.len(), 2);
+        assert_eq!(advisories[0].flight_uuid, a.flight_uuid);
+        assert_eq!(advisories[0].conflicting_flight_uuid, b.flight_uuid);
+        assert_eq!(advisories[1].flight_uuid, b.flight_uuid);
+        assert_eq!(advisories[1].conflicting_flight_uuid, a.flight_uuid);
+
+        // Each advisory resolves the conflict, and both together keep separation
+        let rebuilt = |trajectory: &Trajectory, advisory: &Advisory| {
+            rebuild(
+                trajectory,
+                &advisory.path,
+                advisory.added_flight_time_seconds,
+            )
+            .unwrap()
+        };
+        let resolved_a = rebuilt(&a, &advisories[0]);
+        let resolved_b = rebuilt(&b, &advisories[1]);
+        assert!(!conflicts(&resolved_a, &b, &mock_minima()));
+        assert!(!conflicts(&a, &resolved_b, &mock_minima()));
+        assert!(!conflicts(&resolved_a, &resolved_b, &mock_minima()));
+    }
+
+    #[test]
+    fn test_advisory_store() {
+        let (a, b) = crossing();
+        let mut store = AdvisoryStore::default();
+        for advisory in advise(&a, &b, &mock_minima(), Utc::now()) {
+            store.insert(advisory);
+        }
+
+        // Replaces the advisory for the same conflict
+        for advisory in advise(&a, &b, &mock_minima(), Utc::now()) {
+            store.insert(advisory);
+        }
+
+        assert_eq!(store.get(&a.flight_uuid).len(), 1);
+        assert_eq!(store.get(&b.flight_uuid).len(), 1);
+
+        store.remove(&a.flight_uuid, &b.flight_uuid);
+        assert!(store.get(&a.flight_uuid).is_empty());
+        assert!(store.get("unknown").is_empty());
+    }
+
+    #[test]
+    fn test_advisory_store_replace() {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+        let plans = vec![
+            mock_eastbound_plan(departure, arrival),
+            mock_flight_plan(
+                &[(52.0, 4.02, 100.0), (52.0, 4.0, 100.0)],
+                departure,
+                arrival,
+            ),
+        ];
+
+        let mut store = AdvisoryStore::default();
+        store.replace(find_advisories(&plans, &mock_minima(), departure));
+        let issued = store.get(&plans[0].flight_uuid)[0].issued;
+
+        // The same advisory keeps its issue time
+        let later = departure + Duration::try_seconds(5).unwrap();
+        store.replace(find_advisories(&plans, &mock_minima(), later));
+        assert_eq!(store.get(&plans[0].flight_uuid)[0].issued, issued);
+
+        store.replace(vec![]);
+        assert!(store.get(&plans[0].flight_uuid).is_empty());
+    }
+
+    #[test]
+    fn test_find_advisories() {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+
+        // Head-on conflict between two aircraft, and a third aircraft
+        //  above them
+        let plans = vec![
+            mock_eastbound_plan(departure, arrival),
+            mock_flight_plan(
+                &[(52.0, 4.02, 100.0), (52.0, 4.0, 100.0)],
+                departure,
+                arrival,
+            ),
+            mock_flight_plan(
+                &[(52.0, 4.02, 300.0), (52.0, 4.0, 300.0)],
+                departure,
+                arrival,
+            ),
+        ];
+
+        let advisories = find_advisories(&plans, &mock_minima(), departure);
+        assert_eq!(advisories.len(), 2);
+        assert!(advisories
+            .iter()
+            .all(|advisory| advisory.flight_uuid != plans[2].flight_uuid));
+
+        let mut store = AdvisoryStore::default();
+        store.replace(advisories);
+        assert_eq!(store.get(&plans[0].flight_uuid).len(), 1);
+        assert_eq!(store.get(&plans[1].flight_uuid).len(), 1);
+        assert!(store.get(&plans[2].flight_uuid).is_empty());
+
+        // Advisories expire once the flight plans end
+        let ended = arrival + Duration::try_minutes(10).unwrap();
+        let advisories = find_advisories(&plans, &mock_minima(), ended);
+        assert!(advisories.is_empty());
+        store.replace(advisories);
+        assert!(store.get(&plans[0].flight_uuid).is_empty());
+        assert!(store.get(&plans[1].flight_uuid).is_empty());
+    }
+}
@@ -0,0 +1,739 @@
+//! Temporary flight restrictions (TFR) published through NOTAMs
+//!
+//! Each restriction is added to the zone registry as a zone active between
+//!  its start and end, so candidate flight plans are checked against it like
+//!  any other zone. When a restriction becomes active, the flight plans
+//!  returned to aircraft are re-scanned and the affected ones are flagged or
+//!  cancelled. Restrictions are saved to a local file to survive restarts.
+
+use super::cache::CachePolicy;
+use super::cancel_flight;
+use super::conflict::Trajectory;
+use super::deconfliction::{get_scheduled_plans, CheckError};
+use super::zones::{self, get_zones, ZoneError};
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::{
+    FlightPlan, Restriction, RestrictionAction, RestrictionNotice, TimeWindow, Zone, ZoneViolation,
+};
+use crate::Config;
+use lib_common::time::{DateTime, Utc};
+use lib_common::uuid::{to_uuid, Uuid};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use tokio::sync::{Mutex, OnceCell};
+
+/// Restrictions shared by the REST server and the activation monitor
+static RESTRICTIONS: OnceCell<Mutex<RestrictionStore>> = OnceCell::const_new();
+
+/// Returns the temporary flight restrictions published to this service
+pub async fn get_restrictions() -> &'static Mutex<RestrictionStore> {
+    RESTRICTIONS
+        .get_or_init(|| async { Mutex::new(RestrictionStore::default()) })
+        .await
+}
+
+/// Serializes writes to the restrictions file
+static RESTRICTIONS_FILE: OnceCell<Mutex<()>> = OnceCell::const_new();
+
+/// Errors publishing or saving a restriction
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RestrictionError {
+    /// Restricted area is invalid
+    Zone(ZoneError),
+
+    /// Restriction expires before it starts
+    Window,
+
+    /// Restriction has already expired
+    Expired,
+
+    /// Restrictions file could not be read or written
+    File,
+
+    /// Restrictions file is not valid JSON
+    Json,
+}
+
+impl Display for RestrictionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RestrictionError::Zone(e) => write!(f, "{e}"),
+            RestrictionError::Window => write!(f, "restriction expires before it starts."),
+            RestrictionError::Expired => write!(f, "restriction has already expired."),
+            RestrictionError::File => write!(f, "could not access restrictions file."),
+            RestrictionError::Json => write!(f, "invalid restrictions file."),
+        }
+    }
+}
+
+/// Converts a restriction into a zone active between its start and end
+///
+/// The zone shares the ID of the restriction.
+pub fn to_zone(restriction: &Restriction) -> Zone {
+    Zone {
+        id: restriction.id.clone(),
+        name: restriction.reason.clone(),
+        vertices: restriction.vertices.clone(),
+        floor_meters: restriction.floor_meters,
+        ceiling_meters: restriction.ceiling_meters,
+        windows: vec![TimeWindow {
+            start: restriction.start,
+            end: restriction.end,
+        }],
+    }
+}
+
+/// Returns the flight plans crossing a restriction while it is active,
+///  with the path segments crossing it
+pub fn affected_plans<'a>(
+    restriction: &Restriction,
+    plans: &'a [FlightPlan],
+) -> Vec<(&'a FlightPlan, Vec<ZoneViolation>)> {
+    let zone = to_zone(restriction);
+    plans
+        .iter()
+        .filter_map(|plan| {
+            let trajectory = Trajectory::try_from(plan).ok()?;
+            let violations = zones::violations(&zone, &trajectory);
+            (!violations.is_empty()).then_some((plan, violations))
+        })
+        .collect()
+}
+
+/// Published restrictions and the notices issued for them
+#[derive(Debug, Default)]
+pub struct RestrictionStore {
+    /// Restrictions keyed by restriction ID
+    restrictions: HashMap<String, Restriction>,
+
+    /// Restrictions reported as active
+    activated: HashSet<String>,
+
+    /// Notices keyed by flight plan ID
+    notices: HashMap<String, Vec<RestrictionNotice>>,
+}
+
+impl RestrictionStore {
+    /// Adds or replaces a restriction, assigning an ID if it has none
+    ///
+    /// A replaced restriction is re-scanned once it is active. Returns the
+    ///  stored restriction.
+    pub fn insert(
+        &mut self,
+        mut restriction: Restriction,
+        now: DateTime<Utc>,
+    ) -> Result<Restriction, RestrictionError> {
+        if restriction.end <= restriction.start {
+            return Err(RestrictionError::Window);
+        }
+
+        if restriction.end < now {
+            return Err(RestrictionError::Expired);
+        }
+
+        if restriction.id.is_empty() {
+            restriction.id = Uuid::new_v4().to_string();
+        }
+
+        zones::validate(&to_zone(&restriction)).map_err(RestrictionError::Zone)?;
+
+        self.activated.remove(&restriction.id);
+        self.restrictions
+            .insert(restriction.id.clone(), restriction.clone());
+
+        Ok(restriction)
+    }
+
+    /// Removes a restriction and the notices issued for it
+    pub fn remove(&mut self, restriction_id: &str) -> Option<Restriction> {
+        self.activated.remove(restriction_id);
+        for notices in self.notices.values_mut() {
+            notices.retain(|notice| notice.restriction_id != restriction_id);
+        }
+
+        self.restrictions.remove(restriction_id)
+    }
+
+    /// Returns the restriction with the given ID
+    pub fn get(&self, restriction_id: &str) -> Option<&Restriction> {
+        self.restrictions.get(restriction_id)
+    }
+
+    /// Returns every restriction, ordered by start time
+    pub fn list(&self) -> Vec<Restriction> {
+        let mut restrictions = self
+            .restrictions
+            .values()
+            .cloned()
+            .collect::<Vec<Restriction>>();
+
+        restrictions.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.id.cmp(&b.id)));
+        restrictions
+    }
+
+    /// Returns the restrictions active at the given time
+    pub fn active(&self, now: DateTime<Utc>) -> Vec<Restriction> {
+        self.restrictions
+            .values()
+            .filter(|restriction| restriction.start <= now && now <= restriction.end)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the restrictions that became active since the last call
+    pub fn activate(&mut self, now: DateTime<Utc>) -> Vec<Restriction> {
+        let due = self
+            .restrictions
+            .values()
+            .filter(|restriction| restriction.start <= now && now <= restriction.end)
+            .filter(|restriction| !self.activated.contains(&restriction.id))
+            .cloned()
+            .collect::<Vec<Restriction>>();
+
+        for restriction in due.iter() {
+            self.activated.insert(restriction.id.clone());
+        }
+
+        due
+    }
+
+    /// Removes the restrictions that have expired, returning their IDs
+    pub fn expire(&mut self, now: DateTime<Utc>) -> Vec<String> {
+        let expired = self
+            .restrictions
+            .values()
+            .filter(|restriction| restriction.end < now)
+            .map(|restriction| restriction.id.clone())
+            .collect::<Vec<String>>();
+
+        for restriction_id in expired.iter() {
+            self.remove(restriction_id);
+        }
+
+        expired
+    }
+
+    /// Adds a notice, replacing any notice for the same flight plan and
+    ///  restriction
+    ///
+    /// A notice repeating the action of the replaced notice keeps its issue
+    ///  time.
+    pub fn notify(&mut self, mut notice: RestrictionNotice) {
+        let notices = self.notices.entry(notice.flight_uuid.clone()).or_default();
+        if let Some(existing) = notices.iter().find(|existing| {
+            existing.restriction_id == notice.restriction_id && existing.action == notice.action
+        }) {
+            notice.issued = existing.issued;
+        }
+
+        notices.retain(|existing| existing.restriction_id != notice.restriction_id);
+        notices.push(notice);
+    }
+
+    /// Notices issued for a flight plan
+    pub fn notices(&self, flight_uuid: &str) -> Vec<RestrictionNotice> {
+        self.notices.get(flight_uuid).cloned().unwrap_or_default()
+    }
+
+    /// Number of restrictions
+    pub fn len(&self) -> usize {
+        self.restrictions.len()
+    }
+
+    /// True if there are no restrictions
+    pub fn is_empty(&self) -> bool {
+        self.restrictions.is_empty()
+    }
+}
+
+/// Writes restrictions to a JSON file
+pub async fn save_restrictions_file(
+    path: &str,
+    restrictions: &[Restriction],
+) -> Result<(), RestrictionError> {
+    let data = serde_json::to_string_pretty(restrictions).map_err(|e| {
+        common_error!("could not serialize restrictions: {e}");
+        RestrictionError::Json
+    })?;
+
+    tokio::fs::write(path, data).await.map_err(|e| {
+        common_error!("could not write restrictions file {path}: {e}");
+        RestrictionError::File
+    })
+}
+
+/// Writes the published restrictions to a JSON file
+///
+/// Writes are serialized and the restrictions are read once the previous
+///  write completed, so the file always ends with the latest restrictions.
+///  The store is not locked while writing.
+pub async fn save_restrictions(path: &str) -> Result<(), RestrictionError> {
+    let _file = RESTRICTIONS_FILE
+        .get_or_init(|| async { Mutex::new(()) })
+        .await
+        .lock()
+        .await;
+
+    let restrictions = get_restrictions().await.lock().await.list();
+    save_restrictions_file(path, &restrictions).await
+}
+
+/// Loads restrictions saved to a JSON file into the shared store and the
+///  zone registry, skipping those that have expired
+///
+/// Returns the number of restrictions loaded.
+pub async fn load_restrictions_file(path: &str) -> Result<usize, RestrictionError> {
+    let data = tokio::fs::read_to_string(path).await.map_err(|e| {
+        common_warn!("could not read restrictions file {path}: {e}");
+        RestrictionError::File
+    })?;
+
+    let restrictions: Vec<Restriction> = serde_json::from_str(&data).map_err(|e| {
+        common_error!("could not parse restrictions: {e}");
+        RestrictionError::Json
+    })?;
+
+    let now = Utc::now();
+    let mut store = get_restrictions().await.lock().await;
+    let mut zones = get_zones().await.write().await;
+    for restriction in restrictions {
+        let id = restriction.id.clone();
+        match store.insert(restriction, now) {
+            Ok(restriction) => {
+                zones
+                    .insert(to_zone(&restriction))
+                    .map_err(RestrictionError::Zone)?;
+            }
+            Err(e) => common_warn!("skipping restriction {id}: {e}"),
+        }
+    }
+
+    common_info!("loaded {} restrictions from {path}.", store.len());
+    Ok(store.len())
+}
+
+/// Publishes a restriction and saves the restrictions file
+pub async fn publish_restriction(
+    restriction: Restriction,
+    path: &str,
+) -> Result<Restriction, RestrictionError> {
+    let restriction = {
+        let mut store = get_restrictions().await.lock().await;
+        let restriction = store.insert(restriction, Utc::now())?;
+        get_zones()
+            .await
+            .write()
+            .await
+            .insert(to_zone(&restriction))
+            .map_err(RestrictionError::Zone)?;
+        restriction
+    };
+
+    common_info!(
+        "published restriction {} from {} to {}.",
+        restriction.id,
+        restriction.start,
+        restriction.end
+    );
+
+    save_restrictions(path).await?;
+    Ok(restriction)
+}
+
+/// Withdraws a restriction and saves the restrictions file
+pub async fn withdraw_restriction(
+    restriction_id: &str,
+    path: &str,
+) -> Result<Option<Restriction>, RestrictionError> {
+    let Some(restriction) = get_restrictions().await.lock().await.remove(restriction_id) else {
+        return Ok(None);
+    };
+
+    get_zones().await.write().await.remove(restriction_id);
+    common_info!("withdrew restriction {restriction_id}.");
+
+    save_restrictions(path).await?;
+    Ok(Some(restriction))
+}
+
+/// Scans the flight plans returned to aircraft against the active
+///  restrictions, flagging or cancelling the affected plans
+///
+/// Returns the notices issued to carriers.
+pub async fn apply_restrictions(
+    restrictions: &[Restriction],
+    policy: &CachePolicy,
+    grpc_clients: &GrpcClients,
+) -> Result<Vec<RestrictionNotice>, CheckError> {
+    let now = Utc::now();
+    let (window_start, window_end) = policy.window(now);
+    let plans = get_scheduled_plans(window_start, window_end, grpc_clients).await?;
+
+    let mut notices = vec![];
+    for restriction in restrictions.iter() {
+        notices.extend(apply_restriction(restriction, &plans, now, grpc_clients).await);
+    }
+
+    let mut store = get_restrictions().await.lock().await;
+    for notice in notices.iter() {
+        store.notify(notice.clone());
+    }
+
+    Ok(notices)
+}
+
+/// Flags or cancels the flight plans affected by a restriction, returns
+///  the notices to issue to carriers
+async fn apply_restriction(
+    restriction: &Restriction,
+    plans: &[FlightPlan],
+    now: DateTime<Utc>,
+    grpc_clients: &GrpcClients,
+) -> Vec<RestrictionNotice> {
+    let mut notices = vec![];
+    for (plan, violations) in affected_plans(restriction, plans) {
+        let mut action = RestrictionAction::Flagged;
+        if restriction.cancel_affected {
+            match to_uuid(&plan.flight_uuid) {
+                Some(fp_id) => match cancel_flight(fp_id, grpc_clients).await {
+                    Ok(_) => action = RestrictionAction::Cancelled,
+                    Err(e) => common_warn!(
+                        "could not cancel flight plan {}, flagging it instead: {e}",
+                        plan.flight_uuid
+                    ),
+                },
+                None => common_warn!("invalid flight plan ID {}.", plan.flight_uuid),
+            }
+        }
+
+        common_info!(
+            "restriction {} affects flight plan {}: {:?}.",
+            restriction.id,
+            plan.flight_uuid,
+            action
+        );
+
+        notices.push(RestrictionNotice {
+            restriction_id: restriction.id.clone(),
+            flight_uuid: plan.flight_uuid.clone(),
+            aircraft_id: plan.aircraft_id.clone(),
+            action,
+            violations,
+            issued: now,
+        });
+    }
+
+    notices
+}
+
+/// Expires old restrictions and applies the active ones
+///
+/// Active restrictions are applied on every check so that flight plans
+///  scheduled after a restriction became active are also flagged.
+pub async fn monitor_restrictions(path: &str, policy: &CachePolicy, grpc_clients: &GrpcClients) {
+    let now = Utc::now();
+    let (expired, active) = {
+        let mut store = get_restrictions().await.lock().await;
+        let expired = store.expire(now);
+        for restriction in store.activate(now).iter() {
+            common_info!("restriction {} is active.", restriction.id);
+        }
+
+        (expired, store.active(now))
+    };
+
+    if !expired.is_empty() {
+        let mut zones = get_zones().await.write().await;
+        for restriction_id in expired.iter() {
+            common_info!("restriction {restriction_id} expired.");
+            zones.remove(restriction_id);
+        }
+        drop(zones);
+
+        if let Err(e) = save_restrictions(path).await {
+            common_warn!("could not save restrictions: {e}");
+        }
+    }
+
+    if active.is_empty() {
+        return;
+    }
+
+    if let Err(e) = apply_restrictions(&active, policy, grpc_clients).await {
+        common_warn!("could not scan flight plans for active restrictions: {e}");
+    }
+}
+
+/// Periodically activates and expires temporary flight restrictions
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) infinite loop, tested through monitor_restrictions
+pub async fn restrictions_loop(config: Config) {
+    let grpc_clients = crate::grpc::client::get_clients().await;
+    let policy = CachePolicy::from(&config);
+    let period = std::time::Duration::from_secs(config.restrictions_interval_seconds.max(1) as u64);
+    let mut interval = tokio::time::interval(period);
+
+    common_info!("restriction monitor started.");
+    loop {
+        interval.tick().await;
+        monitor_restrictions(&config.restrictions_file, &policy, grpc_clients).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::Point;
+    use crate::test_util::{mock_flight_plan, mock_flight_plan_data};
+    use lib_common::time::Duration;
+    use svc_storage_client_grpc::prelude::*;
+
+    /// Square of roughly 1.4 km around (52.0, 4.0)
+    fn restriction(start: DateTime<Utc>, end: DateTime<Utc>) -> Restriction {
+        Restriction {
+            id: Uuid::new_v4().to_string(),
+            reason: "VIP movement".to_string(),
+            vertices: vec![
+                Point {
+                    latitude: 51.995,
+                    longitude: 3.99,
+                },
+                Point {
+                    latitude: 51.995,
+                    longitude: 4.01,
+                },
+                Point {
+                    latitude: 52.005,
+                    longitude: 4.01,
+                },
+                Point {
+                    latitude: 52.005,
+                    longitude: 3.99,
+                },
+            ],
+            floor_meters: 0.0,
+            ceiling_meters: 150.0,
+            start,
+            end,
+            cancel_affected: false,
+        }
+    }
+
+    #[test]
+    fn test_affected_plans() {
+        let now = Utc::now();
+        let arrival = now + Duration::try_minutes(10).unwrap();
+        let crossing = mock_flight_plan(&[(52.0, 3.98, 100.0), (52.0, 4.02, 100.0)], now, arrival);
+        let clear = mock_flight_plan(&[(52.02, 3.98, 100.0), (52.02, 4.02, 100.0)], now, arrival);
+        let plans = vec![crossing.clone(), clear];
+
+        let active = restriction(now, now + Duration::try_hours(1).unwrap());
+        let affected = affected_plans(&active, &plans);
+        assert_eq!(affected.len(), 1);
+        assert_eq!(affected[0].0.flight_uuid, crossing.flight_uuid);
+        assert_eq!(affected[0].1.len(), 1);
+
+        // Restriction starts after the flight has landed
+        let later = restriction(
+            arrival + Duration::try_minutes(1).unwrap(),
+            arrival + Duration::try_hours(1).unwrap(),
+        );
+        assert!(affected_plans(&later, &plans).is_empty());
+    }
+
+    #[test]
+    fn test_restriction_store_lifecycle() {
+        let mut store = RestrictionStore::default();
+        let now = Utc::now();
+        let minute = Duration::try_minutes(1).unwrap();
+
+        let mut upcoming = restriction(now + minute, now + minute * 10);
+        upcoming.id = String::new();
+        let upcoming = store.insert(upcoming, now).unwrap();
+        assert!(!upcoming.id.is_empty());
+
+        // Not active yet
+        assert!(store.activate(now).is_empty());
+
+        // Activated once
+        let due = store.activate(now + minute * 2);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, upcoming.id);
+        assert!(store.activate(now + minute * 3).is_empty());
+
+        // Still reported as active after activation
+        assert!(store.active(now).is_empty());
+        assert_eq!(store.active(now + minute * 3).len(), 1);
+
+        let notice = RestrictionNotice {
+            restriction_id: upcoming.id.clone(),
+            flight_uuid: "flight".to_string(),
+            aircraft_id: "aircraft".to_string(),
+            action: RestrictionAction::Flagged,
+            violations: vec![],
+            issued: now,
+        };
+        store.notify(notice.clone());
+        assert_eq!(store.notices("flight").len(), 1);
+
+        // Repeated notices keep their issue time
+        store.notify(RestrictionNotice {
+            issued: now + minute,
+            ..notice
+        });
+        let notices = store.notices("flight");
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].issued, now);
+
+        // Expired restrictions are removed with their notices
+        assert!(store.expire(now + minute * 5).is_empty());
+        let expired = store.expire(now + minute * 11);
+        assert_eq!(expired, vec![upcoming.id.clone()]);
+        assert!(store.is_empty());
+        assert!(store.notices("flight").is_empty());
+    }
+
+    #[test]
+    fn test_restriction_store_invalid() {
+        let mut store = RestrictionStore::default();
+        let now = Utc::now();
+        let minute = Duration::try_minutes(1).unwrap();
+
+        let error = store
+            .insert(restriction(now, now - minute), now)
+            .unwrap_err();
+        assert_eq!(error, RestrictionError::Window);
+
+        let error = store
+            .insert(restriction(now - minute * 10, now - minute), now)
+            .unwrap_err();
+        assert_eq!(error, RestrictionError::Expired);
+
+        let mut tmp = restriction(now, now + minute);
+        tmp.vertices.truncate(2);
+        let error = store.insert(tmp, now).unwrap_err();
+        assert_eq!(error, RestrictionError::Zone(ZoneError::Vertices));
+
+        assert!(store.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_restrictions_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("{}.json", Uuid::new_v4()));
+        let path = path.to_str().unwrap();
+        let now = Utc::now();
+        let saved = restriction(now, now + Duration::try_hours(1).unwrap());
+        save_restrictions_file(path, std::slice::from_ref(&saved))
+            .await
+            .unwrap();
+
+        assert!(load_restrictions_file(path).await.unwrap() >= 1);
+        assert!(get_restrictions()
+            .await
+            .lock()
+            .await
+            .get(&saved.id)
+            .is_some());
+        assert!(get_zones().await.read().await.get(&saved.id).is_some());
+
+        assert!(withdraw_restriction(&saved.id, path)
+            .await
+            .unwrap()
+            .is_some());
+        let _ = std::fs::remove_file(path);
+        assert_eq!(
+            load_restrictions_file(path).await.unwrap_err(),
+            RestrictionError::File
+        );
+    }
+
+    #[tokio::test]
+    async fn test_monitor_restrictions() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let path = std::env::temp_dir().join(format!("{}.json", Uuid::new_v4()));
+        let path = path.to_str().unwrap();
+
+        // Flight crossing the restricted area, far from other tests
+        let now = Utc::now();
+        let data = mock_flight_plan_data(
+            &[(-33.0, 150.98, 100.0), (-33.0, 151.02, 100.0)],
+            now,
+            now + Duration::try_minutes(10).unwrap(),
+        );
+        let fp_id = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        let mut tfr = restriction(now, now + Duration::try_hours(1).unwrap());
+        tfr.vertices = tfr
+            .vertices
+            .iter()
+            .map(|vertex| Point {
+                latitude: vertex.latitude - 85.0,
+                longitude: vertex.longitude + 147.0,
+            })
+            .collect();
+        tfr.cancel_affected = true;
+        let tfr = publish_restriction(tfr, path).await.unwrap();
+
+        monitor_restrictions(path, &CachePolicy::default(), &grpc_clients).await;
+
+        let notices = get_restrictions().await.lock().await.notices(&fp_id);
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].restriction_id, tfr.id);
+        assert_eq!(notices[0].action, RestrictionAction::Cancelled);
+
+        let data = grpc_clients
+            .storage
+            .flight_plan
+            .get_by_id(Id { id: fp_id })
+            .await
+            .unwrap()
+            .into_inner()
+            .data
+            .unwrap();
+        assert_eq!(
+            data.flight_status,
+            flight_plan::FlightStatus::Cancelled as i32
+        );
+
+        let withdrawn = withdraw_restriction(&tfr.id, path).await.unwrap();
+        assert!(withdrawn.is_some());
+        assert!(get_zones().await.read().await.get(&tfr.id).is_none());
+        assert!(withdraw_restriction(&tfr.id, path).await.unwrap().is_none());
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_restriction_error_display() {
+        assert_eq!(
+            RestrictionError::Zone(ZoneError::Altitude).to_string(),
+            "zone floor must be below its ceiling."
+        );
+        assert_eq!(
+            RestrictionError::Window.to_string(),
+            "restriction expires before it starts."
+        );
+        assert_eq!(
+            RestrictionError::Expired.to_string(),
+            "restriction has already expired."
+        );
+        assert_eq!(
+            RestrictionError::File.to_string(),
+            "could not access restrictions file."
+        );
+        assert_eq!(
+            RestrictionError::Json.to_string(),
+            "invalid restrictions file."
+        );
+    }
+}
@@ -0,0 +1,411 @@
+//! Vertipad arrival and departure sequencing
+//!
+//! The occupancy timeline of a vertiport is built from the flight plans
+//!  departing from or arriving at it. A flight plan occupies its origin
+//!  vertipad during its origin timeslot and its target vertipad during its
+//!  target timeslot. Overlapping occupancies of the same vertipad are
+//!  reported as conflicts, and takeoffs and landings are ordered with a
+//!  minimum spacing between them.
+
+use super::cache::{get_vertiport_plans, CachePolicy};
+use super::deconfliction::{is_scheduled, CheckError};
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::{
+    FlightPlan, PadConflict, PadOperation, PadOperationType, VertiportSchedule,
+};
+use crate::Config;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use svc_storage_client_grpc::prelude::*;
+
+/// Minimum spacing between takeoffs and landings at a vertiport
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Spacing(pub Duration);
+
+impl From<&Config> for Spacing {
+    fn from(config: &Config) -> Self {
+        let seconds = config.vertiport_min_spacing_seconds as i64;
+        Spacing(Duration::try_seconds(seconds).unwrap_or(Duration::zero()))
+    }
+}
+
+impl Default for Spacing {
+    fn default() -> Self {
+        Spacing::from(&Config::new())
+    }
+}
+
+/// Returns the vertipad operations of the flight plans at a vertiport
+///
+/// Aircraft take off at the end of their origin timeslot and land at the
+///  start of their target timeslot. Operations are not sequenced yet.
+pub fn pad_operations(vertiport_id: &str, plans: &[FlightPlan]) -> Vec<PadOperation> {
+    let mut operations = vec![];
+    for plan in plans.iter() {
+        if plan.origin_vertiport_id == vertiport_id {
+            operations.push(PadOperation {
+                flight_uuid: plan.flight_uuid.clone(),
+                aircraft_id: plan.aircraft_id.clone(),
+                vertipad_id: plan.origin_vertipad_id.clone(),
+                operation: PadOperationType::Departure,
+                slot_start: plan.origin_timeslot_start,
+                slot_end: plan.origin_timeslot_end,
+                planned_time: plan.origin_timeslot_end,
+                recommended_time: plan.origin_timeslot_end,
+                sequence: 0,
+            });
+        }
+
+        if plan.target_vertiport_id == vertiport_id {
+            operations.push(PadOperation {
+                flight_uuid: plan.flight_uuid.clone(),
+                aircraft_id: plan.aircraft_id.clone(),
+                vertipad_id: plan.target_vertipad_id.clone(),
+                operation: PadOperationType::Arrival,
+                slot_start: plan.target_timeslot_start,
+                slot_end: plan.target_timeslot_end,
+                planned_time: plan.target_timeslot_start,
+                recommended_time: plan.target_timeslot_start,
+                sequence: 0,
+            });
+        }
+    }
+
+    operations
+}
+
+/// Returns every pair of operations occupying the same vertipad at the same time
+pub fn find_pad_conflicts(operations: &[PadOperation]) -> Vec<PadConflict> {
+    let mut pads: HashMap<&str, Vec<&PadOperation>> = HashMap::new();
+    for operation in operations.iter() {
+        pads.entry(&operation.vertipad_id)
+            .or_default()
+            .push(operation);
+    }
+
+    let mut conflicts = vec![];
+    for (vertipad_id, mut timeline) in pads {
+        timeline.sort_by_key(|operation| operation.slot_start);
+        for (i, a) in timeline.iter().enumerate() {
+            // Timeline is sorted, later slots can't overlap if this one doesn't
+            for b in timeline[i + 1..]
+                .iter()
+                .take_while(|b| b.slot_start < a.slot_end)
+            {
+                conflicts.push(PadConflict {
+                    vertipad_id: vertipad_id.to_string(),
+                    flight_uuid_a: a.flight_uuid.clone(),
+                    flight_uuid_b: b.flight_uuid.clone(),
+                    overlap_start: b.slot_start,
+                    overlap_end: a.slot_end.min(b.slot_end),
+                });
+            }
+        }
+    }
+
+    conflicts.sort_by(|a, b| {
+        a.overlap_start
+            .cmp(&b.overlap_start)
+            .then_with(|| a.vertipad_id.cmp(&b.vertipad_id))
+    });
+
+    conflicts
+}
+
+/// Orders operations by planned time and delays each one until at least
+///  `spacing` after the previous one
+///
+/// Landings go before takeoffs planned at the same time, since arriving
+///  aircraft can't wait on the ground.
+pub fn sequence(operations: &mut [PadOperation], spacing: Spacing) {
+    operations.sort_by(|a, b| {
+        a.planned_time
+            .cmp(&b.planned_time)
+            .then_with(|| {
+                let priority = |operation: &PadOperation| match operation.operation {
+                    PadOperationType::Arrival => 0,
+                    PadOperationType::Departure => 1,
+                };
+                priority(a).cmp(&priority(b))
+            })
+            .then_with(|| a.flight_uuid.cmp(&b.flight_uuid))
+    });
+
+    let mut previous: Option<DateTime<Utc>> = None;
+    for (index, operation) in operations.iter_mut().enumerate() {
+        operation.recommended_time = match previous {
+            Some(time) => operation.planned_time.max(time + spacing.0),
+            None => operation.planned_time,
+        };
+
+        operation.sequence = index as u32 + 1;
+        previous = Some(operation.recommended_time);
+    }
+}
+
+/// Builds the occupancy timeline of a vertiport from the given flight plans
+pub fn build_schedule(
+    vertiport_id: &str,
+    plans: &[FlightPlan],
+    spacing: Spacing,
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+) -> VertiportSchedule {
+    let mut operations = pad_operations(vertiport_id, plans);
+    let conflicts = find_pad_conflicts(&operations);
+    sequence(&mut operations, spacing);
+
+    VertiportSchedule {
+        vertiport_id: vertiport_id.to_string(),
+        window_start,
+        window_end,
+        operations,
+        conflicts,
+    }
+}
+
+/// Gets the flight plans departing from or arriving at a vertiport from
+///  svc-storage, with the vertipad occupied within the given window,
+///  skipping cancelled, draft and finished plans
+pub async fn fetch_vertiport_plans(
+    vertiport_id: &str,
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+    grpc_clients: &GrpcClients,
+) -> Result<Vec<FlightPlan>, CheckError> {
+    let filters = [
+        AdvancedSearchFilter::search_equals(
+            "origin_vertiport_id".to_owned(),
+            vertiport_id.to_owned(),
+        )
+        .and_between(
+            "origin_timeslot_start".to_owned(),
+            window_start.to_string(),
+            window_end.to_string(),
+        ),
+        AdvancedSearchFilter::search_equals(
+            "target_vertiport_id".to_owned(),
+            vertiport_id.to_owned(),
+        )
+        .and_between(
+            "target_timeslot_start".to_owned(),
+            window_start.to_string(),
+            window_end.to_string(),
+        ),
+    ];
+
+    let mut plans: Vec<FlightPlan> = vec![];
+    for filter in filters {
+        let objects = grpc_clients
+            .storage
+            .flight_plan
+            .search(filter)
+            .await
+            .map_err(|e| {
+                common_error!("svc-storage failure: {e}");
+                CheckError::Unavailable
+            })?
+            .into_inner()
+            .list;
+
+        for plan in objects
+            .into_iter()
+            .filter(|object| object.data.as_ref().is_some_and(is_scheduled))
+            .filter_map(|object| FlightPlan::try_from(object).ok())
+        {
+            // Flights within the vertiport match both searches
+            if !plans.iter().any(|tmp| tmp.flight_uuid == plan.flight_uuid) {
+                plans.push(plan);
+            }
+        }
+    }
+
+    Ok(plans)
+}
+
+/// Gets the vertipad operations of a vertiport within the default flight
+///  plan window at the given time
+pub async fn get_pad_operations(
+    vertiport_id: &str,
+    now: DateTime<Utc>,
+    policy: &CachePolicy,
+    grpc_clients: &GrpcClients,
+) -> Result<Vec<PadOperation>, CheckError> {
+    let (window_start, window_end) = policy.window(now);
+    let plans =
+        get_vertiport_plans(vertiport_id, window_start, window_end, policy, grpc_clients).await?;
+    Ok(pad_operations(vertiport_id, &plans))
+}
+
+/// Builds the occupancy timeline of a vertiport from the cached or
+///  scheduled flight plans
+pub async fn get_vertiport_schedule(
+    vertiport_id: &str,
+    spacing: Spacing,
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+    policy: &CachePolicy,
+    grpc_clients: &GrpcClients,
+) -> Result<VertiportSchedule, CheckError> {
+    let plans =
+        get_vertiport_plans(vertiport_id, window_start, window_end, policy, grpc_clients).await?;
+    let schedule = build_schedule(vertiport_id, &plans, spacing, window_start, window_end);
+
+    if !schedule.conflicts.is_empty() {
+        common_warn!(
+            "vertiport {vertiport_id} has {} vertipad conflicts.",
+            schedule.conflicts.len()
+        );
+    }
+
+    Ok(schedule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::mock_eastbound_plan;
+    use lib_common::uuid::Uuid;
+
+    /// Flight plan from `origin` to `target`, departing `departure` minutes
+    ///  from `now` and arriving ten minutes later
+    fn plan(
+        now: DateTime<Utc>,
+        origin: (&str, &str),
+        target: (&str, &str),
+        departure: i64,
+    ) -> FlightPlan {
+        let departure = now + Duration::try_minutes(departure).unwrap();
+        let mut plan =
+            mock_eastbound_plan(departure, departure + Duration::try_minutes(10).unwrap());
+        plan.origin_vertiport_id = origin.0.to_string();
+        plan.origin_vertipad_id = origin.1.to_string();
+        plan.target_vertiport_id = target.0.to_string();
+        plan.target_vertipad_id = target.1.to_string();
+        plan
+    }
+
+    #[test]
+    fn test_pad_operations() {
+        let now = Utc::now();
+        let plans = vec![
+            plan(now, ("port", "pad-1"), ("other", "pad-9"), 0),
+            plan(now, ("other", "pad-9"), ("port", "pad-2"), 0),
+            plan(now, ("other", "pad-9"), ("other", "pad-8"), 0),
+        ];
+
+        let operations = pad_operations("port", &plans);
+        assert_eq!(operations.len(), 2);
+        assert_eq!(operations[0].operation, PadOperationType::Departure);
+        assert_eq!(operations[0].vertipad_id, "pad-1");
+        assert_eq!(operations[0].planned_time, plans[0].origin_timeslot_end);
+        assert_eq!(operations[1].operation, PadOperationType::Arrival);
+        assert_eq!(operations[1].vertipad_id, "pad-2");
+        assert_eq!(operations[1].planned_time, plans[1].target_timeslot_start);
+    }
+
+    #[test]
+    fn test_find_pad_conflicts() {
+        let now = Utc::now();
+
+        // Departure slots last five minutes
+        let plans = vec![
+            plan(now, ("port", "pad-1"), ("other", "pad-9"), 0),
+            plan(now, ("port", "pad-1"), ("other", "pad-9"), 3),
+            plan(now, ("port", "pad-1"), ("other", "pad-9"), 10),
+            plan(now, ("port", "pad-2"), ("other", "pad-9"), 0),
+        ];
+
+        let operations = pad_operations("port", &plans);
+        let conflicts = find_pad_conflicts(&operations);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].vertipad_id, "pad-1");
+        assert_eq!(conflicts[0].flight_uuid_a, plans[0].flight_uuid);
+        assert_eq!(conflicts[0].flight_uuid_b, plans[1].flight_uuid);
+        assert_eq!(conflicts[0].overlap_start, plans[1].origin_timeslot_start);
+        assert_eq!(conflicts[0].overlap_end, plans[0].origin_timeslot_end);
+    }
+
+    #[test]
+    fn test_sequence() {
+        let now = Utc::now();
+        let spacing = Spacing(Duration::try_seconds(60).unwrap());
+
+        // Arrival lands at the same time as the first departure takes off
+        let plans = vec![
+            plan(now, ("port", "pad-1"), ("other", "pad-9"), 10),
+            plan(now, ("port", "pad-2"), ("other", "pad-9"), 10),
+            plan(now, ("other", "pad-9"), ("port", "pad-3"), 0),
+            plan(now, ("port", "pad-1"), ("other", "pad-9"), 20),
+        ];
+
+        let mut operations = pad_operations("port", &plans);
+        sequence(&mut operations, spacing);
+
+        let order = operations
+            .iter()
+            .map(|operation| operation.flight_uuid.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(order[0], plans[2].flight_uuid);
+        assert_eq!(order[3], plans[3].flight_uuid);
+
+        assert_eq!(operations[0].operation, PadOperationType::Arrival);
+        assert_eq!(operations[0].recommended_time, operations[0].planned_time);
+        assert_eq!(
+            operations[1].recommended_time,
+            operations[0].recommended_time + spacing.0
+        );
+        assert_eq!(
+            operations[2].recommended_time,
+            operations[1].recommended_time + spacing.0
+        );
+
+        // Far enough from the previous operation to keep its planned time
+        assert_eq!(operations[3].recommended_time, operations[3].planned_time);
+
+        let sequence_numbers = operations
+            .iter()
+            .map(|operation| operation.sequence)
+            .collect::<Vec<u32>>();
+        assert_eq!(sequence_numbers, vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_get_vertiport_schedule() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let vertiport_id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        let mut data = flight_plan::mock::get_data_obj();
+        data.origin_vertiport_id = Some(vertiport_id.clone());
+        data.flight_status = flight_plan::FlightStatus::Ready as i32;
+        grpc_clients.storage.flight_plan.insert(data).await.unwrap();
+
+        let mut data = flight_plan::mock::get_data_obj();
+        data.target_vertiport_id = Some(vertiport_id.clone());
+        data.flight_status = flight_plan::FlightStatus::Ready as i32;
+        grpc_clients.storage.flight_plan.insert(data).await.unwrap();
+
+        // Cancelled flight on the same vertipad, no longer occupying it
+        let mut data = flight_plan::mock::get_data_obj();
+        data.origin_vertiport_id = Some(vertiport_id.clone());
+        data.flight_status = flight_plan::FlightStatus::Cancelled as i32;
+        grpc_clients.storage.flight_plan.insert(data).await.unwrap();
+
+        let schedule = get_vertiport_schedule(
+            &vertiport_id,
+            Spacing::default(),
+            now - Duration::try_hours(1).unwrap(),
+            now + Duration::try_hours(1).unwrap(),
+            &CachePolicy::default(),
+            &grpc_clients,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(schedule.vertiport_id, vertiport_id);
+        assert_eq!(schedule.operations.len(), 2);
+        assert!(schedule.conflicts.is_empty());
+    }
+}
@@ -0,0 +1,622 @@
+//! Short-term conflict alerting between airborne aircraft
+//!
+//! The current position and velocity of every aircraft in the traffic
+//!  picture are extrapolated in a straight line over a short horizon.
+//! Pairs of aircraft predicted to lose separation raise an alert, which is
+//!  updated on every tick and cleared once the geometry resolves.
+
+use super::cache::get_plan_cache;
+use super::conflict::SeparationMinima;
+use super::geo;
+use super::lifecycle::get_lifecycles;
+use super::traffic::get_traffic;
+use super::vertiports::get_vertiports;
+use crate::rest::api::{AircraftPosition, FlightState, PointZ, SeparationAlert};
+use crate::Config;
+use lib_common::time::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use tokio::sync::{Mutex, OnceCell};
+
+/// Relative velocities below this many meters per second are treated as zero
+const MIN_VELOCITY_MPS: f64 = 1e-6;
+
+/// Aircraft less than this many meters above the closest known vertipad
+///  are treated as on the ground
+pub const MIN_AIRBORNE_HEIGHT_METERS: f64 = 5.0;
+
+/// Aircraft away from any known vertipad moving slower than this many
+///  meters per second, horizontally and vertically, are treated as on the
+///  ground
+pub const MIN_AIRBORNE_SPEED_MPS: f64 = 1.0;
+
+/// Tactical alerts shared by the REST and gRPC servers
+static MONITOR: OnceCell<Mutex<TacticalMonitor>> = OnceCell::const_new();
+
+/// Returns the tactical monitor shared by the REST and gRPC servers
+pub async fn get_tactical_monitor() -> &'static Mutex<TacticalMonitor> {
+    MONITOR
+        .get_or_init(|| async { Mutex::new(TacticalMonitor::default()) })
+        .await
+}
+
+/// Predicted closest approach between two aircraft
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Prediction {
+    /// Seconds until separation is lost, zero if already lost
+    pub time_to_conflict_seconds: f64,
+
+    /// Seconds until closest point of approach
+    pub cpa_seconds: f64,
+
+    /// Horizontal distance at closest point of approach in meters
+    pub cpa_horizontal_meters: f64,
+
+    /// Vertical distance at closest point of approach in meters
+    pub cpa_vertical_meters: f64,
+}
+
+/// Velocity of an aircraft as (east, north, up) in meters per second
+fn velocity(position: &AircraftPosition) -> (f64, f64, f64) {
+    let heading = position.heading_degrees.to_radians();
+    (
+        position.ground_speed_mps * heading.sin(),
+        position.ground_speed_mps * heading.cos(),
+        position.vertical_speed_mps,
+    )
+}
+
+/// Returns true if the aircraft is in flight, moving or hovering
+///
+/// Aircraft flying a flight plan that is airborne or diverted are in
+///  flight. Other aircraft are in flight when at least
+///  [`MIN_AIRBORNE_HEIGHT_METERS`] above the closest of `vertipads`, the
+///  vertipads of their flight plans and those in the vertiport registry.
+///  Without a known vertipad the ground elevation is unknown, and aircraft
+///  are in flight only when moving at least [`MIN_AIRBORNE_SPEED_MPS`].
+/// Parked aircraft on adjacent vertipads are closer than the separation
+///  minima and are not predicted against other aircraft.
+pub fn is_airborne(
+    position: &AircraftPosition,
+    states: &[FlightState],
+    vertipads: &[PointZ],
+) -> bool {
+    if states
+        .iter()
+        .any(|state| matches!(state, FlightState::Airborne | FlightState::Diverted))
+    {
+        return true;
+    }
+
+    let ground = vertipads
+        .iter()
+        .min_by(|a, b| {
+            geo::horizontal_distance_meters(&position.position, a)
+                .total_cmp(&geo::horizontal_distance_meters(&position.position, b))
+        })
+        .map(|vertipad| vertipad.altitude_meters);
+
+    match ground {
+        Some(ground) => position.position.altitude_meters - ground >= MIN_AIRBORNE_HEIGHT_METERS,
+        None => {
+            position.ground_speed_mps >= MIN_AIRBORNE_SPEED_MPS
+                || position.vertical_speed_mps.abs() >= MIN_AIRBORNE_SPEED_MPS
+        }
+    }
+}
+
+/// Seconds elapsed from `from` to `to`
+fn seconds_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_milliseconds() as f64 / 1000.0
+}
+
+/// Interval of time during which `|offset + rate * t| < limit`
+///
+/// Returns `None` if the value is never within the limit.
+fn interval_within(offset: f64, rate: f64, limit: f64) -> Option<(f64, f64)> {
+    if rate.abs() < MIN_VELOCITY_MPS {
+        return (offset.abs() < limit).then_some((f64::NEG_INFINITY, f64::INFINITY));
+    }
+
+    let first = (-limit - offset) / rate;
+    let second = (limit - offset) / rate;
+    Some((first.min(second), first.max(second)))
+}
+
+/// Interval of time during which the horizontal distance is below `limit`
+///
+/// Returns `None` if the distance is never below the limit.
+fn horizontal_interval_within(
+    offset: (f64, f64),
+    rate: (f64, f64),
+    limit: f64,
+) -> Option<(f64, f64)> {
+    let a = rate.0.powi(2) + rate.1.powi(2);
+    let b = 2.0 * (offset.0 * rate.0 + offset.1 * rate.1);
+    let c = offset.0.powi(2) + offset.1.powi(2) - limit.powi(2);
+
+    if a < MIN_VELOCITY_MPS.powi(2) {
+        return (c < 0.0).then_some((f64::NEG_INFINITY, f64::INFINITY));
+    }
+
+    let discriminant = b.powi(2) - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let root = discriminant.sqrt();
+    Some(((-b - root) / (2.0 * a), (-b + root) / (2.0 * a)))
+}
+
+/// Predicts whether two aircraft will lose separation within
+///  `lookahead_seconds` of `now`, assuming both keep their current velocity
+pub fn predict(
+    a: &AircraftPosition,
+    b: &AircraftPosition,
+    now: DateTime<Utc>,
+    minima: &SeparationMinima,
+    lookahead_seconds: f64,
+) -> Option<Prediction> {
+    let velocity_a = velocity(a);
+    let velocity_b = velocity(b);
+    let age_a = seconds_between(a.timestamp, now);
+    let age_b = seconds_between(b.timestamp, now);
+
+    // Position of b relative to a at the current time
+    let (east, north) = geo::to_local_meters(&a.position, &b.position);
+    let offset = (
+        east + velocity_b.0 * age_b - velocity_a.0 * age_a,
+        north + velocity_b.1 * age_b - velocity_a.1 * age_a,
+        b.position.altitude_meters - a.position.altitude_meters + velocity_b.2 * age_b
+            - velocity_a.2 * age_a,
+    );
+    let rate = (
+        velocity_b.0 - velocity_a.0,
+        velocity_b.1 - velocity_a.1,
+        velocity_b.2 - velocity_a.2,
+    );
+
+    let horizontal = horizontal_interval_within(
+        (offset.0, offset.1),
+        (rate.0, rate.1),
+        minima.horizontal_meters,
+    )?;
+    let vertical = interval_within(offset.2, rate.2, minima.vertical_meters)?;
+
+    let start = horizontal.0.max(vertical.0).max(0.0);
+    let end = horizontal.1.min(vertical.1).min(lookahead_seconds);
+    if start > end {
+        return None;
+    }
+
+    let horizontal_rate = rate.0.powi(2) + rate.1.powi(2);
+    let cpa_seconds = if horizontal_rate < MIN_VELOCITY_MPS.powi(2) {
+        0.0
+    } else {
+        (-(offset.0 * rate.0 + offset.1 * rate.1) / horizontal_rate).clamp(0.0, lookahead_seconds)
+    };
+
+    Some(Prediction {
+        time_to_conflict_seconds: start,
+        cpa_seconds,
+        cpa_horizontal_meters: (offset.0 + rate.0 * cpa_seconds)
+            .hypot(offset.1 + rate.1 * cpa_seconds),
+        cpa_vertical_meters: (offset.2 + rate.2 * cpa_seconds).abs(),
+    })
+}
+
+/// Alerts raised and cleared by a tick of the tactical monitor
+#[derive(Debug, Clone, Default)]
+pub struct TacticalUpdate {
+    /// Alerts raised for the first time
+    pub raised: Vec<SeparationAlert>,
+
+    /// Alerts cleared because the geometry resolved
+    pub cleared: Vec<SeparationAlert>,
+}
+
+/// Tracks predicted losses of separation between aircraft
+#[derive(Debug, Default)]
+pub struct TacticalMonitor {
+    /// Active alerts, keyed by the pair of aircraft IDs in sorted order
+    alerts: HashMap<(String, String), SeparationAlert>,
+}
+
+impl TacticalMonitor {
+    /// Active alerts
+    pub fn alerts(&self) -> impl Iterator<Item = &SeparationAlert> {
+        self.alerts.values()
+    }
+
+    /// Compares every pair of positions and updates the active alerts
+    ///
+    /// Alerts that are still predicted are updated in place, and alerts
+    ///  that are no longer predicted are cleared.
+    pub fn update(
+        &mut self,
+        positions: &[AircraftPosition],
+        now: DateTime<Utc>,
+        minima: &SeparationMinima,
+        lookahead_seconds: f64,
+    ) -> TacticalUpdate {
+        let mut positions = positions.iter().collect::<Vec<&AircraftPosition>>();
+        positions.sort_by(|a, b| a.aircraft_id.cmp(&b.aircraft_id));
+
+        let mut update = TacticalUpdate::default();
+        let mut current = HashMap::new();
+        for (index, a) in positions.iter().enumerate() {
+            for b in positions.iter().skip(index + 1) {
+                let Some(prediction) = predict(a, b, now, minima, lookahead_seconds) else {
+                    continue;
+                };
+
+                let key = (a.aircraft_id.clone(), b.aircraft_id.clone());
+                let first_detected = match self.alerts.get(&key) {
+                    Some(alert) => alert.first_detected,
+                    None => now,
+                };
+
+                let cpa_offset =
+                    Duration::try_milliseconds((prediction.cpa_seconds * 1000.0).round() as i64)
+                        .unwrap_or(Duration::zero());
+
+                let alert = SeparationAlert {
+                    aircraft_id_a: a.aircraft_id.clone(),
+                    aircraft_id_b: b.aircraft_id.clone(),
+                    time_to_conflict_seconds: prediction.time_to_conflict_seconds,
+                    cpa_time: now + cpa_offset,
+                    cpa_horizontal_meters: prediction.cpa_horizontal_meters,
+                    cpa_vertical_meters: prediction.cpa_vertical_meters,
+                    first_detected,
+                    updated: now,
+                };
+
+                if !self.alerts.contains_key(&key) {
+                    common_warn!("predicted loss of separation: {:?}", alert);
+                    update.raised.push(alert.clone());
+                }
+
+                current.insert(key, alert);
+            }
+        }
+
+        for (key, alert) in self.alerts.drain() {
+            if !current.contains_key(&key) {
+                common_info!(
+                    "separation alert cleared between {} and {}.",
+                    alert.aircraft_id_a,
+                    alert.aircraft_id_b
+                );
+                update.cleared.push(alert);
+            }
+        }
+
+        self.alerts = current;
+        update
+    }
+}
+
+/// Keeps the positions of the aircraft in flight
+///
+/// Aircraft are checked with [`is_airborne`], against the lifecycle states
+///  and vertipads of their cached flight plans and the vertipads in the
+///  vertiport registry.
+pub async fn airborne_positions(positions: Vec<AircraftPosition>) -> Vec<AircraftPosition> {
+    // Vertipads in the registry give the ground elevation of every aircraft
+    let registered = get_vertiports()
+        .await
+        .read()
+        .await
+        .vertiports()
+        .into_iter()
+        .flat_map(|vertiport| vertiport.vertipads)
+        .map(|vertipad| vertipad.position)
+        .collect::<Vec<PointZ>>();
+
+    // Flight plan IDs and vertipads by aircraft ID
+    let mut flights: HashMap<String, (Vec<String>, Vec<PointZ>)> = HashMap::new();
+    for plan in get_plan_cache().await.lock().await.plans() {
+        let (fp_ids, vertipads) = flights.entry(plan.aircraft_id.clone()).or_default();
+        fp_ids.push(plan.flight_uuid.clone());
+        vertipads.extend(plan.path.first().copied());
+        vertipads.extend(plan.path.last().copied());
+    }
+
+    let lifecycles = get_lifecycles().await.lock().await;
+    positions
+        .into_iter()
+        .filter(|position| {
+            let Some((fp_ids, vertipads)) = flights.get(&position.aircraft_id) else {
+                return is_airborne(position, &[], &registered);
+            };
+
+            let states = fp_ids
+                .iter()
+                .filter_map(|fp_id| lifecycles.state(fp_id))
+                .collect::<Vec<FlightState>>();
+
+            let vertipads = [vertipads.as_slice(), registered.as_slice()].concat();
+            is_airborne(position, &states, &vertipads)
+        })
+        .collect()
+}
+
+/// Updates the tactical alerts from the current traffic picture
+///
+/// Aircraft with stale positions, or on the ground, are not extrapolated.
+pub async fn monitor_traffic(
+    minima: &SeparationMinima,
+    lookahead_seconds: f64,
+    stale_threshold: Duration,
+) -> TacticalUpdate {
+    let now = Utc::now();
+    let positions = get_traffic()
+        .await
+        .read()
+        .await
+        .tracks()
+        .filter(|track| !track.is_stale(now, stale_threshold))
+        .map(|track| track.position.clone())
+        .collect::<Vec<AircraftPosition>>();
+
+    let positions = airborne_positions(positions).await;
+
+    get_tactical_monitor()
+        .await
+        .lock()
+        .await
+        .update(&positions, now, minima, lookahead_seconds)
+}
+
+/// Periodically predicts losses of separation between airborne aircraft
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) infinite loop, tested through monitor_traffic
+pub async fn tactical_loop(config: Config) {
+    let minima = SeparationMinima::from(&config);
+    let lookahead_seconds = config.tactical_lookahead_seconds as f64;
+    let stale_threshold =
+        Duration::try_seconds(config.telemetry_stale_seconds as i64).unwrap_or(Duration::zero());
+    let period = std::time::Duration::from_secs(config.tactical_interval_seconds.max(1) as u64);
+    let mut interval = tokio::time::interval(period);
+
+    common_info!("tactical monitor started.");
+    loop {
+        interval.tick().await;
+        monitor_traffic(&minima, lookahead_seconds, stale_threshold).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::traffic::MaxSkew;
+    use crate::test_util::{mock_minima, mock_plan};
+    use lib_common::uuid::Uuid;
+
+    fn aircraft(
+        aircraft_id: &str,
+        point: PointZ,
+        ground_speed_mps: f64,
+        heading_degrees: f64,
+        timestamp: DateTime<Utc>,
+    ) -> AircraftPosition {
+        AircraftPosition {
+            aircraft_id: aircraft_id.to_string(),
+            position: point,
+            ground_speed_mps,
+            vertical_speed_mps: 0.0,
+            heading_degrees,
+            timestamp,
+        }
+    }
+
+    fn origin() -> PointZ {
+        PointZ {
+            latitude: 52.0,
+            longitude: 4.0,
+            altitude_meters: 100.0,
+        }
+    }
+
+    #[test]
+    fn test_predict_head_on() {
+        let now = Utc::now();
+
+        // 2 km apart, closing at 40 m/s
+        let a = aircraft("a", origin(), 20.0, 90.0, now);
+        let b_point = geo::from_local_meters(&origin(), 2000.0, 0.0, 100.0);
+        let b = aircraft("b", b_point, 20.0, 270.0, now);
+
+        let prediction = predict(&a, &b, now, &mock_minima(), 120.0).unwrap();
+        assert!((prediction.time_to_conflict_seconds - (2000.0 - 150.0) / 40.0).abs() < 0.5);
+        assert!((prediction.cpa_seconds - 50.0).abs() < 0.5);
+        assert!(prediction.cpa_horizontal_meters < 1.0);
+        assert!(prediction.cpa_vertical_meters < 1.0);
+
+        // Conflict beyond the lookahead
+        assert!(predict(&a, &b, now, &mock_minima(), 30.0).is_none());
+
+        // Vertically separated
+        let mut high = b.clone();
+        high.position.altitude_meters = 200.0;
+        assert!(predict(&a, &high, now, &mock_minima(), 120.0).is_none());
+    }
+
+    #[test]
+    fn test_predict_diverging_and_parallel() {
+        let now = Utc::now();
+        let a = aircraft("a", origin(), 20.0, 270.0, now);
+        let b_point = geo::from_local_meters(&origin(), 1000.0, 0.0, 100.0);
+        let b = aircraft("b", b_point, 20.0, 90.0, now);
+        assert!(predict(&a, &b, now, &mock_minima(), 120.0).is_none());
+
+        // Same speed and heading, 500 meters apart
+        let b_point = geo::from_local_meters(&origin(), 0.0, 500.0, 100.0);
+        let b = aircraft("b", b_point, 20.0, 270.0, now);
+        assert!(predict(&a, &b, now, &mock_minima(), 120.0).is_none());
+
+        // Same speed and heading, 100 meters apart
+        let b_point = geo::from_local_meters(&origin(), 0.0, 100.0, 100.0);
+        let b = aircraft("b", b_point, 20.0, 270.0, now);
+        let prediction = predict(&a, &b, now, &mock_minima(), 120.0).unwrap();
+        assert_eq!(prediction.time_to_conflict_seconds, 0.0);
+        assert!((prediction.cpa_horizontal_meters - 100.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_predict_extrapolates_old_reports() {
+        let now = Utc::now();
+        let then = now - Duration::try_seconds(10).unwrap();
+
+        // Reported 2 km apart 10 seconds ago, now 1.6 km apart
+        let a = aircraft("a", origin(), 20.0, 90.0, then);
+        let b_point = geo::from_local_meters(&origin(), 2000.0, 0.0, 100.0);
+        let b = aircraft("b", b_point, 20.0, 270.0, then);
+
+        let prediction = predict(&a, &b, now, &mock_minima(), 120.0).unwrap();
+        assert!((prediction.time_to_conflict_seconds - (1600.0 - 150.0) / 40.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_monitor_dedupes_and_clears() {
+        let mut monitor = TacticalMonitor::default();
+        let now = Utc::now();
+        let id_a = Uuid::new_v4().to_string();
+        let id_b = Uuid::new_v4().to_string();
+
+        let a = aircraft(&id_a, origin(), 20.0, 90.0, now);
+        let b_point = geo::from_local_meters(&origin(), 2000.0, 0.0, 100.0);
+        let b = aircraft(&id_b, b_point, 20.0, 270.0, now);
+
+        let update = monitor.update(&[a.clone(), b.clone()], now, &mock_minima(), 120.0);
+        assert_eq!(update.raised.len(), 1);
+        assert!(update.cleared.is_empty());
+
+        // Next tick, same geometry, no new alert
+        let later = now + Duration::try_seconds(1).unwrap();
+        let update = monitor.update(&[b.clone(), a.clone()], later, &mock_minima(), 120.0);
+        assert!(update.raised.is_empty());
+        assert!(update.cleared.is_empty());
+
+        let alerts = monitor.alerts().collect::<Vec<&SeparationAlert>>();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].first_detected, now);
+        assert_eq!(alerts[0].updated, later);
+        assert!(alerts[0].time_to_conflict_seconds < (2000.0 - 150.0) / 40.0);
+
+        // Aircraft b turns away
+        let mut b = b.clone();
+        b.heading_degrees = 90.0;
+        b.timestamp = later;
+        let update = monitor.update(&[a, b], later, &mock_minima(), 120.0);
+        assert!(update.raised.is_empty());
+        assert_eq!(update.cleared.len(), 1);
+        assert_eq!(monitor.alerts().count(), 0);
+    }
+
+    #[test]
+    fn test_is_airborne() {
+        let now = Utc::now();
+        let mut ground = origin();
+        ground.altitude_meters = 0.0;
+
+        // Parked, then hovering above a vertipad on the ground
+        let mut position = aircraft("a", ground, 0.0, 0.0, now);
+        assert!(!is_airborne(&position, &[], &[ground]));
+        position.position.altitude_meters = 100.0;
+        assert!(is_airborne(&position, &[], &[ground]));
+
+        // Without a known vertipad only moving aircraft are in flight
+        assert!(!is_airborne(&position, &[], &[]));
+        position.vertical_speed_mps = -2.0;
+        assert!(is_airborne(&position, &[], &[]));
+        position.vertical_speed_mps = 0.0;
+        position.ground_speed_mps = 20.0;
+        assert!(is_airborne(&position, &[], &[]));
+        position.ground_speed_mps = 0.0;
+
+        // Parked on an elevated vertipad
+        let mut vertipad = origin();
+        vertipad.altitude_meters = 100.0;
+        let far = geo::from_local_meters(&origin(), 5000.0, 0.0, 0.0);
+        assert!(!is_airborne(&position, &[], &[vertipad, far]));
+        assert!(is_airborne(&position, &[], &[far]));
+
+        // Airborne flight plans are in flight, whatever the height
+        assert!(is_airborne(
+            &position,
+            &[FlightState::Diverted],
+            &[vertipad]
+        ));
+        assert!(is_airborne(
+            &position,
+            &[FlightState::Airborne],
+            &[vertipad]
+        ));
+        assert!(!is_airborne(&position, &[FlightState::Landed], &[vertipad]));
+    }
+
+    #[tokio::test]
+    async fn test_monitor_traffic() {
+        let now = Utc::now();
+        let id_a = Uuid::new_v4().to_string();
+        let id_b = Uuid::new_v4().to_string();
+        let a = aircraft(&id_a, origin(), 20.0, 0.0, now);
+        let b_point = geo::from_local_meters(&origin(), 0.0, 1000.0, 100.0);
+        let b = aircraft(&id_b, b_point, 20.0, 180.0, now);
+
+        // Hovering aircraft 100 meters above the vertipad of its flight
+        //  plan, with another converging on it
+        let id_e = Uuid::new_v4().to_string();
+        let id_f = Uuid::new_v4().to_string();
+        let e_pad = geo::from_local_meters(&origin(), -5000.0, 0.0, 0.0);
+        let e_point = geo::from_local_meters(&origin(), -5000.0, 0.0, 100.0);
+        let f_point = geo::from_local_meters(&origin(), -5000.0, 1000.0, 100.0);
+        let e = aircraft(&id_e, e_point, 0.0, 0.0, now);
+        let f = aircraft(&id_f, f_point, 20.0, 180.0, now);
+
+        // Aircraft parked on adjacent vertipads
+        let id_c = Uuid::new_v4().to_string();
+        let id_d = Uuid::new_v4().to_string();
+        let c_point = geo::from_local_meters(&origin(), 5000.0, 0.0, 0.0);
+        let d_point = geo::from_local_meters(&origin(), 5030.0, 0.0, 0.0);
+        let c = aircraft(&id_c, c_point, 0.0, 0.0, now);
+        let d = aircraft(&id_d, d_point, 0.0, 0.0, now);
+
+        let mut e_plan = mock_plan(
+            &[
+                (e_pad.latitude, e_pad.longitude, 0.0),
+                (e_pad.latitude, e_pad.longitude + 0.02, 0.0),
+            ],
+            now + Duration::try_minutes(30).unwrap(),
+        );
+        e_plan.aircraft_id = id_e.clone();
+        get_plan_cache()
+            .await
+            .lock()
+            .await
+            .refresh(&id_e, vec![e_plan], now);
+
+        {
+            let mut traffic = get_traffic().await.write().await;
+            traffic.update(a, MaxSkew::default()).unwrap();
+            traffic.update(b, MaxSkew::default()).unwrap();
+            traffic.update(c, MaxSkew::default()).unwrap();
+            traffic.update(d, MaxSkew::default()).unwrap();
+            traffic.update(e, MaxSkew::default()).unwrap();
+            traffic.update(f, MaxSkew::default()).unwrap();
+        }
+
+        let stale_threshold = Duration::try_seconds(10).unwrap();
+        monitor_traffic(&mock_minima(), 120.0, stale_threshold).await;
+        let monitor = get_tactical_monitor().await.lock().await;
+        assert!(monitor.alerts().any(|alert| (alert.aircraft_id_a == id_a
+            && alert.aircraft_id_b == id_b)
+            || (alert.aircraft_id_a == id_b && alert.aircraft_id_b == id_a)));
+        assert!(monitor.alerts().any(|alert| (alert.aircraft_id_a == id_e
+            && alert.aircraft_id_b == id_f)
+            || (alert.aircraft_id_a == id_f && alert.aircraft_id_b == id_e)));
+        assert!(!monitor.alerts().any(|alert| [&id_c, &id_d]
+            .iter()
+            .any(|id| alert.aircraft_id_a == **id || alert.aircraft_id_b == **id)));
+    }
+}
@@ -0,0 +1,456 @@
+//! In-memory traffic picture built from aircraft position reports
+
+use crate::config::Config;
+use crate::rest::api::{AircraftPosition, TrafficReport};
+use lib_common::time::{DateTime, Duration, Utc};
+use lib_common::uuid::to_uuid;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use tokio::sync::{OnceCell, RwLock};
+
+/// Latest known state of every aircraft reporting its position
+static TRAFFIC: OnceCell<RwLock<TrafficPicture>> = OnceCell::const_new();
+
+/// Returns the traffic picture shared by the REST and gRPC servers
+pub async fn get_traffic() -> &'static RwLock<TrafficPicture> {
+    TRAFFIC
+        .get_or_init(|| async { RwLock::new(TrafficPicture::default()) })
+        .await
+}
+
+/// Errors ingesting a position report
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TelemetryError {
+    /// Aircraft ID is not a valid UUID
+    AircraftId,
+
+    /// Position or velocity is out of range
+    Position,
+
+    /// Report is older than the latest known position
+    OutOfOrder,
+
+    /// Report is dated further ahead of the current time than allowed
+    Future,
+}
+
+impl Display for TelemetryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TelemetryError::AircraftId => write!(f, "invalid aircraft ID."),
+            TelemetryError::Position => write!(f, "position or velocity out of range."),
+            TelemetryError::OutOfOrder => {
+                write!(f, "report is older than the latest known position.")
+            }
+            TelemetryError::Future => write!(f, "report is dated in the future."),
+        }
+    }
+}
+
+/// Maximum time a position report may be dated ahead of the current time
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MaxSkew(pub Duration);
+
+impl From<&Config> for MaxSkew {
+    fn from(config: &Config) -> Self {
+        let seconds = config.telemetry_max_skew_seconds as i64;
+        MaxSkew(Duration::try_seconds(seconds).unwrap_or(Duration::zero()))
+    }
+}
+
+impl Default for MaxSkew {
+    fn default() -> Self {
+        MaxSkew::from(&Config::new())
+    }
+}
+
+/// Latest known state of a single aircraft
+#[derive(Debug, Clone)]
+pub struct Track {
+    /// Latest position report
+    pub position: AircraftPosition,
+
+    /// Time at which the latest position report was received
+    pub received: DateTime<Utc>,
+}
+
+impl Track {
+    /// Returns true if the latest position report is older than `threshold`
+    pub fn is_stale(&self, now: DateTime<Utc>, threshold: Duration) -> bool {
+        now - self.position.timestamp > threshold
+    }
+
+    /// Converts the track into a REST report
+    pub fn to_report(&self, now: DateTime<Utc>, threshold: Duration) -> TrafficReport {
+        TrafficReport {
+            position: self.position.clone(),
+            received: self.received,
+            stale: self.is_stale(now, threshold),
+        }
+    }
+}
+
+/// Checks that a position report contains usable values and is not dated
+///  more than `max_skew` ahead of `now`
+fn validate(
+    position: &AircraftPosition,
+    now: DateTime<Utc>,
+    max_skew: MaxSkew,
+) -> Result<(), TelemetryError> {
+    if to_uuid(&position.aircraft_id).is_none() {
+        return Err(TelemetryError::AircraftId);
+    }
+
+    if position.timestamp > now + max_skew.0 {
+        return Err(TelemetryError::Future);
+    }
+
+    let point = &position.position;
+    let values = [
+        point.latitude,
+        point.longitude,
+        point.altitude_meters,
+        position.ground_speed_mps,
+        position.vertical_speed_mps,
+        position.heading_degrees,
+    ];
+
+    if values.iter().any(|value| !value.is_finite())
+        || !(-90.0..=90.0).contains(&point.latitude)
+        || !(-180.0..=180.0).contains(&point.longitude)
+        || !(0.0..360.0).contains(&position.heading_degrees)
+        || position.ground_speed_mps < 0.0
+    {
+        return Err(TelemetryError::Position);
+    }
+
+    Ok(())
+}
+
+/// Latest known state of every aircraft reporting its position, keyed by aircraft ID
+#[derive(Debug, Default)]
+pub struct TrafficPicture {
+    tracks: HashMap<String, Track>,
+}
+
+impl TrafficPicture {
+    /// Updates the track of an aircraft with a new position report
+    ///
+    /// Reports dated more than `max_skew` ahead of the current time are
+    ///  rejected, so that a skewed clock cannot block later reports.
+    pub fn update(
+        &mut self,
+        position: AircraftPosition,
+        max_skew: MaxSkew,
+    ) -> Result<(), TelemetryError> {
+        let now = Utc::now();
+        validate(&position, now, max_skew)?;
+
+        if let Some(track) = self.tracks.get(&position.aircraft_id) {
+            if position.timestamp <= track.position.timestamp {
+                return Err(TelemetryError::OutOfOrder);
+            }
+        }
+
+        let track = Track {
+            position,
+            received: now,
+        };
+
+        self.tracks
+            .insert(track.position.aircraft_id.clone(), track);
+
+        Ok(())
+    }
+
+    /// Returns the track of the given aircraft
+    pub fn get(&self, aircraft_id: &str) -> Option<&Track> {
+        self.tracks.get(aircraft_id)
+    }
+
+    /// Returns every tracked aircraft
+    pub fn tracks(&self) -> impl Iterator<Item = &Track> {
+        self.tracks.values()
+    }
+
+    /// Returns the tracks that have not been updated within `threshold`
+    pub fn stale(&self, now: DateTime<Utc>, threshold: Duration) -> Vec<&Track> {
+        self.tracks
+            .values()
+            .filter(|track| track.is_stale(now, threshold))
+            .collect()
+    }
+
+    /// Removes an aircraft from the traffic picture
+    pub fn remove(&mut self, aircraft_id: &str) -> Option<Track> {
+        self.tracks.remove(aircraft_id)
+    }
+
+    /// Removes the tracks that have not been updated within `retention`,
+    ///  returns the number of removed tracks
+    pub fn evict(&mut self, now: DateTime<Utc>, retention: Duration) -> usize {
+        let count = self.tracks.len();
+        self.tracks
+            .retain(|_, track| !track.is_stale(now, retention));
+        count - self.tracks.len()
+    }
+
+    /// Number of tracked aircraft
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// True if no aircraft are tracked
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+}
+
+/// Periodically removes aircraft that stopped reporting their position
+///  from the traffic picture
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) infinite loop, tested through TrafficPicture::evict
+pub async fn traffic_loop(config: Config) {
+    let retention = Duration::try_seconds(config.telemetry_retention_seconds as i64)
+        .unwrap_or(Duration::zero());
+    let period = std::time::Duration::from_secs(config.telemetry_stale_seconds.max(1) as u64);
+    let mut interval = tokio::time::interval(period);
+
+    common_info!("traffic picture cleanup started.");
+    loop {
+        interval.tick().await;
+        let count = get_traffic()
+            .await
+            .write()
+            .await
+            .evict(Utc::now(), retention);
+        if count > 0 {
+            common_info!("removed {count} aircraft that stopped reporting.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::PointZ;
+    use lib_common::uuid::Uuid;
+
+    fn position(aircraft_id: &str, timestamp: DateTime<Utc>, longitude: f64) -> AircraftPosition {
+        AircraftPosition {
+            aircraft_id: aircraft_id.to_string(),
+            position: PointZ {
+                latitude: 52.0,
+                longitude,
+                altitude_meters: 100.0,
+            },
+            ground_speed_mps: 20.0,
+            vertical_speed_mps: 0.0,
+            heading_degrees: 90.0,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_traffic_picture_replay() {
+        let mut picture = TrafficPicture::default();
+        let start = Utc::now() - Duration::try_seconds(60).unwrap();
+        let aircraft_a = Uuid::new_v4().to_string();
+        let aircraft_b = Uuid::new_v4().to_string();
+
+        // Replay one report per second from two aircraft
+        for second in 0..30 {
+            let timestamp = start + Duration::try_seconds(second).unwrap();
+            let longitude = 4.0 + second as f64 * 0.0003;
+            picture
+                .update(
+                    position(&aircraft_a, timestamp, longitude),
+                    MaxSkew::default(),
+                )
+                .unwrap();
+
+            // Aircraft B stops reporting after 10 seconds
+            if second < 10 {
+                picture
+                    .update(
+                        position(&aircraft_b, timestamp, longitude),
+                        MaxSkew::default(),
+                    )
+                    .unwrap();
+            }
+        }
+
+        assert_eq!(picture.len(), 2);
+        let track = picture.get(&aircraft_a).unwrap();
+        assert_eq!(
+            track.position.timestamp,
+            start + Duration::try_seconds(29).unwrap()
+        );
+
+        // Both aircraft are stale relative to the current time
+        let threshold = Duration::try_seconds(10).unwrap();
+        assert_eq!(picture.stale(Utc::now(), threshold).len(), 2);
+
+        // Only aircraft B is stale at the end of the replay
+        let replay_end = start + Duration::try_seconds(29).unwrap();
+        let stale = picture.stale(replay_end, threshold);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].position.aircraft_id, aircraft_b);
+
+        let report = picture
+            .get(&aircraft_b)
+            .unwrap()
+            .to_report(replay_end, threshold);
+        assert!(report.stale);
+
+        assert!(picture.remove(&aircraft_b).is_some());
+        assert_eq!(picture.len(), 1);
+    }
+
+    #[test]
+    fn test_traffic_picture_evict() {
+        let mut picture = TrafficPicture::default();
+        let now = Utc::now();
+        let retention = Duration::try_seconds(300).unwrap();
+        let active = Uuid::new_v4().to_string();
+        let parked = Uuid::new_v4().to_string();
+
+        picture
+            .update(
+                position(&active, now - Duration::try_seconds(5).unwrap(), 4.0),
+                MaxSkew::default(),
+            )
+            .unwrap();
+        picture
+            .update(
+                position(&parked, now - Duration::try_seconds(301).unwrap(), 4.0),
+                MaxSkew::default(),
+            )
+            .unwrap();
+
+        assert_eq!(picture.evict(now, retention), 1);
+        assert!(picture.get(&active).is_some());
+        assert!(picture.get(&parked).is_none());
+        assert_eq!(picture.evict(now, retention), 0);
+    }
+
+    #[test]
+    fn test_traffic_picture_out_of_order() {
+        let mut picture = TrafficPicture::default();
+        let now = Utc::now();
+        let aircraft_id = Uuid::new_v4().to_string();
+
+        picture
+            .update(position(&aircraft_id, now, 4.0), MaxSkew::default())
+            .unwrap();
+        let error = picture
+            .update(
+                position(&aircraft_id, now - Duration::try_seconds(1).unwrap(), 4.1),
+                MaxSkew::default(),
+            )
+            .unwrap_err();
+        assert_eq!(error, TelemetryError::OutOfOrder);
+        assert_eq!(
+            picture
+                .get(&aircraft_id)
+                .unwrap()
+                .position
+                .position
+                .longitude,
+            4.0
+        );
+    }
+
+    #[test]
+    fn test_traffic_picture_future() {
+        let mut picture = TrafficPicture::default();
+        let now = Utc::now();
+        let aircraft_id = Uuid::new_v4().to_string();
+
+        // Dated beyond the allowed clock skew
+        let error = picture
+            .update(
+                position(&aircraft_id, now + Duration::try_minutes(1).unwrap(), 4.0),
+                MaxSkew::default(),
+            )
+            .unwrap_err();
+        assert_eq!(error, TelemetryError::Future);
+        assert!(picture.is_empty());
+
+        // Within the allowed clock skew, later reports are still accepted
+        picture
+            .update(
+                position(&aircraft_id, now + Duration::try_seconds(1).unwrap(), 4.0),
+                MaxSkew::default(),
+            )
+            .unwrap();
+        picture
+            .update(
+                position(&aircraft_id, now + Duration::try_seconds(2).unwrap(), 4.1),
+                MaxSkew::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            picture
+                .get(&aircraft_id)
+                .unwrap()
+                .position
+                .position
+                .longitude,
+            4.1
+        );
+    }
+
+    #[test]
+    fn test_traffic_picture_invalid() {
+        let mut picture = TrafficPicture::default();
+        let now = Utc::now();
+        let aircraft_id = Uuid::new_v4().to_string();
+
+        let error = picture
+            .update(position("invalid", now, 4.0), MaxSkew::default())
+            .unwrap_err();
+        assert_eq!(error, TelemetryError::AircraftId);
+
+        let error = picture
+            .update(position(&aircraft_id, now, 200.0), MaxSkew::default())
+            .unwrap_err();
+        assert_eq!(error, TelemetryError::Position);
+
+        let mut tmp = position(&aircraft_id, now, 4.0);
+        tmp.heading_degrees = 360.0;
+        assert_eq!(
+            picture.update(tmp, MaxSkew::default()).unwrap_err(),
+            TelemetryError::Position
+        );
+
+        let mut tmp = position(&aircraft_id, now, 4.0);
+        tmp.ground_speed_mps = f64::NAN;
+        assert_eq!(
+            picture.update(tmp, MaxSkew::default()).unwrap_err(),
+            TelemetryError::Position
+        );
+
+        assert!(picture.is_empty());
+    }
+
+    #[test]
+    fn test_telemetry_error_display() {
+        assert_eq!(
+            TelemetryError::AircraftId.to_string(),
+            "invalid aircraft ID."
+        );
+        assert_eq!(
+            TelemetryError::Position.to_string(),
+            "position or velocity out of range."
+        );
+        assert_eq!(
+            TelemetryError::OutOfOrder.to_string(),
+            "report is older than the latest known position."
+        );
+        assert_eq!(
+            TelemetryError::Future.to_string(),
+            "report is dated in the future."
+        );
+    }
+}
@@ -0,0 +1,490 @@
+//! Flight plan updates pushed to aircraft
+//!
+//! The flight plans of all aircraft are periodically compared with the
+//!  version last sent to them, and every change is published as a
+//!  numbered update. Aircraft subscribe to the updates of their own
+//!  flight plans instead of polling for them, and resume a broken
+//!  subscription from the sequence number of the last update received.
+
+use super::annotate_flight_plans;
+use super::cache::get_plan_cache;
+use super::vertiports::AlternatePolicy;
+use crate::config::Config;
+use crate::rest::api::{FlightPlan, FlightState, PlanUpdate, PlanUpdateKind};
+use lib_common::time::{DateTime, Utc};
+use std::collections::{HashMap, HashSet, VecDeque};
+use tokio::sync::{broadcast, Mutex, OnceCell};
+
+/// Number of updates kept for resuming subscriptions
+pub const REPLAY_CAPACITY: usize = 1000;
+
+/// Number of updates buffered for each subscriber before it lags behind
+pub const UPDATE_CHANNEL_CAPACITY: usize = 256;
+
+/// Flight plan updates published by this service
+static UPDATES: OnceCell<Mutex<UpdateBus>> = OnceCell::const_new();
+
+/// Returns the flight plan updates published by this service
+pub async fn get_updates() -> &'static Mutex<UpdateBus> {
+    UPDATES
+        .get_or_init(|| async { Mutex::new(UpdateBus::default()) })
+        .await
+}
+
+/// Returns true if two versions of a flight plan are identical
+fn same_plan(a: &FlightPlan, b: &FlightPlan) -> bool {
+    match (serde_json::to_value(a), serde_json::to_value(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Classifies the change between the version of a flight plan last sent
+///  to its aircraft and the current version, None if nothing changed
+pub fn classify(previous: Option<&FlightPlan>, plan: &FlightPlan) -> Option<PlanUpdateKind> {
+    let Some(previous) = previous else {
+        return Some(PlanUpdateKind::New);
+    };
+
+    if same_plan(previous, plan) {
+        return None;
+    }
+
+    if plan.state == FlightState::Cancelled && previous.state != FlightState::Cancelled {
+        return Some(PlanUpdateKind::Cancelled);
+    }
+
+    let mut advised = previous.clone();
+    advised.advisories = plan.advisories.clone();
+    advised.weather_alerts = plan.weather_alerts.clone();
+    if same_plan(&advised, plan) {
+        Some(PlanUpdateKind::Advisory)
+    } else {
+        Some(PlanUpdateKind::Amended)
+    }
+}
+
+/// Numbered log of flight plan updates
+#[derive(Debug)]
+pub struct UpdateBus {
+    /// Sequence number of the last published update
+    sequence: u64,
+
+    /// Most recent updates, oldest first
+    log: VecDeque<PlanUpdate>,
+
+    /// Version of each flight plan last published, by flight plan ID
+    latest: HashMap<String, FlightPlan>,
+
+    /// Publishes new updates to subscribers
+    sender: broadcast::Sender<PlanUpdate>,
+}
+
+impl Default for UpdateBus {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
+        UpdateBus {
+            sequence: 0,
+            log: VecDeque::new(),
+            latest: HashMap::new(),
+            sender,
+        }
+    }
+}
+
+impl UpdateBus {
+    /// Sequence number of the last published update
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Number of flight plans tracked
+    pub fn len(&self) -> usize {
+        self.latest.len()
+    }
+
+    /// True if no flight plans are tracked
+    pub fn is_empty(&self) -> bool {
+        self.latest.is_empty()
+    }
+
+    /// Subscribes to new updates
+    pub fn subscribe(&self) -> broadcast::Receiver<PlanUpdate> {
+        self.sender.subscribe()
+    }
+
+    /// Publishes the current version of a flight plan if it changed since
+    ///  it was last published
+    pub fn publish(&mut self, plan: FlightPlan, now: DateTime<Utc>) -> Option<PlanUpdate> {
+        let kind = classify(self.latest.get(&plan.flight_uuid), &plan)?;
+        self.latest.insert(plan.flight_uuid.clone(), plan.clone());
+        Some(self.push(kind, plan, now))
+    }
+
+    /// Numbers an update, logs it and sends it to subscribers
+    fn push(&mut self, kind: PlanUpdateKind, plan: FlightPlan, now: DateTime<Utc>) -> PlanUpdate {
+        self.sequence += 1;
+        let update = PlanUpdate {
+            sequence: self.sequence,
+            aircraft_id: plan.aircraft_id.clone(),
+            fp_id: plan.flight_uuid.clone(),
+            kind,
+            plan,
+            timestamp: now,
+        };
+
+        common_debug!(
+            "flight plan {} update {}: {:?}.",
+            update.fp_id,
+            update.sequence,
+            update.kind
+        );
+
+        self.log.push_back(update.clone());
+        while self.log.len() > REPLAY_CAPACITY {
+            self.log.pop_front();
+        }
+
+        // No subscribers is not an error
+        let _ = self.sender.send(update.clone());
+        update
+    }
+
+    /// Publishes a new lifecycle state of a flight plan already published
+    pub fn publish_state(
+        &mut self,
+        fp_id: &str,
+        state: FlightState,
+        now: DateTime<Utc>,
+    ) -> Option<PlanUpdate> {
+        let mut plan = self.latest.get(fp_id)?.clone();
+        plan.state = state;
+        self.publish(plan, now)
+    }
+
+    /// Publishes the removal of the flight plans that are not in the given
+    ///  set, and stops tracking them
+    pub fn retain(&mut self, fp_ids: &HashSet<String>, now: DateTime<Utc>) -> Vec<PlanUpdate> {
+        let mut removed = self
+            .latest
+            .keys()
+            .filter(|fp_id| !fp_ids.contains(*fp_id))
+            .cloned()
+            .collect::<Vec<String>>();
+        removed.sort();
+
+        let mut updates = vec![];
+        for fp_id in removed {
+            if let Some(plan) = self.latest.remove(&fp_id) {
+                updates.push(self.push(PlanUpdateKind::Removed, plan, now));
+            }
+        }
+
+        updates
+    }
+
+    /// Updates of an aircraft published after the given resume token
+    ///
+    /// Without a resume token, or if updates after it are no longer kept,
+    ///  a snapshot of the current flight plans of the aircraft is returned
+    ///  instead.
+    pub fn replay(
+        &self,
+        aircraft_id: &str,
+        resume_token: Option<u64>,
+        now: DateTime<Utc>,
+    ) -> Vec<PlanUpdate> {
+        let oldest = self
+            .log
+            .front()
+            .map(|update| update.sequence)
+            .unwrap_or(self.sequence + 1);
+
+        match resume_token {
+            Some(token) if token <= self.sequence && token + 1 >= oldest => self
+                .log
+                .iter()
+                .filter(|update| update.sequence > token && update.aircraft_id == aircraft_id)
+                .cloned()
+                .collect(),
+            _ => self.snapshot(aircraft_id, now),
+        }
+    }
+
+    /// Current flight plans of an aircraft, ordered by departure
+    fn snapshot(&self, aircraft_id: &str, now: DateTime<Utc>) -> Vec<PlanUpdate> {
+        let mut plans = self
+            .latest
+            .values()
+            .filter(|plan| plan.aircraft_id == aircraft_id)
+            .collect::<Vec<_>>();
+        plans.sort_by_key(|plan| plan.origin_timeslot_start);
+
+        plans
+            .into_iter()
+            .map(|plan| PlanUpdate {
+                sequence: self.sequence,
+                aircraft_id: plan.aircraft_id.clone(),
+                fp_id: plan.flight_uuid.clone(),
+                kind: PlanUpdateKind::Snapshot,
+                plan: plan.clone(),
+                timestamp: now,
+            })
+            .collect()
+    }
+}
+
+/// Publishes lifecycle state changes as soon as they happen, without
+///  waiting for the next check of the flight plans
+pub async fn publish_state(fp_id: &str, state: FlightState) {
+    get_updates()
+        .await
+        .lock()
+        .await
+        .publish_state(fp_id, state, Utc::now());
+}
+
+/// Publishes the changes to the cached flight plans of all aircraft
+pub async fn monitor_updates(alternates: &AlternatePolicy) -> usize {
+    let now = Utc::now();
+    let mut plans = get_plan_cache().await.lock().await.plans();
+    annotate_flight_plans(&mut plans, alternates).await;
+
+    let fp_ids = plans
+        .iter()
+        .map(|plan| plan.flight_uuid.clone())
+        .collect::<HashSet<_>>();
+
+    let mut updates = get_updates().await.lock().await;
+    let published = plans
+        .into_iter()
+        .filter_map(|plan| updates.publish(plan, now))
+        .count();
+
+    // Plans that left the cache are no longer returned to their aircraft
+    published + updates.retain(&fp_ids, now).len()
+}
+
+/// Periodically publishes the changes to the flight plans of all aircraft
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) infinite loop, tested through monitor_updates
+pub async fn updates_loop(config: Config) {
+    let alternates = AlternatePolicy::from(&config);
+    let period = std::time::Duration::from_secs(config.updates_interval_seconds.max(1) as u64);
+    let mut interval = tokio::time::interval(period);
+
+    common_info!("flight plan updates started.");
+    loop {
+        interval.tick().await;
+        let published = monitor_updates(&alternates).await;
+        common_debug!("published {published} flight plan updates.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::conflict::SeparationMinima;
+    use crate::grpc::client::GrpcClients;
+    use crate::rest::api::{Advisory, ManeuverType, WeatherAlert, WeatherAlertType};
+    use crate::test_util::{mock_flight_plan_data, mock_plan};
+    use lib_common::time::Duration;
+
+    /// Path of the updated flight plans
+    const PATH: [(f64, f64, f64); 2] = [(-37.0, 147.0, 100.0), (-37.0, 147.02, 100.0)];
+
+    #[test]
+    fn test_classify() {
+        let now = Utc::now();
+        let previous = mock_plan(&PATH, now);
+        assert_eq!(classify(None, &previous), Some(PlanUpdateKind::New));
+        assert_eq!(classify(Some(&previous), &previous), None);
+
+        let mut advised = previous.clone();
+        advised.advisories.push(Advisory {
+            flight_uuid: previous.flight_uuid.clone(),
+            aircraft_id: previous.aircraft_id.clone(),
+            conflicting_flight_uuid: "other".to_string(),
+            maneuver: ManeuverType::Delay,
+            value: 60.0,
+            added_flight_time_seconds: 60.0,
+            added_energy_meters: 0.0,
+            path: previous.path.clone(),
+            issued: now,
+        });
+        assert_eq!(
+            classify(Some(&previous), &advised),
+            Some(PlanUpdateKind::Advisory)
+        );
+
+        let mut flagged = previous.clone();
+        flagged.weather_alerts.push(WeatherAlert {
+            flight_uuid: previous.flight_uuid.clone(),
+            aircraft_id: previous.aircraft_id.clone(),
+            alert_type: WeatherAlertType::Wind,
+            hazard_id: None,
+            violations: vec![],
+            vertiport_id: Some(previous.origin_vertiport_id.clone()),
+            value: Some(20.0),
+            limit: Some(15.0),
+            issued: now,
+        });
+        assert_eq!(
+            classify(Some(&previous), &flagged),
+            Some(PlanUpdateKind::Advisory)
+        );
+
+        let mut amended = previous.clone();
+        amended.target_vertipad_id = "other".to_string();
+        assert_eq!(
+            classify(Some(&previous), &amended),
+            Some(PlanUpdateKind::Amended)
+        );
+
+        let mut cancelled = previous.clone();
+        cancelled.state = FlightState::Cancelled;
+        assert_eq!(
+            classify(Some(&previous), &cancelled),
+            Some(PlanUpdateKind::Cancelled)
+        );
+    }
+
+    #[test]
+    fn test_publish_and_replay() {
+        let now = Utc::now();
+        let mut bus = UpdateBus::default();
+        let mut receiver = bus.subscribe();
+
+        let first = mock_plan(&PATH, now);
+        let aircraft_id = first.aircraft_id.clone();
+        let update = bus.publish(first.clone(), now).unwrap();
+        assert_eq!(update.sequence, 1);
+        assert_eq!(update.kind, PlanUpdateKind::New);
+        assert_eq!(receiver.try_recv().unwrap().fp_id, first.flight_uuid);
+
+        // Unchanged plans are not published again
+        assert!(bus.publish(first.clone(), now).is_none());
+        assert_eq!(bus.sequence(), 1);
+
+        // Updates of other aircraft are not replayed
+        let other = mock_plan(&PATH, now);
+        bus.publish(other.clone(), now).unwrap();
+
+        let update = bus
+            .publish_state(&first.flight_uuid, FlightState::Cancelled, now)
+            .unwrap();
+        assert_eq!(update.sequence, 3);
+        assert_eq!(update.kind, PlanUpdateKind::Cancelled);
+        assert!(bus
+            .publish_state("unknown", FlightState::Cancelled, now)
+            .is_none());
+
+        let replay = bus.replay(&aircraft_id, Some(1), now);
+        assert_eq!(replay.len(), 1);
+        assert_eq!(replay[0].sequence, 3);
+        assert!(bus.replay(&aircraft_id, Some(3), now).is_empty());
+
+        // No token or a token from the future gets a snapshot
+        for token in [None, Some(10)] {
+            let replay = bus.replay(&aircraft_id, token, now);
+            assert_eq!(replay.len(), 1);
+            assert_eq!(replay[0].kind, PlanUpdateKind::Snapshot);
+            assert_eq!(replay[0].plan.state, FlightState::Cancelled);
+        }
+
+        let removed = bus.retain(&HashSet::from([other.flight_uuid.clone()]), now);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].sequence, 4);
+        assert_eq!(removed[0].fp_id, first.flight_uuid);
+        assert_eq!(removed[0].kind, PlanUpdateKind::Removed);
+        assert_eq!(receiver.try_recv().unwrap().fp_id, other.flight_uuid);
+        assert_eq!(receiver.try_recv().unwrap().sequence, 3);
+        assert_eq!(receiver.try_recv().unwrap().kind, PlanUpdateKind::Removed);
+        assert_eq!(bus.len(), 1);
+        assert!(bus.replay(&aircraft_id, None, now).is_empty());
+
+        // The removal is replayed to aircraft resuming their subscription
+        let replay = bus.replay(&aircraft_id, Some(3), now);
+        assert_eq!(replay.len(), 1);
+        assert_eq!(replay[0].kind, PlanUpdateKind::Removed);
+        assert_eq!(bus.retain(&HashSet::new(), now).len(), 1);
+        assert!(bus.is_empty());
+    }
+
+    #[test]
+    fn test_replay_expired() {
+        let now = Utc::now();
+        let mut bus = UpdateBus::default();
+        let mut first = mock_plan(&PATH, now);
+        let aircraft_id = first.aircraft_id.clone();
+        bus.publish(first.clone(), now).unwrap();
+
+        for i in 0..REPLAY_CAPACITY {
+            first.session_id = format!("session {i}");
+            bus.publish(first.clone(), now).unwrap();
+        }
+
+        // The update after token 0 is no longer kept
+        let replay = bus.replay(&aircraft_id, Some(0), now);
+        assert_eq!(replay.len(), 1);
+        assert_eq!(replay[0].kind, PlanUpdateKind::Snapshot);
+
+        let replay = bus.replay(&aircraft_id, Some(1), now);
+        assert_eq!(replay.len(), REPLAY_CAPACITY);
+        assert!(replay
+            .iter()
+            .all(|update| update.kind == PlanUpdateKind::Amended));
+    }
+
+    #[tokio::test]
+    async fn test_monitor_updates() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config.clone());
+        let minima = SeparationMinima::from(&config);
+        let now = Utc::now();
+
+        let data = mock_flight_plan_data(
+            &[(-37.5, 147.5, 100.0), (-37.5, 147.52, 100.0)],
+            now,
+            now + Duration::try_minutes(10).unwrap(),
+        );
+        let aircraft_id = data.vehicle_id.clone();
+        let fp_id = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        let policy = crate::common::cache::CachePolicy::from(&config);
+        crate::common::cache::sync_plan_cache(&policy, &minima, &grpc_clients)
+            .await
+            .unwrap();
+        monitor_updates(&AlternatePolicy::from(&config)).await;
+        let replay = get_updates()
+            .await
+            .lock()
+            .await
+            .replay(&aircraft_id, None, now);
+        assert_eq!(replay.len(), 1);
+        assert_eq!(replay[0].fp_id, fp_id);
+
+        let token = replay[0].sequence;
+        let id = lib_common::uuid::Uuid::parse_str(&fp_id).unwrap();
+        crate::common::cancel_flight(id, &grpc_clients)
+            .await
+            .unwrap();
+
+        let replay = get_updates()
+            .await
+            .lock()
+            .await
+            .replay(&aircraft_id, Some(token), now);
+        let cancelled = replay.iter().find(|update| update.fp_id == fp_id).unwrap();
+        assert_eq!(cancelled.kind, PlanUpdateKind::Cancelled);
+    }
+}
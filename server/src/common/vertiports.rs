@@ -0,0 +1,833 @@
+//! Vertiport registry and diversion to alternate landing sites
+//!
+//! The enabled vertipads registered in svc-storage are periodically
+//!  fetched and grouped by vertiport, so that landing sites can be found
+//!  without querying svc-storage. Each flight plan is given the alternate
+//!  landing sites reachable from its target vertipad, and operators divert
+//!  airborne aircraft to one of them.
+
+use super::cache::{get_plan_cache, invalidate};
+use super::clearance::get_clearances;
+use super::conflict::Trajectory;
+use super::conformance::get_conformance_monitor;
+use super::contingency::MIN_GROUND_SPEED_MPS;
+use super::emergency::{get_vertipads, Vertipad};
+use super::geo;
+use super::holding::get_holding;
+use super::lifecycle::{apply_transition, check_transition, lock_flight};
+use super::traffic::get_traffic;
+use super::{get_flight_data, AckError};
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::{
+    Alternate, Clearance, ClearanceStatus, ClearanceType, Diversion, DivertRequest, FlightPlan,
+    FlightState, PointZ, VertipadStatus, Vertiport,
+};
+use crate::Config;
+use lib_common::time::{DateTime, Duration, Utc};
+use lib_common::uuid::Uuid;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use svc_storage_client_grpc::prelude::*;
+use tokio::sync::{OnceCell, RwLock};
+
+/// Maximum number of alternates attached to a flight plan
+pub const MAX_ALTERNATES: usize = 3;
+
+/// Vertiports known to this service, keyed by vertiport ID
+static VERTIPORTS: OnceCell<RwLock<VertiportRegistry>> = OnceCell::const_new();
+
+/// Returns the vertiport registry shared by the REST and gRPC servers
+pub async fn get_vertiports() -> &'static RwLock<VertiportRegistry> {
+    VERTIPORTS
+        .get_or_init(|| async { RwLock::new(VertiportRegistry::default()) })
+        .await
+}
+
+/// Errors diverting a flight plan
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DivertError {
+    /// Flight plan not found
+    FlightPlan,
+
+    /// Flight plan is not airborne
+    InvalidState,
+
+    /// Requested vertipad is not in the registry or can not receive the
+    ///  aircraft
+    Alternate,
+
+    /// No alternate within range can receive the aircraft
+    NoAlternate,
+
+    /// Dependencies not available
+    Unavailable,
+}
+
+impl Display for DivertError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DivertError::FlightPlan => write!(f, "flight plan not found."),
+            DivertError::InvalidState => write!(f, "flight plan is not airborne."),
+            DivertError::Alternate => write!(f, "vertipad not available."),
+            DivertError::NoAlternate => write!(f, "no alternate within range."),
+            DivertError::Unavailable => write!(f, "dependencies not available."),
+        }
+    }
+}
+
+/// Settings used to find alternates and divert aircraft to them
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AlternatePolicy {
+    /// Maximum distance from the target vertipad to an alternate
+    pub max_distance_meters: f64,
+
+    /// Time after the expected arrival that the vertipad of a diverted
+    ///  aircraft stays reserved
+    pub validity: Duration,
+}
+
+impl From<&Config> for AlternatePolicy {
+    fn from(config: &Config) -> Self {
+        AlternatePolicy {
+            max_distance_meters: config.alternate_max_distance_meters,
+            validity: Duration::try_seconds(config.clearance_validity_seconds as i64)
+                .unwrap_or(Duration::zero()),
+        }
+    }
+}
+
+impl Default for AlternatePolicy {
+    fn default() -> Self {
+        AlternatePolicy::from(&Config::new())
+    }
+}
+
+/// Center of a non-empty set of vertipads
+fn centroid(pads: &[VertipadStatus]) -> PointZ {
+    let count = pads.len().max(1) as f64;
+    PointZ {
+        latitude: pads.iter().map(|pad| pad.position.latitude).sum::<f64>() / count,
+        longitude: pads.iter().map(|pad| pad.position.longitude).sum::<f64>() / count,
+        altitude_meters: pads
+            .iter()
+            .map(|pad| pad.position.altitude_meters)
+            .sum::<f64>()
+            / count,
+    }
+}
+
+/// Vertiports with enabled vertipads, as last fetched from svc-storage
+#[derive(Debug, Default)]
+pub struct VertiportRegistry {
+    /// Vertiports keyed by vertiport ID
+    vertiports: HashMap<String, Vertiport>,
+
+    /// Time of the last refresh from svc-storage
+    synced: Option<DateTime<Utc>>,
+}
+
+impl VertiportRegistry {
+    /// Replaces the registry with the given vertipads, grouped by vertiport
+    ///
+    /// Closed vertipads are left out.
+    pub fn sync(&mut self, pads: Vec<Vertipad>, now: DateTime<Utc>) {
+        let mut grouped: HashMap<String, Vec<VertipadStatus>> = HashMap::new();
+        for pad in pads.into_iter().filter(|pad| pad.enabled) {
+            grouped
+                .entry(pad.vertiport_id)
+                .or_default()
+                .push(VertipadStatus {
+                    vertipad_id: pad.id,
+                    position: pad.position,
+                    occupied: pad.occupied,
+                });
+        }
+
+        self.vertiports = grouped
+            .into_iter()
+            .map(|(vertiport_id, mut vertipads)| {
+                vertipads.sort_by(|a, b| a.vertipad_id.cmp(&b.vertipad_id));
+                let vertiport = Vertiport {
+                    vertiport_id: vertiport_id.clone(),
+                    position: centroid(&vertipads),
+                    vertipads,
+                };
+
+                (vertiport_id, vertiport)
+            })
+            .collect();
+
+        self.synced = Some(now);
+    }
+
+    /// Returns a vertiport
+    pub fn get(&self, vertiport_id: &str) -> Option<&Vertiport> {
+        self.vertiports.get(vertiport_id)
+    }
+
+    /// Returns a vertipad and its vertiport
+    pub fn vertipad(&self, vertipad_id: &str) -> Option<(&Vertiport, &VertipadStatus)> {
+        self.vertiports.values().find_map(|vertiport| {
+            vertiport
+                .vertipads
+                .iter()
+                .find(|pad| pad.vertipad_id == vertipad_id)
+                .map(|pad| (vertiport, pad))
+        })
+    }
+
+    /// All vertiports, ordered by ID
+    pub fn vertiports(&self) -> Vec<Vertiport> {
+        let mut vertiports = self.vertiports.values().cloned().collect::<Vec<_>>();
+        vertiports.sort_by(|a, b| a.vertiport_id.cmp(&b.vertiport_id));
+        vertiports
+    }
+
+    /// Time of the last refresh from svc-storage
+    pub fn synced(&self) -> Option<DateTime<Utc>> {
+        self.synced
+    }
+
+    /// Number of vertiports
+    pub fn len(&self) -> usize {
+        self.vertiports.len()
+    }
+
+    /// True if there are no vertiports
+    pub fn is_empty(&self) -> bool {
+        self.vertiports.is_empty()
+    }
+}
+
+/// Average planned speed of a flight plan in meters per second, None if
+///  the timeslots leave no time to fly
+fn planned_speed_mps(plan: &FlightPlan) -> Option<f64> {
+    let seconds =
+        (plan.target_timeslot_start - plan.origin_timeslot_end).num_milliseconds() as f64 / 1000.0;
+    if seconds <= 0.0 {
+        return None;
+    }
+
+    let length = plan
+        .path
+        .windows(2)
+        .map(|leg| geo::distance_meters(&leg[0], &leg[1]))
+        .sum::<f64>();
+
+    Some(length / seconds)
+}
+
+/// Finds the alternate landing sites of a flight plan, nearest first
+///
+/// Each vertiport other than the target offers its nearest free vertipad.
+///  Alternates are within the maximum distance of the target vertipad, and
+///  within the distance flown at the planned speed in the endurance left
+///  at the planned arrival, when the endurance is known.
+pub fn alternates(
+    plan: &FlightPlan,
+    registry: &VertiportRegistry,
+    policy: &AlternatePolicy,
+) -> Vec<Alternate> {
+    let Some(target) = plan.path.last() else {
+        return vec![];
+    };
+
+    let mut range = policy.max_distance_meters;
+    if let Some(reserve) = plan.endurance_reserve_seconds {
+        let speed = planned_speed_mps(plan).unwrap_or(0.0);
+        range = range.min(reserve.max(0) as f64 * speed);
+    }
+
+    let mut alternates = registry
+        .vertiports
+        .values()
+        .filter(|vertiport| vertiport.vertiport_id != plan.target_vertiport_id)
+        .filter_map(|vertiport| {
+            vertiport
+                .vertipads
+                .iter()
+                .filter(|pad| !pad.occupied)
+                .map(|pad| Alternate {
+                    vertiport_id: vertiport.vertiport_id.clone(),
+                    vertipad_id: pad.vertipad_id.clone(),
+                    position: pad.position,
+                    distance_meters: geo::horizontal_distance_meters(target, &pad.position),
+                })
+                .min_by(|a, b| a.distance_meters.total_cmp(&b.distance_meters))
+        })
+        .filter(|alternate| alternate.distance_meters <= range)
+        .collect::<Vec<Alternate>>();
+
+    alternates.sort_by(|a, b| a.distance_meters.total_cmp(&b.distance_meters));
+    alternates.truncate(MAX_ALTERNATES);
+    alternates
+}
+
+/// Attaches the alternate landing sites of each flight plan
+pub async fn attach_alternates(plans: &mut [FlightPlan], policy: &AlternatePolicy) {
+    let registry = get_vertiports().await.read().await;
+    for plan in plans.iter_mut() {
+        plan.alternates = alternates(plan, &registry, policy);
+    }
+}
+
+/// Refreshes the vertiport registry from svc-storage
+///
+/// Returns the number of vertiports.
+pub async fn sync_vertiports(grpc_clients: &GrpcClients) -> Result<usize, DivertError> {
+    let pads = get_vertipads(grpc_clients)
+        .await
+        .map_err(|_| DivertError::Unavailable)?;
+
+    let mut registry = get_vertiports().await.write().await;
+    registry.sync(pads, Utc::now());
+    Ok(registry.len())
+}
+
+/// Path from a position directly to a vertipad at the current altitude,
+///  then down onto the vertipad
+pub fn divert_path(position: &PointZ, vertipad: &PointZ) -> Vec<PointZ> {
+    let mut above = *vertipad;
+    above.altitude_meters = position.altitude_meters;
+    vec![*position, above, *vertipad]
+}
+
+/// Restores the landing clearance a flight plan held before a diversion
+///  that failed, or removes the clearance reserving the alternate
+async fn restore_landing_clearance(fp_id: &str, previous: Option<Clearance>) {
+    let mut store = get_clearances().await.lock().await;
+    match previous {
+        Some(clearance) => store.record(clearance),
+        None => {
+            store.remove(fp_id, ClearanceType::Landing);
+        }
+    }
+}
+
+/// Diverts an airborne aircraft to an alternate landing site
+///
+/// The requested vertipad is used, or the nearest alternate not reserved
+///  by another flight plan. The aircraft flies directly from its latest
+///  reported position, or its planned position without telemetry. The
+///  vertipad is reserved with a landing clearance, and the flight plan is
+///  amended in svc-storage. The aircraft receives the amended flight plan
+///  and the diverted state with its flight plan updates.
+pub async fn divert_flight(
+    fp_id: Uuid,
+    request: &DivertRequest,
+    policy: &AlternatePolicy,
+    grpc_clients: &GrpcClients,
+) -> Result<Diversion, DivertError> {
+    let id = fp_id.to_string();
+    let _guard = lock_flight(&id).await;
+    let mut data = get_flight_data(fp_id, grpc_clients)
+        .await
+        .map_err(|e| match e {
+            AckError::NotFound => DivertError::FlightPlan,
+            _ => DivertError::Unavailable,
+        })?;
+
+    check_transition(&id, FlightState::Diverted)
+        .await
+        .map_err(|_| DivertError::InvalidState)?;
+
+    let object = flight_plan::Object {
+        id: id.clone(),
+        data: Some(data.clone()),
+    };
+
+    let mut plan = FlightPlan::try_from(object).map_err(|e| {
+        common_error!("could not convert flight plan {fp_id}: {e}");
+        DivertError::FlightPlan
+    })?;
+
+    // The endurance is only known for flight plans validated in the cache
+    plan.endurance_reserve_seconds = get_plan_cache()
+        .await
+        .lock()
+        .await
+        .aircraft(&plan.aircraft_id)
+        .and_then(|cached| {
+            cached
+                .plans
+                .into_iter()
+                .find(|cached| cached.flight_uuid == id)
+        })
+        .and_then(|cached| cached.endurance_reserve_seconds);
+
+    let candidates = {
+        let registry = get_vertiports().await.read().await;
+        match &request.vertipad_id {
+            Some(vertipad_id) => {
+                let (vertiport, pad) = registry
+                    .vertipad(vertipad_id)
+                    .filter(|(_, pad)| !pad.occupied && pad.vertipad_id != plan.target_vertipad_id)
+                    .ok_or(DivertError::Alternate)?;
+
+                vec![Alternate {
+                    vertiport_id: vertiport.vertiport_id.clone(),
+                    vertipad_id: pad.vertipad_id.clone(),
+                    position: pad.position,
+                    distance_meters: plan
+                        .path
+                        .last()
+                        .map(|target| geo::horizontal_distance_meters(target, &pad.position))
+                        .unwrap_or(0.0),
+                }]
+            }
+            None => alternates(&plan, &registry, policy),
+        }
+    };
+
+    let now = Utc::now();
+    let track = get_traffic()
+        .await
+        .read()
+        .await
+        .get(&plan.aircraft_id)
+        .map(|track| track.position.clone());
+
+    let planned = Trajectory::try_from(&plan).ok().and_then(|trajectory| {
+        trajectory.position_at(now).or_else(|| {
+            let waypoints = &trajectory.waypoints;
+            if now < trajectory.start() {
+                waypoints.first().map(|waypoint| waypoint.point)
+            } else {
+                waypoints.last().map(|waypoint| waypoint.point)
+            }
+        })
+    });
+
+    let position = track
+        .as_ref()
+        .map(|track| track.position)
+        .or(planned)
+        .ok_or(DivertError::FlightPlan)?;
+
+    let speed_mps = track
+        .as_ref()
+        .map(|track| track.ground_speed_mps)
+        .filter(|speed| *speed >= MIN_GROUND_SPEED_MPS)
+        .or_else(|| planned_speed_mps(&plan))
+        .unwrap_or(MIN_GROUND_SPEED_MPS)
+        .max(MIN_GROUND_SPEED_MPS);
+
+    // The previous landing clearance is restored if the amendment fails
+    let (alternate, path, arrival, clearance, previous) = {
+        let mut store = get_clearances().await.lock().await;
+        let Some(alternate) = candidates
+            .into_iter()
+            .find(|alternate| store.pad_holder(&alternate.vertipad_id, &id, now).is_none())
+        else {
+            return Err(match request.vertipad_id {
+                Some(_) => DivertError::Alternate,
+                None => DivertError::NoAlternate,
+            });
+        };
+
+        let path = divert_path(&position, &alternate.position);
+        let length = path
+            .windows(2)
+            .map(|leg| geo::distance_meters(&leg[0], &leg[1]))
+            .sum::<f64>();
+        let flight_ms = (length / speed_mps * 1000.0).round() as i64;
+        let arrival = now + Duration::try_milliseconds(flight_ms).unwrap_or(Duration::zero());
+
+        let clearance = Clearance {
+            fp_id: id.clone(),
+            aircraft_id: plan.aircraft_id.clone(),
+            vertipad_id: alternate.vertipad_id.clone(),
+            clearance_type: ClearanceType::Landing,
+            status: ClearanceStatus::Granted,
+            reason: None,
+            issued: now,
+            expires: Some(arrival + policy.validity),
+            priority: false,
+        };
+
+        let previous = store
+            .get(&id)
+            .into_iter()
+            .find(|existing| existing.clearance_type == ClearanceType::Landing);
+
+        store.record(clearance.clone());
+        (alternate, path, arrival, clearance, previous)
+    };
+
+    //
+    // Amend the flight plan record with the alternate
+    //
+    let pad_time = (plan.target_timeslot_end - plan.target_timeslot_start).max(Duration::zero());
+    data.target_vertiport_id = Some(alternate.vertiport_id.clone());
+    data.target_vertipad_id = alternate.vertipad_id.clone();
+    data.target_timeslot_start = Some(arrival.into());
+    data.target_timeslot_end = Some((arrival + pad_time).into());
+    data.path = Some(GeoLineStringZ {
+        points: path
+            .iter()
+            .map(|point| GeoPointZ {
+                x: point.longitude,
+                y: point.latitude,
+                z: point.altitude_meters,
+            })
+            .collect(),
+    });
+
+    let request = flight_plan::UpdateObject {
+        id: id.clone(),
+        data: Some(data.clone()),
+        mask: Some(FieldMask {
+            paths: vec![
+                "target_vertiport_id".to_string(),
+                "target_vertipad_id".to_string(),
+                "target_timeslot_start".to_string(),
+                "target_timeslot_end".to_string(),
+                "path".to_string(),
+            ],
+        }),
+    };
+
+    if let Err(e) = grpc_clients.storage.flight_plan.update(request).await {
+        common_error!("{}", e);
+        restore_landing_clearance(&id, previous).await;
+        return Err(DivertError::Unavailable);
+    }
+
+    if apply_transition(&id, FlightState::Diverted).await.is_err() {
+        restore_landing_clearance(&id, previous).await;
+        return Err(DivertError::InvalidState);
+    }
+
+    invalidate(&id).await;
+    get_holding().await.lock().await.remove(&id);
+
+    common_info!(
+        "flight plan {id} diverted to vertipad {} of vertiport {}.",
+        alternate.vertipad_id,
+        alternate.vertiport_id
+    );
+
+    //
+    // Monitor that the aircraft flies the amended flight plan
+    //
+    let object = flight_plan::Object {
+        id: id.clone(),
+        data: Some(data),
+    };
+
+    {
+        let mut monitor = get_conformance_monitor().await.lock().await;
+        monitor.deregister(&id);
+        match FlightPlan::try_from(object) {
+            Ok(amended) => {
+                if let Err(e) = monitor.register(&amended) {
+                    common_warn!("could not monitor conformance of flight plan {id}: {e}");
+                }
+            }
+            Err(e) => common_warn!("could not monitor conformance of flight plan {id}: {e}"),
+        }
+    }
+
+    Ok(Diversion {
+        fp_id: id,
+        aircraft_id: plan.aircraft_id,
+        vertiport_id: alternate.vertiport_id,
+        vertipad_id: alternate.vertipad_id,
+        path,
+        arrival,
+        clearance,
+    })
+}
+
+/// Periodically refreshes the vertiport registry from svc-storage
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) infinite loop, tested through sync_vertiports
+pub async fn vertiports_loop(config: Config) {
+    let grpc_clients = crate::grpc::client::get_clients().await;
+    let period = std::time::Duration::from_secs(config.vertiports_interval_seconds.max(1) as u64);
+    let mut interval = tokio::time::interval(period);
+
+    common_info!("vertiport registry refresh started.");
+    loop {
+        interval.tick().await;
+        match sync_vertiports(grpc_clients).await {
+            Ok(count) => common_debug!("vertiport registry holds {count} vertiports."),
+            Err(e) => common_warn!("could not refresh vertiport registry: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::lifecycle::get_lifecycles;
+    use crate::test_util::{mock_flight_plan_data, mock_plan};
+
+    fn pad(vertiport_id: &str, latitude: f64, longitude: f64) -> Vertipad {
+        Vertipad {
+            id: Uuid::new_v4().to_string(),
+            vertiport_id: vertiport_id.to_string(),
+            position: PointZ {
+                latitude,
+                longitude,
+                altitude_meters: 0.0,
+            },
+            enabled: true,
+            occupied: false,
+        }
+    }
+
+    /// Path heading east along latitude -46.0
+    const PATH: [(f64, f64, f64); 2] = [(-46.0, 135.0, 100.0), (-46.0, 135.05, 100.0)];
+
+    #[test]
+    fn test_registry_sync() {
+        let now = Utc::now();
+        let mut closed = pad("a", -46.0, 135.0);
+        closed.enabled = false;
+        let pads = vec![
+            pad("a", -46.0, 135.0),
+            pad("a", -46.0, 135.002),
+            closed.clone(),
+            pad("b", -46.1, 135.0),
+        ];
+
+        let mut registry = VertiportRegistry::default();
+        assert!(registry.is_empty());
+        registry.sync(pads.clone(), now);
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.synced(), Some(now));
+
+        let vertiport = registry.get("a").unwrap();
+        assert_eq!(vertiport.vertipads.len(), 2);
+        assert!((vertiport.position.longitude - 135.001).abs() < 1e-9);
+        assert!(registry.vertipad(&closed.id).is_none());
+
+        let (vertiport, status) = registry.vertipad(&pads[3].id).unwrap();
+        assert_eq!(vertiport.vertiport_id, "b");
+        assert_eq!(status.position, pads[3].position);
+
+        let ids = registry
+            .vertiports()
+            .into_iter()
+            .map(|vertiport| vertiport.vertiport_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_alternates() {
+        let plan = mock_plan(&PATH, Utc::now());
+        let policy = AlternatePolicy {
+            max_distance_meters: 20000.0,
+            validity: Duration::try_seconds(120).unwrap(),
+        };
+
+        let mut occupied = pad("near", -46.0, 135.051);
+        occupied.occupied = true;
+        let pads = vec![
+            pad(&plan.target_vertiport_id, -46.0, 135.05),
+            occupied,
+            pad("near", -46.0, 135.06),
+            pad("near", -46.0, 135.07),
+            pad("mid", -46.0, 135.11),
+            pad("far", -46.0, 135.2),
+            pad("farther", -46.0, 135.25),
+            pad("out", -46.0, 136.0),
+        ];
+
+        let mut registry = VertiportRegistry::default();
+        registry.sync(pads.clone(), Utc::now());
+
+        let alternates = alternates(&plan, &registry, &policy);
+        let ids = alternates
+            .iter()
+            .map(|alternate| alternate.vertipad_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![&pads[2].id, &pads[4].id, &pads[5].id]);
+        assert!(alternates[0].distance_meters > 700.0 && alternates[0].distance_meters < 800.0);
+
+        // The path is flown at about 6.5 m/s, a 10 minute reserve reaches
+        //  about 3.9 km
+        let mut limited = plan.clone();
+        limited.endurance_reserve_seconds = Some(600);
+        let ids = super::alternates(&limited, &registry, &policy)
+            .into_iter()
+            .map(|alternate| alternate.vertiport_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["near"]);
+
+        limited.endurance_reserve_seconds = Some(-60);
+        assert!(super::alternates(&limited, &registry, &policy).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_divert_flight() {
+        let grpc_clients = GrpcClients::default(Config::default());
+        let policy = AlternatePolicy::default();
+        let now = Utc::now();
+
+        let mut pad_ids = vec![];
+        for longitude in [135.56, 135.6] {
+            let object = grpc_clients
+                .storage
+                .vertipad
+                .insert(vertipad::Data {
+                    vertiport_id: Uuid::new_v4().to_string(),
+                    name: "alternate".to_string(),
+                    geo_location: Some(GeoPointZ {
+                        x: longitude,
+                        y: -46.5,
+                        z: 0.0,
+                    }),
+                    schedule: None,
+                    enabled: true,
+                    occupied: false,
+                })
+                .await
+                .unwrap()
+                .into_inner()
+                .object
+                .unwrap();
+            pad_ids.push(object.id);
+        }
+
+        assert!(sync_vertiports(&grpc_clients).await.unwrap() >= 2);
+
+        let mut fp_ids = vec![];
+        for flight_status in [
+            flight_plan::FlightStatus::InFlight,
+            flight_plan::FlightStatus::InFlight,
+            flight_plan::FlightStatus::Ready,
+        ] {
+            let mut data = mock_flight_plan_data(
+                &[(-46.5, 135.5, 100.0), (-46.5, 135.55, 100.0)],
+                now - Duration::try_minutes(5).unwrap(),
+                now + Duration::try_minutes(5).unwrap(),
+            );
+            data.flight_status = flight_status as i32;
+            data.carrier_ack = None;
+            let object = grpc_clients
+                .storage
+                .flight_plan
+                .insert(data)
+                .await
+                .unwrap()
+                .into_inner()
+                .object
+                .unwrap();
+            fp_ids.push(Uuid::parse_str(&object.id).unwrap());
+        }
+
+        let nearest = DivertRequest { vertipad_id: None };
+        let diversion = divert_flight(fp_ids[0], &nearest, &policy, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(diversion.vertipad_id, pad_ids[0]);
+        assert_eq!(diversion.clearance.clearance_type, ClearanceType::Landing);
+        assert_eq!(diversion.path.len(), 3);
+        assert!(diversion.arrival > now);
+
+        // The flight plan record is amended
+        let data = get_flight_data(fp_ids[0], &grpc_clients).await.unwrap();
+        assert_eq!(data.target_vertipad_id, pad_ids[0]);
+        assert_eq!(data.path.unwrap().points.len(), 3);
+        assert_eq!(
+            get_lifecycles()
+                .await
+                .lock()
+                .await
+                .state(&fp_ids[0].to_string()),
+            Some(FlightState::Diverted)
+        );
+
+        // The reserved vertipad is skipped for the next diversion
+        let error = divert_flight(
+            fp_ids[1],
+            &DivertRequest {
+                vertipad_id: Some(pad_ids[0].clone()),
+            },
+            &policy,
+            &grpc_clients,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, DivertError::Alternate);
+
+        let diversion = divert_flight(fp_ids[1], &nearest, &policy, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(diversion.vertipad_id, pad_ids[1]);
+
+        let error = divert_flight(fp_ids[2], &nearest, &policy, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, DivertError::InvalidState);
+
+        let error = divert_flight(Uuid::new_v4(), &nearest, &policy, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, DivertError::FlightPlan);
+    }
+
+    #[tokio::test]
+    async fn test_restore_landing_clearance() {
+        let now = Utc::now();
+        let fp_id = Uuid::new_v4().to_string();
+        let clearance = |vertipad_id: &str| Clearance {
+            fp_id: fp_id.clone(),
+            aircraft_id: Uuid::new_v4().to_string(),
+            vertipad_id: vertipad_id.to_string(),
+            clearance_type: ClearanceType::Landing,
+            status: ClearanceStatus::Granted,
+            reason: None,
+            issued: now,
+            expires: Some(now + Duration::try_minutes(5).unwrap()),
+            priority: false,
+        };
+
+        // The alternate reservation is replaced by the previous clearance
+        let previous = clearance("target");
+        get_clearances()
+            .await
+            .lock()
+            .await
+            .record(clearance("alternate"));
+        restore_landing_clearance(&fp_id, Some(previous.clone())).await;
+        let clearances = get_clearances().await.lock().await.get(&fp_id);
+        assert_eq!(clearances, vec![previous]);
+
+        // The alternate reservation is removed without a previous clearance
+        get_clearances()
+            .await
+            .lock()
+            .await
+            .record(clearance("alternate"));
+        restore_landing_clearance(&fp_id, None).await;
+        assert!(get_clearances().await.lock().await.get(&fp_id).is_empty());
+    }
+
+    #[test]
+    fn test_divert_error_display() {
+        assert_eq!(
+            DivertError::FlightPlan.to_string(),
+            "flight plan not found."
+        );
+        assert_eq!(
+            DivertError::InvalidState.to_string(),
+            "flight plan is not airborne."
+        );
+        assert_eq!(
+            DivertError::Alternate.to_string(),
+            "vertipad not available."
+        );
+        assert_eq!(
+            DivertError::NoAlternate.to_string(),
+            "no alternate within range."
+        );
+        assert_eq!(
+            DivertError::Unavailable.to_string(),
+            "dependencies not available."
+        );
+    }
+}
@@ -0,0 +1,792 @@
+//! Weather hazard ingestion and weather-aware flight plan re-evaluation
+//!
+//! Weather reports carry gridded surface wind and visibility, and hazard
+//!  areas such as convective cells. Reports are ingested through the REST
+//!  API or read from a local file standing in for a weather feed. The
+//!  flight plans returned to aircraft are periodically re-evaluated, and
+//!  those crossing an active hazard or with wind or visibility outside of
+//!  limits at their origin or target vertiport are flagged.
+
+use super::cache::get_plan_cache;
+use super::conflict::Trajectory;
+use super::geo;
+use super::zones::{self, ZoneError};
+use crate::rest::api::{
+    FlightPlan, PointZ, TimeWindow, WeatherAlert, WeatherAlertType, WeatherHazard, WeatherReport,
+    WeatherSample, Zone,
+};
+use crate::Config;
+use lib_common::time::{DateTime, Utc};
+use lib_common::uuid::Uuid;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::time::SystemTime;
+use tokio::sync::{Mutex, OnceCell};
+
+/// Latest weather report and the flight plans it affects
+static WEATHER: OnceCell<Mutex<WeatherStore>> = OnceCell::const_new();
+
+/// Returns the weather known to this service
+pub async fn get_weather() -> &'static Mutex<WeatherStore> {
+    WEATHER
+        .get_or_init(|| async { Mutex::new(WeatherStore::default()) })
+        .await
+}
+
+/// Errors ingesting a weather report
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WeatherError {
+    /// Hazard area is invalid
+    Zone(ZoneError),
+
+    /// Report or hazard validity ends before it starts
+    Window,
+
+    /// Weather sample has an invalid location, speed or visibility
+    Sample,
+
+    /// Weather file could not be read
+    File,
+
+    /// Weather file is not a valid weather report
+    Json,
+}
+
+impl Display for WeatherError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            WeatherError::Zone(e) => write!(f, "{e}"),
+            WeatherError::Window => write!(f, "weather validity ends before it starts."),
+            WeatherError::Sample => write!(f, "invalid weather sample."),
+            WeatherError::File => write!(f, "could not read weather file."),
+            WeatherError::Json => write!(f, "invalid weather report."),
+        }
+    }
+}
+
+/// Wind and visibility limits at vertiports
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct WeatherLimits {
+    /// Maximum wind or gust speed in meters per second
+    pub max_wind_mps: f64,
+
+    /// Minimum visibility in meters
+    pub min_visibility_meters: f64,
+
+    /// Maximum distance in meters from a vertiport to the grid point used
+    ///  for its weather
+    pub sample_radius_meters: f64,
+}
+
+impl From<&Config> for WeatherLimits {
+    fn from(config: &Config) -> Self {
+        WeatherLimits {
+            max_wind_mps: config.weather_max_wind_mps,
+            min_visibility_meters: config.weather_min_visibility_meters,
+            sample_radius_meters: config.weather_sample_radius_meters,
+        }
+    }
+}
+
+impl Default for WeatherLimits {
+    fn default() -> Self {
+        WeatherLimits::from(&Config::new())
+    }
+}
+
+/// Converts a hazard into a zone active between its start and end
+///
+/// The zone shares the ID of the hazard.
+pub fn to_zone(hazard: &WeatherHazard) -> Zone {
+    Zone {
+        id: hazard.id.clone(),
+        name: hazard.name.clone(),
+        vertices: hazard.vertices.clone(),
+        floor_meters: hazard.floor_meters,
+        ceiling_meters: hazard.ceiling_meters,
+        windows: vec![TimeWindow {
+            start: hazard.start,
+            end: hazard.end,
+        }],
+    }
+}
+
+/// Checks that a weather sample can be used to evaluate flight plans
+pub fn validate_sample(sample: &WeatherSample) -> Result<(), WeatherError> {
+    let located = (-90.0..=90.0).contains(&sample.point.latitude)
+        && (-180.0..=180.0).contains(&sample.point.longitude);
+
+    let measured = [
+        sample.wind_speed_mps,
+        sample.wind_gust_mps.unwrap_or(0.0),
+        sample.visibility_meters,
+    ]
+    .iter()
+    .all(|value| value.is_finite() && *value >= 0.0);
+
+    if !located || !measured || !sample.wind_direction_degrees.is_finite() {
+        return Err(WeatherError::Sample);
+    }
+
+    Ok(())
+}
+
+/// Wind speed to compare with the wind limit, the gust speed if higher
+///  than the mean speed
+pub fn wind_mps(sample: &WeatherSample) -> f64 {
+    sample
+        .wind_speed_mps
+        .max(sample.wind_gust_mps.unwrap_or(0.0))
+}
+
+/// Latest weather report and the alerts issued for flight plans
+#[derive(Debug, Default)]
+pub struct WeatherStore {
+    /// Window in which the weather grid is valid, None without a report
+    valid: Option<TimeWindow>,
+
+    /// Weather at the grid points
+    samples: Vec<WeatherSample>,
+
+    /// Hazards keyed by hazard ID
+    hazards: HashMap<String, WeatherHazard>,
+
+    /// Alerts keyed by flight plan ID
+    alerts: HashMap<String, Vec<WeatherAlert>>,
+}
+
+impl WeatherStore {
+    /// Replaces the weather grid and hazards with those of a report,
+    ///  assigning IDs to hazards without one
+    ///
+    /// Hazards that have already expired are skipped. Returns the number of
+    ///  hazards stored.
+    pub fn ingest(
+        &mut self,
+        report: WeatherReport,
+        now: DateTime<Utc>,
+    ) -> Result<usize, WeatherError> {
+        if report.valid_until <= report.valid_from {
+            return Err(WeatherError::Window);
+        }
+
+        for sample in report.samples.iter() {
+            validate_sample(sample)?;
+        }
+
+        let mut hazards = HashMap::new();
+        for mut hazard in report.hazards {
+            if hazard.end <= hazard.start {
+                return Err(WeatherError::Window);
+            }
+
+            if hazard.id.is_empty() {
+                hazard.id = Uuid::new_v4().to_string();
+            }
+
+            zones::validate(&to_zone(&hazard)).map_err(WeatherError::Zone)?;
+            if hazard.end >= now {
+                hazards.insert(hazard.id.clone(), hazard);
+            }
+        }
+
+        self.valid = Some(TimeWindow {
+            start: report.valid_from,
+            end: report.valid_until,
+        });
+        self.samples = report.samples;
+        self.hazards = hazards;
+
+        Ok(self.hazards.len())
+    }
+
+    /// Removes expired hazards and the weather grid once it has expired,
+    ///  returning the IDs of the removed hazards
+    pub fn expire(&mut self, now: DateTime<Utc>) -> Vec<String> {
+        if self.valid.is_some_and(|valid| valid.end < now) {
+            self.valid = None;
+            self.samples.clear();
+        }
+
+        let expired = self
+            .hazards
+            .values()
+            .filter(|hazard| hazard.end < now)
+            .map(|hazard| hazard.id.clone())
+            .collect::<Vec<String>>();
+
+        for hazard_id in expired.iter() {
+            self.hazards.remove(hazard_id);
+        }
+
+        expired
+    }
+
+    /// Returns the hazards active at the given time, soonest first
+    pub fn hazards(&self, now: DateTime<Utc>) -> Vec<WeatherHazard> {
+        let mut hazards = self
+            .hazards
+            .values()
+            .filter(|hazard| hazard.start <= now && now <= hazard.end)
+            .cloned()
+            .collect::<Vec<WeatherHazard>>();
+
+        hazards.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.id.cmp(&b.id)));
+        hazards
+    }
+
+    /// Returns the grid point closest to a position, if the grid is valid at
+    ///  the given time and the grid point is within the given distance
+    pub fn sample_near(
+        &self,
+        position: &PointZ,
+        time: DateTime<Utc>,
+        radius_meters: f64,
+    ) -> Option<&WeatherSample> {
+        let valid = self.valid?;
+        if time < valid.start || time > valid.end {
+            return None;
+        }
+
+        self.samples
+            .iter()
+            .map(|sample| {
+                let point = PointZ {
+                    latitude: sample.point.latitude,
+                    longitude: sample.point.longitude,
+                    altitude_meters: position.altitude_meters,
+                };
+
+                (geo::horizontal_distance_meters(position, &point), sample)
+            })
+            .filter(|(distance, _)| *distance <= radius_meters)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, sample)| sample)
+    }
+
+    /// Finds the hazards crossed by a flight plan, and the wind and
+    ///  visibility outside of limits at its origin and target vertiports
+    pub fn check(
+        &self,
+        plan: &FlightPlan,
+        limits: &WeatherLimits,
+        now: DateTime<Utc>,
+    ) -> Vec<WeatherAlert> {
+        let alert = |alert_type| WeatherAlert {
+            flight_uuid: plan.flight_uuid.clone(),
+            aircraft_id: plan.aircraft_id.clone(),
+            alert_type,
+            hazard_id: None,
+            violations: vec![],
+            vertiport_id: None,
+            value: None,
+            limit: None,
+            issued: now,
+        };
+
+        let mut alerts = vec![];
+        if let Ok(trajectory) = Trajectory::try_from(plan) {
+            let mut hazards = self.hazards.values().collect::<Vec<_>>();
+            hazards.sort_by(|a, b| a.id.cmp(&b.id));
+            for hazard in hazards {
+                let violations = zones::violations(&to_zone(hazard), &trajectory);
+                if !violations.is_empty() {
+                    alerts.push(WeatherAlert {
+                        hazard_id: Some(hazard.id.clone()),
+                        violations,
+                        ..alert(WeatherAlertType::Hazard)
+                    });
+                }
+            }
+        }
+
+        let vertiports = [
+            (
+                plan.path.first(),
+                plan.origin_timeslot_end,
+                &plan.origin_vertiport_id,
+            ),
+            (
+                plan.path.last(),
+                plan.target_timeslot_start,
+                &plan.target_vertiport_id,
+            ),
+        ];
+
+        for (position, time, vertiport_id) in vertiports {
+            let Some(sample) = position
+                .and_then(|position| self.sample_near(position, time, limits.sample_radius_meters))
+            else {
+                continue;
+            };
+
+            if wind_mps(sample) > limits.max_wind_mps {
+                alerts.push(WeatherAlert {
+                    vertiport_id: Some(vertiport_id.clone()),
+                    value: Some(wind_mps(sample)),
+                    limit: Some(limits.max_wind_mps),
+                    ..alert(WeatherAlertType::Wind)
+                });
+            }
+
+            if sample.visibility_meters < limits.min_visibility_meters {
+                alerts.push(WeatherAlert {
+                    vertiport_id: Some(vertiport_id.clone()),
+                    value: Some(sample.visibility_meters),
+                    limit: Some(limits.min_visibility_meters),
+                    ..alert(WeatherAlertType::Visibility)
+                });
+            }
+        }
+
+        alerts
+    }
+
+    /// Re-evaluates flight plans against the current weather, replacing
+    ///  the alerts issued for them
+    ///
+    /// Returns the number of affected flight plans.
+    pub fn evaluate(
+        &mut self,
+        plans: &[FlightPlan],
+        limits: &WeatherLimits,
+        now: DateTime<Utc>,
+    ) -> usize {
+        self.alerts = plans
+            .iter()
+            .map(|plan| (plan.flight_uuid.clone(), self.check(plan, limits, now)))
+            .filter(|(_, alerts)| !alerts.is_empty())
+            .collect();
+
+        self.alerts.len()
+    }
+
+    /// Alerts issued for a flight plan
+    pub fn alerts(&self, flight_uuid: &str) -> Vec<WeatherAlert> {
+        self.alerts.get(flight_uuid).cloned().unwrap_or_default()
+    }
+
+    /// Alerts issued for every affected flight plan, by flight plan ID
+    pub fn affected(&self) -> Vec<WeatherAlert> {
+        let mut alerts = self
+            .alerts
+            .values()
+            .flatten()
+            .cloned()
+            .collect::<Vec<WeatherAlert>>();
+
+        alerts.sort_by(|a, b| a.flight_uuid.cmp(&b.flight_uuid));
+        alerts
+    }
+}
+
+/// Reads a weather report from a JSON file
+pub fn read_weather_file(path: &str) -> Result<WeatherReport, WeatherError> {
+    let data = std::fs::read_to_string(path).map_err(|e| {
+        common_warn!("could not read weather file {path}: {e}");
+        WeatherError::File
+    })?;
+
+    serde_json::from_str(&data).map_err(|e| {
+        common_error!("could not parse weather report: {e}");
+        WeatherError::Json
+    })
+}
+
+/// Re-evaluates the flight plans returned to aircraft against the
+///  current weather
+///
+/// Returns the number of affected flight plans.
+pub async fn reevaluate(limits: &WeatherLimits) -> usize {
+    let plans = get_plan_cache().await.lock().await.plans();
+    let affected = get_weather()
+        .await
+        .lock()
+        .await
+        .evaluate(&plans, limits, Utc::now());
+
+    common_debug!(
+        "{affected} of {} flight plans affected by weather.",
+        plans.len()
+    );
+
+    affected
+}
+
+/// Ingests a weather report and re-evaluates the flight plans returned to
+///  aircraft
+///
+/// Returns the number of affected flight plans.
+pub async fn ingest_report(
+    report: WeatherReport,
+    limits: &WeatherLimits,
+) -> Result<usize, WeatherError> {
+    let source = report.source.clone();
+    let hazards = get_weather()
+        .await
+        .lock()
+        .await
+        .ingest(report, Utc::now())?;
+    common_info!("ingested weather report from '{source}' with {hazards} hazards.");
+
+    Ok(reevaluate(limits).await)
+}
+
+/// Ingests the weather file if it changed since it was last read, expires
+///  old weather and re-evaluates the flight plans returned to aircraft
+///
+/// `modified` holds the modification time of the file when it was last
+///  read.
+pub async fn monitor_weather(
+    path: &str,
+    modified: &mut Option<SystemTime>,
+    limits: &WeatherLimits,
+) {
+    let changed = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .filter(|time| Some(*time) != *modified);
+
+    if let Some(time) = changed {
+        *modified = Some(time);
+        match read_weather_file(path) {
+            Ok(report) => {
+                if let Err(e) = get_weather().await.lock().await.ingest(report, Utc::now()) {
+                    common_warn!("could not ingest weather file {path}: {e}");
+                }
+            }
+            Err(e) => common_warn!("{e}"),
+        }
+    }
+
+    for hazard_id in get_weather().await.lock().await.expire(Utc::now()) {
+        common_info!("weather hazard {hazard_id} expired.");
+    }
+
+    reevaluate(limits).await;
+}
+
+/// Periodically reads the weather file and re-evaluates flight plans
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (Rnever) infinite loop, tested through monitor_weather
+pub async fn weather_loop(config: Config) {
+    let limits = WeatherLimits::from(&config);
+    let period = std::time::Duration::from_secs(config.weather_interval_seconds.max(1) as u64);
+    let mut interval = tokio::time::interval(period);
+    let mut modified = None;
+
+    common_info!("weather monitor started.");
+    loop {
+        interval.tick().await;
+        monitor_weather(&config.weather_file, &mut modified, &limits).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::{HazardType, Point};
+    use crate::test_util::mock_flight_plan;
+    use lib_common::time::Duration;
+
+    /// Square of roughly 2 km around (-43.0, 140.0)
+    fn hazard(start: DateTime<Utc>, end: DateTime<Utc>) -> WeatherHazard {
+        WeatherHazard {
+            id: Uuid::new_v4().to_string(),
+            hazard_type: HazardType::Convective,
+            name: "cell".to_string(),
+            vertices: vec![
+                Point {
+                    latitude: -43.01,
+                    longitude: 139.99,
+                },
+                Point {
+                    latitude: -43.01,
+                    longitude: 140.01,
+                },
+                Point {
+                    latitude: -42.99,
+                    longitude: 140.01,
+                },
+                Point {
+                    latitude: -42.99,
+                    longitude: 139.99,
+                },
+            ],
+            floor_meters: 0.0,
+            ceiling_meters: 3000.0,
+            start,
+            end,
+        }
+    }
+
+    fn sample(latitude: f64, longitude: f64, wind_speed_mps: f64) -> WeatherSample {
+        WeatherSample {
+            point: Point {
+                latitude,
+                longitude,
+            },
+            wind_speed_mps,
+            wind_gust_mps: None,
+            wind_direction_degrees: 270.0,
+            visibility_meters: 10000.0,
+        }
+    }
+
+    fn report(now: DateTime<Utc>) -> WeatherReport {
+        WeatherReport {
+            source: "test".to_string(),
+            valid_from: now - Duration::try_hours(1).unwrap(),
+            valid_until: now + Duration::try_hours(1).unwrap(),
+            samples: vec![],
+            hazards: vec![],
+        }
+    }
+
+    #[test]
+    fn test_ingest() {
+        let mut store = WeatherStore::default();
+        let now = Utc::now();
+        let hour = Duration::try_hours(1).unwrap();
+
+        let mut unnamed = hazard(now, now + hour);
+        unnamed.id = String::new();
+        let ingested = WeatherReport {
+            samples: vec![sample(-43.0, 140.0, 5.0)],
+            hazards: vec![
+                unnamed,
+                hazard(now + hour, now + hour * 2),
+                hazard(now - hour * 2, now - hour),
+            ],
+            ..report(now)
+        };
+
+        // The expired hazard is skipped
+        assert_eq!(store.ingest(ingested, now).unwrap(), 2);
+        let active = store.hazards(now);
+        assert_eq!(active.len(), 1);
+        assert!(!active[0].id.is_empty());
+
+        // A new report replaces the previous one
+        assert_eq!(store.ingest(report(now), now).unwrap(), 0);
+        assert!(store.hazards(now).is_empty());
+
+        let mut invalid = report(now);
+        invalid.valid_until = invalid.valid_from;
+        assert_eq!(
+            store.ingest(invalid, now).unwrap_err(),
+            WeatherError::Window
+        );
+
+        let mut invalid = report(now);
+        invalid.samples.push(sample(-91.0, 140.0, 5.0));
+        assert_eq!(
+            store.ingest(invalid, now).unwrap_err(),
+            WeatherError::Sample
+        );
+
+        let mut invalid = report(now);
+        invalid.samples.push(sample(-43.0, 140.0, f64::NAN));
+        assert_eq!(
+            store.ingest(invalid, now).unwrap_err(),
+            WeatherError::Sample
+        );
+
+        let mut invalid = report(now);
+        let mut tmp = hazard(now, now + hour);
+        tmp.vertices.truncate(2);
+        invalid.hazards.push(tmp);
+        assert_eq!(
+            store.ingest(invalid, now).unwrap_err(),
+            WeatherError::Zone(ZoneError::Vertices)
+        );
+
+        let mut invalid = report(now);
+        invalid.hazards.push(hazard(now + hour, now));
+        assert_eq!(
+            store.ingest(invalid, now).unwrap_err(),
+            WeatherError::Window
+        );
+    }
+
+    #[test]
+    fn test_expire() {
+        let mut store = WeatherStore::default();
+        let now = Utc::now();
+        let hour = Duration::try_hours(1).unwrap();
+        let expiring = hazard(now, now + hour);
+        let lasting = hazard(now, now + hour * 3);
+        store
+            .ingest(
+                WeatherReport {
+                    samples: vec![sample(-43.0, 140.0, 5.0)],
+                    hazards: vec![expiring.clone(), lasting.clone()],
+                    ..report(now)
+                },
+                now,
+            )
+            .unwrap();
+
+        let position = PointZ {
+            latitude: -43.0,
+            longitude: 140.0,
+            altitude_meters: 0.0,
+        };
+        assert!(store.sample_near(&position, now, 1000.0).is_some());
+
+        let later = now + hour * 2;
+        assert_eq!(store.expire(later), vec![expiring.id]);
+        assert_eq!(store.hazards(later)[0].id, lasting.id);
+        assert!(store.sample_near(&position, later, 1000.0).is_none());
+    }
+
+    #[test]
+    fn test_sample_near() {
+        let mut store = WeatherStore::default();
+        let now = Utc::now();
+        let position = PointZ {
+            latitude: -43.0,
+            longitude: 140.0,
+            altitude_meters: 100.0,
+        };
+        assert!(store.sample_near(&position, now, 10000.0).is_none());
+
+        // Grid points about 1.1 km and 5.6 km away
+        store
+            .ingest(
+                WeatherReport {
+                    samples: vec![sample(-43.05, 140.0, 10.0), sample(-43.01, 140.0, 5.0)],
+                    ..report(now)
+                },
+                now,
+            )
+            .unwrap();
+
+        let closest = store.sample_near(&position, now, 10000.0).unwrap();
+        assert_eq!(closest.wind_speed_mps, 5.0);
+        assert!(store.sample_near(&position, now, 1000.0).is_none());
+
+        // Outside of the report validity
+        let later = now + Duration::try_hours(2).unwrap();
+        assert!(store.sample_near(&position, later, 10000.0).is_none());
+    }
+
+    #[test]
+    fn test_check() {
+        let mut store = WeatherStore::default();
+        let limits = WeatherLimits::default();
+        let now = Utc::now();
+        let hour = Duration::try_hours(1).unwrap();
+        let arrival = now + Duration::try_minutes(10).unwrap();
+
+        // Crosses the hazard, departs in strong gusts and arrives in fog
+        let crossing = mock_flight_plan(
+            &[(-43.0, 139.98, 100.0), (-43.0, 140.02, 100.0)],
+            now,
+            arrival,
+        );
+        let clear = mock_flight_plan(
+            &[(-43.1, 139.98, 100.0), (-43.1, 139.985, 100.0)],
+            now,
+            arrival,
+        );
+
+        let mut gusty = sample(-43.0, 139.98, 8.0);
+        gusty.wind_gust_mps = Some(limits.max_wind_mps + 5.0);
+        let mut foggy = sample(-43.0, 140.02, 3.0);
+        foggy.visibility_meters = limits.min_visibility_meters / 2.0;
+        let hazard = hazard(now, now + hour);
+
+        store
+            .ingest(
+                WeatherReport {
+                    samples: vec![gusty, foggy, sample(-43.1, 139.98, 3.0)],
+                    hazards: vec![hazard.clone()],
+                    ..report(now)
+                },
+                now,
+            )
+            .unwrap();
+
+        let alerts = store.check(&crossing, &limits, now);
+        assert_eq!(alerts.len(), 3);
+        assert_eq!(alerts[0].alert_type, WeatherAlertType::Hazard);
+        assert_eq!(alerts[0].hazard_id, Some(hazard.id));
+        assert_eq!(alerts[0].violations.len(), 1);
+        assert_eq!(alerts[1].alert_type, WeatherAlertType::Wind);
+        assert_eq!(
+            alerts[1].vertiport_id,
+            Some(crossing.origin_vertiport_id.clone())
+        );
+        assert_eq!(alerts[1].value, Some(limits.max_wind_mps + 5.0));
+        assert_eq!(alerts[2].alert_type, WeatherAlertType::Visibility);
+        assert_eq!(
+            alerts[2].vertiport_id,
+            Some(crossing.target_vertiport_id.clone())
+        );
+
+        assert!(store.check(&clear, &limits, now).is_empty());
+
+        let plans = vec![crossing.clone(), clear.clone()];
+        assert_eq!(store.evaluate(&plans, &limits, now), 1);
+        assert_eq!(store.alerts(&crossing.flight_uuid).len(), 3);
+        assert!(store.alerts(&clear.flight_uuid).is_empty());
+        assert_eq!(store.affected().len(), 3);
+
+        // Alerts are cleared once the weather improves
+        store.ingest(report(now), now).unwrap();
+        assert_eq!(store.evaluate(&plans, &limits, now), 0);
+        assert!(store.affected().is_empty());
+    }
+
+    #[test]
+    fn test_read_weather_file() {
+        let error = read_weather_file("/nonsense/weather.json").unwrap_err();
+        assert_eq!(error, WeatherError::File);
+
+        let path = std::env::temp_dir().join(format!("{}.json", Uuid::new_v4()));
+        let now = Utc::now();
+        std::fs::write(&path, serde_json::to_string(&report(now)).unwrap()).unwrap();
+        assert_eq!(
+            read_weather_file(path.to_str().unwrap()).unwrap(),
+            report(now)
+        );
+
+        std::fs::write(&path, "{}").unwrap();
+        let error = read_weather_file(path.to_str().unwrap()).unwrap_err();
+        assert_eq!(error, WeatherError::Json);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_monitor_weather() {
+        // The weather file is only read once it exists
+        let mut modified = None;
+        let path = std::env::temp_dir().join(format!("{}.json", Uuid::new_v4()));
+        monitor_weather(
+            path.to_str().unwrap(),
+            &mut modified,
+            &WeatherLimits::default(),
+        )
+        .await;
+        assert!(modified.is_none());
+    }
+
+    #[test]
+    fn test_weather_error_display() {
+        assert_eq!(
+            WeatherError::Zone(ZoneError::Altitude).to_string(),
+            "zone floor must be below its ceiling."
+        );
+        assert_eq!(
+            WeatherError::Window.to_string(),
+            "weather validity ends before it starts."
+        );
+        assert_eq!(WeatherError::Sample.to_string(), "invalid weather sample.");
+        assert_eq!(
+            WeatherError::File.to_string(),
+            "could not read weather file."
+        );
+        assert_eq!(WeatherError::Json.to_string(), "invalid weather report.");
+    }
+}
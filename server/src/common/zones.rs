@@ -0,0 +1,695 @@
+//! Restricted airspace zones and flight plan violation checks
+//!
+//! Zones are polygons with a floor and ceiling altitude, optionally
+//!  restricted to a set of time windows. They are loaded from a GeoJSON
+//!  file at startup and can be edited through the REST API.
+
+use super::conflict::{Trajectory, Waypoint4D};
+use super::geo;
+use crate::rest::api::{Point, PointZ, TimeWindow, Zone, ZoneViolation};
+use lib_common::time::{DateTime, Utc};
+use lib_common::uuid::Uuid;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use tokio::sync::{OnceCell, RwLock};
+
+/// Zones that flight plans may not cross while they are active
+static ZONES: OnceCell<RwLock<ZoneRegistry>> = OnceCell::const_new();
+
+/// Returns the zone registry shared by the REST and gRPC servers
+pub async fn get_zones() -> &'static RwLock<ZoneRegistry> {
+    ZONES
+        .get_or_init(|| async { RwLock::new(ZoneRegistry::default()) })
+        .await
+}
+
+/// Errors loading or adding a zone
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ZoneError {
+    /// Zone has fewer than three valid vertices
+    Vertices,
+
+    /// Zone floor is not below its ceiling
+    Altitude,
+
+    /// Zone time window ends before it starts
+    Window,
+
+    /// Zones file could not be read
+    File,
+
+    /// Zones file is not a valid GeoJSON feature collection
+    GeoJson,
+}
+
+impl Display for ZoneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ZoneError::Vertices => write!(f, "zone needs at least three valid vertices."),
+            ZoneError::Altitude => write!(f, "zone floor must be below its ceiling."),
+            ZoneError::Window => write!(f, "zone time window ends before it starts."),
+            ZoneError::File => write!(f, "could not read zones file."),
+            ZoneError::GeoJson => write!(f, "invalid GeoJSON feature collection."),
+        }
+    }
+}
+
+/// Checks that a zone can be used for violation checks
+pub fn validate(zone: &Zone) -> Result<(), ZoneError> {
+    let valid_vertices = zone.vertices.len() >= 3
+        && zone.vertices.iter().all(|vertex| {
+            (-90.0..=90.0).contains(&vertex.latitude)
+                && (-180.0..=180.0).contains(&vertex.longitude)
+        });
+
+    if !valid_vertices {
+        return Err(ZoneError::Vertices);
+    }
+
+    if !zone.floor_meters.is_finite()
+        || !zone.ceiling_meters.is_finite()
+        || zone.floor_meters >= zone.ceiling_meters
+    {
+        return Err(ZoneError::Altitude);
+    }
+
+    if zone.windows.iter().any(|window| window.end < window.start) {
+        return Err(ZoneError::Window);
+    }
+
+    Ok(())
+}
+
+/// Returns true if the zone is active at any time between `start` and `end`
+pub fn is_active(zone: &Zone, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+    zone.windows.is_empty()
+        || zone
+            .windows
+            .iter()
+            .any(|window| window.start <= end && window.end >= start)
+}
+
+/// Returns true if the point lies inside the polygon
+///
+/// Uses the even-odd rule, the polygon is implicitly closed.
+pub fn point_in_polygon(point: (f64, f64), polygon: &[(f64, f64)]) -> bool {
+    let (x, y) = point;
+    let mut inside = false;
+    let mut j = polygon.len().saturating_sub(1);
+    for i in 0..polygon.len() {
+        let (xi, yi) = polygon[i];
+        let (xj, yj) = polygon[j];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+
+        j = i;
+    }
+
+    inside
+}
+
+/// Signed area of the triangle (a, b, c), positive if counter-clockwise
+fn orientation(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+/// Returns true if `c` lies within the bounding box of segment (a, b)
+fn within_bounds(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> bool {
+    c.0 >= a.0.min(b.0) && c.0 <= a.0.max(b.0) && c.1 >= a.1.min(b.1) && c.1 <= a.1.max(b.1)
+}
+
+/// Returns true if segment (a1, a2) touches or crosses segment (b1, b2)
+pub fn segments_intersect(a1: (f64, f64), a2: (f64, f64), b1: (f64, f64), b2: (f64, f64)) -> bool {
+    let d1 = orientation(b1, b2, a1);
+    let d2 = orientation(b1, b2, a2);
+    let d3 = orientation(a1, a2, b1);
+    let d4 = orientation(a1, a2, b2);
+
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+
+    // Collinear or touching endpoints
+    (d1 == 0.0 && within_bounds(b1, b2, a1))
+        || (d2 == 0.0 && within_bounds(b1, b2, a2))
+        || (d3 == 0.0 && within_bounds(a1, a2, b1))
+        || (d4 == 0.0 && within_bounds(a1, a2, b2))
+}
+
+/// Returns true if any part of segment (a, b) lies inside or on the polygon
+pub fn segment_intersects_polygon(a: (f64, f64), b: (f64, f64), polygon: &[(f64, f64)]) -> bool {
+    if point_in_polygon(a, polygon) || point_in_polygon(b, polygon) {
+        return true;
+    }
+
+    (0..polygon.len()).any(|i| {
+        let next = (i + 1) % polygon.len();
+        segments_intersect(a, b, polygon[i], polygon[next])
+    })
+}
+
+/// Converts a latitude/longitude point into a [`PointZ`] at ground level
+fn to_point_z(point: &Point) -> PointZ {
+    PointZ {
+        latitude: point.latitude,
+        longitude: point.longitude,
+        altitude_meters: 0.0,
+    }
+}
+
+/// Returns the path segments of a trajectory crossing an active zone
+pub fn violations(zone: &Zone, trajectory: &Trajectory) -> Vec<ZoneViolation> {
+    let origin = match zone.vertices.first() {
+        Some(vertex) => to_point_z(vertex),
+        None => return vec![],
+    };
+
+    let polygon = zone
+        .vertices
+        .iter()
+        .map(|vertex| geo::to_local_meters(&origin, &to_point_z(vertex)))
+        .collect::<Vec<(f64, f64)>>();
+
+    // A single waypoint is treated as a segment of zero length
+    let waypoints = &trajectory.waypoints;
+    let segments: Vec<(&Waypoint4D, &Waypoint4D)> = match waypoints.len() {
+        0 => vec![],
+        1 => vec![(&waypoints[0], &waypoints[0])],
+        _ => waypoints
+            .windows(2)
+            .map(|segment| (&segment[0], &segment[1]))
+            .collect(),
+    };
+
+    segments
+        .into_iter()
+        .enumerate()
+        .filter(|(_, (a, b))| is_active(zone, a.time, b.time))
+        .filter(|(_, (a, b))| {
+            let lowest = a.point.altitude_meters.min(b.point.altitude_meters);
+            let highest = a.point.altitude_meters.max(b.point.altitude_meters);
+            lowest <= zone.ceiling_meters && highest >= zone.floor_meters
+        })
+        .filter(|(_, (a, b))| {
+            segment_intersects_polygon(
+                geo::to_local_meters(&origin, &a.point),
+                geo::to_local_meters(&origin, &b.point),
+                &polygon,
+            )
+        })
+        .map(|(segment_index, (a, b))| ZoneViolation {
+            zone_id: zone.id.clone(),
+            segment_index,
+            segment_start: a.time,
+            segment_end: b.time,
+        })
+        .collect()
+}
+
+/// GeoJSON feature collection
+#[derive(Debug, Deserialize)]
+struct FeatureCollection {
+    features: Vec<Feature>,
+}
+
+/// GeoJSON feature with the zone attributes as properties
+#[derive(Debug, Deserialize)]
+struct Feature {
+    geometry: Geometry,
+    properties: ZoneProperties,
+}
+
+/// GeoJSON geometry, only polygons are supported
+#[derive(Debug, Deserialize)]
+struct Geometry {
+    #[serde(rename = "type")]
+    kind: String,
+    coordinates: serde_json::Value,
+}
+
+/// Zone attributes stored in the GeoJSON feature properties
+#[derive(Debug, Deserialize)]
+struct ZoneProperties {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    floor_meters: f64,
+    ceiling_meters: f64,
+    #[serde(default)]
+    windows: Vec<TimeWindow>,
+}
+
+/// Parses zones from a GeoJSON feature collection of polygons
+///
+/// Only the exterior ring of each polygon is used, holes are ignored.
+///  Features with other geometry types are skipped.
+pub fn parse_geojson(data: &str) -> Result<Vec<Zone>, ZoneError> {
+    let collection: FeatureCollection = serde_json::from_str(data).map_err(|e| {
+        common_error!("could not parse zones: {e}");
+        ZoneError::GeoJson
+    })?;
+
+    let mut zones = vec![];
+    for feature in collection.features {
+        if feature.geometry.kind != "Polygon" {
+            common_warn!(
+                "skipping zone with unsupported geometry: {}",
+                feature.geometry.kind
+            );
+            continue;
+        }
+
+        let rings: Vec<Vec<Vec<f64>>> = serde_json::from_value(feature.geometry.coordinates)
+            .map_err(|e| {
+                common_error!("invalid polygon coordinates: {e}");
+                ZoneError::GeoJson
+            })?;
+
+        // GeoJSON positions are [longitude, latitude, (altitude)]
+        let mut vertices = rings
+            .first()
+            .ok_or(ZoneError::Vertices)?
+            .iter()
+            .map(|position| match position[..] {
+                [longitude, latitude, ..] => Ok(Point {
+                    latitude,
+                    longitude,
+                }),
+                _ => Err(ZoneError::GeoJson),
+            })
+            .collect::<Result<Vec<Point>, ZoneError>>()?;
+
+        // Rings repeat the first vertex at the end
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+
+        let properties = feature.properties;
+        zones.push(Zone {
+            id: properties.id,
+            name: properties.name,
+            vertices,
+            floor_meters: properties.floor_meters,
+            ceiling_meters: properties.ceiling_meters,
+            windows: properties.windows,
+        });
+    }
+
+    Ok(zones)
+}
+
+/// Replaces the zones in the shared registry with those in a GeoJSON file
+///
+/// Returns the number of zones loaded.
+pub async fn load_zones_file(path: &str) -> Result<usize, ZoneError> {
+    let data = std::fs::read_to_string(path).map_err(|e| {
+        common_warn!("could not read zones file {path}: {e}");
+        ZoneError::File
+    })?;
+
+    let zones = parse_geojson(&data)?;
+    let mut registry = ZoneRegistry::default();
+    for zone in zones {
+        registry.insert(zone)?;
+    }
+
+    let count = registry.len();
+    *get_zones().await.write().await = registry;
+    common_info!("loaded {count} zones from {path}.");
+
+    Ok(count)
+}
+
+/// Restricted zones, keyed by zone ID
+#[derive(Debug, Default)]
+pub struct ZoneRegistry {
+    zones: HashMap<String, Zone>,
+}
+
+impl ZoneRegistry {
+    /// Adds or replaces a zone, assigning an ID if it has none
+    ///
+    /// Returns the stored zone.
+    pub fn insert(&mut self, mut zone: Zone) -> Result<Zone, ZoneError> {
+        validate(&zone)?;
+
+        if zone.id.is_empty() {
+            zone.id = Uuid::new_v4().to_string();
+        }
+
+        self.zones.insert(zone.id.clone(), zone.clone());
+        Ok(zone)
+    }
+
+    /// Removes a zone
+    pub fn remove(&mut self, zone_id: &str) -> Option<Zone> {
+        self.zones.remove(zone_id)
+    }
+
+    /// Returns the zone with the given ID
+    pub fn get(&self, zone_id: &str) -> Option<&Zone> {
+        self.zones.get(zone_id)
+    }
+
+    /// Returns every zone, ordered by ID
+    pub fn list(&self) -> Vec<Zone> {
+        let mut zones = self.zones.values().cloned().collect::<Vec<Zone>>();
+        zones.sort_by(|a, b| a.id.cmp(&b.id));
+        zones
+    }
+
+    /// Returns the path segments of a trajectory crossing any active zone,
+    ///  ordered by segment
+    pub fn check(&self, trajectory: &Trajectory) -> Vec<ZoneViolation> {
+        let mut result = self
+            .zones
+            .values()
+            .flat_map(|zone| violations(zone, trajectory))
+            .collect::<Vec<ZoneViolation>>();
+
+        result.sort_by(|a, b| {
+            a.segment_index
+                .cmp(&b.segment_index)
+                .then_with(|| a.zone_id.cmp(&b.zone_id))
+        });
+
+        result
+    }
+
+    /// Number of zones
+    pub fn len(&self) -> usize {
+        self.zones.len()
+    }
+
+    /// True if there are no zones
+    pub fn is_empty(&self) -> bool {
+        self.zones.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::mock_flight_plan;
+    use lib_common::time::Duration;
+
+    /// Unit square with a notch cut out of the top edge
+    const NOTCHED: [(f64, f64); 8] = [
+        (0.0, 0.0),
+        (4.0, 0.0),
+        (4.0, 4.0),
+        (3.0, 4.0),
+        (3.0, 2.0),
+        (1.0, 2.0),
+        (1.0, 4.0),
+        (0.0, 4.0),
+    ];
+
+    /// Square of roughly 1.4 km around (52.0, 4.0)
+    fn zone(windows: Vec<TimeWindow>) -> Zone {
+        Zone {
+            id: Uuid::new_v4().to_string(),
+            name: "test".to_string(),
+            vertices: vec![
+                Point {
+                    latitude: 51.995,
+                    longitude: 3.99,
+                },
+                Point {
+                    latitude: 51.995,
+                    longitude: 4.01,
+                },
+                Point {
+                    latitude: 52.005,
+                    longitude: 4.01,
+                },
+                Point {
+                    latitude: 52.005,
+                    longitude: 3.99,
+                },
+            ],
+            floor_meters: 0.0,
+            ceiling_meters: 150.0,
+            windows,
+        }
+    }
+
+    #[test]
+    fn test_point_in_polygon() {
+        assert!(point_in_polygon((0.5, 0.5), &NOTCHED));
+        assert!(point_in_polygon((3.5, 3.5), &NOTCHED));
+        assert!(point_in_polygon((2.0, 1.0), &NOTCHED));
+
+        // Inside the notch
+        assert!(!point_in_polygon((2.0, 3.0), &NOTCHED));
+
+        // Outside the bounding box
+        assert!(!point_in_polygon((-1.0, 1.0), &NOTCHED));
+        assert!(!point_in_polygon((5.0, 1.0), &NOTCHED));
+        assert!(!point_in_polygon((2.0, 5.0), &NOTCHED));
+
+        // Degenerate polygons
+        assert!(!point_in_polygon((0.0, 0.0), &[]));
+        assert!(!point_in_polygon((0.0, 0.0), &[(1.0, 1.0)]));
+    }
+
+    #[test]
+    fn test_segments_intersect() {
+        // Crossing
+        assert!(segments_intersect(
+            (0.0, 0.0),
+            (2.0, 2.0),
+            (0.0, 2.0),
+            (2.0, 0.0)
+        ));
+
+        // Parallel
+        assert!(!segments_intersect(
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (0.0, 1.0),
+            (2.0, 1.0)
+        ));
+
+        // Would cross if extended
+        assert!(!segments_intersect(
+            (0.0, 0.0),
+            (1.0, 1.0),
+            (3.0, 0.0),
+            (2.0, 1.0)
+        ));
+
+        // Touching at an endpoint
+        assert!(segments_intersect(
+            (0.0, 0.0),
+            (1.0, 1.0),
+            (1.0, 1.0),
+            (2.0, 0.0)
+        ));
+
+        // Collinear and overlapping, collinear and disjoint
+        assert!(segments_intersect(
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (1.0, 0.0),
+            (3.0, 0.0)
+        ));
+        assert!(!segments_intersect(
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (3.0, 0.0)
+        ));
+    }
+
+    #[test]
+    fn test_segment_intersects_polygon() {
+        // Fully inside
+        assert!(segment_intersects_polygon((0.5, 0.5), (3.5, 0.5), &NOTCHED));
+
+        // Crosses the polygon without an endpoint inside it
+        assert!(segment_intersects_polygon(
+            (-1.0, 1.0),
+            (5.0, 1.0),
+            &NOTCHED
+        ));
+
+        // Passes through the notch only
+        assert!(!segment_intersects_polygon(
+            (2.0, 5.0),
+            (2.0, 2.5),
+            &NOTCHED
+        ));
+
+        // Fully outside
+        assert!(!segment_intersects_polygon(
+            (5.0, 0.0),
+            (5.0, 4.0),
+            &NOTCHED
+        ));
+    }
+
+    #[test]
+    fn test_violations() {
+        let departure = Utc::now();
+        let arrival = departure + Duration::try_minutes(10).unwrap();
+
+        // Three segments: approach, cross the zone, depart
+        let plan = mock_flight_plan(
+            &[
+                (52.0, 3.97, 100.0),
+                (52.0, 3.98, 100.0),
+                (52.0, 4.02, 100.0),
+                (52.0, 4.03, 100.0),
+            ],
+            departure,
+            arrival,
+        );
+        let trajectory = Trajectory::try_from(&plan).unwrap();
+
+        let always = zone(vec![]);
+        let result = violations(&always, &trajectory);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].zone_id, always.id);
+        assert_eq!(result[0].segment_index, 1);
+        assert_eq!(result[0].segment_start, trajectory.waypoints[1].time);
+        assert_eq!(result[0].segment_end, trajectory.waypoints[2].time);
+
+        // Flying above the ceiling
+        let mut high = zone(vec![]);
+        high.ceiling_meters = 50.0;
+        assert!(violations(&high, &trajectory).is_empty());
+
+        // Zone is only active after the flight
+        let later = zone(vec![TimeWindow {
+            start: arrival + Duration::try_minutes(1).unwrap(),
+            end: arrival + Duration::try_minutes(60).unwrap(),
+        }]);
+        assert!(violations(&later, &trajectory).is_empty());
+
+        // Zone activates while the aircraft crosses it
+        let during = zone(vec![TimeWindow {
+            start: trajectory.waypoints[2].time - Duration::try_seconds(1).unwrap(),
+            end: arrival + Duration::try_minutes(60).unwrap(),
+        }]);
+        assert_eq!(violations(&during, &trajectory).len(), 1);
+    }
+
+    #[test]
+    fn test_zone_registry() {
+        let mut registry = ZoneRegistry::default();
+        let mut new_zone = zone(vec![]);
+        new_zone.id = String::new();
+
+        let stored = registry.insert(new_zone).unwrap();
+        assert!(!stored.id.is_empty());
+        assert_eq!(registry.len(), 1);
+        assert!(registry.get(&stored.id).is_some());
+
+        let mut invalid = zone(vec![]);
+        invalid.vertices.truncate(2);
+        assert_eq!(registry.insert(invalid).unwrap_err(), ZoneError::Vertices);
+
+        let mut invalid = zone(vec![]);
+        invalid.floor_meters = 200.0;
+        assert_eq!(registry.insert(invalid).unwrap_err(), ZoneError::Altitude);
+
+        let now = Utc::now();
+        let invalid = zone(vec![TimeWindow {
+            start: now,
+            end: now - Duration::try_minutes(1).unwrap(),
+        }]);
+        assert_eq!(registry.insert(invalid).unwrap_err(), ZoneError::Window);
+
+        let plan = mock_flight_plan(
+            &[(52.0, 3.98, 100.0), (52.0, 4.02, 100.0)],
+            now,
+            now + Duration::try_minutes(10).unwrap(),
+        );
+        let trajectory = Trajectory::try_from(&plan).unwrap();
+        assert_eq!(registry.check(&trajectory).len(), 1);
+
+        assert!(registry.remove(&stored.id).is_some());
+        assert!(registry.is_empty());
+        assert!(registry.check(&trajectory).is_empty());
+    }
+
+    #[test]
+    fn test_parse_geojson() {
+        let data = r#"{
+            "type": "FeatureCollection",
+            "features": [
+                {
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "Polygon",
+                        "coordinates": [[
+                            [3.99, 51.995], [4.01, 51.995], [4.01, 52.005],
+                            [3.99, 52.005], [3.99, 51.995]
+                        ]]
+                    },
+                    "properties": {
+                        "id": "hospital",
+                        "name": "Hospital",
+                        "floor_meters": 0.0,
+                        "ceiling_meters": 150.0,
+                        "windows": [{
+                            "start": "2024-01-01T08:00:00Z",
+                            "end": "2024-01-01T18:00:00Z"
+                        }]
+                    }
+                },
+                {
+                    "type": "Feature",
+                    "geometry": { "type": "Point", "coordinates": [4.0, 52.0] },
+                    "properties": { "ceiling_meters": 100.0 }
+                }
+            ]
+        }"#;
+
+        let zones = parse_geojson(data).unwrap();
+        assert_eq!(zones.len(), 1);
+        assert_eq!(zones[0].id, "hospital");
+        assert_eq!(zones[0].vertices.len(), 4);
+        assert_eq!(zones[0].vertices[0].latitude, 51.995);
+        assert_eq!(zones[0].vertices[0].longitude, 3.99);
+        assert_eq!(zones[0].windows.len(), 1);
+
+        assert_eq!(parse_geojson("{}").unwrap_err(), ZoneError::GeoJson);
+    }
+
+    #[tokio::test]
+    async fn test_load_zones_file() {
+        let error = load_zones_file("/nonsense/zones.geojson")
+            .await
+            .unwrap_err();
+        assert_eq!(error, ZoneError::File);
+    }
+
+    #[test]
+    fn test_zone_error_display() {
+        assert_eq!(
+            ZoneError::Vertices.to_string(),
+            "zone needs at least three valid vertices."
+        );
+        assert_eq!(
+            ZoneError::Altitude.to_string(),
+            "zone floor must be below its ceiling."
+        );
+        assert_eq!(
+            ZoneError::Window.to_string(),
+            "zone time window ends before it starts."
+        );
+        assert_eq!(ZoneError::File.to_string(), "could not read zones file.");
+        assert_eq!(
+            ZoneError::GeoJson.to_string(),
+            "invalid GeoJSON feature collection."
+        );
+    }
+}
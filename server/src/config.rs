@@ -27,6 +27,106 @@ pub struct Config {
     /// Full url (including port number) to be allowed as request origin for
     /// REST requests
     pub rest_cors_allowed_origin: String,
+    /// Minimum horizontal separation between aircraft in meters
+    pub separation_horizontal_meters: f64,
+    /// Minimum vertical separation between aircraft in meters
+    pub separation_vertical_meters: f64,
+    /// Seconds without a position report before an aircraft track is stale
+    pub telemetry_stale_seconds: u16,
+    /// Seconds without a position report before an aircraft track is removed
+    pub telemetry_retention_seconds: u16,
+    /// Seconds a position report may be dated ahead of the current time
+    pub telemetry_max_skew_seconds: u16,
+    /// Maximum horizontal distance from the planned path in meters
+    pub conformance_lateral_meters: f64,
+    /// Maximum distance above or below the planned altitude in meters
+    pub conformance_altitude_meters: f64,
+    /// Maximum seconds the projected arrival may fall outside of the target timeslot
+    pub conformance_schedule_seconds: u32,
+    /// Seconds between conformance checks of the traffic picture
+    pub conformance_interval_seconds: u16,
+    /// Seconds ahead to predict losses of separation between airborne aircraft
+    pub tactical_lookahead_seconds: u16,
+    /// Seconds between predictions of losses of separation
+    pub tactical_interval_seconds: u16,
+    /// path to GeoJSON file with the restricted zones loaded at startup
+    pub zones_file: String,
+    /// path to JSON file where temporary flight restrictions are saved
+    pub restrictions_file: String,
+    /// Seconds between checks for activated and expired flight restrictions
+    pub restrictions_interval_seconds: u16,
+    /// Minimum seconds between takeoffs and landings at a vertiport
+    pub vertiport_min_spacing_seconds: u16,
+    /// Seconds a granted takeoff or landing clearance remains valid
+    pub clearance_validity_seconds: u16,
+    /// Distance in meters from a vertipad within which other aircraft hold clearances
+    pub clearance_traffic_radius_meters: f64,
+    /// Radius in meters of the holding orbit around a vertipad
+    pub holding_radius_meters: f64,
+    /// Height in meters above the target vertipad of the lowest level of a
+    ///  holding stack
+    pub holding_base_altitude_meters: f64,
+    /// Distance in meters from the target vertipad to the holding fix, back
+    ///  along the inbound track
+    pub holding_fix_offset_meters: f64,
+    /// Vertical distance in meters between the levels of a holding stack
+    pub holding_stack_separation_meters: f64,
+    /// Interval in seconds between checks for aircraft to release from holding
+    pub holding_interval_seconds: u16,
+    /// Interval in seconds between checks for flight plan updates to push
+    ///  to aircraft
+    pub updates_interval_seconds: u16,
+    /// Interval in seconds between refreshes of the flight plan cache
+    pub plan_cache_interval_seconds: u16,
+    /// Age in seconds after which cached flight plans are fetched again
+    ///  from svc-storage when requested
+    pub plan_cache_max_age_seconds: u16,
+    /// Minutes before the current time in which flight plans are returned
+    ///  to aircraft by default
+    pub plans_lookbehind_minutes: u32,
+    /// Minutes after the current time in which flight plans are returned
+    ///  to aircraft by default
+    pub plans_lookahead_minutes: u32,
+    /// Maximum length in minutes of the window in which flight plans are
+    ///  requested by aircraft
+    pub plans_max_window_minutes: u32,
+    /// path to JSON file with the performance profiles of aircraft types
+    pub performance_profiles_file: String,
+    /// path to JSON weather report file, re-read whenever it changes
+    pub weather_file: String,
+    /// Seconds between re-evaluations of flight plans against the weather
+    pub weather_interval_seconds: u16,
+    /// Maximum wind or gust speed at a vertiport in meters per second
+    pub weather_max_wind_mps: f64,
+    /// Minimum visibility at a vertiport in meters
+    pub weather_min_visibility_meters: f64,
+    /// Maximum distance in meters from a vertiport to the weather grid
+    ///  point used for its wind and visibility
+    pub weather_sample_radius_meters: f64,
+    /// path to the file emergency events are appended to, one JSON object
+    ///  per line
+    pub emergency_audit_file: String,
+    /// Seconds a vertipad is reserved for an aircraft declaring an emergency
+    pub emergency_reservation_seconds: u16,
+    /// Maximum distance in meters from an aircraft declaring an emergency
+    ///  to the vertipads considered for landing
+    pub emergency_search_radius_meters: f64,
+    /// Seconds without a position report before an airborne aircraft is
+    ///  assumed to have lost its link and follow its contingency procedure
+    pub lost_link_threshold_seconds: u16,
+    /// Seconds between checks for airborne aircraft that lost their link
+    pub lost_link_interval_seconds: u16,
+    /// Seconds between refreshes of the vertiport registry from svc-storage
+    pub vertiports_interval_seconds: u16,
+    /// Maximum distance in meters from the target vertipad to the
+    ///  alternate landing sites attached to a flight plan
+    pub alternate_max_distance_meters: f64,
+    /// Maximum takeoffs and landings per 15 minutes at vertiports without
+    ///  a configured capacity
+    pub vertiport_capacity: u16,
+    /// Maximum departure delay in minutes proposed to bring the demand
+    ///  under capacity
+    pub max_ground_delay_minutes: u16,
 }
 
 impl Default for Config {
@@ -48,6 +148,49 @@ impl Config {
             rest_request_limit_per_second: 2,
             rest_concurrency_limit_per_service: 5,
             rest_cors_allowed_origin: String::from("http://localhost:3000"),
+            separation_horizontal_meters: 150.0,
+            separation_vertical_meters: 30.0,
+            telemetry_stale_seconds: 10,
+            telemetry_retention_seconds: 300,
+            telemetry_max_skew_seconds: 5,
+            conformance_lateral_meters: 100.0,
+            conformance_altitude_meters: 50.0,
+            conformance_schedule_seconds: 120,
+            conformance_interval_seconds: 1,
+            tactical_lookahead_seconds: 120,
+            tactical_interval_seconds: 1,
+            zones_file: String::from("zones.geojson"),
+            restrictions_file: String::from("restrictions.json"),
+            restrictions_interval_seconds: 10,
+            vertiport_min_spacing_seconds: 60,
+            clearance_validity_seconds: 120,
+            clearance_traffic_radius_meters: 500.0,
+            holding_radius_meters: 1000.0,
+            holding_base_altitude_meters: 150.0,
+            holding_fix_offset_meters: 2000.0,
+            holding_stack_separation_meters: 50.0,
+            holding_interval_seconds: 5,
+            updates_interval_seconds: 5,
+            plan_cache_interval_seconds: 5,
+            plan_cache_max_age_seconds: 30,
+            plans_lookbehind_minutes: 60,
+            plans_lookahead_minutes: 60,
+            plans_max_window_minutes: 1440,
+            performance_profiles_file: String::from("performance_profiles.json"),
+            weather_file: String::from("weather.json"),
+            weather_interval_seconds: 30,
+            weather_max_wind_mps: 15.0,
+            weather_min_visibility_meters: 1500.0,
+            weather_sample_radius_meters: 10000.0,
+            emergency_audit_file: String::from("logs/emergency_audit.log"),
+            emergency_reservation_seconds: 900,
+            emergency_search_radius_meters: 50000.0,
+            lost_link_threshold_seconds: 30,
+            lost_link_interval_seconds: 5,
+            vertiports_interval_seconds: 60,
+            alternate_max_distance_meters: 20000.0,
+            vertiport_capacity: 8,
+            max_ground_delay_minutes: 60,
         }
     }
 
@@ -73,6 +216,160 @@ impl Config {
                 "rest_cors_allowed_origin",
                 default_config.rest_cors_allowed_origin,
             )?
+            .set_default(
+                "separation_horizontal_meters",
+                default_config.separation_horizontal_meters,
+            )?
+            .set_default(
+                "separation_vertical_meters",
+                default_config.separation_vertical_meters,
+            )?
+            .set_default(
+                "telemetry_stale_seconds",
+                default_config.telemetry_stale_seconds,
+            )?
+            .set_default(
+                "telemetry_retention_seconds",
+                default_config.telemetry_retention_seconds,
+            )?
+            .set_default(
+                "telemetry_max_skew_seconds",
+                default_config.telemetry_max_skew_seconds,
+            )?
+            .set_default(
+                "conformance_lateral_meters",
+                default_config.conformance_lateral_meters,
+            )?
+            .set_default(
+                "conformance_altitude_meters",
+                default_config.conformance_altitude_meters,
+            )?
+            .set_default(
+                "conformance_schedule_seconds",
+                default_config.conformance_schedule_seconds,
+            )?
+            .set_default(
+                "conformance_interval_seconds",
+                default_config.conformance_interval_seconds,
+            )?
+            .set_default(
+                "tactical_lookahead_seconds",
+                default_config.tactical_lookahead_seconds,
+            )?
+            .set_default(
+                "tactical_interval_seconds",
+                default_config.tactical_interval_seconds,
+            )?
+            .set_default("zones_file", default_config.zones_file)?
+            .set_default("restrictions_file", default_config.restrictions_file)?
+            .set_default(
+                "restrictions_interval_seconds",
+                default_config.restrictions_interval_seconds,
+            )?
+            .set_default(
+                "vertiport_min_spacing_seconds",
+                default_config.vertiport_min_spacing_seconds,
+            )?
+            .set_default(
+                "clearance_validity_seconds",
+                default_config.clearance_validity_seconds,
+            )?
+            .set_default(
+                "clearance_traffic_radius_meters",
+                default_config.clearance_traffic_radius_meters,
+            )?
+            .set_default(
+                "holding_radius_meters",
+                default_config.holding_radius_meters,
+            )?
+            .set_default(
+                "holding_base_altitude_meters",
+                default_config.holding_base_altitude_meters,
+            )?
+            .set_default(
+                "holding_fix_offset_meters",
+                default_config.holding_fix_offset_meters,
+            )?
+            .set_default(
+                "holding_stack_separation_meters",
+                default_config.holding_stack_separation_meters,
+            )?
+            .set_default(
+                "holding_interval_seconds",
+                default_config.holding_interval_seconds,
+            )?
+            .set_default(
+                "updates_interval_seconds",
+                default_config.updates_interval_seconds,
+            )?
+            .set_default(
+                "plan_cache_interval_seconds",
+                default_config.plan_cache_interval_seconds,
+            )?
+            .set_default(
+                "plan_cache_max_age_seconds",
+                default_config.plan_cache_max_age_seconds,
+            )?
+            .set_default(
+                "plans_lookbehind_minutes",
+                default_config.plans_lookbehind_minutes,
+            )?
+            .set_default(
+                "plans_lookahead_minutes",
+                default_config.plans_lookahead_minutes,
+            )?
+            .set_default(
+                "plans_max_window_minutes",
+                default_config.plans_max_window_minutes,
+            )?
+            .set_default(
+                "performance_profiles_file",
+                default_config.performance_profiles_file,
+            )?
+            .set_default("weather_file", default_config.weather_file)?
+            .set_default(
+                "weather_interval_seconds",
+                default_config.weather_interval_seconds,
+            )?
+            .set_default("weather_max_wind_mps", default_config.weather_max_wind_mps)?
+            .set_default(
+                "weather_min_visibility_meters",
+                default_config.weather_min_visibility_meters,
+            )?
+            .set_default(
+                "weather_sample_radius_meters",
+                default_config.weather_sample_radius_meters,
+            )?
+            .set_default("emergency_audit_file", default_config.emergency_audit_file)?
+            .set_default(
+                "emergency_reservation_seconds",
+                default_config.emergency_reservation_seconds,
+            )?
+            .set_default(
+                "emergency_search_radius_meters",
+                default_config.emergency_search_radius_meters,
+            )?
+            .set_default(
+                "lost_link_threshold_seconds",
+                default_config.lost_link_threshold_seconds,
+            )?
+            .set_default(
+                "lost_link_interval_seconds",
+                default_config.lost_link_interval_seconds,
+            )?
+            .set_default(
+                "vertiports_interval_seconds",
+                default_config.vertiports_interval_seconds,
+            )?
+            .set_default(
+                "alternate_max_distance_meters",
+                default_config.alternate_max_distance_meters,
+            )?
+            .set_default("vertiport_capacity", default_config.vertiport_capacity)?
+            .set_default(
+                "max_ground_delay_minutes",
+                default_config.max_ground_delay_minutes,
+            )?
             .add_source(Environment::default().separator("__"))
             .build()?
             .try_deserialize()
@@ -101,6 +398,55 @@ mod tests {
             config.rest_cors_allowed_origin,
             String::from("http://localhost:3000")
         );
+        assert_eq!(config.separation_horizontal_meters, 150.0);
+        assert_eq!(config.separation_vertical_meters, 30.0);
+        assert_eq!(config.telemetry_stale_seconds, 10);
+        assert_eq!(config.telemetry_retention_seconds, 300);
+        assert_eq!(config.telemetry_max_skew_seconds, 5);
+        assert_eq!(config.conformance_lateral_meters, 100.0);
+        assert_eq!(config.conformance_altitude_meters, 50.0);
+        assert_eq!(config.conformance_schedule_seconds, 120);
+        assert_eq!(config.conformance_interval_seconds, 1);
+        assert_eq!(config.tactical_lookahead_seconds, 120);
+        assert_eq!(config.tactical_interval_seconds, 1);
+        assert_eq!(config.zones_file, String::from("zones.geojson"));
+        assert_eq!(config.restrictions_file, String::from("restrictions.json"));
+        assert_eq!(config.restrictions_interval_seconds, 10);
+        assert_eq!(config.vertiport_min_spacing_seconds, 60);
+        assert_eq!(config.clearance_validity_seconds, 120);
+        assert_eq!(config.clearance_traffic_radius_meters, 500.0);
+        assert_eq!(config.holding_radius_meters, 1000.0);
+        assert_eq!(config.holding_base_altitude_meters, 150.0);
+        assert_eq!(config.holding_fix_offset_meters, 2000.0);
+        assert_eq!(config.holding_stack_separation_meters, 50.0);
+        assert_eq!(config.holding_interval_seconds, 5);
+        assert_eq!(config.updates_interval_seconds, 5);
+        assert_eq!(config.plan_cache_interval_seconds, 5);
+        assert_eq!(config.plan_cache_max_age_seconds, 30);
+        assert_eq!(config.plans_lookbehind_minutes, 60);
+        assert_eq!(config.plans_lookahead_minutes, 60);
+        assert_eq!(config.plans_max_window_minutes, 1440);
+        assert_eq!(
+            config.performance_profiles_file,
+            String::from("performance_profiles.json")
+        );
+        assert_eq!(config.weather_file, String::from("weather.json"));
+        assert_eq!(config.weather_interval_seconds, 30);
+        assert_eq!(config.weather_max_wind_mps, 15.0);
+        assert_eq!(config.weather_min_visibility_meters, 1500.0);
+        assert_eq!(config.weather_sample_radius_meters, 10000.0);
+        assert_eq!(
+            config.emergency_audit_file,
+            String::from("logs/emergency_audit.log")
+        );
+        assert_eq!(config.emergency_reservation_seconds, 900);
+        assert_eq!(config.emergency_search_radius_meters, 50000.0);
+        assert_eq!(config.lost_link_threshold_seconds, 30);
+        assert_eq!(config.lost_link_interval_seconds, 5);
+        assert_eq!(config.vertiports_interval_seconds, 60);
+        assert_eq!(config.alternate_max_distance_meters, 20000.0);
+        assert_eq!(config.vertiport_capacity, 8);
+        assert_eq!(config.max_ground_delay_minutes, 60);
 
         ut_info!("success");
     }
@@ -121,6 +467,52 @@ mod tests {
             "REST_CORS_ALLOWED_ORIGIN",
             "https://allowed.origin.host:443",
         );
+        std::env::set_var("SEPARATION_HORIZONTAL_METERS", "300.5");
+        std::env::set_var("SEPARATION_VERTICAL_METERS", "60");
+        std::env::set_var("TELEMETRY_STALE_SECONDS", "30");
+        std::env::set_var("TELEMETRY_RETENTION_SECONDS", "600");
+        std::env::set_var("TELEMETRY_MAX_SKEW_SECONDS", "2");
+        std::env::set_var("CONFORMANCE_LATERAL_METERS", "250");
+        std::env::set_var("CONFORMANCE_ALTITUDE_METERS", "75.5");
+        std::env::set_var("CONFORMANCE_SCHEDULE_SECONDS", "300");
+        std::env::set_var("CONFORMANCE_INTERVAL_SECONDS", "5");
+        std::env::set_var("TACTICAL_LOOKAHEAD_SECONDS", "30");
+        std::env::set_var("TACTICAL_INTERVAL_SECONDS", "2");
+        std::env::set_var("ZONES_FILE", "test_zones.geojson");
+        std::env::set_var("RESTRICTIONS_FILE", "test_restrictions.json");
+        std::env::set_var("RESTRICTIONS_INTERVAL_SECONDS", "60");
+        std::env::set_var("VERTIPORT_MIN_SPACING_SECONDS", "90");
+        std::env::set_var("CLEARANCE_VALIDITY_SECONDS", "45");
+        std::env::set_var("CLEARANCE_TRAFFIC_RADIUS_METERS", "750.5");
+        std::env::set_var("HOLDING_RADIUS_METERS", "800");
+        std::env::set_var("HOLDING_BASE_ALTITUDE_METERS", "200.5");
+        std::env::set_var("HOLDING_FIX_OFFSET_METERS", "1500");
+        std::env::set_var("HOLDING_STACK_SEPARATION_METERS", "75");
+        std::env::set_var("HOLDING_INTERVAL_SECONDS", "3");
+        std::env::set_var("UPDATES_INTERVAL_SECONDS", "15");
+        std::env::set_var("PLAN_CACHE_INTERVAL_SECONDS", "10");
+        std::env::set_var("PLAN_CACHE_MAX_AGE_SECONDS", "60");
+        std::env::set_var("PLANS_LOOKBEHIND_MINUTES", "30");
+        std::env::set_var("PLANS_LOOKAHEAD_MINUTES", "120");
+        std::env::set_var("PLANS_MAX_WINDOW_MINUTES", "720");
+        std::env::set_var(
+            "PERFORMANCE_PROFILES_FILE",
+            "test_performance_profiles.json",
+        );
+        std::env::set_var("WEATHER_FILE", "test_weather.json");
+        std::env::set_var("WEATHER_INTERVAL_SECONDS", "60");
+        std::env::set_var("WEATHER_MAX_WIND_MPS", "12.5");
+        std::env::set_var("WEATHER_MIN_VISIBILITY_METERS", "3000");
+        std::env::set_var("WEATHER_SAMPLE_RADIUS_METERS", "5000.5");
+        std::env::set_var("EMERGENCY_AUDIT_FILE", "test_emergency_audit.log");
+        std::env::set_var("EMERGENCY_RESERVATION_SECONDS", "600");
+        std::env::set_var("EMERGENCY_SEARCH_RADIUS_METERS", "25000.5");
+        std::env::set_var("LOST_LINK_THRESHOLD_SECONDS", "60");
+        std::env::set_var("LOST_LINK_INTERVAL_SECONDS", "10");
+        std::env::set_var("VERTIPORTS_INTERVAL_SECONDS", "120");
+        std::env::set_var("ALTERNATE_MAX_DISTANCE_METERS", "15000.5");
+        std::env::set_var("VERTIPORT_CAPACITY", "12");
+        std::env::set_var("MAX_GROUND_DELAY_MINUTES", "90");
 
         let config = Config::try_from_env();
         assert!(config.is_ok());
@@ -137,6 +529,58 @@ mod tests {
             config.rest_cors_allowed_origin,
             String::from("https://allowed.origin.host:443")
         );
+        assert_eq!(config.separation_horizontal_meters, 300.5);
+        assert_eq!(config.separation_vertical_meters, 60.0);
+        assert_eq!(config.telemetry_stale_seconds, 30);
+        assert_eq!(config.telemetry_retention_seconds, 600);
+        assert_eq!(config.telemetry_max_skew_seconds, 2);
+        assert_eq!(config.conformance_lateral_meters, 250.0);
+        assert_eq!(config.conformance_altitude_meters, 75.5);
+        assert_eq!(config.conformance_schedule_seconds, 300);
+        assert_eq!(config.conformance_interval_seconds, 5);
+        assert_eq!(config.tactical_lookahead_seconds, 30);
+        assert_eq!(config.tactical_interval_seconds, 2);
+        assert_eq!(config.zones_file, String::from("test_zones.geojson"));
+        assert_eq!(
+            config.restrictions_file,
+            String::from("test_restrictions.json")
+        );
+        assert_eq!(config.restrictions_interval_seconds, 60);
+        assert_eq!(config.vertiport_min_spacing_seconds, 90);
+        assert_eq!(config.clearance_validity_seconds, 45);
+        assert_eq!(config.clearance_traffic_radius_meters, 750.5);
+        assert_eq!(config.holding_radius_meters, 800.0);
+        assert_eq!(config.holding_base_altitude_meters, 200.5);
+        assert_eq!(config.holding_fix_offset_meters, 1500.0);
+        assert_eq!(config.holding_stack_separation_meters, 75.0);
+        assert_eq!(config.holding_interval_seconds, 3);
+        assert_eq!(config.updates_interval_seconds, 15);
+        assert_eq!(config.plan_cache_interval_seconds, 10);
+        assert_eq!(config.plan_cache_max_age_seconds, 60);
+        assert_eq!(config.plans_lookbehind_minutes, 30);
+        assert_eq!(config.plans_lookahead_minutes, 120);
+        assert_eq!(config.plans_max_window_minutes, 720);
+        assert_eq!(
+            config.performance_profiles_file,
+            String::from("test_performance_profiles.json")
+        );
+        assert_eq!(config.weather_file, String::from("test_weather.json"));
+        assert_eq!(config.weather_interval_seconds, 60);
+        assert_eq!(config.weather_max_wind_mps, 12.5);
+        assert_eq!(config.weather_min_visibility_meters, 3000.0);
+        assert_eq!(config.weather_sample_radius_meters, 5000.5);
+        assert_eq!(
+            config.emergency_audit_file,
+            String::from("test_emergency_audit.log")
+        );
+        assert_eq!(config.emergency_reservation_seconds, 600);
+        assert_eq!(config.emergency_search_radius_meters, 25000.5);
+        assert_eq!(config.lost_link_threshold_seconds, 60);
+        assert_eq!(config.lost_link_interval_seconds, 10);
+        assert_eq!(config.vertiports_interval_seconds, 120);
+        assert_eq!(config.alternate_max_distance_meters, 15000.5);
+        assert_eq!(config.vertiport_capacity, 12);
+        assert_eq!(config.max_ground_delay_minutes, 90);
 
         ut_info!("success");
     }
@@ -2,27 +2,360 @@
 
 ///module generated from proto/svc-atc-grpc.proto
 pub mod grpc_server {
-    #![allow(unused_qualifications, missing_docs)]
+    #![allow(unused_qualifications, missing_docs, non_camel_case_types)]
     tonic::include_proto!("grpc");
 }
 pub use grpc_server::rpc_service_server::{RpcService, RpcServiceServer};
-pub use grpc_server::{ReadyRequest, ReadyResponse};
+pub use grpc_server::{
+    AircraftPosition, CapacityHotspot, CapacityResourceType, CheckCapacityRequest,
+    CheckCapacityResponse, CheckFlightPlanRequest, CheckFlightPlanResponse, ClearanceReason,
+    ClearanceRequest, ClearanceResponse, ClearanceStatus, ClearanceType, ConformanceAlert,
+    ConformanceAlertType, ConformanceAlertsRequest, EmergencyRequest, EmergencyResponse,
+    EmergencyType, FlightConflict, PointZ, ReadyRequest, ReadyResponse, TelemetryResponse,
+    ZoneViolation,
+};
 
+use crate::common::capacity::CapacityPolicy;
+use crate::common::clearance::ClearancePolicy;
+use crate::common::emergency::EmergencyPolicy;
+use crate::common::traffic::MaxSkew;
+
+use crate::common::conflict::{SeparationMinima, Trajectory, TrajectoryError};
+use crate::rest::api::rest_types;
 use crate::shutdown_signal;
 use crate::Config;
+use lib_common::time::{DateTime, Utc};
 
 use std::fmt::Debug;
 use std::net::SocketAddr;
+use std::pin::Pin;
 use tonic::transport::Server;
-use tonic::{Request, Response, Status};
+use tonic::{Request, Response, Status, Streaming};
 
 /// struct to implement the gRPC server functions
 #[derive(Debug, Default, Copy, Clone)]
-pub struct ServerImpl {}
+pub struct ServerImpl {
+    /// Separation minima used to check flight plans
+    pub minima: SeparationMinima,
+
+    /// Settings used to decide on clearance requests
+    pub clearance: ClearancePolicy,
+
+    /// Settings used to handle emergency declarations
+    pub emergency: EmergencyPolicy,
+
+    /// Settings used to check flight plans against capacities
+    pub capacity: CapacityPolicy,
+
+    /// Time a position report may be dated ahead of the current time
+    pub max_skew: MaxSkew,
+}
+
+/// Stream of conformance alerts sent to subscribers
+pub type ConformanceAlertStream =
+    Pin<Box<dyn futures::Stream<Item = Result<ConformanceAlert, Status>> + Send>>;
+
+/// Converts a protobuf timestamp into a [`DateTime<Utc>`]
+pub fn to_datetime(timestamp: Option<prost_types::Timestamp>) -> Option<DateTime<Utc>> {
+    let timestamp = timestamp?;
+    DateTime::from_timestamp(timestamp.seconds, timestamp.nanos.try_into().ok()?)
+}
+
+/// Converts a [`DateTime<Utc>`] into a protobuf timestamp
+pub fn to_timestamp(time: DateTime<Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: time.timestamp(),
+        nanos: time.timestamp_subsec_nanos() as i32,
+    }
+}
+
+impl From<PointZ> for rest_types::PointZ {
+    fn from(point: PointZ) -> Self {
+        rest_types::PointZ {
+            latitude: point.latitude,
+            longitude: point.longitude,
+            altitude_meters: point.altitude_meters,
+        }
+    }
+}
+
+impl From<rest_types::PointZ> for PointZ {
+    fn from(point: rest_types::PointZ) -> Self {
+        PointZ {
+            latitude: point.latitude,
+            longitude: point.longitude,
+            altitude_meters: point.altitude_meters,
+        }
+    }
+}
+
+impl From<rest_types::ConformanceAlertType> for ConformanceAlertType {
+    fn from(alert_type: rest_types::ConformanceAlertType) -> Self {
+        match alert_type {
+            rest_types::ConformanceAlertType::Lateral => ConformanceAlertType::Lateral,
+            rest_types::ConformanceAlertType::Altitude => ConformanceAlertType::Altitude,
+            rest_types::ConformanceAlertType::Schedule => ConformanceAlertType::Schedule,
+        }
+    }
+}
+
+impl From<rest_types::ConformanceAlert> for ConformanceAlert {
+    fn from(alert: rest_types::ConformanceAlert) -> Self {
+        ConformanceAlert {
+            flight_id: alert.fp_id,
+            aircraft_id: alert.aircraft_id,
+            alert_type: ConformanceAlertType::from(alert.alert_type) as i32,
+            deviation: alert.deviation,
+            tolerance: alert.tolerance,
+            position: Some(alert.position.into()),
+            timestamp: Some(to_timestamp(alert.timestamp)),
+        }
+    }
+}
+
+impl From<rest_types::ZoneViolation> for ZoneViolation {
+    fn from(violation: rest_types::ZoneViolation) -> Self {
+        ZoneViolation {
+            zone_id: violation.zone_id,
+            segment_index: violation.segment_index as u32,
+            segment_start: Some(to_timestamp(violation.segment_start)),
+            segment_end: Some(to_timestamp(violation.segment_end)),
+        }
+    }
+}
+
+impl From<ClearanceType> for rest_types::ClearanceType {
+    fn from(clearance_type: ClearanceType) -> Self {
+        match clearance_type {
+            ClearanceType::Takeoff => rest_types::ClearanceType::Takeoff,
+            ClearanceType::Landing => rest_types::ClearanceType::Landing,
+        }
+    }
+}
+
+impl From<rest_types::ClearanceType> for ClearanceType {
+    fn from(clearance_type: rest_types::ClearanceType) -> Self {
+        match clearance_type {
+            rest_types::ClearanceType::Takeoff => ClearanceType::Takeoff,
+            rest_types::ClearanceType::Landing => ClearanceType::Landing,
+        }
+    }
+}
+
+impl From<rest_types::ClearanceStatus> for ClearanceStatus {
+    fn from(status: rest_types::ClearanceStatus) -> Self {
+        match status {
+            rest_types::ClearanceStatus::Granted => ClearanceStatus::Granted,
+            rest_types::ClearanceStatus::Hold => ClearanceStatus::Hold,
+            rest_types::ClearanceStatus::Denied => ClearanceStatus::Denied,
+        }
+    }
+}
+
+impl From<Option<rest_types::ClearanceReason>> for ClearanceReason {
+    fn from(reason: Option<rest_types::ClearanceReason>) -> Self {
+        match reason {
+            None => ClearanceReason::NoReason,
+            Some(rest_types::ClearanceReason::NotAcknowledged) => ClearanceReason::NotAcknowledged,
+            Some(rest_types::ClearanceReason::AircraftMismatch) => {
+                ClearanceReason::AircraftMismatch
+            }
+            Some(rest_types::ClearanceReason::VertipadMismatch) => {
+                ClearanceReason::VertipadMismatch
+            }
+            Some(rest_types::ClearanceReason::PadOccupied) => ClearanceReason::PadOccupied,
+            Some(rest_types::ClearanceReason::TrafficNearby) => ClearanceReason::TrafficNearby,
+            Some(rest_types::ClearanceReason::InvalidState) => ClearanceReason::InvalidState,
+        }
+    }
+}
+
+impl TryFrom<ClearanceRequest> for rest_types::ClearanceRequest {
+    type Error = Status;
+
+    fn try_from(request: ClearanceRequest) -> Result<Self, Self::Error> {
+        let clearance_type = ClearanceType::try_from(request.clearance_type)
+            .map_err(|_| Status::invalid_argument("invalid clearance type."))?;
+
+        Ok(rest_types::ClearanceRequest {
+            fp_id: request.flight_id,
+            aircraft_id: request.aircraft_id,
+            vertipad_id: request.vertipad_id,
+            clearance_type: clearance_type.into(),
+        })
+    }
+}
+
+impl From<rest_types::Clearance> for ClearanceResponse {
+    fn from(clearance: rest_types::Clearance) -> Self {
+        ClearanceResponse {
+            flight_id: clearance.fp_id,
+            clearance_type: ClearanceType::from(clearance.clearance_type) as i32,
+            status: ClearanceStatus::from(clearance.status) as i32,
+            reason: ClearanceReason::from(clearance.reason) as i32,
+            issued: Some(to_timestamp(clearance.issued)),
+            expires: clearance.expires.map(to_timestamp),
+            priority: clearance.priority,
+        }
+    }
+}
+
+impl From<EmergencyType> for rest_types::EmergencyType {
+    fn from(emergency_type: EmergencyType) -> Self {
+        match emergency_type {
+            EmergencyType::LowBattery => rest_types::EmergencyType::LowBattery,
+            EmergencyType::LostLink => rest_types::EmergencyType::LostLink,
+            EmergencyType::Mechanical => rest_types::EmergencyType::Mechanical,
+            EmergencyType::Medical => rest_types::EmergencyType::Medical,
+        }
+    }
+}
+
+impl TryFrom<EmergencyRequest> for rest_types::EmergencyDeclaration {
+    type Error = Status;
+
+    fn try_from(request: EmergencyRequest) -> Result<Self, Self::Error> {
+        let emergency_type = EmergencyType::try_from(request.emergency_type)
+            .map_err(|_| Status::invalid_argument("invalid emergency type."))?;
+        let position = request
+            .position
+            .ok_or_else(|| Status::invalid_argument("missing position."))?;
+
+        Ok(rest_types::EmergencyDeclaration {
+            aircraft_id: request.aircraft_id,
+            emergency_type: emergency_type.into(),
+            position: position.into(),
+        })
+    }
+}
+
+impl From<rest_types::EmergencyResponse> for EmergencyResponse {
+    fn from(response: rest_types::EmergencyResponse) -> Self {
+        EmergencyResponse {
+            emergency_id: response.emergency_id,
+            aircraft_id: response.aircraft_id,
+            flight_id: response.fp_id,
+            vertiport_id: response.vertiport_id,
+            vertipad_id: response.vertipad_id,
+            distance_meters: response.distance_meters,
+            clearance: Some(response.clearance.into()),
+            delayed_flight_ids: response
+                .advisories
+                .into_iter()
+                .map(|advisory| advisory.flight_uuid)
+                .collect(),
+            declared: Some(to_timestamp(response.declared)),
+        }
+    }
+}
+
+impl From<rest_types::CapacityResourceType> for CapacityResourceType {
+    fn from(resource_type: rest_types::CapacityResourceType) -> Self {
+        match resource_type {
+            rest_types::CapacityResourceType::Sector => CapacityResourceType::Sector,
+            rest_types::CapacityResourceType::Vertiport => CapacityResourceType::Vertiport,
+        }
+    }
+}
+
+impl From<rest_types::Hotspot> for CapacityHotspot {
+    fn from(hotspot: rest_types::Hotspot) -> Self {
+        CapacityHotspot {
+            resource_type: CapacityResourceType::from(hotspot.resource_type) as i32,
+            resource_id: hotspot.resource_id,
+            period_start: Some(to_timestamp(hotspot.period_start)),
+            period_end: Some(to_timestamp(hotspot.period_end)),
+            capacity: hotspot.capacity,
+            demand: hotspot.demand,
+        }
+    }
+}
+
+impl TryFrom<AircraftPosition> for rest_types::AircraftPosition {
+    type Error = Status;
+
+    fn try_from(position: AircraftPosition) -> Result<Self, Self::Error> {
+        let point = position
+            .position
+            .ok_or_else(|| Status::invalid_argument("missing position."))?;
+        let timestamp = to_datetime(position.timestamp)
+            .ok_or_else(|| Status::invalid_argument("missing or invalid timestamp."))?;
+
+        Ok(rest_types::AircraftPosition {
+            aircraft_id: position.aircraft_id,
+            position: point.into(),
+            ground_speed_mps: position.ground_speed_mps,
+            vertical_speed_mps: position.vertical_speed_mps,
+            heading_degrees: position.heading_degrees,
+            timestamp,
+        })
+    }
+}
+
+/// Distributes the path of a candidate flight plan between its departure,
+///  the end of the origin timeslot, and its arrival, the start of the
+///  target timeslot
+fn candidate_trajectory(
+    aircraft_id: String,
+    path: Vec<PointZ>,
+    origin_timeslot_end: Option<prost_types::Timestamp>,
+    target_timeslot_start: Option<prost_types::Timestamp>,
+) -> Result<Trajectory, TrajectoryError> {
+    let departure = to_datetime(origin_timeslot_end).ok_or(TrajectoryError::InvalidTimeslot)?;
+    let arrival = to_datetime(target_timeslot_start).ok_or(TrajectoryError::InvalidTimeslot)?;
+    let path = path
+        .into_iter()
+        .map(rest_types::PointZ::from)
+        .collect::<Vec<rest_types::PointZ>>();
+
+    // Candidate flight plans have not been assigned an ID yet
+    Trajectory::new(String::new(), aircraft_id, &path, departure, arrival)
+}
+
+impl TryFrom<CheckFlightPlanRequest> for Trajectory {
+    type Error = TrajectoryError;
+
+    fn try_from(request: CheckFlightPlanRequest) -> Result<Self, Self::Error> {
+        // The aircraft occupies its origin vertipad until it departs and its
+        //  target vertipad from the moment it arrives
+        let origin_start =
+            to_datetime(request.origin_timeslot_start).ok_or(TrajectoryError::InvalidTimeslot)?;
+        let target_end =
+            to_datetime(request.target_timeslot_end).ok_or(TrajectoryError::InvalidTimeslot)?;
+
+        let trajectory = candidate_trajectory(
+            request.aircraft_id,
+            request.path,
+            request.origin_timeslot_end,
+            request.target_timeslot_start,
+        )?;
+
+        if origin_start > trajectory.start() || target_end < trajectory.end() {
+            return Err(TrajectoryError::InvalidTimeslot);
+        }
+
+        Ok(trajectory)
+    }
+}
+
+impl TryFrom<CheckCapacityRequest> for Trajectory {
+    type Error = TrajectoryError;
+
+    fn try_from(request: CheckCapacityRequest) -> Result<Self, Self::Error> {
+        candidate_trajectory(
+            request.aircraft_id,
+            request.path,
+            request.origin_timeslot_end,
+            request.target_timeslot_start,
+        )
+    }
+}
 
 #[cfg(not(feature = "stub_server"))]
 #[tonic::async_trait]
 impl RpcService for ServerImpl {
+    type streamConformanceAlertsStream = ConformanceAlertStream;
+
     /// Returns ready:true when service is available
     async fn is_ready(
         &self,
@@ -33,6 +366,270 @@ impl RpcService for ServerImpl {
         let response = ReadyResponse { ready: true };
         Ok(Response::new(response))
     }
+
+    /// Checks a candidate flight plan against the scheduled flight plans
+    async fn check_flight_plan(
+        &self,
+        request: Request<CheckFlightPlanRequest>,
+    ) -> Result<Response<CheckFlightPlanResponse>, Status> {
+        grpc_info!("atc server.");
+        grpc_debug!("[{:?}].", request);
+
+        let candidate = Trajectory::try_from(request.into_inner()).map_err(|e| {
+            grpc_error!("invalid candidate flight plan: {e}");
+            Status::invalid_argument(e.to_string())
+        })?;
+
+        let grpc_clients = crate::grpc::client::get_clients().await;
+        let result =
+            crate::common::deconfliction::check_flight_plan(&candidate, &self.minima, grpc_clients)
+                .await
+                .map_err(|e| {
+                    grpc_error!("could not check flight plan: {e}");
+                    match e {
+                        crate::common::deconfliction::CheckError::Unavailable => {
+                            Status::unavailable(e.to_string())
+                        }
+                        crate::common::deconfliction::CheckError::Internal => {
+                            Status::internal(e.to_string())
+                        }
+                    }
+                })?;
+
+        let response = CheckFlightPlanResponse {
+            approved: result.approved(),
+            conflicts: result
+                .conflicts
+                .iter()
+                .map(|conflict| FlightConflict {
+                    flight_id: conflict.flight_uuid_b.clone(),
+                    time: Some(to_timestamp(conflict.time)),
+                    horizontal_distance_meters: conflict.horizontal_distance_meters,
+                    vertical_distance_meters: conflict.vertical_distance_meters,
+                })
+                .collect(),
+            suggested_time_shifts_seconds: result
+                .suggested_time_shifts
+                .iter()
+                .map(|shift| shift.num_seconds())
+                .collect(),
+            zone_violations: result
+                .zone_violations
+                .into_iter()
+                .map(ZoneViolation::from)
+                .collect(),
+        };
+
+        Ok(Response::new(response))
+    }
+
+    /// Adds a stream of aircraft position reports to the traffic picture
+    async fn stream_telemetry(
+        &self,
+        request: Request<Streaming<AircraftPosition>>,
+    ) -> Result<Response<TelemetryResponse>, Status> {
+        grpc_info!("atc server.");
+
+        let mut stream = request.into_inner();
+        let mut response = TelemetryResponse::default();
+        let traffic = crate::common::traffic::get_traffic().await;
+        while let Some(position) = stream.message().await? {
+            let position = match rest_types::AircraftPosition::try_from(position) {
+                Ok(position) => position,
+                Err(e) => {
+                    grpc_warn!("rejected position report: {}", e.message());
+                    response.rejected += 1;
+                    continue;
+                }
+            };
+
+            grpc_debug!("[{:?}].", position);
+            let result = traffic.write().await.update(position, self.max_skew);
+            match result {
+                Ok(_) => response.accepted += 1,
+                Err(e) => {
+                    grpc_warn!("rejected position report: {e}");
+                    response.rejected += 1;
+                }
+            }
+        }
+
+        grpc_info!(
+            "telemetry stream closed, accepted {} and rejected {} position reports.",
+            response.accepted,
+            response.rejected
+        );
+
+        Ok(Response::new(response))
+    }
+
+    /// Streams conformance alerts as they are raised
+    async fn stream_conformance_alerts(
+        &self,
+        request: Request<ConformanceAlertsRequest>,
+    ) -> Result<Response<Self::streamConformanceAlertsStream>, Status> {
+        grpc_info!("atc server.");
+        grpc_debug!("[{:?}].", request);
+
+        let aircraft_id = request.into_inner().aircraft_id;
+        let receiver = crate::common::conformance::get_conformance_monitor()
+            .await
+            .lock()
+            .await
+            .subscribe();
+
+        let stream = futures::stream::unfold(receiver, move |mut receiver| {
+            let aircraft_id = aircraft_id.clone();
+            async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(alert) => {
+                            if aircraft_id.is_empty() || alert.aircraft_id == aircraft_id {
+                                return Some((Ok(ConformanceAlert::from(alert)), receiver));
+                            }
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                            grpc_warn!("subscriber lagged, skipped {skipped} conformance alerts.");
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    /// Decides on a takeoff or landing clearance request
+    async fn request_clearance(
+        &self,
+        request: Request<ClearanceRequest>,
+    ) -> Result<Response<ClearanceResponse>, Status> {
+        grpc_info!("atc server.");
+        grpc_debug!("[{:?}].", request);
+
+        let request = rest_types::ClearanceRequest::try_from(request.into_inner())?;
+        let fp_id = lib_common::uuid::to_uuid(&request.fp_id)
+            .ok_or_else(|| Status::invalid_argument("invalid flight plan UUID."))?;
+
+        let grpc_clients = crate::grpc::client::get_clients().await;
+        let clearance = crate::common::clearance::request_clearance(
+            fp_id,
+            &request,
+            &self.clearance,
+            grpc_clients,
+        )
+        .await
+        .map_err(|e| {
+            grpc_error!("could not process clearance request: {e}");
+            match e {
+                crate::common::clearance::ClearanceError::NotFound => {
+                    Status::not_found(e.to_string())
+                }
+                crate::common::clearance::ClearanceError::Unavailable => {
+                    Status::unavailable(e.to_string())
+                }
+                crate::common::clearance::ClearanceError::Internal => {
+                    Status::internal(e.to_string())
+                }
+            }
+        })?;
+
+        Ok(Response::new(clearance.into()))
+    }
+
+    /// Assigns a vertipad and a priority landing clearance to an aircraft
+    ///  declaring an emergency
+    async fn declare_emergency(
+        &self,
+        request: Request<EmergencyRequest>,
+    ) -> Result<Response<EmergencyResponse>, Status> {
+        grpc_info!("atc server.");
+        grpc_debug!("[{:?}].", request);
+
+        let declaration = rest_types::EmergencyDeclaration::try_from(request.into_inner())?;
+        let grpc_clients = crate::grpc::client::get_clients().await;
+        let response = crate::common::emergency::declare_emergency(
+            &declaration,
+            &self.emergency,
+            grpc_clients,
+        )
+        .await
+        .map_err(|e| {
+            grpc_error!("could not process emergency declaration: {e}");
+            match e {
+                crate::common::emergency::EmergencyError::AircraftId
+                | crate::common::emergency::EmergencyError::Position => {
+                    Status::invalid_argument(e.to_string())
+                }
+                crate::common::emergency::EmergencyError::NoSuitablePad => {
+                    Status::not_found(e.to_string())
+                }
+                crate::common::emergency::EmergencyError::Unavailable => {
+                    Status::unavailable(e.to_string())
+                }
+            }
+        })?;
+
+        Ok(Response::new(response.into()))
+    }
+
+    /// Checks a candidate flight plan against the sector and vertiport
+    ///  capacities
+    async fn check_capacity(
+        &self,
+        request: Request<CheckCapacityRequest>,
+    ) -> Result<Response<CheckCapacityResponse>, Status> {
+        grpc_info!("atc server.");
+        grpc_debug!("[{:?}].", request);
+
+        let request = request.into_inner();
+        let (Some(origin_vertiport_id), Some(target_vertiport_id)) = (
+            lib_common::uuid::to_uuid(&request.origin_vertiport_id),
+            lib_common::uuid::to_uuid(&request.target_vertiport_id),
+        ) else {
+            grpc_error!("invalid vertiport UUID.");
+            return Err(Status::invalid_argument("invalid vertiport ID."));
+        };
+
+        let candidate = Trajectory::try_from(request).map_err(|e| {
+            grpc_error!("invalid candidate flight plan: {e}");
+            Status::invalid_argument(e.to_string())
+        })?;
+
+        let grpc_clients = crate::grpc::client::get_clients().await;
+        let result = crate::common::capacity::check_capacity(
+            &origin_vertiport_id.to_string(),
+            &target_vertiport_id.to_string(),
+            &candidate,
+            &self.capacity,
+            grpc_clients,
+        )
+        .await
+        .map_err(|e| {
+            grpc_error!("could not check capacity: {e}");
+            match e {
+                crate::common::deconfliction::CheckError::Unavailable => {
+                    Status::unavailable(e.to_string())
+                }
+                crate::common::deconfliction::CheckError::Internal => {
+                    Status::internal(e.to_string())
+                }
+            }
+        })?;
+
+        let response = CheckCapacityResponse {
+            approved: result.approved(),
+            hotspots: result
+                .hotspots
+                .into_iter()
+                .map(CapacityHotspot::from)
+                .collect(),
+            ground_delay_seconds: result.ground_delay.map(|delay| delay.num_seconds()),
+        };
+
+        Ok(Response::new(response))
+    }
 }
 
 /// Starts the grpc servers for this microservice using the provided configuration
@@ -59,7 +656,13 @@ pub async fn grpc_server(config: Config, shutdown_rx: Option<tokio::sync::onesho
         }
     };
 
-    let imp = ServerImpl::default();
+    let imp = ServerImpl {
+        minima: SeparationMinima::from(&config),
+        clearance: ClearancePolicy::from(&config),
+        emergency: EmergencyPolicy::from(&config),
+        capacity: CapacityPolicy::from(&config),
+        max_skew: MaxSkew::from(&config),
+    };
     let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
     health_reporter
         .set_serving::<RpcServiceServer<ServerImpl>>()
@@ -83,6 +686,8 @@ pub async fn grpc_server(config: Config, shutdown_rx: Option<tokio::sync::onesho
 #[cfg(feature = "stub_server")]
 #[tonic::async_trait]
 impl RpcService for ServerImpl {
+    type streamConformanceAlertsStream = ConformanceAlertStream;
+
     async fn is_ready(
         &self,
         request: Request<ReadyRequest>,
@@ -92,6 +697,108 @@ impl RpcService for ServerImpl {
         let response = ReadyResponse { ready: true };
         Ok(Response::new(response))
     }
+
+    async fn check_flight_plan(
+        &self,
+        request: Request<CheckFlightPlanRequest>,
+    ) -> Result<Response<CheckFlightPlanResponse>, Status> {
+        grpc_warn!("(MOCK) atc server.");
+        grpc_debug!("(MOCK) [{:?}].", request);
+        let response = CheckFlightPlanResponse {
+            approved: true,
+            conflicts: vec![],
+            suggested_time_shifts_seconds: vec![],
+            zone_violations: vec![],
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn stream_telemetry(
+        &self,
+        request: Request<Streaming<AircraftPosition>>,
+    ) -> Result<Response<TelemetryResponse>, Status> {
+        grpc_warn!("(MOCK) atc server.");
+        let mut stream = request.into_inner();
+        let mut response = TelemetryResponse::default();
+        while stream.message().await?.is_some() {
+            response.accepted += 1;
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn stream_conformance_alerts(
+        &self,
+        request: Request<ConformanceAlertsRequest>,
+    ) -> Result<Response<Self::streamConformanceAlertsStream>, Status> {
+        grpc_warn!("(MOCK) atc server.");
+        grpc_debug!("(MOCK) [{:?}].", request);
+        Ok(Response::new(Box::pin(futures::stream::empty())))
+    }
+
+    async fn request_clearance(
+        &self,
+        request: Request<ClearanceRequest>,
+    ) -> Result<Response<ClearanceResponse>, Status> {
+        grpc_warn!("(MOCK) atc server.");
+        grpc_debug!("(MOCK) [{:?}].", request);
+        let request = request.into_inner();
+        let now = Utc::now();
+        let response = ClearanceResponse {
+            flight_id: request.flight_id,
+            clearance_type: request.clearance_type,
+            status: ClearanceStatus::Granted as i32,
+            reason: ClearanceReason::NoReason as i32,
+            issued: Some(to_timestamp(now)),
+            expires: Some(to_timestamp(now + self.clearance.validity)),
+            priority: false,
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn declare_emergency(
+        &self,
+        request: Request<EmergencyRequest>,
+    ) -> Result<Response<EmergencyResponse>, Status> {
+        grpc_warn!("(MOCK) atc server.");
+        grpc_debug!("(MOCK) [{:?}].", request);
+        let request = request.into_inner();
+        let now = Utc::now();
+        let emergency_id = lib_common::uuid::Uuid::new_v4().to_string();
+        let response = EmergencyResponse {
+            emergency_id: emergency_id.clone(),
+            aircraft_id: request.aircraft_id,
+            flight_id: None,
+            vertiport_id: String::new(),
+            vertipad_id: String::new(),
+            distance_meters: 0.0,
+            clearance: Some(ClearanceResponse {
+                flight_id: emergency_id,
+                clearance_type: ClearanceType::Landing as i32,
+                status: ClearanceStatus::Granted as i32,
+                reason: ClearanceReason::NoReason as i32,
+                issued: Some(to_timestamp(now)),
+                expires: Some(to_timestamp(now + self.emergency.reservation)),
+                priority: true,
+            }),
+            delayed_flight_ids: vec![],
+            declared: Some(to_timestamp(now)),
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn check_capacity(
+        &self,
+        request: Request<CheckCapacityRequest>,
+    ) -> Result<Response<CheckCapacityResponse>, Status> {
+        grpc_warn!("(MOCK) atc server.");
+        grpc_debug!("(MOCK) [{:?}].", request);
+        let response = CheckCapacityResponse {
+            approved: true,
+            hotspots: vec![],
+            ground_delay_seconds: Some(0),
+        };
+        Ok(Response::new(response))
+    }
 }
 
 #[cfg(test)]
@@ -112,6 +819,352 @@ mod tests {
         ut_info!("success");
     }
 
+    fn check_flight_plan_request() -> CheckFlightPlanRequest {
+        let now = Utc::now();
+        let minutes = |n| now + lib_common::time::Duration::try_minutes(n).unwrap();
+        CheckFlightPlanRequest {
+            aircraft_id: "aircraft_id".to_string(),
+            path: vec![
+                PointZ {
+                    latitude: 52.0,
+                    longitude: 4.0,
+                    altitude_meters: 100.0,
+                },
+                PointZ {
+                    latitude: 52.0,
+                    longitude: 4.02,
+                    altitude_meters: 100.0,
+                },
+            ],
+            origin_timeslot_start: Some(to_timestamp(now)),
+            origin_timeslot_end: Some(to_timestamp(minutes(2))),
+            target_timeslot_start: Some(to_timestamp(minutes(12))),
+            target_timeslot_end: Some(to_timestamp(minutes(14))),
+        }
+    }
+
+    #[test]
+    fn test_timestamp_conversion() {
+        let now = Utc::now();
+        assert_eq!(to_datetime(Some(to_timestamp(now))), Some(now));
+        assert_eq!(to_datetime(None), None);
+        assert_eq!(
+            to_datetime(Some(prost_types::Timestamp {
+                seconds: 0,
+                nanos: -1
+            })),
+            None
+        );
+    }
+
+    #[test]
+    fn test_trajectory_from_check_flight_plan_request() {
+        let request = check_flight_plan_request();
+        let trajectory = Trajectory::try_from(request.clone()).unwrap();
+        assert_eq!(trajectory.aircraft_id, request.aircraft_id);
+        assert_eq!(trajectory.waypoints.len(), 2);
+        assert_eq!(
+            Some(trajectory.start()),
+            to_datetime(request.origin_timeslot_end.clone())
+        );
+        assert_eq!(
+            Some(trajectory.end()),
+            to_datetime(request.target_timeslot_start.clone())
+        );
+
+        let mut tmp = request.clone();
+        tmp.path = vec![];
+        assert_eq!(
+            Trajectory::try_from(tmp).unwrap_err(),
+            TrajectoryError::EmptyPath
+        );
+
+        let mut tmp = request.clone();
+        tmp.target_timeslot_start = None;
+        assert_eq!(
+            Trajectory::try_from(tmp).unwrap_err(),
+            TrajectoryError::InvalidTimeslot
+        );
+
+        // The pad timeslots must contain the departure and arrival
+        let mut tmp = request.clone();
+        tmp.origin_timeslot_start = None;
+        assert_eq!(
+            Trajectory::try_from(tmp).unwrap_err(),
+            TrajectoryError::InvalidTimeslot
+        );
+
+        let mut tmp = request.clone();
+        tmp.origin_timeslot_start = tmp.target_timeslot_start.clone();
+        assert_eq!(
+            Trajectory::try_from(tmp).unwrap_err(),
+            TrajectoryError::InvalidTimeslot
+        );
+
+        let mut tmp = request.clone();
+        tmp.target_timeslot_end = tmp.origin_timeslot_end.clone();
+        assert_eq!(
+            Trajectory::try_from(tmp).unwrap_err(),
+            TrajectoryError::InvalidTimeslot
+        );
+    }
+
+    #[tokio::test]
+    async fn test_grpc_server_check_flight_plan() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let imp = ServerImpl::default();
+        let result = imp
+            .check_flight_plan(Request::new(check_flight_plan_request()))
+            .await;
+        assert!(result.is_ok());
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    #[cfg(not(feature = "stub_server"))]
+    async fn test_grpc_server_request_clearance_invalid() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let imp = ServerImpl::default();
+        let mut request = ClearanceRequest {
+            flight_id: "invalid".to_string(),
+            aircraft_id: "aircraft".to_string(),
+            vertipad_id: "vertipad".to_string(),
+            clearance_type: ClearanceType::Landing as i32,
+        };
+        let error = imp
+            .request_clearance(Request::new(request.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+        request.flight_id = lib_common::uuid::Uuid::new_v4().to_string();
+        request.clearance_type = 100;
+        let error = imp
+            .request_clearance(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+        ut_info!("success");
+    }
+
+    #[test]
+    fn test_clearance_response_from() {
+        let now = Utc::now();
+        let clearance = rest_types::Clearance {
+            fp_id: "flight".to_string(),
+            aircraft_id: "aircraft".to_string(),
+            vertipad_id: "vertipad".to_string(),
+            clearance_type: rest_types::ClearanceType::Takeoff,
+            status: rest_types::ClearanceStatus::Hold,
+            reason: Some(rest_types::ClearanceReason::TrafficNearby),
+            issued: now,
+            expires: None,
+            priority: false,
+        };
+
+        let response = ClearanceResponse::from(clearance);
+        assert_eq!(response.flight_id, "flight");
+        assert_eq!(response.clearance_type, ClearanceType::Takeoff as i32);
+        assert_eq!(response.status, ClearanceStatus::Hold as i32);
+        assert_eq!(response.reason, ClearanceReason::TrafficNearby as i32);
+        assert_eq!(response.issued, Some(to_timestamp(now)));
+        assert_eq!(response.expires, None);
+        assert!(!response.priority);
+    }
+
+    #[tokio::test]
+    #[cfg(not(feature = "stub_server"))]
+    async fn test_grpc_server_declare_emergency_invalid() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let imp = ServerImpl::default();
+        let mut request = EmergencyRequest {
+            aircraft_id: lib_common::uuid::Uuid::new_v4().to_string(),
+            emergency_type: EmergencyType::LostLink as i32,
+            position: None,
+        };
+        let error = imp
+            .declare_emergency(Request::new(request.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+        request.position = Some(PointZ {
+            latitude: 52.0,
+            longitude: 4.0,
+            altitude_meters: 100.0,
+        });
+        request.emergency_type = 100;
+        let error = imp
+            .declare_emergency(Request::new(request.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+        request.emergency_type = EmergencyType::LostLink as i32;
+        request.aircraft_id = "invalid".to_string();
+        let error = imp
+            .declare_emergency(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    #[cfg(not(feature = "stub_server"))]
+    async fn test_grpc_server_check_flight_plan_invalid() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let mut request = check_flight_plan_request();
+        request.path = vec![];
+
+        let imp = ServerImpl::default();
+        let error = imp
+            .check_flight_plan(Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+        ut_info!("success");
+    }
+
+    fn check_capacity_request() -> CheckCapacityRequest {
+        let request = check_flight_plan_request();
+        CheckCapacityRequest {
+            aircraft_id: request.aircraft_id,
+            origin_vertiport_id: lib_common::uuid::Uuid::new_v4().to_string(),
+            target_vertiport_id: lib_common::uuid::Uuid::new_v4().to_string(),
+            path: request.path,
+            origin_timeslot_end: request.origin_timeslot_end,
+            target_timeslot_start: request.target_timeslot_start,
+        }
+    }
+
+    #[test]
+    fn test_capacity_hotspot_from() {
+        let now = Utc::now();
+        let hotspot = rest_types::Hotspot {
+            resource_type: rest_types::CapacityResourceType::Vertiport,
+            resource_id: "vertiport".to_string(),
+            period_start: now,
+            period_end: now + lib_common::time::Duration::try_minutes(15).unwrap(),
+            capacity: 2,
+            demand: 3,
+            flight_uuids: vec![],
+        };
+
+        let response = CapacityHotspot::from(hotspot.clone());
+        assert_eq!(
+            response.resource_type,
+            CapacityResourceType::Vertiport as i32
+        );
+        assert_eq!(response.resource_id, hotspot.resource_id);
+        assert_eq!(response.period_start, Some(to_timestamp(now)));
+        assert_eq!(response.capacity, 2);
+        assert_eq!(response.demand, 3);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_server_check_capacity() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let imp = ServerImpl::default();
+        let response = imp
+            .check_capacity(Request::new(check_capacity_request()))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.approved);
+        assert_eq!(response.ground_delay_seconds, Some(0));
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    #[cfg(not(feature = "stub_server"))]
+    async fn test_grpc_server_check_capacity_invalid() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let imp = ServerImpl::default();
+        let mut request = check_capacity_request();
+        request.origin_vertiport_id = "invalid".to_string();
+        let error = imp.check_capacity(Request::new(request)).await.unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+        let mut request = check_capacity_request();
+        request.path = vec![];
+        let error = imp.check_capacity(Request::new(request)).await.unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    #[cfg(not(feature = "stub_server"))]
+    async fn test_grpc_server_stream_conformance_alerts() {
+        use crate::common::conformance::{get_conformance_monitor, ConformanceTolerances};
+        use futures::StreamExt;
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let departure = Utc::now();
+        let plan = crate::test_util::mock_eastbound_plan(
+            departure,
+            departure + lib_common::time::Duration::try_minutes(10).unwrap(),
+        );
+
+        let imp = ServerImpl::default();
+        let mut stream = imp
+            .stream_conformance_alerts(Request::new(ConformanceAlertsRequest {
+                aircraft_id: plan.aircraft_id.clone(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        // Aircraft reports a position 1 km off the planned path
+        let position = rest_types::AircraftPosition {
+            aircraft_id: plan.aircraft_id.clone(),
+            position: rest_types::PointZ {
+                latitude: 52.01,
+                longitude: 4.01,
+                altitude_meters: 100.0,
+            },
+            ground_speed_mps: 20.0,
+            vertical_speed_mps: 0.0,
+            heading_degrees: 90.0,
+            timestamp: departure + lib_common::time::Duration::try_minutes(5).unwrap(),
+        };
+
+        {
+            let mut monitor = get_conformance_monitor().await.lock().await;
+            monitor.register(&plan).unwrap();
+            monitor.check(&position, &ConformanceTolerances::default());
+        }
+
+        let alert = tokio::time::timeout(std::time::Duration::from_secs(1), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(alert.flight_id, plan.flight_uuid);
+        assert_eq!(alert.alert_type, ConformanceAlertType::Lateral as i32);
+
+        ut_info!("success");
+    }
+
     #[tokio::test]
     async fn test_grpc_server_start_and_shutdown() {
         use tokio::time::{sleep, Duration};
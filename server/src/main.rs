@@ -31,6 +31,38 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         return generate_openapi_spec::<ApiDoc>(&target).map_err(|e| e.into());
     }
 
+    // Start without restricted zones if the file can not be loaded.
+    if let Err(e) = common::zones::load_zones_file(&config.zones_file).await {
+        log::warn!("(main) {}", e);
+    }
+
+    // Restrictions are loaded after the zones so that they are added to them.
+    if let Err(e) = common::restrictions::load_restrictions_file(&config.restrictions_file).await {
+        log::warn!("(main) {}", e);
+    }
+
+    // Flight plans are not validated if the file can not be loaded.
+    if let Err(e) = common::performance::load_profiles_file(&config.performance_profiles_file).await
+    {
+        log::warn!("(main) {}", e);
+    }
+
+    common::emergency::get_emergencies()
+        .await
+        .lock()
+        .await
+        .set_audit_file(&config.emergency_audit_file);
+
+    tokio::spawn(common::traffic::traffic_loop(config.clone()));
+    tokio::spawn(common::conformance::conformance_loop(config.clone()));
+    tokio::spawn(common::tactical::tactical_loop(config.clone()));
+    tokio::spawn(common::restrictions::restrictions_loop(config.clone()));
+    tokio::spawn(common::holding::holding_loop(config.clone()));
+    tokio::spawn(common::cache::cache_loop(config.clone()));
+    tokio::spawn(common::updates::updates_loop(config.clone()));
+    tokio::spawn(common::weather::weather_loop(config.clone()));
+    tokio::spawn(common::contingency::lost_link_loop(config.clone()));
+    tokio::spawn(common::vertiports::vertiports_loop(config.clone()));
     tokio::spawn(rest_server(config.clone(), None));
     tokio::spawn(grpc_server(config, None)).await?;
 
@@ -6,13 +6,40 @@ pub mod rest_types {
 
 pub use rest_types::*;
 
+use crate::common::cache::{get_aircraft_plans, CachePolicy};
+use crate::common::capacity::{get_capacities, get_capacity_report, CapacityPolicy};
+use crate::common::clearance::{ClearanceError, ClearancePolicy};
+use crate::common::contingency::{get_contingencies, ContingencyError};
+use crate::common::deconfliction::CheckError;
+use crate::common::emergency::{get_emergencies, EmergencyError, EmergencyPolicy};
+use crate::common::holding::{get_holding as get_holding_stacks, HoldingError, HoldingPolicy};
+use crate::common::restrictions::{
+    get_restrictions as get_restriction_store, publish_restriction, withdraw_restriction,
+    RestrictionError,
+};
+use crate::common::traffic::{get_traffic as get_traffic_picture, MaxSkew, TelemetryError};
+use crate::common::updates::get_updates;
+use crate::common::vertiports::{
+    get_vertiports as get_vertiport_registry, AlternatePolicy, DivertError,
+};
+use crate::common::weather::{get_weather, ingest_report, WeatherLimits};
+use crate::common::zones::get_zones as get_zone_registry;
+use crate::common::AckError;
+use crate::config::Config;
 use crate::grpc::client::GrpcClients;
-use axum::{body::Bytes, extract::Extension, Json};
+use axum::{
+    extract::{Extension, Path, Query},
+    http::{header, HeaderMap, HeaderName},
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use futures::stream::{self, Stream, StreamExt};
 use hyper::StatusCode;
 use lib_common::time::{Duration, Utc};
 use lib_common::uuid::to_uuid;
 use std::fmt::{self, Display, Formatter};
 use svc_storage_client_grpc::prelude::*;
+use tokio::sync::broadcast::error::RecvError;
 
 // Provides a way to tell a caller if the service is healthy.
 /// Checks dependencies, making sure all connections can be made.
@@ -123,6 +150,8 @@ impl TryFrom<flight_plan::Object> for FlightPlan {
             FlightPlanError::Data
         })?;
 
+        let state = crate::common::lifecycle::initial_state(&data);
+
         let origin_vertiport_id = data.origin_vertiport_id.ok_or_else(|| {
             rest_error!("could not get origin_vertiport_id from data.");
             FlightPlanError::OriginVertiportId
@@ -183,6 +212,21 @@ impl TryFrom<flight_plan::Object> for FlightPlan {
             path,
             acquire: vec![],
             deliver: vec![],
+            flight_type: FlightType::Ferry,
+            passengers: vec![],
+            passengers_unavailable: false,
+            advisories: vec![],
+            restrictions: vec![],
+            clearances: vec![],
+            holding: None,
+            state,
+            denial: None,
+            performance_violations: vec![],
+            weather_alerts: vec![],
+            payload_weight_g: Some(0),
+            contingency: None,
+            endurance_reserve_seconds: None,
+            alternates: vec![],
         };
 
         Ok(plan)
@@ -190,20 +234,26 @@ impl TryFrom<flight_plan::Object> for FlightPlan {
 }
 
 /// Acknowledge a flight
+///
+/// A confirmation stamps the carrier acknowledgement on the flight plan.
+/// A rejection registers the reason code and marks the flight plan for re-planning.
 #[utoipa::path(
     post,
     path = "/atc/acknowledge",
     tag = "svc-atc",
     request_body = AckRequest,
     responses(
-        (status = 200, description = "Request successful.", body = String),
+        (status = 200, description = "Request successful.", body = AckResponse),
+        (status = 400, description = "Invalid flight plan ID."),
+        (status = 404, description = "Flight plan not found."),
+        (status = 409, description = "Flight plan state does not allow the acknowledgement."),
         (status = 500, description = "Request unsuccessful."),
     )
 )]
 pub async fn acknowledge_flight_plan(
     Extension(grpc_clients): Extension<GrpcClients>,
     Json(payload): Json<AckRequest>,
-) -> Result<(), StatusCode> {
+) -> Result<Json<AckResponse>, StatusCode> {
     rest_debug!("entry.");
 
     let id = to_uuid(&payload.fp_id).ok_or_else(|| {
@@ -211,331 +261,2487 @@ pub async fn acknowledge_flight_plan(
         StatusCode::BAD_REQUEST
     })?;
 
-    crate::common::ack_flight(id, &grpc_clients)
-        .await
-        .map_err(|e| {
-            rest_error!("{e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })
+    let response = match payload.status {
+        AckStatus::Confirm => {
+            crate::common::ack_flight(id, &grpc_clients)
+                .await
+                .map_err(|e| {
+                    rest_error!("{e}");
+                    match e {
+                        AckError::NotFound => StatusCode::NOT_FOUND,
+                        AckError::InvalidState => StatusCode::CONFLICT,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    }
+                })?;
+
+            AckResponse {
+                fp_id: id.to_string(),
+                status: AckStatus::Confirm,
+                reason: None,
+                replan_required: false,
+                timestamp: Utc::now(),
+            }
+        }
+        AckStatus::Deny => {
+            let reason = payload.reason.unwrap_or(DenyReason::Unspecified);
+            let denial = crate::common::deny_flight(id, reason, &grpc_clients)
+                .await
+                .map_err(|e| {
+                    rest_error!("{e}");
+                    match e {
+                        AckError::NotFound => StatusCode::NOT_FOUND,
+                        AckError::InvalidState => StatusCode::CONFLICT,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    }
+                })?;
+
+            AckResponse {
+                fp_id: id.to_string(),
+                status: AckStatus::Deny,
+                reason: Some(denial.reason),
+                replan_required: true,
+                timestamp: denial.timestamp,
+            }
+        }
+    };
+
+    Ok(Json(response))
 }
 
 /// Get flight plans
+///
+/// Flight plans include the maneuvers advised to resolve conflicts with
+///  other scheduled flight plans. Flight plans are served from a local
+///  cache, the `Age` header gives the number of seconds since they were
+///  fetched from svc-storage.
 #[utoipa::path(
     get,
     path = "/atc/plans",
     tag = "svc-atc",
-    request_body = String,
+    params(PlansQuery),
     responses(
-        (status = 200, description = "Request successful.", body = String),
+        (status = 200, description = "Request successful.", body = [FlightPlan],
+            headers(("Age" = u64, description = "Seconds since the flight plans were fetched from svc-storage."))),
+        (status = 400, description = "Invalid aircraft ID, window or limit."),
         (status = 500, description = "Request unsuccessful."),
+        (status = 503, description = "Dependencies of svc-atc were unavailable."),
     )
 )]
 pub async fn get_flight_plans(
     Extension(grpc_clients): Extension<GrpcClients>,
-    aircraft_id: Bytes,
-) -> Result<Json<Vec<FlightPlan>>, StatusCode> {
+    Extension(config): Extension<Config>,
+    Query(query): Query<PlansQuery>,
+) -> Result<([(HeaderName, String); 1], Json<Vec<FlightPlan>>), StatusCode> {
     rest_debug!("entry.");
-    let aircraft_id = String::from_utf8(aircraft_id.to_vec()).map_err(|_| {
-        rest_error!("could not convert aircraft_id to string.");
-        StatusCode::BAD_REQUEST
-    })?;
-
-    let aircraft_id = to_uuid(&aircraft_id).ok_or_else(|| {
+    let aircraft_id = to_uuid(&query.aircraft_id).ok_or_else(|| {
         rest_error!("invalid aircraft UUID.");
         StatusCode::BAD_REQUEST
     })?;
 
-    let now = Utc::now();
+    let policy = CachePolicy::from(&config);
+    let (default_start, default_end) = policy.window(Utc::now());
+    let start = query.from.unwrap_or(default_start);
+    let end = query.to.unwrap_or(default_end);
+    if start > end {
+        rest_error!("window starts after it ends.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
 
-    // TODO(R5): parameterize duration lookahead
-    let delta = Duration::try_minutes(60).ok_or_else(|| {
-        rest_error!("could not create duration.");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let max_window =
+        Duration::try_minutes(config.plans_max_window_minutes as i64).unwrap_or(Duration::zero());
+    if end - start > max_window {
+        rest_error!(
+            "window is longer than {} minutes.",
+            config.plans_max_window_minutes
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
 
-    //
-    // TODO(R5): Move this search to a loop searching for all
-    //  aircraft, and push results to local cache
-    // So that we can return cached results and not search svc-storage
-    //  every time an aircraft asks for flight plans
-    //
-    let filter =
-        AdvancedSearchFilter::search_equals("vehicle_id".to_owned(), aircraft_id.to_string())
-            .and_between(
-                "origin_timeslot_start".to_owned(),
-                (now - delta).to_string(),
-                (now + delta).to_string(),
-            );
+    if query.limit == Some(0) {
+        rest_error!("limit must be positive.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
 
-    let mut plans = grpc_clients
-        .storage
-        .flight_plan
-        .search(filter)
+    let cached = get_aircraft_plans(&aircraft_id.to_string(), start, end, &policy, &grpc_clients)
         .await
         .map_err(|e| {
-            rest_error!("svc-storage failure: {e}");
-            StatusCode::NOT_FOUND
-        })?
-        .into_inner()
-        .list
-        .into_iter()
-        .filter_map(|object| FlightPlan::try_from(object).ok())
-        .collect::<Vec<FlightPlan>>();
-
-    // TODO(R6): Check cargo or rideshare
-    for plan in plans.iter_mut() {
-        let filter = AdvancedSearchFilter::search_equals(
-            "flight_plan_id".to_owned(),
-            plan.flight_uuid.clone(),
-        );
-
-        grpc_clients
-            .storage
-            .flight_plan_parcel
-            .search(filter)
-            .await
-            .map_err(|e| {
-                let error_msg = "svc-storage failure.".to_string();
-                rest_error!("{}: {e}", &error_msg);
+            rest_error!("could not get flight plans: {e}");
+            match e {
+                CheckError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+                CheckError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })?;
 
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?
-            .into_inner()
-            .list
-            .into_iter()
-            .for_each(|parcel| {
-                let id = parcel.parcel_id;
-                if parcel.acquire {
-                    plan.acquire.push(Cargo {
-                        id: id.clone(),
-                        // weight_g: parcel.weight_g
-                    })
-                }
+    let age = (Utc::now() - cached.fetched).num_seconds().max(0);
+    let mut plans = cached.plans;
+    if let Some(limit) = query.limit {
+        plans.truncate(limit as usize);
+    }
 
-                if parcel.deliver {
-                    plan.deliver.push(Cargo {
-                        id,
-                        // weight_g: parcel.weight_g
-                    })
-                }
-            });
+    // The flight type and payload weight still count the cargo on board
+    if !query.include_cargo.unwrap_or(true) {
+        for plan in plans.iter_mut() {
+            plan.acquire.clear();
+            plan.deliver.clear();
+        }
     }
 
-    rest_debug!("returning {} plans.", plans.len());
-    Ok(Json(plans))
+    let alternates = AlternatePolicy::from(&config);
+    crate::common::annotate_flight_plans(&mut plans, &alternates).await;
+
+    rest_debug!("returning {} plans, {age} seconds old.", plans.len());
+    Ok(([(header::AGE, age.to_string())], Json(plans)))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use lib_common::uuid::Uuid;
-    use svc_storage_client_grpc::prelude::{GeoLineStringZ, GeoPointZ};
+/// Subscribe to the flight plan updates of an aircraft
+///
+/// Streams server-sent events, one per new, amended, advised or cancelled
+///  flight plan of the aircraft. The event ID is the resume token: after
+///  a disconnection, the updates published after the token given in the
+///  `Last-Event-ID` header or the `resume_token` parameter are replayed
+///  before live updates. A snapshot of the current flight plans is sent
+///  instead when no token is given or the token is too old.
+#[utoipa::path(
+    get,
+    path = "/atc/updates",
+    tag = "svc-atc",
+    params(PlanUpdatesQuery),
+    responses(
+        (status = 200, description = "Stream of flight plan updates.", body = PlanUpdate, content_type = "text/event-stream"),
+        (status = 400, description = "Invalid aircraft ID or resume token."),
+    )
+)]
+pub async fn get_plan_updates(
+    Query(query): Query<PlanUpdatesQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, serde_json::Error>>>, StatusCode> {
+    rest_debug!("entry.");
+    let aircraft_id = to_uuid(&query.aircraft_id)
+        .ok_or_else(|| {
+            rest_error!("invalid aircraft UUID.");
+            StatusCode::BAD_REQUEST
+        })?
+        .to_string();
+
+    let last_event_id = headers
+        .get("last-event-id")
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+                .ok_or_else(|| {
+                    rest_error!("invalid Last-Event-ID header.");
+                    StatusCode::BAD_REQUEST
+                })
+        })
+        .transpose()?;
+
+    // The header is set by clients reconnecting on their own
+    let resume_token = last_event_id.or(query.resume_token);
+
+    // Subscribe and replay under the same lock so no update is missed
+    let (receiver, replay, after) = {
+        let updates = get_updates().await.lock().await;
+        (
+            updates.subscribe(),
+            updates.replay(&aircraft_id, resume_token, Utc::now()),
+            updates.sequence(),
+        )
+    };
+
+    rest_info!(
+        "aircraft {aircraft_id} subscribed to updates, replaying {}.",
+        replay.len()
+    );
+
+    let live = stream::unfold(receiver, move |mut receiver| {
+        let aircraft_id = aircraft_id.clone();
+        async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(update) if update.sequence > after && update.aircraft_id == aircraft_id => {
+                        return Some((update, receiver))
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => {
+                        // Ends the stream, the client resumes from its last token
+                        rest_warn!("subscriber of {aircraft_id} missed {missed} updates.");
+                        return None;
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }
+    });
 
-    #[test]
-    fn test_from_flight_plan_object_valid() {
-        let now = Utc::now();
-        let object = flight_plan::Object {
-            id: "id".to_string(),
-            data: Some(flight_plan::Data {
-                session_id: "session_id".to_string(),
-                vehicle_id: "vehicle_id".to_string(),
-                origin_vertiport_id: Some("origin_vertiport_id".to_string()),
-                target_vertiport_id: Some("target_vertiport_id".to_string()),
-                origin_vertipad_id: "origin_vertipad_id".to_string(),
-                target_vertipad_id: "target_vertipad_id".to_string(),
-                origin_timeslot_start: Some((now + Duration::try_seconds(1).unwrap()).into()),
-                origin_timeslot_end: Some((now + Duration::try_seconds(2).unwrap()).into()),
-                target_timeslot_start: Some((now + Duration::try_seconds(3).unwrap()).into()),
-                target_timeslot_end: Some((now + Duration::try_seconds(4).unwrap()).into()),
-                path: Some(GeoLineStringZ {
-                    points: vec![GeoPointZ {
-                        y: 1.0,
-                        x: 2.0,
-                        z: 3.0,
-                    }],
-                }),
-                ..Default::default()
-            }),
-        };
+    let events = stream::iter(replay).chain(live).map(|update| {
+        Event::default()
+            .id(update.sequence.to_string())
+            .event(format!("{:?}", update.kind))
+            .json_data(&update)
+    });
 
-        let flight_plan = FlightPlan::try_from(object).unwrap();
-        assert_eq!(flight_plan.session_id, "session_id");
-        assert_eq!(flight_plan.flight_uuid, "id");
-        assert_eq!(flight_plan.aircraft_id, "vehicle_id");
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
 
-        assert_eq!(
-            flight_plan.origin_vertiport_id,
-            "origin_vertiport_id".to_string()
-        );
-        assert_eq!(
-            flight_plan.target_vertiport_id,
-            "target_vertiport_id".to_string()
-        );
+/// Report the position of an aircraft
+///
+/// Adds the position report to the traffic picture.
+#[utoipa::path(
+    post,
+    path = "/atc/telemetry",
+    tag = "svc-atc",
+    request_body = AircraftPosition,
+    responses(
+        (status = 200, description = "Position report accepted."),
+        (status = 400, description = "Invalid aircraft ID, position or velocity, or report dated in the future."),
+        (status = 409, description = "Position report is older than the latest known position."),
+    )
+)]
+pub async fn post_telemetry(
+    Extension(config): Extension<Config>,
+    Json(payload): Json<AircraftPosition>,
+) -> Result<(), StatusCode> {
+    rest_debug!("entry.");
 
-        assert_eq!(flight_plan.origin_vertipad_id, "origin_vertipad_id");
-        assert_eq!(flight_plan.target_vertipad_id, "target_vertipad_id");
+    get_traffic_picture()
+        .await
+        .write()
+        .await
+        .update(payload, MaxSkew::from(&config))
+        .map_err(|e| {
+            rest_warn!("rejected position report: {e}");
+            match e {
+                TelemetryError::OutOfOrder => StatusCode::CONFLICT,
+                _ => StatusCode::BAD_REQUEST,
+            }
+        })
+}
 
-        assert_eq!(
-            flight_plan.origin_timeslot_start,
-            now + Duration::try_seconds(1).unwrap()
-        );
-        assert_eq!(
-            flight_plan.origin_timeslot_end,
-            now + Duration::try_seconds(2).unwrap()
-        );
-        assert_eq!(
-            flight_plan.target_timeslot_start,
-            now + Duration::try_seconds(3).unwrap()
-        );
-        assert_eq!(
-            flight_plan.target_timeslot_end,
-            now + Duration::try_seconds(4).unwrap()
-        );
+/// Get the traffic picture
+///
+/// Returns the latest known position of every aircraft reporting telemetry.
+#[utoipa::path(
+    get,
+    path = "/atc/traffic",
+    tag = "svc-atc",
+    responses(
+        (status = 200, description = "Request successful.", body = [TrafficReport]),
+        (status = 500, description = "Request unsuccessful."),
+    )
+)]
+pub async fn get_traffic(
+    Extension(config): Extension<Config>,
+) -> Result<Json<Vec<TrafficReport>>, StatusCode> {
+    rest_debug!("entry.");
 
-        let path = flight_plan.path;
-        assert_eq!(path.len(), 1);
-        assert_eq!(path[0].latitude, 1.0);
-        assert_eq!(path[0].longitude, 2.0);
-        assert_eq!(path[0].altitude_meters, 3.0);
+    let threshold =
+        Duration::try_seconds(config.telemetry_stale_seconds as i64).ok_or_else(|| {
+            rest_error!("could not create duration.");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-        assert!(flight_plan.acquire.is_empty());
-        assert!(flight_plan.deliver.is_empty());
-    }
+    let now = Utc::now();
+    let reports = get_traffic_picture()
+        .await
+        .read()
+        .await
+        .tracks()
+        .map(|track| track.to_report(now, threshold))
+        .collect::<Vec<TrafficReport>>();
 
-    #[test]
-    fn test_from_flight_plan_object_invalid() {
-        let now = Utc::now();
-        let object = flight_plan::Object {
-            id: "id".to_string(),
-            data: Some(flight_plan::Data {
-                session_id: "session_id".to_string(),
-                vehicle_id: "vehicle_id".to_string(),
-                origin_vertiport_id: Some("origin_vertiport_id".to_string()),
-                target_vertiport_id: Some("target_vertiport_id".to_string()),
-                origin_vertipad_id: "origin_vertipad_id".to_string(),
-                target_vertipad_id: "target_vertipad_id".to_string(),
-                origin_timeslot_start: Some((now + Duration::try_seconds(1).unwrap()).into()),
-                origin_timeslot_end: Some((now + Duration::try_seconds(2).unwrap()).into()),
-                target_timeslot_start: Some((now + Duration::try_seconds(3).unwrap()).into()),
-                target_timeslot_end: Some((now + Duration::try_seconds(4).unwrap()).into()),
-                path: Some(GeoLineStringZ {
-                    points: vec![GeoPointZ {
-                        y: 1.0,
-                        x: 2.0,
-                        z: 3.0,
-                    }],
-                }),
-                ..Default::default()
-            }),
-        };
+    rest_debug!("returning {} tracks.", reports.len());
+    Ok(Json(reports))
+}
 
-        let mut tmp = object.clone();
-        tmp.data = None;
-        assert_eq!(
-            FlightPlan::try_from(tmp).unwrap_err(),
-            FlightPlanError::Data
-        );
+/// Get conformance alerts
+///
+/// Returns the most recent alerts raised for aircraft deviating from their
+///  acknowledged flight plans, oldest first.
+#[utoipa::path(
+    get,
+    path = "/atc/alerts/conformance",
+    tag = "svc-atc",
+    responses(
+        (status = 200, description = "Request successful.", body = [ConformanceAlert]),
+    )
+)]
+pub async fn get_conformance_alerts() -> Json<Vec<ConformanceAlert>> {
+    rest_debug!("entry.");
 
-        let mut tmp = object.clone();
-        tmp.data.as_mut().unwrap().origin_vertiport_id = None;
-        assert_eq!(
-            FlightPlan::try_from(tmp).unwrap_err(),
-            FlightPlanError::OriginVertiportId
-        );
+    let alerts = crate::common::conformance::get_conformance_monitor()
+        .await
+        .lock()
+        .await
+        .alerts()
+        .cloned()
+        .collect::<Vec<ConformanceAlert>>();
 
-        let mut tmp = object.clone();
-        tmp.data.as_mut().unwrap().target_vertiport_id = None;
-        assert_eq!(
-            FlightPlan::try_from(tmp).unwrap_err(),
-            FlightPlanError::TargetVertiportId
-        );
+    rest_debug!("returning {} alerts.", alerts.len());
+    Json(alerts)
+}
 
-        let mut tmp = object.clone();
-        tmp.data.as_mut().unwrap().origin_timeslot_start = None;
-        assert_eq!(
-            FlightPlan::try_from(tmp).unwrap_err(),
-            FlightPlanError::OriginTimeslotStart
-        );
+/// Get separation alerts
+///
+/// Returns the active predictions of loss of separation between airborne
+///  aircraft, soonest conflict first.
+#[utoipa::path(
+    get,
+    path = "/atc/alerts/separation",
+    tag = "svc-atc",
+    responses(
+        (status = 200, description = "Request successful.", body = [SeparationAlert]),
+    )
+)]
+pub async fn get_separation_alerts() -> Json<Vec<SeparationAlert>> {
+    rest_debug!("entry.");
 
-        let mut tmp = object.clone();
-        tmp.data.as_mut().unwrap().origin_timeslot_end = None;
-        assert_eq!(
-            FlightPlan::try_from(tmp).unwrap_err(),
-            FlightPlanError::OriginTimeslotEnd
-        );
+    let mut alerts = crate::common::tactical::get_tactical_monitor()
+        .await
+        .lock()
+        .await
+        .alerts()
+        .cloned()
+        .collect::<Vec<SeparationAlert>>();
 
-        let mut tmp = object.clone();
-        tmp.data.as_mut().unwrap().target_timeslot_start = None;
-        assert_eq!(
-            FlightPlan::try_from(tmp).unwrap_err(),
-            FlightPlanError::TargetTimeslotStart
-        );
+    alerts.sort_by(|a, b| {
+        a.time_to_conflict_seconds
+            .total_cmp(&b.time_to_conflict_seconds)
+    });
 
-        let mut tmp = object.clone();
-        tmp.data.as_mut().unwrap().target_timeslot_end = None;
-        assert_eq!(
-            FlightPlan::try_from(tmp).unwrap_err(),
-            FlightPlanError::TargetTimeslotEnd
-        );
+    rest_debug!("returning {} alerts.", alerts.len());
+    Json(alerts)
+}
 
-        let mut tmp = object.clone();
-        tmp.data.as_mut().unwrap().path = None;
-        assert_eq!(
-            FlightPlan::try_from(tmp).unwrap_err(),
-            FlightPlanError::Path
-        );
-    }
+/// Get lost-link alerts
+///
+/// Returns the airborne aircraft that stopped reporting their position,
+///  with the contingency path they are assumed to fly, oldest alert first.
+#[utoipa::path(
+    get,
+    path = "/atc/alerts/lost-link",
+    tag = "svc-atc",
+    responses(
+        (status = 200, description = "Request successful.", body = [LostLinkAlert]),
+    )
+)]
+pub async fn get_lost_link_alerts() -> Json<Vec<LostLinkAlert>> {
+    rest_debug!("entry.");
 
-    #[tokio::test]
-    async fn test_health_check() {
-        let config = crate::config::Config::default();
-        let grpc_clients = GrpcClients::default(config);
-        let result = health_check(Extension(grpc_clients)).await;
-        assert!(result.is_ok());
+    let alerts = get_contingencies().await.lock().await.alerts();
+
+    rest_debug!("returning {} alerts.", alerts.len());
+    Json(alerts)
+}
+
+/// Get vertiports
+///
+/// Returns the vertiports in the registry with their enabled vertipads,
+///  as last fetched from svc-storage.
+#[utoipa::path(
+    get,
+    path = "/atc/vertiports",
+    tag = "svc-atc",
+    responses(
+        (status = 200, description = "Request successful.", body = [Vertiport]),
+    )
+)]
+pub async fn get_vertiports() -> Json<Vec<Vertiport>> {
+    rest_debug!("entry.");
+
+    let vertiports = get_vertiport_registry().await.read().await.vertiports();
+
+    rest_debug!("returning {} vertiports.", vertiports.len());
+    Json(vertiports)
+}
+
+/// Get restricted zones
+///
+/// Returns every restricted zone, active or not.
+#[utoipa::path(
+    get,
+    path = "/atc/zones",
+    tag = "svc-atc",
+    responses(
+        (status = 200, description = "Request successful.", body = [Zone]),
+    )
+)]
+pub async fn get_zones() -> Json<Vec<Zone>> {
+    rest_debug!("entry.");
+
+    let zones = get_zone_registry().await.read().await.list();
+
+    rest_debug!("returning {} zones.", zones.len());
+    Json(zones)
+}
+
+/// Add or replace a restricted zone
+///
+/// A zone with an existing ID replaces that zone. Zones without an ID are
+///  assigned one, returned in the response.
+#[utoipa::path(
+    post,
+    path = "/atc/zones",
+    tag = "svc-atc",
+    request_body = Zone,
+    responses(
+        (status = 200, description = "Zone stored.", body = Zone),
+        (status = 400, description = "Invalid vertices, altitudes or time windows."),
+    )
+)]
+pub async fn post_zone(Json(payload): Json<Zone>) -> Result<Json<Zone>, StatusCode> {
+    rest_debug!("entry.");
+
+    let zone = get_zone_registry()
+        .await
+        .write()
+        .await
+        .insert(payload)
+        .map_err(|e| {
+            rest_warn!("rejected zone: {e}");
+            StatusCode::BAD_REQUEST
+        })?;
+
+    rest_info!("stored zone {}.", zone.id);
+    Ok(Json(zone))
+}
+
+/// Remove a restricted zone
+#[utoipa::path(
+    delete,
+    path = "/atc/zones/{id}",
+    tag = "svc-atc",
+    params(
+        ("id" = String, Path, description = "Zone ID"),
+    ),
+    responses(
+        (status = 200, description = "Zone removed."),
+        (status = 404, description = "Zone not found."),
+    )
+)]
+pub async fn delete_zone(Path(zone_id): Path<String>) -> Result<(), StatusCode> {
+    rest_debug!("entry.");
+
+    match get_zone_registry().await.write().await.remove(&zone_id) {
+        Some(_) => {
+            rest_info!("removed zone {zone_id}.");
+            Ok(())
+        }
+        None => {
+            rest_warn!("zone {zone_id} not found.");
+            Err(StatusCode::NOT_FOUND)
+        }
+    }
+}
+
+/// Get temporary flight restrictions
+///
+/// Returns every published restriction that has not expired, soonest first.
+#[utoipa::path(
+    get,
+    path = "/atc/restrictions",
+    tag = "svc-atc",
+    responses(
+        (status = 200, description = "Request successful.", body = [Restriction]),
+    )
+)]
+pub async fn get_restrictions() -> Json<Vec<Restriction>> {
+    rest_debug!("entry.");
+
+    let restrictions = get_restriction_store().await.lock().await.list();
+
+    rest_debug!("returning {} restrictions.", restrictions.len());
+    Json(restrictions)
+}
+
+/// Publish a temporary flight restriction
+///
+/// A restriction with an existing ID replaces that restriction. Flight
+///  plans affected by the restriction are flagged or cancelled once it
+///  becomes active.
+#[utoipa::path(
+    post,
+    path = "/atc/restrictions",
+    tag = "svc-atc",
+    request_body = Restriction,
+    responses(
+        (status = 200, description = "Restriction published.", body = Restriction),
+        (status = 400, description = "Invalid area, altitudes or times."),
+        (status = 500, description = "Restriction could not be saved."),
+    )
+)]
+pub async fn post_restriction(
+    Extension(config): Extension<Config>,
+    Json(payload): Json<Restriction>,
+) -> Result<Json<Restriction>, StatusCode> {
+    rest_debug!("entry.");
+
+    publish_restriction(payload, &config.restrictions_file)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            rest_warn!("could not publish restriction: {e}");
+            match e {
+                RestrictionError::File | RestrictionError::Json => {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+                _ => StatusCode::BAD_REQUEST,
+            }
+        })
+}
+
+/// Withdraw a temporary flight restriction
+#[utoipa::path(
+    delete,
+    path = "/atc/restrictions/{id}",
+    tag = "svc-atc",
+    params(
+        ("id" = String, Path, description = "Restriction ID"),
+    ),
+    responses(
+        (status = 200, description = "Restriction withdrawn."),
+        (status = 404, description = "Restriction not found."),
+        (status = 500, description = "Restrictions could not be saved."),
+    )
+)]
+pub async fn delete_restriction(
+    Extension(config): Extension<Config>,
+    Path(restriction_id): Path<String>,
+) -> Result<(), StatusCode> {
+    rest_debug!("entry.");
+
+    match withdraw_restriction(&restriction_id, &config.restrictions_file).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => {
+            rest_warn!("restriction {restriction_id} not found.");
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            rest_error!("could not withdraw restriction: {e}");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Ingest a weather report
+///
+/// The report replaces the previous weather grid and hazards, and the
+///  flight plans returned to aircraft are re-evaluated against it. Returns
+///  the weather alerts of the affected flight plans.
+#[utoipa::path(
+    post,
+    path = "/atc/weather",
+    tag = "svc-atc",
+    request_body = WeatherReport,
+    responses(
+        (status = 200, description = "Weather report ingested.", body = [WeatherAlert]),
+        (status = 400, description = "Invalid validity, samples or hazard areas."),
+    )
+)]
+pub async fn post_weather(
+    Extension(config): Extension<Config>,
+    Json(payload): Json<WeatherReport>,
+) -> Result<Json<Vec<WeatherAlert>>, StatusCode> {
+    rest_debug!("entry.");
+
+    let limits = WeatherLimits::from(&config);
+    let affected = ingest_report(payload, &limits).await.map_err(|e| {
+        rest_warn!("could not ingest weather report: {e}");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    rest_info!("{affected} flight plans affected by weather.");
+    Ok(Json(get_weather().await.lock().await.affected()))
+}
+
+/// Get active weather hazards
+///
+/// Returns the hazard areas of the latest weather report that are active,
+///  soonest first.
+#[utoipa::path(
+    get,
+    path = "/atc/weather/hazards",
+    tag = "svc-atc",
+    responses(
+        (status = 200, description = "Request successful.", body = [WeatherHazard]),
+    )
+)]
+pub async fn get_weather_hazards() -> Json<Vec<WeatherHazard>> {
+    rest_debug!("entry.");
+
+    let hazards = get_weather().await.lock().await.hazards(Utc::now());
+
+    rest_debug!("returning {} hazards.", hazards.len());
+    Json(hazards)
+}
+
+/// Get flights affected by weather
+///
+/// Returns the weather alerts of the flight plans returned to aircraft, as
+///  of their last re-evaluation.
+#[utoipa::path(
+    get,
+    path = "/atc/weather/flights",
+    tag = "svc-atc",
+    responses(
+        (status = 200, description = "Request successful.", body = [WeatherAlert]),
+    )
+)]
+pub async fn get_weather_flights() -> Json<Vec<WeatherAlert>> {
+    rest_debug!("entry.");
+
+    let alerts = get_weather().await.lock().await.affected();
+
+    rest_debug!("returning {} weather alerts.", alerts.len());
+    Json(alerts)
+}
+
+/// Get the vertipad schedule of a vertiport
+///
+/// Returns the takeoffs and landings at the vertiport within an hour of the
+///  current time in the recommended order, and the flight plans occupying
+///  the same vertipad at the same time.
+#[utoipa::path(
+    get,
+    path = "/atc/vertiports/{id}/schedule",
+    tag = "svc-atc",
+    params(
+        ("id" = String, Path, description = "Vertiport ID"),
+    ),
+    responses(
+        (status = 200, description = "Request successful.", body = VertiportSchedule),
+        (status = 400, description = "Invalid vertiport ID."),
+        (status = 500, description = "Request unsuccessful."),
+        (status = 503, description = "Dependencies not available."),
+    )
+)]
+pub async fn get_vertiport_schedule(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(config): Extension<Config>,
+    Path(vertiport_id): Path<String>,
+) -> Result<Json<VertiportSchedule>, StatusCode> {
+    rest_debug!("entry.");
+
+    let vertiport_id = to_uuid(&vertiport_id).ok_or_else(|| {
+        rest_error!("invalid vertiport UUID.");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let policy = CachePolicy::from(&config);
+    let (window_start, window_end) = policy.window(Utc::now());
+    let spacing = crate::common::sequencing::Spacing::from(&config);
+    crate::common::sequencing::get_vertiport_schedule(
+        &vertiport_id.to_string(),
+        spacing,
+        window_start,
+        window_end,
+        &policy,
+        &grpc_clients,
+    )
+    .await
+    .map(Json)
+    .map_err(|e| {
+        rest_error!("could not get vertiport schedule: {e}");
+        match e {
+            crate::common::deconfliction::CheckError::Unavailable => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            crate::common::deconfliction::CheckError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    })
+}
+
+/// Get airspace sectors
+///
+/// Returns every sector with a capacity, ordered by ID.
+#[utoipa::path(
+    get,
+    path = "/atc/capacity/sectors",
+    tag = "svc-atc",
+    responses(
+        (status = 200, description = "Request successful.", body = [Sector]),
+    )
+)]
+pub async fn get_sectors() -> Json<Vec<Sector>> {
+    rest_debug!("entry.");
+
+    let sectors = get_capacities().await.read().await.sectors();
+
+    rest_debug!("returning {} sectors.", sectors.len());
+    Json(sectors)
+}
+
+/// Add or replace an airspace sector
+///
+/// A sector with an existing ID replaces that sector. Sectors without an
+///  ID are assigned one, returned in the response.
+#[utoipa::path(
+    post,
+    path = "/atc/capacity/sectors",
+    tag = "svc-atc",
+    request_body = Sector,
+    responses(
+        (status = 200, description = "Sector stored.", body = Sector),
+        (status = 400, description = "Invalid vertices or altitudes."),
+    )
+)]
+pub async fn post_sector(Json(payload): Json<Sector>) -> Result<Json<Sector>, StatusCode> {
+    rest_debug!("entry.");
+
+    let sector = get_capacities()
+        .await
+        .write()
+        .await
+        .insert_sector(payload)
+        .map_err(|e| {
+            rest_warn!("rejected sector: {e}");
+            StatusCode::BAD_REQUEST
+        })?;
+
+    rest_info!(
+        "stored sector {} with capacity {}.",
+        sector.id,
+        sector.capacity
+    );
+    Ok(Json(sector))
+}
+
+/// Remove an airspace sector
+#[utoipa::path(
+    delete,
+    path = "/atc/capacity/sectors/{id}",
+    tag = "svc-atc",
+    params(
+        ("id" = String, Path, description = "Sector ID"),
+    ),
+    responses(
+        (status = 200, description = "Sector removed."),
+        (status = 404, description = "Sector not found."),
+    )
+)]
+pub async fn delete_sector(Path(sector_id): Path<String>) -> Result<(), StatusCode> {
+    rest_debug!("entry.");
+
+    match get_capacities()
+        .await
+        .write()
+        .await
+        .remove_sector(&sector_id)
+    {
+        Some(_) => {
+            rest_info!("removed sector {sector_id}.");
+            Ok(())
+        }
+        None => {
+            rest_warn!("sector {sector_id} not found.");
+            Err(StatusCode::NOT_FOUND)
+        }
+    }
+}
+
+/// Get vertiport capacities
+///
+/// Returns the vertiports with a configured capacity, other vertiports
+///  use the default capacity.
+#[utoipa::path(
+    get,
+    path = "/atc/capacity/vertiports",
+    tag = "svc-atc",
+    responses(
+        (status = 200, description = "Request successful.", body = [VertiportCapacity]),
+    )
+)]
+pub async fn get_vertiport_capacities() -> Json<Vec<VertiportCapacity>> {
+    rest_debug!("entry.");
+
+    let vertiports = get_capacities().await.read().await.vertiports();
+
+    rest_debug!("returning {} vertiport capacities.", vertiports.len());
+    Json(vertiports)
+}
+
+/// Set the capacity of a vertiport
+#[utoipa::path(
+    post,
+    path = "/atc/capacity/vertiports",
+    tag = "svc-atc",
+    request_body = VertiportCapacity,
+    responses(
+        (status = 200, description = "Capacity stored."),
+        (status = 400, description = "Invalid vertiport ID."),
+    )
+)]
+pub async fn post_vertiport_capacity(
+    Json(payload): Json<VertiportCapacity>,
+) -> Result<(), StatusCode> {
+    rest_debug!("entry.");
+
+    let vertiport_id = payload.vertiport_id.clone();
+    let capacity = payload.capacity;
+    get_capacities()
+        .await
+        .write()
+        .await
+        .set_vertiport(payload)
+        .map_err(|e| {
+            rest_warn!("rejected vertiport capacity: {e}");
+            StatusCode::BAD_REQUEST
+        })?;
+
+    rest_info!("set capacity of vertiport {vertiport_id} to {capacity}.");
+    Ok(())
+}
+
+/// Remove the capacity of a vertiport
+///
+/// The vertiport falls back to the default capacity.
+#[utoipa::path(
+    delete,
+    path = "/atc/capacity/vertiports/{id}",
+    tag = "svc-atc",
+    params(
+        ("id" = String, Path, description = "Vertiport ID"),
+    ),
+    responses(
+        (status = 200, description = "Capacity removed."),
+        (status = 404, description = "Vertiport has no configured capacity."),
+    )
+)]
+pub async fn delete_vertiport_capacity(Path(vertiport_id): Path<String>) -> Result<(), StatusCode> {
+    rest_debug!("entry.");
+
+    match get_capacities()
+        .await
+        .write()
+        .await
+        .remove_vertiport(&vertiport_id)
+    {
+        Some(_) => {
+            rest_info!("removed capacity of vertiport {vertiport_id}.");
+            Ok(())
+        }
+        None => {
+            rest_warn!("vertiport {vertiport_id} has no configured capacity.");
+            Err(StatusCode::NOT_FOUND)
+        }
+    }
+}
+
+/// Get the demand and capacity balance
+///
+/// Returns the periods within the window where the flight plans scheduled
+///  in svc-storage exceed the capacity of a sector or vertiport, and the
+///  ground delays proposed to bring the demand under capacity.
+#[utoipa::path(
+    get,
+    path = "/atc/capacity/hotspots",
+    tag = "svc-atc",
+    params(CapacityQuery),
+    responses(
+        (status = 200, description = "Request successful.", body = CapacityReport),
+        (status = 400, description = "Invalid window."),
+        (status = 500, description = "Request unsuccessful."),
+        (status = 503, description = "Dependencies not available."),
+    )
+)]
+pub async fn get_capacity_hotspots(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(config): Extension<Config>,
+    Query(query): Query<CapacityQuery>,
+) -> Result<Json<CapacityReport>, StatusCode> {
+    rest_debug!("entry.");
+
+    let now = Utc::now();
+    let start = query.from.unwrap_or(now);
+    let end = query
+        .to
+        .unwrap_or(start + CachePolicy::from(&config).lookahead);
+    if start > end {
+        rest_error!("window starts after it ends.");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let policy = CapacityPolicy::from(&config);
+    get_capacity_report(start, end, now, &policy, &grpc_clients)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            rest_error!("could not get capacity report: {e}");
+            match e {
+                CheckError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+                CheckError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })
+}
+
+/// Request takeoff or landing clearance at a vertipad
+///
+/// Clearance is granted if the vertipad is free and no other aircraft are
+///  flying nearby, held until they are, or denied if the request does not
+///  match an acknowledged flight plan. Granted clearances expire after the
+///  configured validity.
+#[utoipa::path(
+    post,
+    path = "/atc/clearance",
+    tag = "svc-atc",
+    request_body = ClearanceRequest,
+    responses(
+        (status = 200, description = "Request successful.", body = Clearance),
+        (status = 400, description = "Invalid flight plan ID."),
+        (status = 404, description = "Flight plan not found."),
+        (status = 500, description = "Request unsuccessful."),
+        (status = 503, description = "Dependencies not available."),
+    )
+)]
+pub async fn request_clearance(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(config): Extension<Config>,
+    Json(payload): Json<ClearanceRequest>,
+) -> Result<Json<Clearance>, StatusCode> {
+    rest_debug!("entry.");
+
+    let fp_id = to_uuid(&payload.fp_id).ok_or_else(|| {
+        rest_error!("invalid flight plan UUID.");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let policy = ClearancePolicy::from(&config);
+    crate::common::clearance::request_clearance(fp_id, &payload, &policy, &grpc_clients)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            rest_error!("could not process clearance request: {e}");
+            match e {
+                ClearanceError::NotFound => StatusCode::NOT_FOUND,
+                ClearanceError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+                ClearanceError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })
+}
+
+/// Declare an emergency
+///
+/// The aircraft is granted a priority landing clearance on the nearest
+///  vertipad able to receive it, and the other aircraft scheduled on the
+///  vertipad are advised to delay. Every emergency event is written to the
+///  audit stream.
+#[utoipa::path(
+    post,
+    path = "/atc/emergency",
+    tag = "svc-atc",
+    request_body = EmergencyDeclaration,
+    responses(
+        (status = 200, description = "Request successful.", body = EmergencyResponse),
+        (status = 400, description = "Invalid aircraft ID or position."),
+        (status = 404, description = "No suitable vertipad within range."),
+        (status = 503, description = "Dependencies not available."),
+    )
+)]
+pub async fn declare_emergency(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(config): Extension<Config>,
+    Json(payload): Json<EmergencyDeclaration>,
+) -> Result<Json<EmergencyResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let policy = EmergencyPolicy::from(&config);
+    crate::common::emergency::declare_emergency(&payload, &policy, &grpc_clients)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            rest_error!("could not process emergency declaration: {e}");
+            match e {
+                EmergencyError::AircraftId | EmergencyError::Position => StatusCode::BAD_REQUEST,
+                EmergencyError::NoSuitablePad => StatusCode::NOT_FOUND,
+                EmergencyError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            }
+        })
+}
+
+/// Get the emergency audit stream
+///
+/// Returns the latest emergency events, oldest first.
+#[utoipa::path(
+    get,
+    path = "/atc/emergency/audit",
+    tag = "svc-atc",
+    responses(
+        (status = 200, description = "Request successful.", body = [EmergencyEvent]),
+    )
+)]
+pub async fn get_emergency_audit() -> Json<Vec<EmergencyEvent>> {
+    rest_debug!("entry.");
+
+    let events = get_emergencies().await.lock().await.events();
+
+    rest_debug!("returning {} emergency events.", events.len());
+    Json(events)
+}
+
+/// Assign a holding pattern to an arriving aircraft
+///
+/// The aircraft is stacked above the aircraft already holding for its
+///  target vertipad and released for approach when the vertipad frees up.
+///  The holding pattern is returned with the flight plan.
+#[utoipa::path(
+    post,
+    path = "/atc/holding",
+    tag = "svc-atc",
+    request_body = HoldingRequest,
+    responses(
+        (status = 200, description = "Holding pattern assigned.", body = HoldingPattern),
+        (status = 400, description = "Invalid flight plan ID."),
+        (status = 404, description = "Flight plan not found."),
+        (status = 409, description = "Target vertipad is not occupied."),
+        (status = 500, description = "Request unsuccessful."),
+        (status = 503, description = "Dependencies not available."),
+    )
+)]
+pub async fn assign_holding(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(config): Extension<Config>,
+    Json(payload): Json<HoldingRequest>,
+) -> Result<Json<HoldingPattern>, StatusCode> {
+    rest_debug!("entry.");
+
+    let fp_id = to_uuid(&payload.fp_id).ok_or_else(|| {
+        rest_error!("invalid flight plan UUID.");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let policy = HoldingPolicy::from(&config);
+    crate::common::holding::assign_holding(fp_id, &policy, &grpc_clients)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            rest_warn!("could not assign holding pattern: {e}");
+            match e {
+                HoldingError::NotFound => StatusCode::NOT_FOUND,
+                HoldingError::PadAvailable => StatusCode::CONFLICT,
+                HoldingError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+                HoldingError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })
+}
+
+/// Get the holding stacks of a vertiport
+///
+/// Returns the aircraft holding for the vertipads of the vertiport, by
+///  vertipad and from the lowest level up.
+#[utoipa::path(
+    get,
+    path = "/atc/vertiports/{id}/holding",
+    tag = "svc-atc",
+    params(
+        ("id" = String, Path, description = "Vertiport ID"),
+    ),
+    responses(
+        (status = 200, description = "Request successful.", body = [HoldingPattern]),
+    )
+)]
+pub async fn get_vertiport_holding(Path(vertiport_id): Path<String>) -> Json<Vec<HoldingPattern>> {
+    rest_debug!("entry.");
+    Json(get_holding_stacks().await.lock().await.list(&vertiport_id))
+}
+
+/// Get the rejection of a flight plan
+///
+/// Returns the reason code of the latest rejection by the carrier, if the
+///  flight plan was not acknowledged since.
+#[utoipa::path(
+    get,
+    path = "/atc/flights/{id}/denial",
+    tag = "svc-atc",
+    params(
+        ("id" = String, Path, description = "Flight plan ID"),
+    ),
+    responses(
+        (status = 200, description = "Request successful.", body = FlightDenial),
+        (status = 400, description = "Invalid flight plan ID."),
+        (status = 404, description = "No rejection registered for the flight plan."),
+    )
+)]
+pub async fn get_flight_denial(
+    Path(fp_id): Path<String>,
+) -> Result<Json<FlightDenial>, StatusCode> {
+    rest_debug!("entry.");
+
+    let fp_id = to_uuid(&fp_id).ok_or_else(|| {
+        rest_error!("invalid flight plan UUID.");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    crate::common::get_denials()
+        .await
+        .lock()
+        .await
+        .get(&fp_id)
+        .copied()
+        .map(|denial| Json(FlightDenial::from(denial)))
+        .ok_or_else(|| {
+            rest_info!("no rejection registered for flight plan {fp_id}.");
+            StatusCode::NOT_FOUND
+        })
+}
+
+/// Get the lifecycle history of a flight plan
+///
+/// Returns the current lifecycle state of the flight plan and the log of
+///  its state transitions.
+#[utoipa::path(
+    get,
+    path = "/atc/flights/{id}/history",
+    tag = "svc-atc",
+    params(
+        ("id" = String, Path, description = "Flight plan ID"),
+    ),
+    responses(
+        (status = 200, description = "Request successful.", body = FlightHistory),
+        (status = 400, description = "Invalid flight plan ID."),
+        (status = 404, description = "Flight plan not found."),
+        (status = 500, description = "Request unsuccessful."),
+    )
+)]
+pub async fn get_flight_history(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Path(fp_id): Path<String>,
+) -> Result<Json<FlightHistory>, StatusCode> {
+    rest_debug!("entry.");
+
+    let fp_id = to_uuid(&fp_id).ok_or_else(|| {
+        rest_error!("invalid flight plan UUID.");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    crate::common::lifecycle::get_flight_history(fp_id, &grpc_clients)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            rest_error!("could not get flight history: {e}");
+            match e {
+                AckError::NotFound => StatusCode::NOT_FOUND,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })
+}
+
+/// Report the lifecycle state of a flight plan
+///
+/// Aircraft report when they are airborne, have landed or were diverted.
+///  Flight plans can be cancelled until the aircraft takes off.
+#[utoipa::path(
+    post,
+    path = "/atc/flights/{id}/state",
+    tag = "svc-atc",
+    params(
+        ("id" = String, Path, description = "Flight plan ID"),
+    ),
+    request_body = FlightStateRequest,
+    responses(
+        (status = 200, description = "Request successful.", body = FlightHistory),
+        (status = 400, description = "Invalid flight plan ID or state."),
+        (status = 404, description = "Flight plan not found."),
+        (status = 409, description = "Flight plan state does not allow the transition."),
+        (status = 500, description = "Request unsuccessful."),
+    )
+)]
+pub async fn report_flight_state(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Path(fp_id): Path<String>,
+    Json(payload): Json<FlightStateRequest>,
+) -> Result<Json<FlightHistory>, StatusCode> {
+    rest_debug!("entry.");
+
+    let fp_id = to_uuid(&fp_id).ok_or_else(|| {
+        rest_error!("invalid flight plan UUID.");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    if !matches!(
+        payload.state,
+        FlightState::Airborne
+            | FlightState::Landed
+            | FlightState::Diverted
+            | FlightState::Cancelled
+    ) {
+        rest_error!("{:?} can not be reported.", payload.state);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    crate::common::lifecycle::report_flight_state(fp_id, payload.state, &grpc_clients)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            rest_warn!("could not report flight state: {e}");
+            match e {
+                AckError::NotFound => StatusCode::NOT_FOUND,
+                AckError::InvalidState => StatusCode::CONFLICT,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })
+}
+
+/// Divert an airborne aircraft to an alternate landing site
+///
+/// The flight plan is amended to fly directly to the requested vertipad,
+///  or to the nearest alternate if none is requested, and the vertipad is
+///  reserved with a landing clearance. The aircraft receives the amended
+///  flight plan with its flight plan updates.
+#[utoipa::path(
+    post,
+    path = "/atc/flights/{id}/divert",
+    tag = "svc-atc",
+    params(
+        ("id" = String, Path, description = "Flight plan ID"),
+    ),
+    request_body = DivertRequest,
+    responses(
+        (status = 200, description = "Request successful.", body = Diversion),
+        (status = 400, description = "Invalid flight plan ID or vertipad not available."),
+        (status = 404, description = "Flight plan not found or no alternate within range."),
+        (status = 409, description = "Flight plan is not airborne."),
+        (status = 503, description = "Dependencies not available."),
+    )
+)]
+pub async fn divert_flight(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(config): Extension<Config>,
+    Path(fp_id): Path<String>,
+    Json(payload): Json<DivertRequest>,
+) -> Result<Json<Diversion>, StatusCode> {
+    rest_debug!("entry.");
+
+    let fp_id = to_uuid(&fp_id).ok_or_else(|| {
+        rest_error!("invalid flight plan UUID.");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let policy = AlternatePolicy::from(&config);
+    crate::common::vertiports::divert_flight(fp_id, &payload, &policy, &grpc_clients)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            rest_warn!("could not divert flight: {e}");
+            match e {
+                DivertError::Alternate => StatusCode::BAD_REQUEST,
+                DivertError::FlightPlan | DivertError::NoAlternate => StatusCode::NOT_FOUND,
+                DivertError::InvalidState => StatusCode::CONFLICT,
+                DivertError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            }
+        })
+}
+
+/// Assign a lost-link contingency procedure to a flight plan
+///
+/// The procedure is delivered to the aircraft with its flight plan, and
+///  assumed by deconfliction if the aircraft stops reporting its position
+///  while airborne. Flight plans without an assigned procedure continue
+///  to their target vertipad.
+#[utoipa::path(
+    put,
+    path = "/atc/flights/{id}/contingency",
+    tag = "svc-atc",
+    params(
+        ("id" = String, Path, description = "Flight plan ID"),
+    ),
+    request_body = ContingencyRequest,
+    responses(
+        (status = 200, description = "Request successful.", body = Contingency),
+        (status = 400, description = "Invalid flight plan ID or alternate vertipad."),
+        (status = 404, description = "Flight plan not found."),
+        (status = 503, description = "Dependencies not available."),
+    )
+)]
+pub async fn assign_contingency(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Path(fp_id): Path<String>,
+    Json(payload): Json<ContingencyRequest>,
+) -> Result<Json<Contingency>, StatusCode> {
+    rest_debug!("entry.");
+
+    let fp_id = to_uuid(&fp_id).ok_or_else(|| {
+        rest_error!("invalid flight plan UUID.");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    crate::common::contingency::assign_contingency(fp_id, &payload, &grpc_clients)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            rest_warn!("could not assign contingency: {e}");
+            match e {
+                ContingencyError::FlightPlan => StatusCode::NOT_FOUND,
+                ContingencyError::Alternate => StatusCode::BAD_REQUEST,
+                ContingencyError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::uuid::Uuid;
+    use svc_storage_client_grpc::prelude::{GeoLineStringZ, GeoPointZ};
+
+    #[test]
+    fn test_from_flight_plan_object_valid() {
+        let now = Utc::now();
+        let object = flight_plan::Object {
+            id: "id".to_string(),
+            data: Some(flight_plan::Data {
+                session_id: "session_id".to_string(),
+                vehicle_id: "vehicle_id".to_string(),
+                origin_vertiport_id: Some("origin_vertiport_id".to_string()),
+                target_vertiport_id: Some("target_vertiport_id".to_string()),
+                origin_vertipad_id: "origin_vertipad_id".to_string(),
+                target_vertipad_id: "target_vertipad_id".to_string(),
+                origin_timeslot_start: Some((now + Duration::try_seconds(1).unwrap()).into()),
+                origin_timeslot_end: Some((now + Duration::try_seconds(2).unwrap()).into()),
+                target_timeslot_start: Some((now + Duration::try_seconds(3).unwrap()).into()),
+                target_timeslot_end: Some((now + Duration::try_seconds(4).unwrap()).into()),
+                path: Some(GeoLineStringZ {
+                    points: vec![GeoPointZ {
+                        y: 1.0,
+                        x: 2.0,
+                        z: 3.0,
+                    }],
+                }),
+                ..Default::default()
+            }),
+        };
+
+        let flight_plan = FlightPlan::try_from(object).unwrap();
+        assert_eq!(flight_plan.session_id, "session_id");
+        assert_eq!(flight_plan.flight_uuid, "id");
+        assert_eq!(flight_plan.aircraft_id, "vehicle_id");
+
+        assert_eq!(
+            flight_plan.origin_vertiport_id,
+            "origin_vertiport_id".to_string()
+        );
+        assert_eq!(
+            flight_plan.target_vertiport_id,
+            "target_vertiport_id".to_string()
+        );
+
+        assert_eq!(flight_plan.origin_vertipad_id, "origin_vertipad_id");
+        assert_eq!(flight_plan.target_vertipad_id, "target_vertipad_id");
+
+        assert_eq!(
+            flight_plan.origin_timeslot_start,
+            now + Duration::try_seconds(1).unwrap()
+        );
+        assert_eq!(
+            flight_plan.origin_timeslot_end,
+            now + Duration::try_seconds(2).unwrap()
+        );
+        assert_eq!(
+            flight_plan.target_timeslot_start,
+            now + Duration::try_seconds(3).unwrap()
+        );
+        assert_eq!(
+            flight_plan.target_timeslot_end,
+            now + Duration::try_seconds(4).unwrap()
+        );
+
+        let path = flight_plan.path;
+        assert_eq!(path.len(), 1);
+        assert_eq!(path[0].latitude, 1.0);
+        assert_eq!(path[0].longitude, 2.0);
+        assert_eq!(path[0].altitude_meters, 3.0);
+
+        assert!(flight_plan.acquire.is_empty());
+        assert!(flight_plan.deliver.is_empty());
+    }
+
+    #[test]
+    fn test_from_flight_plan_object_invalid() {
+        let now = Utc::now();
+        let object = flight_plan::Object {
+            id: "id".to_string(),
+            data: Some(flight_plan::Data {
+                session_id: "session_id".to_string(),
+                vehicle_id: "vehicle_id".to_string(),
+                origin_vertiport_id: Some("origin_vertiport_id".to_string()),
+                target_vertiport_id: Some("target_vertiport_id".to_string()),
+                origin_vertipad_id: "origin_vertipad_id".to_string(),
+                target_vertipad_id: "target_vertipad_id".to_string(),
+                origin_timeslot_start: Some((now + Duration::try_seconds(1).unwrap()).into()),
+                origin_timeslot_end: Some((now + Duration::try_seconds(2).unwrap()).into()),
+                target_timeslot_start: Some((now + Duration::try_seconds(3).unwrap()).into()),
+                target_timeslot_end: Some((now + Duration::try_seconds(4).unwrap()).into()),
+                path: Some(GeoLineStringZ {
+                    points: vec![GeoPointZ {
+                        y: 1.0,
+                        x: 2.0,
+                        z: 3.0,
+                    }],
+                }),
+                ..Default::default()
+            }),
+        };
+
+        let mut tmp = object.clone();
+        tmp.data = None;
+        assert_eq!(
+            FlightPlan::try_from(tmp).unwrap_err(),
+            FlightPlanError::Data
+        );
+
+        let mut tmp = object.clone();
+        tmp.data.as_mut().unwrap().origin_vertiport_id = None;
+        assert_eq!(
+            FlightPlan::try_from(tmp).unwrap_err(),
+            FlightPlanError::OriginVertiportId
+        );
+
+        let mut tmp = object.clone();
+        tmp.data.as_mut().unwrap().target_vertiport_id = None;
+        assert_eq!(
+            FlightPlan::try_from(tmp).unwrap_err(),
+            FlightPlanError::TargetVertiportId
+        );
+
+        let mut tmp = object.clone();
+        tmp.data.as_mut().unwrap().origin_timeslot_start = None;
+        assert_eq!(
+            FlightPlan::try_from(tmp).unwrap_err(),
+            FlightPlanError::OriginTimeslotStart
+        );
+
+        let mut tmp = object.clone();
+        tmp.data.as_mut().unwrap().origin_timeslot_end = None;
+        assert_eq!(
+            FlightPlan::try_from(tmp).unwrap_err(),
+            FlightPlanError::OriginTimeslotEnd
+        );
+
+        let mut tmp = object.clone();
+        tmp.data.as_mut().unwrap().target_timeslot_start = None;
+        assert_eq!(
+            FlightPlan::try_from(tmp).unwrap_err(),
+            FlightPlanError::TargetTimeslotStart
+        );
+
+        let mut tmp = object.clone();
+        tmp.data.as_mut().unwrap().target_timeslot_end = None;
+        assert_eq!(
+            FlightPlan::try_from(tmp).unwrap_err(),
+            FlightPlanError::TargetTimeslotEnd
+        );
+
+        let mut tmp = object.clone();
+        tmp.data.as_mut().unwrap().path = None;
+        assert_eq!(
+            FlightPlan::try_from(tmp).unwrap_err(),
+            FlightPlanError::Path
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let result = health_check(Extension(grpc_clients)).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_acknowledge_flight_plan() {
+        // bad request - invalid uuid
+        let payload = AckRequest {
+            fp_id: "invalid".to_string(),
+            status: AckStatus::Confirm,
+            reason: None,
+        };
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let error = acknowledge_flight_plan(Extension(grpc_clients.clone()), Json(payload))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        // denial of an existing flight plan
+        let data = flight_plan::mock::get_data_obj();
+        let fp_id = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        let payload = AckRequest {
+            fp_id: fp_id.clone(),
+            status: AckStatus::Deny,
+            reason: Some(DenyReason::Maintenance),
+        };
+        let response = acknowledge_flight_plan(Extension(grpc_clients.clone()), Json(payload))
+            .await
+            .unwrap()
+            .0;
+        assert_eq!(response.fp_id, fp_id);
+        assert_eq!(response.status, AckStatus::Deny);
+        assert_eq!(response.reason, Some(DenyReason::Maintenance));
+        assert!(response.replan_required);
+
+        let denial = get_flight_denial(Path(fp_id.clone())).await.unwrap().0;
+        assert_eq!(denial.fp_id, fp_id);
+        assert_eq!(denial.reason, DenyReason::Maintenance);
+        assert_eq!(denial.timestamp, response.timestamp);
+
+        let error = get_flight_denial(Path("invalid".to_string()))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+        let error = get_flight_denial(Path(Uuid::new_v4().to_string()))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+
+        // acknowledgement of a missing flight plan
+        let payload = AckRequest {
+            fp_id: Uuid::new_v4().to_string(),
+            status: AckStatus::Confirm,
+            reason: None,
+        };
+        let error = acknowledge_flight_plan(Extension(grpc_clients.clone()), Json(payload))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+
+        // confirmation of a cancelled flight plan
+        crate::common::cancel_flight(to_uuid(&fp_id).unwrap(), &grpc_clients)
+            .await
+            .unwrap();
+        let payload = AckRequest {
+            fp_id: fp_id.clone(),
+            status: AckStatus::Confirm,
+            reason: None,
+        };
+        let error = acknowledge_flight_plan(Extension(grpc_clients), Json(payload))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_get_flight_plans() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config.clone());
+        let query = |aircraft_id: &str| PlansQuery {
+            aircraft_id: aircraft_id.to_string(),
+            from: None,
+            to: None,
+            include_cargo: None,
+            limit: None,
+        };
+
+        let error = get_flight_plans(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Query(query("invalid")),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        let aircraft_id = Uuid::new_v4().to_string();
+        let (headers, results) = get_flight_plans(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Query(query(&aircraft_id)),
+        )
+        .await
+        .unwrap();
+        assert!(results.0.is_empty());
+        assert_eq!(headers[0].0, header::AGE);
+
+        let now = Utc::now();
+        let invalid = [
+            PlansQuery {
+                from: Some(now),
+                to: Some(now - Duration::try_minutes(1).unwrap()),
+                ..query(&aircraft_id)
+            },
+            PlansQuery {
+                from: Some(now),
+                to: Some(
+                    now + Duration::try_minutes(config.plans_max_window_minutes as i64 + 1)
+                        .unwrap(),
+                ),
+                ..query(&aircraft_id)
+            },
+            PlansQuery {
+                limit: Some(0),
+                ..query(&aircraft_id)
+            },
+        ];
+        for invalid in invalid {
+            let error = get_flight_plans(
+                Extension(grpc_clients.clone()),
+                Extension(config.clone()),
+                Query(invalid),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(error, StatusCode::BAD_REQUEST);
+        }
+
+        let data = vehicle::mock::get_data_obj();
+        let aircraft_id = grpc_clients
+            .storage
+            .vehicle
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        let results = get_flight_plans(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Query(query(&aircraft_id)),
+        )
+        .await
+        .unwrap()
+        .1
+         .0;
+        assert!(results.is_empty());
+
+        // Flight plans departing later than the default window
+        let departure = now + Duration::try_hours(5).unwrap();
+        let mut fp_ids = vec![];
+        for minutes in [20, 0] {
+            let departure = departure + Duration::try_minutes(minutes).unwrap();
+            let mut data = crate::test_util::mock_flight_plan_data(
+                &[(-40.5, 143.5, 100.0), (-40.5, 143.52, 100.0)],
+                departure,
+                departure + Duration::try_minutes(10).unwrap(),
+            );
+            data.vehicle_id = aircraft_id.clone();
+            let fp_id = grpc_clients
+                .storage
+                .flight_plan
+                .insert(data)
+                .await
+                .unwrap()
+                .into_inner()
+                .object
+                .unwrap()
+                .id;
+
+            grpc_clients
+                .storage
+                .flight_plan_parcel
+                .insert(flight_plan_parcel::RowData {
+                    flight_plan_id: fp_id.clone(),
+                    parcel_id: Uuid::new_v4().to_string(),
+                    acquire: true,
+                    deliver: false,
+                })
+                .await
+                .unwrap();
+            fp_ids.push(fp_id);
+        }
+
+        let results = get_flight_plans(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Query(query(&aircraft_id)),
+        )
+        .await
+        .unwrap()
+        .1
+         .0;
+        assert!(results.is_empty());
+
+        let window = PlansQuery {
+            from: Some(now + Duration::try_hours(4).unwrap()),
+            to: Some(now + Duration::try_hours(6).unwrap()),
+            ..query(&aircraft_id)
+        };
+        let results = get_flight_plans(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Query(window.clone()),
+        )
+        .await
+        .unwrap()
+        .1
+         .0;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].flight_uuid, fp_ids[1]);
+        assert_eq!(results[0].acquire.len(), 1);
+        let with_cargo = results[0].clone();
+
+        let results = get_flight_plans(
+            Extension(grpc_clients.clone()),
+            Extension(config),
+            Query(PlansQuery {
+                include_cargo: Some(false),
+                limit: Some(1),
+                ..window
+            }),
+        )
+        .await
+        .unwrap()
+        .1
+         .0;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].flight_uuid, fp_ids[1]);
+        assert!(results[0].acquire.is_empty());
+
+        // The cargo left out of the response is still on board
+        assert_eq!(results[0].flight_type, with_cargo.flight_type);
+        assert_eq!(results[0].payload_weight_g, with_cargo.payload_weight_g);
+    }
+
+    #[tokio::test]
+    async fn test_post_telemetry() {
+        let config = crate::config::Config::default();
+        let aircraft_id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        let payload = AircraftPosition {
+            aircraft_id: aircraft_id.clone(),
+            position: PointZ {
+                latitude: 52.0,
+                longitude: 4.0,
+                altitude_meters: 100.0,
+            },
+            ground_speed_mps: 20.0,
+            vertical_speed_mps: 0.0,
+            heading_degrees: 90.0,
+            timestamp: now,
+        };
+
+        let result = post_telemetry(Extension(config.clone()), Json(payload.clone())).await;
+        assert!(result.is_ok());
+
+        // same report again is out of order
+        let error = post_telemetry(Extension(config.clone()), Json(payload.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::CONFLICT);
+
+        // bad request - invalid uuid
+        let mut tmp = payload.clone();
+        tmp.aircraft_id = "invalid".to_string();
+        let error = post_telemetry(Extension(config.clone()), Json(tmp))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        // bad request - dated in the future
+        let mut tmp = payload.clone();
+        tmp.timestamp = now + Duration::try_minutes(5).unwrap();
+        let error = post_telemetry(Extension(config.clone()), Json(tmp))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        let reports = get_traffic(Extension(config)).await.unwrap().0;
+        let report = reports
+            .iter()
+            .find(|report| report.position.aircraft_id == aircraft_id)
+            .unwrap();
+        assert_eq!(report.position.timestamp, now);
+        assert!(!report.stale);
+    }
+
+    #[tokio::test]
+    async fn test_get_conformance_alerts() {
+        let departure = Utc::now();
+        let plan = crate::test_util::mock_eastbound_plan(
+            departure,
+            departure + Duration::try_minutes(10).unwrap(),
+        );
+
+        let position = AircraftPosition {
+            aircraft_id: plan.aircraft_id.clone(),
+            position: PointZ {
+                latitude: 52.01,
+                longitude: 4.01,
+                altitude_meters: 100.0,
+            },
+            ground_speed_mps: 20.0,
+            vertical_speed_mps: 0.0,
+            heading_degrees: 90.0,
+            timestamp: departure + Duration::try_minutes(5).unwrap(),
+        };
+
+        {
+            let tolerances = crate::common::conformance::ConformanceTolerances::default();
+            let mut monitor = crate::common::conformance::get_conformance_monitor()
+                .await
+                .lock()
+                .await;
+            monitor.register(&plan).unwrap();
+            assert_eq!(monitor.check(&position, &tolerances).len(), 1);
+        }
+
+        let alerts = get_conformance_alerts().await.0;
+        assert!(alerts.iter().any(|alert| alert.fp_id == plan.flight_uuid
+            && alert.alert_type == ConformanceAlertType::Lateral));
+    }
+
+    #[tokio::test]
+    async fn test_get_separation_alerts() {
+        let now = Utc::now();
+        let position = |aircraft_id: &str, longitude: f64, heading_degrees: f64| AircraftPosition {
+            aircraft_id: aircraft_id.to_string(),
+            position: PointZ {
+                latitude: 53.0,
+                longitude,
+                altitude_meters: 100.0,
+            },
+            ground_speed_mps: 20.0,
+            vertical_speed_mps: 0.0,
+            heading_degrees,
+            timestamp: now,
+        };
+
+        let id_a = Uuid::new_v4().to_string();
+        let id_b = Uuid::new_v4().to_string();
+        {
+            let mut traffic = crate::common::traffic::get_traffic().await.write().await;
+            traffic
+                .update(position(&id_a, 4.0, 90.0), MaxSkew::default())
+                .unwrap();
+            traffic
+                .update(position(&id_b, 4.02, 270.0), MaxSkew::default())
+                .unwrap();
+        }
+
+        crate::common::tactical::monitor_traffic(
+            &crate::common::conflict::SeparationMinima::default(),
+            120.0,
+            Duration::try_seconds(10).unwrap(),
+        )
+        .await;
+
+        let alerts = get_separation_alerts().await.0;
+        assert!(alerts.iter().any(|alert| {
+            (alert.aircraft_id_a == id_a && alert.aircraft_id_b == id_b)
+                || (alert.aircraft_id_a == id_b && alert.aircraft_id_b == id_a)
+        }));
+        assert!(alerts
+            .windows(2)
+            .all(|pair| pair[0].time_to_conflict_seconds <= pair[1].time_to_conflict_seconds));
+    }
+
+    #[tokio::test]
+    async fn test_zones() {
+        let payload = Zone {
+            id: String::new(),
+            name: "test".to_string(),
+            vertices: vec![
+                Point {
+                    latitude: -33.0,
+                    longitude: 151.0,
+                },
+                Point {
+                    latitude: -33.0,
+                    longitude: 151.01,
+                },
+                Point {
+                    latitude: -33.01,
+                    longitude: 151.01,
+                },
+            ],
+            floor_meters: 0.0,
+            ceiling_meters: 120.0,
+            windows: vec![],
+        };
+
+        let zone = post_zone(Json(payload.clone())).await.unwrap().0;
+        assert!(!zone.id.is_empty());
+
+        let zones = get_zones().await.0;
+        assert!(zones.iter().any(|tmp| tmp.id == zone.id));
+
+        // bad request - floor above ceiling
+        let mut tmp = payload.clone();
+        tmp.floor_meters = 200.0;
+        let error = post_zone(Json(tmp)).await.unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        assert!(delete_zone(Path(zone.id.clone())).await.is_ok());
+        let error = delete_zone(Path(zone.id.clone())).await.unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+
+        let zones = get_zones().await.0;
+        assert!(!zones.iter().any(|tmp| tmp.id == zone.id));
+    }
+
+    #[tokio::test]
+    async fn test_restrictions() {
+        let mut config = crate::config::Config::default();
+        let path = std::env::temp_dir().join(format!("{}.json", Uuid::new_v4()));
+        config.restrictions_file = path.to_str().unwrap().to_string();
+
+        let now = Utc::now();
+        let payload = Restriction {
+            id: String::new(),
+            reason: "test".to_string(),
+            vertices: vec![
+                Point {
+                    latitude: -34.0,
+                    longitude: 151.0,
+                },
+                Point {
+                    latitude: -34.0,
+                    longitude: 151.01,
+                },
+                Point {
+                    latitude: -34.01,
+                    longitude: 151.01,
+                },
+            ],
+            floor_meters: 0.0,
+            ceiling_meters: 120.0,
+            start: now + Duration::try_hours(1).unwrap(),
+            end: now + Duration::try_hours(2).unwrap(),
+            cancel_affected: false,
+        };
+
+        let restriction = post_restriction(Extension(config.clone()), Json(payload.clone()))
+            .await
+            .unwrap()
+            .0;
+        assert!(!restriction.id.is_empty());
+        assert!(path.exists());
+
+        let restrictions = get_restrictions().await.0;
+        assert!(restrictions.iter().any(|tmp| tmp.id == restriction.id));
+
+        // bad request - expired
+        let mut tmp = payload.clone();
+        tmp.start = now - Duration::try_hours(2).unwrap();
+        tmp.end = now - Duration::try_hours(1).unwrap();
+        let error = post_restriction(Extension(config.clone()), Json(tmp))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        assert!(
+            delete_restriction(Extension(config.clone()), Path(restriction.id.clone()))
+                .await
+                .is_ok()
+        );
+        let error = delete_restriction(Extension(config.clone()), Path(restriction.id.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_weather() {
+        let config = crate::config::Config::default();
+        let now = Utc::now();
+        let hazard = WeatherHazard {
+            id: String::new(),
+            hazard_type: HazardType::Convective,
+            name: "test".to_string(),
+            vertices: vec![
+                Point {
+                    latitude: -43.5,
+                    longitude: 139.5,
+                },
+                Point {
+                    latitude: -43.5,
+                    longitude: 139.51,
+                },
+                Point {
+                    latitude: -43.51,
+                    longitude: 139.51,
+                },
+            ],
+            floor_meters: 0.0,
+            ceiling_meters: 3000.0,
+            start: now - Duration::try_minutes(10).unwrap(),
+            end: now + Duration::try_hours(1).unwrap(),
+        };
+
+        let payload = WeatherReport {
+            source: "test".to_string(),
+            valid_from: now,
+            valid_until: now + Duration::try_hours(1).unwrap(),
+            samples: vec![],
+            hazards: vec![hazard.clone()],
+        };
+
+        // Without a weather grid only hazards affect flights
+        let alerts = post_weather(Extension(config.clone()), Json(payload.clone()))
+            .await
+            .unwrap()
+            .0;
+        assert!(alerts
+            .iter()
+            .all(|alert| alert.alert_type == WeatherAlertType::Hazard));
+
+        let hazards = get_weather_hazards().await.0;
+        assert!(hazards.iter().any(|tmp| tmp.name == hazard.name));
+        assert!(get_weather_flights()
+            .await
+            .0
+            .iter()
+            .all(|alert| alert.alert_type == WeatherAlertType::Hazard));
+
+        // bad request - validity ends before it starts
+        let mut tmp = payload.clone();
+        tmp.valid_until = tmp.valid_from - Duration::try_hours(1).unwrap();
+        let error = post_weather(Extension(config), Json(tmp))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
     }
 
     #[tokio::test]
-    async fn test_acknowledge_flight_plan() {
+    async fn test_declare_emergency() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config.clone());
+        let pad = grpc_clients
+            .storage
+            .vertipad
+            .insert(vertipad::Data {
+                vertiport_id: Uuid::new_v4().to_string(),
+                name: "pad".to_string(),
+                geo_location: Some(GeoPointZ {
+                    x: 137.5,
+                    y: -44.5,
+                    z: 0.0,
+                }),
+                schedule: None,
+                enabled: true,
+                occupied: false,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap();
+
+        let payload = EmergencyDeclaration {
+            aircraft_id: Uuid::new_v4().to_string(),
+            emergency_type: EmergencyType::Mechanical,
+            position: PointZ {
+                latitude: -44.51,
+                longitude: 137.5,
+                altitude_meters: 120.0,
+            },
+        };
+
+        let response = declare_emergency(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Json(payload.clone()),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!(response.vertipad_id, pad.id);
+        assert!(response.clearance.priority);
+
+        let events = get_emergency_audit().await.0;
+        assert!(events
+            .iter()
+            .any(|event| event.emergency_id == response.emergency_id
+                && event.event_type == EmergencyEventType::PadAssigned));
+
+        // not found - the only pad in range is reserved
+        let error = declare_emergency(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Json(payload.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+
+        // bad request - invalid aircraft ID
+        let mut tmp = payload.clone();
+        tmp.aircraft_id = "invalid".to_string();
+        let error = declare_emergency(Extension(grpc_clients), Extension(config), Json(tmp))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_get_vertiport_schedule() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config.clone());
+
         // bad request - invalid uuid
-        let payload = AckRequest {
+        let error = get_vertiport_schedule(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Path("invalid".to_string()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        let vertiport_id = Uuid::new_v4().to_string();
+        let mut data = flight_plan::mock::get_data_obj();
+        data.origin_vertiport_id = Some(vertiport_id.clone());
+        grpc_clients.storage.flight_plan.insert(data).await.unwrap();
+
+        let schedule = get_vertiport_schedule(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Path(vertiport_id.clone()),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!(schedule.vertiport_id, vertiport_id);
+        assert_eq!(schedule.operations.len(), 1);
+        assert_eq!(schedule.operations[0].sequence, 1);
+    }
+
+    #[tokio::test]
+    async fn test_capacity() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config.clone());
+
+        let payload = Sector {
+            id: String::new(),
+            name: "test".to_string(),
+            vertices: vec![
+                Point {
+                    latitude: -47.0,
+                    longitude: 134.0,
+                },
+                Point {
+                    latitude: -47.0,
+                    longitude: 134.01,
+                },
+                Point {
+                    latitude: -47.01,
+                    longitude: 134.01,
+                },
+            ],
+            floor_meters: 0.0,
+            ceiling_meters: 120.0,
+            capacity: 5,
+        };
+
+        let sector = post_sector(Json(payload.clone())).await.unwrap().0;
+        assert!(!sector.id.is_empty());
+        assert!(get_sectors().await.0.iter().any(|tmp| tmp.id == sector.id));
+
+        // bad request - floor above ceiling
+        let mut tmp = payload.clone();
+        tmp.floor_meters = 200.0;
+        let error = post_sector(Json(tmp)).await.unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        assert!(delete_sector(Path(sector.id.clone())).await.is_ok());
+        let error = delete_sector(Path(sector.id.clone())).await.unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+
+        // bad request - invalid uuid
+        let error = post_vertiport_capacity(Json(VertiportCapacity {
+            vertiport_id: "invalid".to_string(),
+            capacity: 1,
+        }))
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        let vertiport_id = Uuid::new_v4().to_string();
+        post_vertiport_capacity(Json(VertiportCapacity {
+            vertiport_id: vertiport_id.clone(),
+            capacity: 1,
+        }))
+        .await
+        .unwrap();
+        assert!(get_vertiport_capacities()
+            .await
+            .0
+            .iter()
+            .any(|tmp| tmp.vertiport_id == vertiport_id && tmp.capacity == 1));
+
+        let now = Utc::now();
+        let report = get_capacity_hotspots(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Query(CapacityQuery {
+                from: Some(now),
+                to: None,
+            }),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!(report.window_start, now);
+        assert_eq!(report.window_end, now + Duration::try_hours(1).unwrap());
+
+        // bad request - window ends before it starts
+        let error = get_capacity_hotspots(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Query(CapacityQuery {
+                from: Some(now),
+                to: Some(now - Duration::try_minutes(1).unwrap()),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        assert!(delete_vertiport_capacity(Path(vertiport_id.clone()))
+            .await
+            .is_ok());
+        let error = delete_vertiport_capacity(Path(vertiport_id))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_request_clearance() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config.clone());
+
+        let mut data = flight_plan::mock::get_data_obj();
+        data.vehicle_id = Uuid::new_v4().to_string();
+        data.origin_vertipad_id = Uuid::new_v4().to_string();
+        let object = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data.clone())
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap();
+
+        let mut request = ClearanceRequest {
             fp_id: "invalid".to_string(),
-            status: AckStatus::Confirm,
+            aircraft_id: data.vehicle_id.clone(),
+            vertipad_id: data.origin_vertipad_id.clone(),
+            clearance_type: ClearanceType::Takeoff,
         };
+
+        // bad request - invalid uuid
+        let error = request_clearance(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Json(request.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        // not found
+        request.fp_id = Uuid::new_v4().to_string();
+        let error = request_clearance(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Json(request.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+
+        // not acknowledged by the carrier
+        request.fp_id = object.id.clone();
+        let clearance = request_clearance(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Json(request.clone()),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!(clearance.fp_id, object.id);
+        assert_eq!(clearance.status, ClearanceStatus::Denied);
+        assert_eq!(clearance.reason, Some(ClearanceReason::NotAcknowledged));
+    }
+
+    #[tokio::test]
+    async fn test_assign_holding() {
         let config = crate::config::Config::default();
-        let grpc_clients = GrpcClients::default(config);
-        let error = acknowledge_flight_plan(Extension(grpc_clients), Json(payload))
+        let grpc_clients = GrpcClients::default(config.clone());
+
+        let object = grpc_clients
+            .storage
+            .flight_plan
+            .insert(flight_plan::mock::get_data_obj())
             .await
-            .unwrap_err();
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap();
+
+        // bad request - invalid uuid
+        let error = assign_holding(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Json(HoldingRequest {
+                fp_id: "invalid".to_string(),
+            }),
+        )
+        .await
+        .unwrap_err();
         assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        // not found
+        let error = assign_holding(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Json(HoldingRequest {
+                fp_id: Uuid::new_v4().to_string(),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+
+        // target vertipad is free
+        let error = assign_holding(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Json(HoldingRequest {
+                fp_id: object.id.clone(),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::CONFLICT);
+
+        let holding = get_vertiport_holding(Path(Uuid::new_v4().to_string())).await;
+        assert!(holding.0.is_empty());
     }
 
     #[tokio::test]
-    async fn test_get_flight_plans() {
-        let aircraft_id = Bytes::from("invalid");
+    async fn test_flight_state_and_history() {
         let config = crate::config::Config::default();
         let grpc_clients = GrpcClients::default(config);
-        let error = get_flight_plans(Extension(grpc_clients.clone()), aircraft_id)
-            .await
-            .unwrap_err();
+
+        // bad request - invalid uuid
+        let error =
+            get_flight_history(Extension(grpc_clients.clone()), Path("invalid".to_string()))
+                .await
+                .unwrap_err();
         assert_eq!(error, StatusCode::BAD_REQUEST);
 
-        let aircraft_id = Bytes::from(vec![0xFF]); // non-UTF8 character
-        let error = get_flight_plans(Extension(grpc_clients.clone()), aircraft_id)
+        let error = get_flight_history(
+            Extension(grpc_clients.clone()),
+            Path(Uuid::new_v4().to_string()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+
+        let mut data = flight_plan::mock::get_data_obj();
+        data.carrier_ack = None;
+        let fp_id = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
             .await
-            .unwrap_err();
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        // bad request - state reached through acknowledgements
+        let error = report_flight_state(
+            Extension(grpc_clients.clone()),
+            Path(fp_id.clone()),
+            Json(FlightStateRequest {
+                state: FlightState::Acknowledged,
+            }),
+        )
+        .await
+        .unwrap_err();
         assert_eq!(error, StatusCode::BAD_REQUEST);
 
-        let aircraft_id = Bytes::from(Uuid::new_v4().to_string());
-        let results = get_flight_plans(Extension(grpc_clients.clone()), aircraft_id)
+        // conflict - not airborne yet
+        let error = report_flight_state(
+            Extension(grpc_clients.clone()),
+            Path(fp_id.clone()),
+            Json(FlightStateRequest {
+                state: FlightState::Landed,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::CONFLICT);
+
+        let history = report_flight_state(
+            Extension(grpc_clients.clone()),
+            Path(fp_id.clone()),
+            Json(FlightStateRequest {
+                state: FlightState::Cancelled,
+            }),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!(history.state, FlightState::Cancelled);
+
+        let history = get_flight_history(Extension(grpc_clients.clone()), Path(fp_id.clone()))
             .await
             .unwrap()
             .0;
-        assert!(results.is_empty());
+        assert_eq!(history.fp_id, fp_id);
+        assert_eq!(history.transitions.len(), 1);
+        assert_eq!(history.transitions[0].from, FlightState::Scheduled);
+        assert_eq!(history.transitions[0].to, FlightState::Cancelled);
+    }
 
-        let data = vehicle::mock::get_data_obj();
-        let aircraft_id = grpc_clients
+    #[tokio::test]
+    async fn test_contingency() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let request = |action| ContingencyRequest {
+            action,
+            alternate_vertipad_id: None,
+        };
+
+        // bad request - invalid uuid
+        let error = assign_contingency(
+            Extension(grpc_clients.clone()),
+            Path("invalid".to_string()),
+            Json(request(ContingencyAction::Continue)),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        let error = assign_contingency(
+            Extension(grpc_clients.clone()),
+            Path(Uuid::new_v4().to_string()),
+            Json(request(ContingencyAction::Continue)),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+
+        let now = Utc::now();
+        let data = crate::test_util::mock_flight_plan_data(
+            &[(-45.8, 135.2, 0.0), (-45.8, 135.25, 0.0)],
+            now,
+            now + Duration::try_minutes(10).unwrap(),
+        );
+        let origin_vertiport_id = data.origin_vertiport_id.clone().unwrap();
+        let fp_id = grpc_clients
             .storage
-            .vehicle
+            .flight_plan
             .insert(data)
             .await
             .unwrap()
@@ -544,15 +2750,137 @@ mod tests {
             .unwrap()
             .id;
 
-        let results = get_flight_plans(
+        // bad request - diverting without an alternate
+        let error = assign_contingency(
+            Extension(grpc_clients.clone()),
+            Path(fp_id.clone()),
+            Json(request(ContingencyAction::DivertToAlternate)),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        let contingency = assign_contingency(
             Extension(grpc_clients.clone()),
-            Bytes::from(aircraft_id.clone()),
+            Path(fp_id.clone()),
+            Json(request(ContingencyAction::ReturnToOrigin)),
         )
         .await
         .unwrap()
         .0;
+        assert_eq!(contingency.action, ContingencyAction::ReturnToOrigin);
+        assert_eq!(contingency.vertiport_id, origin_vertiport_id);
+        assert_eq!(contingency.destination.longitude, 135.2);
+
+        let alerts = get_lost_link_alerts().await.0;
+        assert!(alerts
+            .windows(2)
+            .all(|pair| pair[0].raised <= pair[1].raised));
+    }
 
-        assert!(results.is_empty());
+    #[tokio::test]
+    async fn test_divert_flight() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config.clone());
+        let request = || DivertRequest { vertipad_id: None };
+
+        // bad request - invalid uuid
+        let error = divert_flight(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Path("invalid".to_string()),
+            Json(request()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        let error = divert_flight(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Path(Uuid::new_v4().to_string()),
+            Json(request()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+
+        // conflict - not airborne
+        let mut data = flight_plan::mock::get_data_obj();
+        data.carrier_ack = None;
+        let fp_id = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        let error = divert_flight(
+            Extension(grpc_clients.clone()),
+            Extension(config.clone()),
+            Path(fp_id),
+            Json(request()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::CONFLICT);
+
+        let vertiports = get_vertiports().await.0;
+        assert!(vertiports
+            .windows(2)
+            .all(|pair| pair[0].vertiport_id < pair[1].vertiport_id));
+    }
+
+    #[tokio::test]
+    async fn test_get_plan_updates() {
+        use axum::body::HttpBody;
+        use axum::response::IntoResponse;
+
+        let query = PlanUpdatesQuery {
+            aircraft_id: "invalid".to_string(),
+            resume_token: None,
+        };
+        let error = get_plan_updates(Query(query), HeaderMap::new())
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        let now = Utc::now();
+        let plan = crate::test_util::mock_flight_plan(
+            &[(-38.0, 146.0, 100.0), (-38.0, 146.02, 100.0)],
+            now,
+            now + Duration::try_minutes(10).unwrap(),
+        );
+        let query = PlanUpdatesQuery {
+            aircraft_id: plan.aircraft_id.clone(),
+            resume_token: None,
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert("last-event-id", "invalid".parse().unwrap());
+        let error = get_plan_updates(Query(query.clone()), headers)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        get_updates().await.lock().await.publish(plan.clone(), now);
+
+        // The current flight plan is sent first
+        let mut body = get_plan_updates(Query(query), HeaderMap::new())
+            .await
+            .unwrap()
+            .into_response()
+            .into_body();
+        let event = body.data().await.unwrap().unwrap();
+        let event = String::from_utf8(event.to_vec()).unwrap();
+        assert!(event.contains("event:Snapshot"));
+        assert!(event.contains(&plan.flight_uuid));
     }
 
     #[test]
@@ -16,14 +16,113 @@ use utoipa::OpenApi;
         api::health_check,
         api::acknowledge_flight_plan,
         api::get_flight_plans,
+        api::post_telemetry,
+        api::get_traffic,
+        api::get_conformance_alerts,
+        api::get_separation_alerts,
+        api::get_lost_link_alerts,
+        api::get_vertiports,
+        api::get_zones,
+        api::post_zone,
+        api::delete_zone,
+        api::get_restrictions,
+        api::post_restriction,
+        api::delete_restriction,
+        api::post_weather,
+        api::get_weather_hazards,
+        api::get_weather_flights,
+        api::get_vertiport_schedule,
+        api::get_sectors,
+        api::post_sector,
+        api::delete_sector,
+        api::get_vertiport_capacities,
+        api::post_vertiport_capacity,
+        api::delete_vertiport_capacity,
+        api::get_capacity_hotspots,
+        api::request_clearance,
+        api::declare_emergency,
+        api::get_emergency_audit,
+        api::assign_holding,
+        api::get_vertiport_holding,
+        api::get_flight_denial,
+        api::get_flight_history,
+        api::report_flight_state,
+        api::assign_contingency,
+        api::divert_flight,
+        api::get_plan_updates,
     ),
     components(
         schemas(
             api::rest_types::AckRequest,
             api::rest_types::AckStatus,
+            api::rest_types::AckResponse,
+            api::rest_types::DenyReason,
+            api::rest_types::FlightDenial,
             api::rest_types::PointZ,
             api::rest_types::FlightPlan,
-            api::rest_types::Cargo
+            api::rest_types::Cargo,
+            api::rest_types::CargoDimensions,
+            api::rest_types::FlightType,
+            api::rest_types::ContingencyAction,
+            api::rest_types::Contingency,
+            api::rest_types::ContingencyRequest,
+            api::rest_types::LostLinkAlert,
+            api::rest_types::Alternate,
+            api::rest_types::VertipadStatus,
+            api::rest_types::Vertiport,
+            api::rest_types::DivertRequest,
+            api::rest_types::Diversion,
+            api::rest_types::Sector,
+            api::rest_types::VertiportCapacity,
+            api::rest_types::CapacityResourceType,
+            api::rest_types::Hotspot,
+            api::rest_types::GroundDelay,
+            api::rest_types::CapacityReport,
+            api::rest_types::Passenger,
+            api::rest_types::AircraftPosition,
+            api::rest_types::TrafficReport,
+            api::rest_types::ConformanceAlert,
+            api::rest_types::ConformanceAlertType,
+            api::rest_types::SeparationAlert,
+            api::rest_types::Advisory,
+            api::rest_types::ManeuverType,
+            api::rest_types::Point,
+            api::rest_types::TimeWindow,
+            api::rest_types::Zone,
+            api::rest_types::ZoneViolation,
+            api::rest_types::PerformanceViolation,
+            api::rest_types::PerformanceViolationType,
+            api::rest_types::Restriction,
+            api::rest_types::RestrictionAction,
+            api::rest_types::RestrictionNotice,
+            api::rest_types::WeatherSample,
+            api::rest_types::HazardType,
+            api::rest_types::WeatherHazard,
+            api::rest_types::WeatherReport,
+            api::rest_types::WeatherAlertType,
+            api::rest_types::WeatherAlert,
+            api::rest_types::PadOperationType,
+            api::rest_types::PadOperation,
+            api::rest_types::PadConflict,
+            api::rest_types::VertiportSchedule,
+            api::rest_types::ClearanceType,
+            api::rest_types::ClearanceStatus,
+            api::rest_types::ClearanceReason,
+            api::rest_types::ClearanceRequest,
+            api::rest_types::Clearance,
+            api::rest_types::EmergencyType,
+            api::rest_types::EmergencyDeclaration,
+            api::rest_types::EmergencyResponse,
+            api::rest_types::EmergencyEventType,
+            api::rest_types::EmergencyEvent,
+            api::rest_types::HoldingRequest,
+            api::rest_types::HoldingPattern,
+            api::rest_types::FlightState,
+            api::rest_types::FlightTransition,
+            api::rest_types::FlightHistory,
+            api::rest_types::FlightStateRequest,
+            api::rest_types::PlanUpdateKind,
+            api::rest_types::PlanUpdate
         )
     ),
     tags(
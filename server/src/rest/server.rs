@@ -77,6 +77,96 @@ pub async fn rest_server(
             routing::post(api::acknowledge_flight_plan),
         )
         .route("/atc/plans", routing::get(api::get_flight_plans))
+        .route("/atc/telemetry", routing::post(api::post_telemetry))
+        .route("/atc/traffic", routing::get(api::get_traffic))
+        .route(
+            "/atc/alerts/conformance",
+            routing::get(api::get_conformance_alerts),
+        )
+        .route(
+            "/atc/alerts/separation",
+            routing::get(api::get_separation_alerts),
+        )
+        .route(
+            "/atc/alerts/lost-link",
+            routing::get(api::get_lost_link_alerts),
+        )
+        .route("/atc/vertiports", routing::get(api::get_vertiports))
+        .route(
+            "/atc/zones",
+            routing::get(api::get_zones).post(api::post_zone),
+        )
+        .route("/atc/zones/:id", routing::delete(api::delete_zone))
+        .route(
+            "/atc/restrictions",
+            routing::get(api::get_restrictions).post(api::post_restriction),
+        )
+        .route(
+            "/atc/restrictions/:id",
+            routing::delete(api::delete_restriction),
+        )
+        .route("/atc/weather", routing::post(api::post_weather))
+        .route(
+            "/atc/weather/hazards",
+            routing::get(api::get_weather_hazards),
+        )
+        .route(
+            "/atc/weather/flights",
+            routing::get(api::get_weather_flights),
+        )
+        .route(
+            "/atc/vertiports/:id/schedule",
+            routing::get(api::get_vertiport_schedule),
+        )
+        .route(
+            "/atc/capacity/sectors",
+            routing::get(api::get_sectors).post(api::post_sector),
+        )
+        .route(
+            "/atc/capacity/sectors/:id",
+            routing::delete(api::delete_sector),
+        )
+        .route(
+            "/atc/capacity/vertiports",
+            routing::get(api::get_vertiport_capacities).post(api::post_vertiport_capacity),
+        )
+        .route(
+            "/atc/capacity/vertiports/:id",
+            routing::delete(api::delete_vertiport_capacity),
+        )
+        .route(
+            "/atc/capacity/hotspots",
+            routing::get(api::get_capacity_hotspots),
+        )
+        .route("/atc/clearance", routing::post(api::request_clearance))
+        .route("/atc/emergency", routing::post(api::declare_emergency))
+        .route(
+            "/atc/emergency/audit",
+            routing::get(api::get_emergency_audit),
+        )
+        .route("/atc/holding", routing::post(api::assign_holding))
+        .route(
+            "/atc/flights/:id/denial",
+            routing::get(api::get_flight_denial),
+        )
+        .route(
+            "/atc/flights/:id/history",
+            routing::get(api::get_flight_history),
+        )
+        .route(
+            "/atc/flights/:id/state",
+            routing::post(api::report_flight_state),
+        )
+        .route(
+            "/atc/flights/:id/contingency",
+            routing::put(api::assign_contingency),
+        )
+        .route("/atc/flights/:id/divert", routing::post(api::divert_flight))
+        .route(
+            "/atc/vertiports/:id/holding",
+            routing::get(api::get_vertiport_holding),
+        )
+        .route("/atc/updates", routing::get(api::get_plan_updates))
         .layer(
             CorsLayer::new()
                 .allow_origin(cors_allowed_origin)
@@ -84,6 +174,7 @@ pub async fn rest_server(
                 .allow_methods(Any),
         )
         .layer(limit_middleware)
+        .layer(Extension(config.clone()))
         .layer(Extension(grpc_clients)); // Extension layer must be last
 
     //
@@ -1,3 +1,84 @@
 use lib_common::log_macros;
 
 log_macros!("ut", "test");
+
+use crate::common::conflict::SeparationMinima;
+use crate::rest::api::FlightPlan;
+use lib_common::time::{DateTime, Duration, Utc};
+use lib_common::uuid::Uuid;
+use svc_storage_client_grpc::prelude::*;
+
+/// Creates mock storage data with the given path and times, where each
+///  point in `path` is (latitude, longitude, altitude).
+///
+/// The aircraft departs at `departure` and arrives at `arrival`.
+pub fn mock_flight_plan_data(
+    path: &[(f64, f64, f64)],
+    departure: DateTime<Utc>,
+    arrival: DateTime<Utc>,
+) -> flight_plan::Data {
+    let pad_time = Duration::try_minutes(5).unwrap();
+    let mut data = flight_plan::mock::get_data_obj();
+    data.vehicle_id = Uuid::new_v4().to_string();
+    data.origin_vertiport_id = Some(Uuid::new_v4().to_string());
+    data.target_vertiport_id = Some(Uuid::new_v4().to_string());
+    data.origin_timeslot_start = Some((departure - pad_time).into());
+    data.origin_timeslot_end = Some(departure.into());
+    data.target_timeslot_start = Some(arrival.into());
+    data.target_timeslot_end = Some((arrival + pad_time).into());
+    data.path = Some(GeoLineStringZ {
+        points: path
+            .iter()
+            .map(|(latitude, longitude, altitude)| GeoPointZ {
+                y: *latitude,
+                x: *longitude,
+                z: *altitude,
+            })
+            .collect(),
+    });
+
+    data
+}
+
+/// Creates a flight plan from mock storage data with the given path and
+///  times, see [`mock_flight_plan_data`].
+pub fn mock_flight_plan(
+    path: &[(f64, f64, f64)],
+    departure: DateTime<Utc>,
+    arrival: DateTime<Utc>,
+) -> FlightPlan {
+    let object = flight_plan::Object {
+        id: Uuid::new_v4().to_string(),
+        data: Some(mock_flight_plan_data(path, departure, arrival)),
+    };
+
+    FlightPlan::try_from(object).unwrap()
+}
+
+/// Creates a flight plan along `path` departing at `departure` and
+///  arriving 10 minutes later, see [`mock_flight_plan`].
+pub fn mock_plan(path: &[(f64, f64, f64)], departure: DateTime<Utc>) -> FlightPlan {
+    mock_flight_plan(
+        path,
+        departure,
+        departure + Duration::try_minutes(10).unwrap(),
+    )
+}
+
+/// Path heading east over 0.02 degrees of longitude at 100 meters, as
+///  (latitude, longitude, altitude) points
+pub const MOCK_EASTBOUND_PATH: [(f64, f64, f64); 2] = [(52.0, 4.0, 100.0), (52.0, 4.02, 100.0)];
+
+/// Creates a flight plan along [`MOCK_EASTBOUND_PATH`] with the given times,
+///  see [`mock_flight_plan`]
+pub fn mock_eastbound_plan(departure: DateTime<Utc>, arrival: DateTime<Utc>) -> FlightPlan {
+    mock_flight_plan(&MOCK_EASTBOUND_PATH, departure, arrival)
+}
+
+/// Separation minima of 150 meters horizontally and 30 meters vertically
+pub fn mock_minima() -> SeparationMinima {
+    SeparationMinima {
+        horizontal_meters: 150.0,
+        vertical_meters: 30.0,
+    }
+}